@@ -8,7 +8,11 @@ use ron::ser::{to_string_pretty, PrettyConfig};
 use serde::{Deserialize, Serialize};
 use wql::Types;
 
-use crate::{actors::wql::Executor, model::error::Error, repository::local::EncryptContext};
+use crate::{
+    actors::wql::Executor,
+    model::{error::Error, DataEncryptContext},
+    repository::local::EncryptContext,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WriteWithEncryption {
@@ -58,6 +62,117 @@ impl Handler<CreateWithEncryption> for Executor {
     }
 }
 
+pub struct EvictEncryption {
+    pub entity: String,
+    pub data: DataEncryptContext,
+}
+
+impl EvictEncryption {
+    pub fn new(entity: &str, data: DataEncryptContext) -> Self {
+        Self {
+            entity: entity.to_owned(),
+            data,
+        }
+    }
+}
+
+impl Message for EvictEncryption {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<EvictEncryption> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: EvictEncryption, _: &mut Self::Context) -> Self::Result {
+        let mut encrypt_data_context = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        if encrypt_data_context.remove(&msg.entity).is_some() {
+            rewrite_encrypts(&encrypt_data_context)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct RenameEncryptedKey {
+    pub entity: String,
+    pub from: String,
+    pub to: String,
+    pub data: DataEncryptContext,
+}
+
+impl Message for RenameEncryptedKey {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<RenameEncryptedKey> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: RenameEncryptedKey, _: &mut Self::Context) -> Self::Result {
+        let mut encrypt_data_context = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        let encrypts = encrypt_data_context.get_mut(&msg.entity);
+        if let Some(encrypts) = encrypts.filter(|encrypts| encrypts.contains(&msg.from)) {
+            encrypts.remove(&msg.from);
+            encrypts.insert(msg.to);
+            rewrite_encrypts(&encrypt_data_context)?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes a dropped field from the encrypted keys of an entity.
+pub struct DropEncryptedKey {
+    pub entity: String,
+    pub field: String,
+    pub data: DataEncryptContext,
+}
+
+impl Message for DropEncryptedKey {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<DropEncryptedKey> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: DropEncryptedKey, _: &mut Self::Context) -> Self::Result {
+        let mut encrypt_data_context = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        let encrypts = encrypt_data_context.get_mut(&msg.entity);
+        if encrypts.is_some_and(|encrypts| encrypts.remove(&msg.field)) {
+            rewrite_encrypts(&encrypt_data_context)?;
+        }
+        Ok(())
+    }
+}
+
+fn rewrite_encrypts(encrypt_data_context: &EncryptContext) -> Result<(), Error> {
+    use crate::io::write::encrypt_data;
+    let encrypt_log = encrypt_data_context
+        .iter()
+        .map(|(entity, encrypts)| {
+            let encrypts = WriteWithEncryption {
+                entity: entity.to_owned(),
+                encrypts: encrypts.iter().cloned().collect(),
+            };
+            to_string_pretty(&encrypts, pretty_config())
+        })
+        .collect::<Result<String, _>>()
+        .map_err(Error::Serialization)?;
+    Ok(encrypt_data(&encrypt_log)?)
+}
+
 pub struct EncryptContent {
     pub entity: String,
     pub content: HashMap<String, Types>,
@@ -1,6 +1,7 @@
 pub mod encrypts;
 pub mod recovery;
 pub mod scheduler;
+pub mod schemas;
 pub mod state;
 pub mod uniques;
 pub mod when;
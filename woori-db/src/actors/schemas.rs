@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use actix::prelude::*;
+use wql::{Entity, TypeTag};
+
+use crate::{
+    actors::wql::Executor,
+    core::pretty_config_inner,
+    io::write::{document_store_data, schema_data, schema_defaults_data},
+    model::{error::Error, DataDocumentStoreContext, DataSchemaContext, DataSchemaDefaultsContext},
+};
+
+pub struct CreateWithSchema {
+    pub entity: String,
+    pub schema: HashMap<String, TypeTag>,
+    pub defaults: Entity,
+    pub data: DataSchemaContext,
+    pub defaults_data: DataSchemaDefaultsContext,
+}
+
+impl Message for CreateWithSchema {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<CreateWithSchema> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: CreateWithSchema, _: &mut Self::Context) -> Self::Result {
+        let mut schema_context = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        schema_context.insert(msg.entity.to_owned(), msg.schema);
+        let schema_ron =
+            ron::ser::to_string_pretty(&schema_context.clone(), pretty_config_inner())?;
+        schema_data(&schema_ron)?;
+
+        let mut defaults_context = if let Ok(guard) = msg.defaults_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        // A new schema replaces the defaults of the previous one
+        let replaced = defaults_context.remove(&msg.entity).is_some();
+        let added = !msg.defaults.is_empty();
+        if added {
+            defaults_context.insert(msg.entity, msg.defaults);
+        }
+        if replaced || added {
+            let defaults_ron =
+                ron::ser::to_string_pretty(&defaults_context.clone(), pretty_config_inner())?;
+            schema_defaults_data(&defaults_ron)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct CreateDocumentStore {
+    pub entity: String,
+    pub data: DataDocumentStoreContext,
+}
+
+impl Message for CreateDocumentStore {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<CreateDocumentStore> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: CreateDocumentStore, _: &mut Self::Context) -> Self::Result {
+        let mut document_stores = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        document_stores.insert(msg.entity);
+        let document_store_ron =
+            ron::ser::to_string_pretty(&document_stores.clone(), pretty_config_inner())?;
+        document_store_data(&document_store_ron)?;
+        Ok(())
+    }
+}
+
+/// Removes the schema, its defaults or the document store flag of an evicted entity.
+pub struct EvictSchema {
+    pub entity: String,
+    pub schemas: DataSchemaContext,
+    pub schema_defaults: DataSchemaDefaultsContext,
+    pub document_stores: DataDocumentStoreContext,
+}
+
+impl Message for EvictSchema {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<EvictSchema> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: EvictSchema, _: &mut Self::Context) -> Self::Result {
+        let mut schema_context = if let Ok(guard) = msg.schemas.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if schema_context.remove(&msg.entity).is_some() {
+            let schema_ron =
+                ron::ser::to_string_pretty(&schema_context.clone(), pretty_config_inner())?;
+            schema_data(&schema_ron)?;
+        }
+
+        let mut defaults_context = if let Ok(guard) = msg.schema_defaults.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if defaults_context.remove(&msg.entity).is_some() {
+            let defaults_ron =
+                ron::ser::to_string_pretty(&defaults_context.clone(), pretty_config_inner())?;
+            schema_defaults_data(&defaults_ron)?;
+        }
+
+        let mut document_stores = if let Ok(guard) = msg.document_stores.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if document_stores.remove(&msg.entity) {
+            let document_store_ron =
+                ron::ser::to_string_pretty(&document_stores.clone(), pretty_config_inner())?;
+            document_store_data(&document_store_ron)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renames the field `from` in the schema and the `OR DEFAULT` values of an entity to `to`, a dropped
+/// field, `to` is `None`, is removed from them.
+pub struct RenameSchemaField {
+    pub entity: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub schemas: DataSchemaContext,
+    pub schema_defaults: DataSchemaDefaultsContext,
+}
+
+impl Message for RenameSchemaField {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<RenameSchemaField> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: RenameSchemaField, _: &mut Self::Context) -> Self::Result {
+        let mut schema_context = if let Ok(guard) = msg.schemas.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        let schema = schema_context.get_mut(&msg.entity);
+        if let Some(schema) = schema.filter(|schema| schema.contains_key(&msg.from)) {
+            let tag = schema.remove(&msg.from);
+            if let (Some(to), Some(tag)) = (&msg.to, tag) {
+                schema.insert(to.to_owned(), tag);
+            }
+            let schema_ron = ron::ser::to_string_pretty(&*schema_context, pretty_config_inner())?;
+            schema_data(&schema_ron)?;
+        }
+
+        let mut defaults_context = if let Ok(guard) = msg.schema_defaults.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        let defaults = defaults_context.get_mut(&msg.entity);
+        if let Some(defaults) = defaults.filter(|defaults| defaults.contains_key(&msg.from)) {
+            let value = defaults.remove(&msg.from);
+            if let (Some(to), Some(value)) = (msg.to, value) {
+                defaults.insert(to, value);
+            }
+            let defaults_ron =
+                ron::ser::to_string_pretty(&*defaults_context, pretty_config_inner())?;
+            schema_defaults_data(&defaults_ron)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::web;
+    use std::sync::{Arc, Mutex};
+    use wql::Types;
+
+    use crate::repository::local::{SchemaContext, SchemaDefaultsContext};
+
+    #[actix_rt::test]
+    async fn schema_follows_renamed_and_dropped_fields() {
+        let mut schema = HashMap::new();
+        schema.insert(String::from("tags"), TypeTag::Vector);
+        schema.insert(String::from("name"), TypeTag::String);
+        let mut schemas = SchemaContext::new();
+        schemas.insert(String::from("my-entity"), schema);
+        let mut defaults = HashMap::new();
+        defaults.insert(String::from("tags"), Types::Vector(Vec::new()));
+        let mut schema_defaults = SchemaDefaultsContext::new();
+        schema_defaults.insert(String::from("my-entity"), defaults);
+        let schemas = web::Data::new(Arc::new(Mutex::new(schemas)));
+        let schema_defaults = web::Data::new(Arc::new(Mutex::new(schema_defaults)));
+        let actor = Executor::new().start();
+        let rename = |from: &str, to: Option<&str>| RenameSchemaField {
+            entity: String::from("my-entity"),
+            from: from.to_owned(),
+            to: to.map(String::from),
+            schemas: schemas.clone(),
+            schema_defaults: schema_defaults.clone(),
+        };
+
+        assert!(actor
+            .send(rename("tags", Some("labels")))
+            .await
+            .unwrap()
+            .is_ok());
+        assert_eq!(
+            schemas.lock().unwrap()["my-entity"].get("labels"),
+            Some(&TypeTag::Vector)
+        );
+        assert!(!schemas.lock().unwrap()["my-entity"].contains_key("tags"));
+        assert_eq!(
+            schema_defaults.lock().unwrap()["my-entity"].get("labels"),
+            Some(&Types::Vector(Vec::new()))
+        );
+
+        assert!(actor.send(rename("labels", None)).await.unwrap().is_ok());
+        assert_eq!(schemas.lock().unwrap()["my-entity"].len(), 1);
+        assert!(schema_defaults.lock().unwrap()["my-entity"].is_empty());
+    }
+}
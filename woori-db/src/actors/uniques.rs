@@ -9,7 +9,13 @@ use serde::Serialize;
 use wql::Types;
 
 use crate::{
-    actors::wql::Executor, core::pretty_config_inner, io::write::unique_data, model::error::Error,
+    actors::wql::Executor,
+    core::{
+        keys::{composite_fields, rename_in_key, unique_value},
+        pretty_config_inner,
+    },
+    io::write::{primary_key_data, unique_data},
+    model::{error::Error, DataPrimaryKeyContext, DataUniquenessContext},
     repository::local::UniquenessContext,
 };
 
@@ -97,8 +103,9 @@ impl Handler<CheckForUniqueKeys> for Executor {
 
         if !uniqueness_data.is_empty() {
             if let Some(uniques_for_entity) = uniqueness_data.get_mut(&msg.entity) {
-                msg.content.iter().try_for_each(|(k, v)| {
-                    if uniques_for_entity.contains_key(k) {
+                let keys = uniques_for_entity.keys().cloned().collect::<Vec<String>>();
+                keys.iter().try_for_each(|k| {
+                    if let Some(v) = unique_value(k, &msg.content) {
                         let val = uniques_for_entity.get_mut(k).ok_or_else(|| {
                             Error::EntityNotCreatedWithUniqueness(msg.entity.to_owned())
                         })?;
@@ -106,7 +113,7 @@ impl Handler<CheckForUniqueKeys> for Executor {
                             Err(Error::DuplicatedUnique(
                                 msg.entity.to_owned(),
                                 k.to_owned(),
-                                v.to_owned(),
+                                v,
                             ))
                         } else {
                             val.insert(format!("{:?}", v));
@@ -126,6 +133,315 @@ impl Handler<CheckForUniqueKeys> for Executor {
     }
 }
 
+/// Moves the unique values of an entity id from its `previous` state to its `current` state, an
+/// insert has an empty `previous` and an evicted id an empty `current`. A value already held by
+/// another entity fails with `DuplicatedUnique` and leaves the uniqueness context untouched.
+pub struct UpdateUniqueKeys {
+    pub entity: String,
+    pub previous: HashMap<String, Types>,
+    pub current: HashMap<String, Types>,
+    pub uniqueness: DataUniquenessContext,
+}
+
+impl Message for UpdateUniqueKeys {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<UpdateUniqueKeys> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: UpdateUniqueKeys, _: &mut Self::Context) -> Self::Result {
+        let mut uniqueness_data = if let Ok(guard) = msg.uniqueness.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        let uniques_for_entity = if let Some(uniques) = uniqueness_data.get_mut(&msg.entity) {
+            uniques
+        } else {
+            return Ok(());
+        };
+        let changed = uniques_for_entity
+            .keys()
+            .filter(|k| unique_value(k, &msg.previous) != unique_value(k, &msg.current))
+            .cloned()
+            .collect::<Vec<String>>();
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        if let Some((k, v)) = changed.iter().find_map(|k| {
+            let v = unique_value(k, &msg.current)?;
+            uniques_for_entity[k]
+                .contains(&format!("{:?}", v))
+                .then_some((k, v))
+        }) {
+            return Err(Error::DuplicatedUnique(
+                msg.entity.to_owned(),
+                k.to_owned(),
+                v,
+            ));
+        }
+        changed.iter().for_each(|k| {
+            let values = uniques_for_entity.get_mut(k).unwrap();
+            if let Some(v) = unique_value(k, &msg.previous) {
+                values.remove(&format!("{:?}", v));
+            }
+            if let Some(v) = unique_value(k, &msg.current) {
+                values.insert(format!("{:?}", v));
+            }
+        });
+
+        let unique_ron =
+            ron::ser::to_string_pretty(&uniqueness_data.clone(), pretty_config_inner())?;
+        unique_data(&unique_ron)?;
+        Ok(())
+    }
+}
+
+pub struct EvictUniqueKeys {
+    pub entity: String,
+    pub data: DataUniquenessContext,
+}
+
+impl EvictUniqueKeys {
+    pub fn new(entity: &str, data: DataUniquenessContext) -> Self {
+        Self {
+            entity: entity.to_owned(),
+            data,
+        }
+    }
+}
+
+impl Message for EvictUniqueKeys {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<EvictUniqueKeys> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: EvictUniqueKeys, _: &mut Self::Context) -> Self::Result {
+        let mut uniqueness_data = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        if uniqueness_data.remove(&msg.entity).is_some() {
+            let unique_ron =
+                ron::ser::to_string_pretty(&uniqueness_data.clone(), pretty_config_inner())?;
+            unique_data(&unique_ron)?;
+        }
+        Ok(())
+    }
+}
+
+/// Clears the unique values of a truncated entity, its unique fields are kept.
+pub struct TruncateUniqueKeys {
+    pub entity: String,
+    pub data: DataUniquenessContext,
+}
+
+impl TruncateUniqueKeys {
+    pub fn new(entity: &str, data: DataUniquenessContext) -> Self {
+        Self {
+            entity: entity.to_owned(),
+            data,
+        }
+    }
+}
+
+impl Message for TruncateUniqueKeys {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<TruncateUniqueKeys> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: TruncateUniqueKeys, _: &mut Self::Context) -> Self::Result {
+        let mut uniqueness_data = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        if let Some(uniques) = uniqueness_data.get_mut(&msg.entity) {
+            uniques.values_mut().for_each(HashSet::clear);
+            let unique_ron =
+                ron::ser::to_string_pretty(&uniqueness_data.clone(), pretty_config_inner())?;
+            unique_data(&unique_ron)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct RenameUniqueKey {
+    pub entity: String,
+    pub from: String,
+    pub to: String,
+    pub data: DataUniquenessContext,
+}
+
+impl Message for RenameUniqueKey {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<RenameUniqueKey> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: RenameUniqueKey, _: &mut Self::Context) -> Self::Result {
+        let mut uniqueness_data = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        let uniques = if let Some(uniques) = uniqueness_data.get_mut(&msg.entity) {
+            uniques
+        } else {
+            return Ok(());
+        };
+        // The field is renamed in the composite keys holding it too
+        let renamed = uniques
+            .keys()
+            .map(|k| (k.to_owned(), rename_in_key(k, &msg.from, &msg.to)))
+            .filter(|(k, renamed)| k != renamed)
+            .collect::<Vec<(String, String)>>();
+        if !renamed.is_empty() {
+            renamed.into_iter().for_each(|(k, renamed)| {
+                if let Some(values) = uniques.remove(&k) {
+                    uniques.insert(renamed, values);
+                }
+            });
+            let unique_ron = ron::ser::to_string_pretty(&*uniqueness_data, pretty_config_inner())?;
+            unique_data(&unique_ron)?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes the unique values of a dropped field and of the composite keys holding it, they are no
+/// longer unique.
+pub struct DropUniqueKey {
+    pub entity: String,
+    pub field: String,
+    pub data: DataUniquenessContext,
+}
+
+impl Message for DropUniqueKey {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<DropUniqueKey> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: DropUniqueKey, _: &mut Self::Context) -> Self::Result {
+        let mut uniqueness_data = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        let uniques = if let Some(uniques) = uniqueness_data.get_mut(&msg.entity) {
+            uniques
+        } else {
+            return Ok(());
+        };
+        let len = uniques.len();
+        uniques.retain(|k, _| {
+            k != &msg.field
+                && !composite_fields(k).is_some_and(|fields| fields.contains(&msg.field.as_str()))
+        });
+        if uniques.len() != len {
+            let unique_ron =
+                ron::ser::to_string_pretty(&uniqueness_data.clone(), pretty_config_inner())?;
+            unique_data(&unique_ron)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sets the declared `PRIMARY KEY` of an entity, `None` removes it.
+pub struct SetPrimaryKey {
+    pub entity: String,
+    pub primary_key: Option<Vec<String>>,
+    pub data: DataPrimaryKeyContext,
+}
+
+impl Message for SetPrimaryKey {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<SetPrimaryKey> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: SetPrimaryKey, _: &mut Self::Context) -> Self::Result {
+        let mut primary_keys = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        let changed = match msg.primary_key {
+            Some(primary_key) => {
+                primary_keys.insert(msg.entity, primary_key);
+                true
+            }
+            None => primary_keys.remove(&msg.entity).is_some(),
+        };
+        if changed {
+            let primary_key_ron =
+                ron::ser::to_string_pretty(&primary_keys.clone(), pretty_config_inner())?;
+            primary_key_data(&primary_key_ron)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renames the field `from` of the `PRIMARY KEY` of an entity to `to`, a dropped field, `to` is `None`,
+/// removes the primary key.
+pub struct RenamePrimaryKeyField {
+    pub entity: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub data: DataPrimaryKeyContext,
+}
+
+impl Message for RenamePrimaryKeyField {
+    type Result = Result<(), Error>;
+}
+
+impl Handler<RenamePrimaryKeyField> for Executor {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, msg: RenamePrimaryKeyField, _: &mut Self::Context) -> Self::Result {
+        let mut primary_keys = if let Ok(guard) = msg.data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+
+        let primary_key = match primary_keys.get_mut(&msg.entity) {
+            Some(primary_key) if primary_key.contains(&msg.from) => primary_key,
+            _ => return Ok(()),
+        };
+        match &msg.to {
+            Some(to) => primary_key
+                .iter_mut()
+                .filter(|field| **field == msg.from)
+                .for_each(|field| *field = to.clone()),
+            None => {
+                primary_keys.remove(&msg.entity);
+            }
+        }
+        let primary_key_ron =
+            ron::ser::to_string_pretty(&primary_keys.clone(), pretty_config_inner())?;
+        primary_key_data(&primary_key_ron)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -158,4 +474,99 @@ mod test {
         let resp = actor.send(uniques).await.unwrap();
         assert!(resp.is_ok());
     }
+
+    #[actix_rt::test]
+    async fn update_uniques_match_brute_force_scan() {
+        let mut data = UniquenessContext::new();
+        let mut ssn = HashMap::new();
+        ssn.insert(String::from("ssn"), HashSet::new());
+        data.insert(String::from("my-entity"), ssn);
+        let uniqueness = actix_web::web::Data::new(Arc::new(Mutex::new(data)));
+        let actor = Executor::new().start();
+        let entity = |ssn: isize| {
+            let mut entity = HashMap::new();
+            entity.insert(String::from("ssn"), Types::Integer(ssn));
+            entity.insert(String::from("name"), Types::String(ssn.to_string()));
+            entity
+        };
+
+        let mut store: HashMap<usize, HashMap<String, Types>> = HashMap::new();
+        // (id, state after the mutation), `None` evicts the id
+        let mutations = vec![
+            (1, Some(entity(10))),
+            (2, Some(entity(20))),
+            (1, Some(entity(11))),
+            (3, Some(entity(10))),
+            (2, Some(HashMap::new())),
+            (3, Some(entity(20))),
+            (1, None),
+            (4, Some(entity(11))),
+        ];
+        for (id, current) in mutations {
+            let previous = store.remove(&id).unwrap_or_default();
+            let current = current.unwrap_or_default();
+            let resp = actor
+                .send(UpdateUniqueKeys {
+                    entity: String::from("my-entity"),
+                    previous,
+                    current: current.clone(),
+                    uniqueness: uniqueness.clone(),
+                })
+                .await
+                .unwrap();
+            assert!(resp.is_ok());
+            store.insert(id, current);
+
+            let scan = store
+                .values()
+                .filter_map(|e| e.get("ssn"))
+                .map(|v| format!("{:?}", v))
+                .collect::<HashSet<String>>();
+            assert_eq!(uniqueness.lock().unwrap()["my-entity"]["ssn"], scan);
+        }
+
+        let resp = actor
+            .send(UpdateUniqueKeys {
+                entity: String::from("my-entity"),
+                previous: store[&4].clone(),
+                current: entity(20),
+                uniqueness: uniqueness.clone(),
+            })
+            .await
+            .unwrap();
+        assert!(resp.is_err());
+        assert!(uniqueness.lock().unwrap()["my-entity"]["ssn"].contains("Integer(11)"));
+    }
+
+    #[actix_rt::test]
+    async fn composite_unique_values() {
+        let mut data = UniquenessContext::new();
+        let mut composite = HashMap::new();
+        composite.insert(String::from("(tenant, email)"), HashSet::new());
+        data.insert(String::from("my-entity"), composite);
+        let uniqueness = Arc::new(Arc::new(Mutex::new(data)));
+        let actor = Executor::new().start();
+        let entity = |tenant: &str| {
+            let mut entity = HashMap::new();
+            entity.insert(String::from("tenant"), Types::String(tenant.to_owned()));
+            entity.insert(String::from("email"), Types::String(String::from("a@b.c")));
+            entity
+        };
+
+        for (tenant, is_ok) in [("t1", true), ("t2", true), ("t1", false)] {
+            let resp = actor
+                .send(CheckForUniqueKeys {
+                    entity: String::from("my-entity"),
+                    content: entity(tenant),
+                    uniqueness: uniqueness.clone(),
+                })
+                .await
+                .unwrap();
+            assert_eq!(resp.is_ok(), is_ok);
+        }
+        assert_eq!(
+            uniqueness.lock().unwrap()["my-entity"]["(tenant, email)"].len(),
+            2
+        );
+    }
 }
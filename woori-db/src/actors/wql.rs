@@ -5,7 +5,8 @@ use uuid::Uuid;
 
 use crate::core::wql::{
     create_entity, delete_entity_content, evict_entity_content, evict_entity_id_content,
-    insert_entity_content, update_content_entity_content, update_set_entity_content,
+    insert_entity_content, truncate_entity_content, update_content_entity_content,
+    update_set_entity_content,
 };
 
 pub struct Executor;
@@ -49,13 +50,15 @@ impl Handler<CreateEntity> for Executor {
 pub struct InsertEntityContent {
     pub name: String,
     pub content: String,
+    pub uuid: Uuid,
 }
 
 impl InsertEntityContent {
-    pub fn new(name: &str, content: &str) -> Self {
+    pub fn new(name: &str, content: &str, uuid: Uuid) -> Self {
         Self {
             name: name.to_owned(),
             content: content.to_owned(),
+            uuid,
         }
     }
 }
@@ -217,6 +220,32 @@ impl Handler<EvictEntity> for Executor {
     }
 }
 
+pub struct TruncateEntity {
+    pub name: String,
+}
+
+impl TruncateEntity {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+        }
+    }
+}
+
+impl Message for TruncateEntity {
+    type Result = Result<(usize, bool), Error>;
+}
+
+impl Handler<TruncateEntity> for Executor {
+    type Result = Result<(usize, bool), Error>;
+
+    fn handle(&mut self, msg: TruncateEntity, _: &mut Self::Context) -> Self::Result {
+        use crate::io::write::write_to_log;
+        let content = truncate_entity_content(&msg.name);
+        write_to_log(&content)
+    }
+}
+
 pub struct EvictEntityId {
     pub name: String,
     pub id: Uuid,
@@ -253,7 +282,7 @@ mod test {
 
     use super::{
         CreateEntity, DeleteId, EvictEntity, EvictEntityId, Executor, InsertEntityContent,
-        UpdateSetEntityContent,
+        TruncateEntity, UpdateSetEntityContent,
     };
 
     #[actix_rt::test]
@@ -273,6 +302,7 @@ mod test {
         let insert = InsertEntityContent {
             name: String::from("insert-my-entity"),
             content: String::from("this is the content"),
+            uuid: uuid::Uuid::new_v4(),
         };
         let actor = Executor::new().start();
 
@@ -353,6 +383,17 @@ mod test {
         read::assert_content("evict-my-entity");
     }
 
+    #[actix_rt::test]
+    async fn truncate_test() {
+        let truncate = TruncateEntity::new("truncate-my-entity");
+        let actor = Executor::new().start();
+
+        let resp = actor.send(truncate).await.unwrap();
+        assert!(resp.is_ok());
+        read::assert_content("TRUNCATE_ENTITY|");
+        read::assert_content("truncate-my-entity");
+    }
+
     #[actix_rt::test]
     async fn evict_id_test() {
         let uuid = uuid::Uuid::new_v4();
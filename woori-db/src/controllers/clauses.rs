@@ -2,12 +2,16 @@ use std::collections::{BTreeMap, HashMap};
 
 use futures::{future, stream, StreamExt};
 use uuid::Uuid;
-use wql::{Clause, ToSelect, Types, Value};
+use wql::{eval_filter, Clause, ToSelect, Types};
 
 use crate::{
     actors::state::State,
-    core::{pretty_config_output, registry::get_registries},
-    model::{error::Error, DataExecutor, DataLocalContext, DataRegister},
+    core::{
+        planner::{estimate_cardinality, unique_values_match, IndexStats},
+        pretty_config_output,
+        registry::get_registries,
+    },
+    model::{error::Error, DataExecutor, DataLocalContext, DataRegister, DataUniquenessContext},
 };
 
 pub async fn select_where(
@@ -15,151 +19,162 @@ pub async fn select_where(
     args_to_select: ToSelect,
     clauses: Vec<Clause>,
     local_data: DataLocalContext,
+    uniqueness: DataUniquenessContext,
     actor: DataExecutor,
 ) -> Result<String, Error> {
-    let args_to_key = clauses
-        .clone()
-        .into_iter()
-        .filter_map(|clause| {
-            if let Clause::ValueAttribution(_, key, Value(arg)) = clause {
-                Some((arg, key))
-            } else {
-                None
-            }
-        })
-        .collect::<HashMap<String, String>>();
     let registries = get_registries(&entity, &local_data)?;
+    let (indexes, may_match) = if let Ok(guard) = uniqueness.lock() {
+        (
+            IndexStats::from_uniques(&entity, &guard),
+            unique_values_match(&entity, &clauses, &guard),
+        )
+    } else {
+        return Err(Error::LockData);
+    };
+    if !may_match || estimate_cardinality(&clauses, registries.len(), &indexes) == 0 {
+        let states: BTreeMap<Uuid, HashMap<String, Types>> = BTreeMap::new();
+        return Ok(ron::ser::to_string_pretty(&states, pretty_config_output())?);
+    }
     let states = generate_state(&registries, args_to_select, &actor).await?;
-    let states = filter_where_clauses(states, args_to_key, &clauses).await;
+    let states = filter_where_clauses(states, &clauses).await;
 
     Ok(ron::ser::to_string_pretty(&states, pretty_config_output())?)
 }
 
+pub async fn select_count(
+    entity: String,
+    clauses: Vec<Clause>,
+    local_data: DataLocalContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let mut count = 0usize;
+    for_each_match(&entity, &clauses, &local_data, &actor, |_| {
+        count += 1;
+        Ok(())
+    })
+    .await?;
+
+    Ok(ron::ser::to_string_pretty(&count, pretty_config_output())?)
+}
+
+pub async fn select_sum(
+    entity: String,
+    field: String,
+    clauses: Vec<Clause>,
+    local_data: DataLocalContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let mut accumulator = Accumulator::default();
+    for_each_match(&entity, &clauses, &local_data, &actor, |state| {
+        accumulator.push(&field, state.get(&field))
+    })
+    .await?;
+
+    Ok(ron::ser::to_string_pretty(
+        &accumulator.sum(),
+        pretty_config_output(),
+    )?)
+}
+
+pub async fn select_avg(
+    entity: String,
+    field: String,
+    clauses: Vec<Clause>,
+    local_data: DataLocalContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let mut accumulator = Accumulator::default();
+    for_each_match(&entity, &clauses, &local_data, &actor, |state| {
+        accumulator.push(&field, state.get(&field))
+    })
+    .await?;
+
+    Ok(ron::ser::to_string_pretty(
+        &accumulator.avg(),
+        pretty_config_output(),
+    )?)
+}
+
+/// Reads the entity states of the entity tree one at a time and calls `f` with each state matching
+/// `clauses`, so aggregates keep a single state in memory instead of every match.
+async fn for_each_match<F>(
+    entity: &str,
+    clauses: &[Clause],
+    local_data: &DataLocalContext,
+    actor: &DataExecutor,
+    mut f: F,
+) -> Result<(), Error>
+where
+    F: FnMut(&HashMap<String, Types>) -> Result<(), Error>,
+{
+    let registries = get_registries(entity, local_data)?;
+    for regs in registries.values() {
+        let content = actor.send(regs.to_owned()).await??;
+        let state = actor
+            .send(State(content))
+            .await??
+            .into_iter()
+            .filter(|(_, v)| !v.is_hash())
+            .collect::<HashMap<String, Types>>();
+
+        if eval_filter(clauses, &state) {
+            f(&state)?;
+        }
+    }
+    Ok(())
+}
+
+/// Running `SUM` and `AVG` of the numeric values of a field. `Integer`s are added as `i128`, so a
+/// sum past `isize` is returned as `Precise`, and `Nil` or missing values are neither added nor counted.
+#[derive(Debug, Default)]
+struct Accumulator {
+    integers: i128,
+    floats: Option<f64>,
+    count: usize,
+}
+
+impl Accumulator {
+    fn push(&mut self, field: &str, value: Option<&Types>) -> Result<(), Error> {
+        match value {
+            Some(Types::Integer(i)) => self.integers += *i as i128,
+            Some(Types::Float(f)) => self.floats = Some(self.floats.unwrap_or(0f64) + f),
+            Some(Types::Nil) | None => return Ok(()),
+            Some(value) => return Err(Error::NonNumericValue(field.to_owned(), value.to_owned())),
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn sum(&self) -> Types {
+        match self.floats {
+            Some(f) => Types::Float(f + self.integers as f64),
+            None if self.integers >= isize::MIN as i128 && self.integers <= isize::MAX as i128 => {
+                Types::Integer(self.integers as isize)
+            }
+            None => Types::Precise(self.integers.to_string()),
+        }
+    }
+
+    /// `Nil` when no value was pushed.
+    fn avg(&self) -> Types {
+        if self.count == 0 {
+            return Types::Nil;
+        }
+        let sum = self.floats.unwrap_or(0f64) + self.integers as f64;
+        Types::Float(sum / self.count as f64)
+    }
+}
+
 async fn filter_where_clauses(
     states: BTreeMap<Uuid, HashMap<String, Types>>,
-    args_to_key: HashMap<String, String>,
     clauses: &[Clause],
 ) -> BTreeMap<Uuid, HashMap<String, Types>> {
-    let default = String::new();
     stream::iter(states)
-        .filter(|(_, state)| {
-            future::ready(
-                clauses
-                    .iter()
-                    .map(|clause| match clause {
-                        Clause::ValueAttribution(_, _, _) => true,
-                        Clause::Or(_, inner_clauses) => {
-                            or_clauses(state, &args_to_key, inner_clauses)
-                        }
-                        Clause::ContainsKeyValue(_, key, value) => {
-                            state.get(key).map_or(false, |v| value == v)
-                        }
-                        Clause::SimpleComparisonFunction(f, key, value) => {
-                            let key = args_to_key.get(key).unwrap_or(&default);
-                            state.get(key).map_or(false, |v| match f {
-                                wql::Function::Eq => v == value,
-                                wql::Function::NotEq => v != value,
-                                wql::Function::GEq => v >= value,
-                                wql::Function::G => v > value,
-                                wql::Function::LEq => v <= value,
-                                wql::Function::L => v < value,
-                                wql::Function::Like => {
-                                    if let (Types::String(content), Types::String(regex)) =
-                                        (v, value)
-                                    {
-                                        if regex.starts_with('%') && regex.ends_with('%') {
-                                            content.contains(&regex[1..regex.len() - 1])
-                                        } else if regex.starts_with('%') {
-                                            content.ends_with(&regex[..regex.len() - 1])
-                                        } else if regex.ends_with('%') {
-                                            content.starts_with(&regex[1..])
-                                        } else {
-                                            content.contains(&regex[..])
-                                        }
-                                    } else {
-                                        false
-                                    }
-                                }
-                                _ => false,
-                            })
-                        }
-                        Clause::ComplexComparisonFunctions(wql::Function::In, key, set) => {
-                            let key = args_to_key.get(key).unwrap_or(&default);
-                            state.get(key).map_or(false, |v| set.contains(v))
-                        }
-                        Clause::ComplexComparisonFunctions(
-                            wql::Function::Between,
-                            key,
-                            start_end,
-                        ) => {
-                            let key = args_to_key.get(key).unwrap_or(&default);
-                            state
-                                .get(key)
-                                .map_or(false, |v| v >= &start_end[0] && v <= &start_end[1])
-                        }
-                        _ => false,
-                    })
-                    .all(|f| f),
-            )
-        })
+        .filter(|(_, state)| future::ready(eval_filter(clauses, state)))
         .collect::<BTreeMap<Uuid, HashMap<String, Types>>>()
         .await
 }
 
-fn or_clauses(
-    state: &HashMap<std::string::String, wql::Types>,
-    args_to_key: &HashMap<String, String>,
-    inner_clauses: &[Clause],
-) -> bool {
-    let default = String::new();
-    inner_clauses
-        .iter()
-        .map(|clause| match clause {
-            Clause::ValueAttribution(_, _, _) => true,
-            Clause::Error => false,
-            Clause::Or(_, or_inner_clauses) => or_clauses(state, &args_to_key, or_inner_clauses),
-            Clause::ContainsKeyValue(_, key, value) => state.get(key).map_or(false, |v| value == v),
-            Clause::SimpleComparisonFunction(f, key, value) => {
-                state.get(key).map_or(false, |v| match f {
-                    wql::Function::Eq => v == value,
-                    wql::Function::NotEq => v != value,
-                    wql::Function::GEq => v >= value,
-                    wql::Function::G => v > value,
-                    wql::Function::LEq => v <= value,
-                    wql::Function::L => v < value,
-                    wql::Function::Like => {
-                        if let (Types::String(content), Types::String(regex)) = (v, value) {
-                            if regex.starts_with('%') && regex.ends_with('%') {
-                                content.contains(&regex[1..regex.len() - 1])
-                            } else if regex.starts_with('%') {
-                                content.ends_with(&regex[..regex.len() - 1])
-                            } else if regex.ends_with('%') {
-                                content.starts_with(&regex[1..])
-                            } else {
-                                content.contains(&regex[..])
-                            }
-                        } else {
-                            false
-                        }
-                    }
-                    _ => false,
-                })
-            }
-            Clause::ComplexComparisonFunctions(wql::Function::In, key, set) => {
-                let key = args_to_key.get(key).unwrap_or(&default);
-                state.get(key).map_or(false, |v| set.contains(v))
-            }
-            Clause::ComplexComparisonFunctions(wql::Function::Between, key, start_end) => {
-                let key = args_to_key.get(key).unwrap_or(&default);
-                state
-                    .get(key)
-                    .map_or(false, |v| v >= &start_end[0] && v <= &start_end[1])
-            }
-            _ => false,
-        })
-        .any(|f| f)
-}
-
 async fn generate_state(
     registries: &BTreeMap<Uuid, DataRegister>,
     args_to_select: ToSelect,
@@ -173,15 +188,150 @@ async fn generate_state(
             .await??
             .into_iter()
             .filter(|(_, v)| !v.is_hash());
-        let filtered = if let ToSelect::Keys(ref keys) = args_to_select {
-            state
+        let filtered = match args_to_select {
+            ToSelect::Keys(ref keys) => state
                 .filter(|(k, _)| keys.contains(k))
-                .collect::<HashMap<String, Types>>()
-        } else {
-            state.collect::<HashMap<String, Types>>()
+                .collect::<HashMap<String, Types>>(),
+            ToSelect::Computed(ref keys, ref computed) => {
+                wql::project_computed(&state.collect(), keys, computed)
+            }
+            ToSelect::All => state.collect::<HashMap<String, Types>>(),
         };
 
         states.insert(uuid.to_owned(), filtered);
     }
     Ok(states)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sum_values(field: &str, values: &[Types]) -> Result<Types, Error> {
+        let mut accumulator = Accumulator::default();
+        for value in values {
+            accumulator.push(field, Some(value))?;
+        }
+        Ok(accumulator.sum())
+    }
+
+    /// Sums a materialized `Vec` of matches, as `SELECT SUM` did before it streamed.
+    fn materialized_sum(values: &[Types]) -> Types {
+        let integers = values
+            .iter()
+            .filter_map(|v| {
+                if let Types::Integer(i) = v {
+                    Some(*i as i128)
+                } else {
+                    None
+                }
+            })
+            .sum::<i128>();
+        let floats = values
+            .iter()
+            .filter_map(|v| {
+                if let Types::Float(f) = v {
+                    Some(*f)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<f64>>();
+        if floats.is_empty() {
+            Types::Integer(integers as isize)
+        } else {
+            Types::Float(floats.iter().sum::<f64>() + integers as f64)
+        }
+    }
+
+    fn as_f64(value: &Types) -> Option<f64> {
+        match value {
+            Types::Integer(i) => Some(*i as f64),
+            Types::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn streamed_aggregates_equal_materialized() {
+        let states = (0..1000isize)
+            .map(|i| {
+                let mut state = HashMap::new();
+                let amount = match i % 4 {
+                    0 => Types::Integer(i),
+                    1 => Types::Float(i as f64 / 4f64),
+                    2 => Types::Nil,
+                    _ => Types::Integer(-i),
+                };
+                state.insert("amount".to_string(), amount);
+                state.insert("even".to_string(), Types::Boolean(i % 2 == 0));
+                state
+            })
+            .collect::<Vec<HashMap<String, Types>>>();
+        let matches = |state: &&HashMap<String, Types>| state["even"] == Types::Boolean(false);
+
+        let mut streamed = Accumulator::default();
+        states
+            .iter()
+            .filter(matches)
+            .try_for_each(|state| streamed.push("amount", state.get("amount")))
+            .unwrap();
+        let materialized = states
+            .iter()
+            .filter(matches)
+            .map(|state| state["amount"].to_owned())
+            .filter(|v| v != &Types::Nil)
+            .collect::<Vec<Types>>();
+        let materialized_avg =
+            materialized.iter().filter_map(as_f64).sum::<f64>() / materialized.len() as f64;
+
+        assert_eq!(streamed.count, materialized.len());
+        assert_eq!(streamed.sum(), materialized_sum(&materialized));
+        assert_eq!(streamed.avg(), Types::Float(materialized_avg));
+    }
+
+    #[test]
+    fn avg_skips_missing_values() {
+        let mut accumulator = Accumulator::default();
+        accumulator.push("a", Some(&Types::Integer(3))).unwrap();
+        accumulator.push("a", None).unwrap();
+        accumulator.push("a", Some(&Types::Nil)).unwrap();
+        accumulator.push("a", Some(&Types::Float(4.0))).unwrap();
+
+        assert_eq!(accumulator.avg(), Types::Float(3.5));
+        assert_eq!(Accumulator::default().avg(), Types::Nil);
+    }
+
+    #[test]
+    fn sum_integers() {
+        let values = vec![Types::Integer(3), Types::Integer(4), Types::Nil];
+
+        assert_eq!(sum_values("a", &values).unwrap(), Types::Integer(7));
+    }
+
+    #[test]
+    fn sum_integers_over_isize() {
+        let values = vec![Types::Integer(isize::MAX), Types::Integer(isize::MAX)];
+
+        assert_eq!(
+            sum_values("a", &values).unwrap(),
+            Types::Precise((isize::MAX as i128 * 2).to_string())
+        );
+    }
+
+    #[test]
+    fn sum_integers_and_floats() {
+        let values = vec![Types::Integer(3), Types::Float(4.5)];
+
+        assert_eq!(sum_values("a", &values).unwrap(), Types::Float(7.5));
+    }
+
+    #[test]
+    fn sum_non_numeric() {
+        let values = vec![Types::Integer(3), Types::String("4".to_string())];
+        let sum = sum_values("a", &values);
+
+        assert!(matches!(sum, Err(Error::NonNumericValue(key, value))
+            if key == "a" && value == Types::String("4".to_string())));
+    }
+}
@@ -344,6 +344,138 @@ async fn clause_or() {
     clear();
 }
 
+#[ignore]
+#[actix_rt::test]
+async fn clause_count() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_count")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+    for a in &[3, 43, 948, 123] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!(
+                "INSERT {{a: {}, b: \"hello\",}} INTO test_count",
+                a
+            ))
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(
+            "Select * From test_count WHERE {
+            ?* test_count:a ?a,
+            (>= ?a 100),
+        }",
+        )
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    let result: BTreeMap<Uuid, HashMap<String, Types>> = ron::de::from_str(&body).unwrap();
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(
+            "Select COUNT From test_count WHERE {
+            ?* test_count:a ?a,
+            (>= ?a 100),
+        }",
+        )
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    let count: usize = ron::de::from_str(&body).unwrap();
+    assert_eq!(count, result.len());
+    assert_eq!(count, 2);
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("Select COUNT From test_count")
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    let count: usize = ron::de::from_str(&body).unwrap();
+    assert_eq!(count, 4);
+
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn clause_composite_primary_key() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_composite_where PRIMARY KEY (tenant, email)")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    for (tenant, name) in [("t1", "julia"), ("t2", "otavio")] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!(
+                "INSERT {{tenant: \"{}\", email: \"a@b.c\", name: \"{}\",}} INTO test_composite_where",
+                tenant, name
+            ))
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(
+            "Select * From test_composite_where WHERE {
+            (email, tenant) == (\"a@b.c\", \"t2\"),
+        }",
+        )
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    let result: BTreeMap<Uuid, HashMap<String, Types>> = ron::de::from_str(&body).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+        result.values().next().unwrap()["name"],
+        Types::String("otavio".to_string())
+    );
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(
+            "Select * From test_composite_where WHERE {
+            (tenant, email) == (\"t3\", \"a@b.c\"),
+        }",
+        )
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    let result: BTreeMap<Uuid, HashMap<String, Types>> = ron::de::from_str(&body).unwrap();
+    assert!(result.is_empty());
+
+    clear();
+}
+
 trait BodyTest {
     fn as_str(&self) -> &str;
 }
@@ -1,30 +1,39 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Bound,
     str::FromStr,
 };
 
 use actix_web::{HttpResponse, Responder};
 use ron::ser::to_string_pretty;
 use uuid::Uuid;
-use wql::{ToSelect, Types, Wql};
+use wql::{ComputedField, Cursor, ToSelect, Types, Wql};
 
 use crate::{
     actors::{
-        state::State,
+        state::{PreviousRegistry, State},
         when::{ReadEntitiesAt, ReadEntityIdAt, ReadEntityRange},
     },
-    core::pretty_config_output,
-    model::{error::Error, DataExecutor, DataLocalContext, DataRegister},
+    core::{
+        pretty_config_output,
+        registry::get_registries,
+        sample::{random_seed, reservoir_sample},
+    },
+    model::{error::Error, DataExecutor, DataLocalContext, DataRegister, DataUniquenessContext},
+    schemas::query::PageResponse,
 };
 
-use super::clauses::select_where;
+use super::clauses::{select_avg, select_count, select_sum, select_where};
 
 pub async fn wql_handler(
     body: String,
     local_data: DataLocalContext,
+    uniqueness: DataUniquenessContext,
     actor: DataExecutor,
 ) -> impl Responder {
-    let query = Wql::from_str(&body);
+    let query = Wql::from_str(&body)
+        // Placeholders are never bound over HTTP, binding nothing rejects them.
+        .and_then(|wql| wql::bind(wql, &HashMap::new()));
     let response = match query {
         Ok(Wql::Select(entity, ToSelect::All, Some(uuid))) => {
             select_all_with_id(entity, uuid, local_data, actor).await
@@ -36,6 +45,20 @@ pub async fn wql_handler(
         Ok(Wql::Select(entity, ToSelect::Keys(keys), None)) => {
             select_args(entity, keys, local_data, actor).await
         }
+        Ok(Wql::Select(entity, ToSelect::Computed(keys, computed), None)) => {
+            select_computed(entity, keys, computed, local_data, actor).await
+        }
+        Ok(Wql::Select(_, ToSelect::Computed(_, _), Some(_)))
+        | Ok(Wql::SelectIds(_, ToSelect::Computed(_, _), _))
+        | Ok(Wql::SelectWhen(_, ToSelect::Computed(_, _), _, _)) => Err(Error::QueryFormat(
+            String::from("Computed fields are only supported by SELECT without ID and WHEN"),
+        )),
+        Ok(Wql::SelectPage(entity, to_select, cursor, limit)) => {
+            select_page(entity, to_select, cursor, limit, local_data, actor).await
+        }
+        Ok(Wql::SelectSample(entity, to_select, size, seed)) => {
+            select_sample(entity, to_select, size, seed, local_data, actor).await
+        }
         Ok(Wql::SelectIds(entity, ToSelect::All, uuids)) => {
             select_all_with_ids(entity, uuids, local_data, actor).await
         }
@@ -58,10 +81,33 @@ pub async fn wql_handler(
             select_all_when_range_controller(entity_name, uuid, start_date, end_date, actor).await
         }
         Ok(Wql::SelectWhere(entity_name, args_to_select, clauses)) => {
-            select_where(entity_name, args_to_select, clauses, local_data, actor).await
+            select_where(
+                entity_name,
+                args_to_select,
+                clauses,
+                local_data,
+                uniqueness,
+                actor,
+            )
+            .await
+        }
+        Ok(Wql::SelectCount(entity_name, clauses)) => {
+            select_count(entity_name, clauses, local_data, actor).await
+        }
+        Ok(Wql::SelectSum(entity_name, field, clauses)) => {
+            select_sum(entity_name, field, clauses, local_data, actor).await
+        }
+        Ok(Wql::SelectAvg(entity_name, field, clauses)) => {
+            select_avg(entity_name, field, clauses, local_data, actor).await
+        }
+        Ok(Wql::SelectHistory(entity_name, uuid)) => {
+            select_history(entity_name, uuid, local_data, actor).await
+        }
+        Ok(Wql::SelectLatest(entity_name, uuid)) => {
+            select_all_with_id(entity_name, uuid, local_data, actor).await
         }
         Ok(_) => Err(Error::NonSelectQuery),
-        Err(e) => Err(Error::QueryFormat(e)),
+        Err(e) => Err(Error::from(e)),
     };
 
     match response {
@@ -154,10 +200,7 @@ async fn select_keys_id_when_controller(
     let result = actor
         .send(ReadEntityIdAt::new(&entity, uuid, date_log))
         .await??;
-    let result = result
-        .into_iter()
-        .filter(|(k, _)| keys.contains(k))
-        .collect::<HashMap<String, Types>>();
+    let result = wql::project(&result, &keys);
 
     Ok(to_string_pretty(&result, pretty_config_output())?)
 }
@@ -230,6 +273,57 @@ async fn select_all_with_id(
     )?)
 }
 
+async fn select_history(
+    entity: String,
+    uuid: Uuid,
+    local_data: DataLocalContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    use chrono::{DateTime, Utc};
+    let mut registry = {
+        let local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        let registry = if let Some(id_to_registry) = local_data.get(&entity) {
+            if let Some(reg) = id_to_registry.get(&uuid) {
+                reg
+            } else {
+                return Err(Error::UuidNotCreatedForEntity(entity, uuid));
+            }
+        } else {
+            return Err(Error::EntityNotCreated(entity));
+        }
+        .to_owned();
+        Some(registry)
+    };
+
+    let mut history = Vec::new();
+    while let Some(reg) = registry {
+        let content = actor.send(reg.clone()).await??;
+        let date: DateTime<Utc> = content
+            .split('|')
+            .nth(1)
+            .and_then(|date| ron::de::from_str(date).ok())
+            .ok_or(Error::FailedToParseDate)?;
+        let state = actor.send(State(content.clone())).await??;
+        let filterd_state = state
+            .into_iter()
+            .filter(|(_, v)| !v.is_hash())
+            .collect::<HashMap<String, Types>>();
+
+        history.push((reg, date, filterd_state));
+        registry = actor.send(PreviousRegistry(content)).await??;
+    }
+    history.reverse();
+
+    Ok(ron::ser::to_string_pretty(
+        &history,
+        pretty_config_output(),
+    )?)
+}
+
 async fn select_all_with_ids(
     entity: String,
     uuids: Vec<Uuid>,
@@ -409,6 +503,110 @@ async fn select_all(
     Ok(ron::ser::to_string_pretty(&states, pretty_config_output())?)
 }
 
+async fn select_page(
+    entity: String,
+    to_select: ToSelect,
+    cursor: Option<Cursor>,
+    limit: Option<usize>,
+    local_data: DataLocalContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    // Only the registries of the page, and the one after it to know if there is a next page, are copied
+    let mut registries = {
+        let local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        let id_to_registries = if let Some(id_to_registries) = local_data.get(&entity) {
+            id_to_registries
+        } else {
+            return Err(Error::EntityNotCreated(entity));
+        };
+        let after = cursor.map_or(Bound::Unbounded, |cursor| Bound::Excluded(cursor.id()));
+        id_to_registries
+            .range((after, Bound::Unbounded))
+            .take(limit.map_or(usize::MAX, |limit| limit.saturating_add(1)))
+            .map(|(uuid, regs)| (*uuid, regs.to_owned()))
+            .collect::<Vec<(Uuid, DataRegister)>>()
+    };
+    let next_cursor = match limit {
+        Some(limit) if registries.len() > limit => {
+            registries.truncate(limit);
+            registries
+                .last()
+                .map(|(uuid, _)| Cursor::new(*uuid).to_string())
+        }
+        _ => None,
+    };
+
+    let states = selected_states(registries, &to_select, &actor).await?;
+    Ok(PageResponse::new(states, next_cursor).write()?)
+}
+
+/// Up to `size` random entities of `entity`, reservoir sampling reads the entity ids once and only
+/// the states of the sampled ids are read.
+async fn select_sample(
+    entity: String,
+    to_select: ToSelect,
+    size: usize,
+    seed: Option<u64>,
+    local_data: DataLocalContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let registries = {
+        let local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        let id_to_registries = if let Some(id_to_registries) = local_data.get(&entity) {
+            id_to_registries
+        } else {
+            return Err(Error::EntityNotCreated(entity));
+        };
+        reservoir_sample(
+            id_to_registries.iter(),
+            size,
+            seed.unwrap_or_else(random_seed),
+        )
+        .into_iter()
+        .map(|(uuid, regs)| (*uuid, regs.to_owned()))
+        .collect::<Vec<(Uuid, DataRegister)>>()
+    };
+
+    let states = selected_states(registries, &to_select, &actor).await?;
+    Ok(ron::ser::to_string_pretty(&states, pretty_config_output())?)
+}
+
+/// Reads the state of every registry and keeps the `to_select` keys, hash values are never returned.
+async fn selected_states(
+    registries: Vec<(Uuid, DataRegister)>,
+    to_select: &ToSelect,
+    actor: &DataExecutor,
+) -> Result<BTreeMap<Uuid, HashMap<String, Types>>, Error> {
+    let mut states: BTreeMap<Uuid, HashMap<String, Types>> = BTreeMap::new();
+    for (uuid, regs) in registries {
+        let content = actor.send(regs).await??;
+        let state = actor
+            .send(State(content))
+            .await??
+            .into_iter()
+            .filter(|(_, v)| !v.is_hash())
+            .collect::<HashMap<String, Types>>();
+        let state = match to_select {
+            ToSelect::All => state,
+            ToSelect::Keys(keys) => state
+                .into_iter()
+                .filter(|(k, _)| keys.contains(k))
+                .collect::<HashMap<String, Types>>(),
+            ToSelect::Computed(keys, computed) => wql::project_computed(&state, keys, computed),
+        };
+        states.insert(uuid, state);
+    }
+    Ok(states)
+}
+
 async fn select_args(
     entity: String,
     keys: Vec<String>,
@@ -445,3 +643,27 @@ async fn select_args(
 
     Ok(ron::ser::to_string_pretty(&states, pretty_config_output())?)
 }
+
+async fn select_computed(
+    entity: String,
+    keys: Vec<String>,
+    computed: Vec<ComputedField>,
+    local_data: DataLocalContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let registries = get_registries(&entity, &local_data)?;
+
+    let mut states: HashMap<Uuid, HashMap<String, Types>> = HashMap::new();
+    for (uuid, regs) in registries {
+        let content = actor.send(regs).await??;
+        let state = actor
+            .send(State(content))
+            .await??
+            .into_iter()
+            .filter(|(_, v)| !v.is_hash())
+            .collect::<HashMap<String, Types>>();
+        states.insert(uuid, wql::project_computed(&state, &keys, &computed));
+    }
+
+    Ok(ron::ser::to_string_pretty(&states, pretty_config_output())?)
+}
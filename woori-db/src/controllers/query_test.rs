@@ -1,6 +1,17 @@
-use crate::{http::routes, schemas::tx::InsertEntityResponse};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    http::routes,
+    model::DataRegister,
+    schemas::{query::PageResponse, tx::InsertEntityResponse},
+};
 use actix_http::body::ResponseBody;
 use actix_web::{body::Body, test, App};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use wql::Types;
+
+use super::tx_test::clear;
 
 #[ignore]
 #[actix_rt::test]
@@ -181,6 +192,134 @@ async fn test_select_all_post_ok() {
     assert!(body.contains("Integer(123)"));
 }
 
+#[ignore]
+#[actix_rt::test]
+async fn test_select_page_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_select_page")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    for a in 1..=3 {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!("INSERT {{a: {},}} INTO test_select_page", a))
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("SELECT * FROM test_select_page LIMIT 2")
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    let first_page: PageResponse = ron::de::from_str(&body).unwrap();
+    assert_eq!(first_page.entities.len(), 2);
+    let cursor = first_page.cursor.unwrap();
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(format!(
+            "SELECT * FROM test_select_page AFTER {} LIMIT 2",
+            cursor
+        ))
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    let second_page: PageResponse = ron::de::from_str(&body).unwrap();
+    assert_eq!(second_page.entities.len(), 1);
+    assert!(second_page.cursor.is_none());
+
+    let mut values = first_page
+        .entities
+        .values()
+        .chain(second_page.entities.values())
+        .map(|state| state["a"].clone())
+        .collect::<Vec<Types>>();
+    values.sort_by_key(Types::sort_key);
+    assert_eq!(
+        values,
+        vec![Types::Integer(1), Types::Integer(2), Types::Integer(3)]
+    );
+    assert!(first_page.entities.keys().last() < second_page.entities.keys().next());
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_select_sample_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_select_sample")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    for a in 1..=5 {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!("INSERT {{a: {},}} INTO test_select_sample", a))
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let mut samples = Vec::new();
+    for payload in &[
+        "SELECT * FROM test_select_sample SAMPLE 3 SEED 42",
+        "SELECT * FROM test_select_sample SAMPLE 3 SEED 42",
+        "SELECT * FROM test_select_sample SAMPLE 10",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/query")
+            .to_request();
+
+        let mut resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let body = resp.take_body().as_str().to_string();
+        let sample: BTreeMap<Uuid, HashMap<String, Types>> = ron::de::from_str(&body).unwrap();
+        samples.push(sample);
+    }
+
+    assert_eq!(samples[0].len(), 3);
+    assert_eq!(samples[0], samples[1]);
+    assert_eq!(samples[2].len(), 5);
+    clear();
+}
+
+#[actix_rt::test]
+async fn test_select_page_malformed_cursor_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("SELECT * FROM test_select_page AFTER 2df2b8cf LIMIT 2")
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_client_error());
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains("Cursor `2df2b8cf` is not a valid pagination cursor"));
+}
+
 #[ignore]
 #[actix_rt::test]
 async fn test_select_keys_post_ok() {
@@ -236,6 +375,52 @@ async fn test_select_keys_post_ok() {
     assert!(!body.contains("Integer(123)"));
 }
 
+#[ignore]
+#[actix_rt::test]
+async fn test_select_computed_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_select_computed")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: 4365, b: 76, c: 24,} INTO test_select_computed")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: 7654, b: 98, c: \"hello\",} INTO test_select_computed")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("Select #{a, (b + c) AS total,} FROM test_select_computed")
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains("\"a\": Integer(4365)"));
+    assert!(body.contains("\"total\": Integer(100)"));
+    assert!(body.contains("\"a\": Integer(7654)"));
+    assert!(body.contains("\"total\": Nil"));
+    assert!(!body.contains("\"b\""));
+    clear();
+}
+
 #[ignore]
 #[actix_rt::test]
 async fn test_select_all_ids_post_ok() {
@@ -538,6 +723,171 @@ async fn test_select_when_range_all_post_ok() {
     assert!(body.contains("\"f\": String(\"JULIA\")"));
 }
 
+#[ignore]
+#[actix_rt::test]
+async fn test_select_history_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_select_history")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: 123, b: 12.3,} INTO test_select_history")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp_insert = test::call_service(&mut app, req).await;
+    let body = resp_insert.take_body().as_str().to_string();
+    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+    let uuid = response.uuid;
+
+    for a in &[12, 1] {
+        let payload = format!("UPDATE test_select_history SET {{a: {},}} INTO {}", a, uuid);
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload)
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let payload = format!(
+        "SELECT HISTORY FROM test_select_history WHERE id = {}",
+        uuid
+    );
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(payload)
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    let history: Vec<(DataRegister, DateTime<Utc>, HashMap<String, Types>)> =
+        ron::de::from_str(&body).unwrap();
+    let values = history
+        .iter()
+        .map(|(_, _, state)| state["a"].clone())
+        .collect::<Vec<Types>>();
+
+    assert_eq!(
+        values,
+        vec![Types::Integer(123), Types::Integer(12), Types::Integer(1)]
+    );
+    assert!(history
+        .iter()
+        .all(|(_, _, state)| state["b"] == Types::Float(12.3)));
+    assert!(history.windows(2).all(|revisions| {
+        let (previous, next) = (&revisions[0], &revisions[1]);
+        previous.1 <= next.1
+            && (previous.0.file_name != next.0.file_name || previous.0.offset != next.0.offset)
+    }));
+
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_select_latest_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_select_latest")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: 123, b: 12.3,} INTO test_select_latest")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp_insert = test::call_service(&mut app, req).await;
+    let body = resp_insert.take_body().as_str().to_string();
+    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+    let uuid = response.uuid;
+
+    let payload = format!("UPDATE test_select_latest SET {{a: 12,}} INTO {}", uuid);
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(payload)
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let payload = format!("SELECT LATEST FROM test_select_latest WHERE id = {}", uuid);
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(payload)
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    let state: HashMap<String, Types> = ron::de::from_str(&body).unwrap();
+
+    assert_eq!(state["a"], Types::Integer(12));
+    assert_eq!(state["b"], Types::Float(12.3));
+
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_select_after_restart_post_ok() {
+    let uuid = {
+        let mut app = test::init_service(App::new().configure(routes)).await;
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload("CREATE ENTITY test_select_after_restart")
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload("INSERT {a: 123, b: 12.3,} INTO test_select_after_restart")
+            .uri("/wql/tx")
+            .to_request();
+
+        let mut resp_insert = test::call_service(&mut app, req).await;
+        let body = resp_insert.take_body().as_str().to_string();
+        let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+        response.uuid
+    };
+
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let payload = format!("Select * FROM test_select_after_restart ID {}", uuid);
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(payload)
+        .uri("/wql/query")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains("\"a\": Integer(123)"));
+    assert!(body.contains("\"b\": Float(12.3)"));
+
+    clear();
+}
+
 trait BodyTest {
     fn as_str(&self) -> &str;
 }
@@ -3,22 +3,33 @@ use crate::{
         encrypts::{CreateWithEncryption, EncryptContent, VerifyEncryption, WriteWithEncryption},
         recovery::{LocalData, OffsetCounter},
         state::{MatchUpdate, PreviousRegistry, State},
-        uniques::{CreateWithUniqueKeys, WriteWithUniqueKeys},
+        uniques::{
+            CreateWithUniqueKeys, RenamePrimaryKeyField, SetPrimaryKey, WriteWithUniqueKeys,
+        },
         wql::{DeleteId, InsertEntityContent, UpdateContentEntityContent, UpdateSetEntityContent},
     },
-    core::wql::update_content_state,
+    core::{
+        keys::{unique_key_name, unique_value},
+        wql::{coerce_to_schema, update_content_state},
+    },
     model::{
-        wql::{InsertArgs, MatchUpdateArgs, UpdateArgs},
-        DataAtomicUsize, DataEncryptContext, DataExecutor, DataLocalContext, DataU32,
-        DataUniquenessContext,
+        wql::{InsertArgs, MatchUpdateArgs, RenameFieldArgs, UpdateArgs},
+        DataAtomicUsize, DataDocumentStoreContext, DataEncryptContext, DataExecOptions,
+        DataExecutor, DataLocalContext, DataPrimaryKeyContext, DataSchemaContext,
+        DataSchemaDefaultsContext, DataUniquenessContext,
     },
 };
 use crate::{
     actors::{
-        uniques::CheckForUniqueKeys,
-        wql::{CreateEntity, EvictEntity, EvictEntityId},
+        encrypts::{DropEncryptedKey, EvictEncryption, RenameEncryptedKey},
+        schemas::{CreateDocumentStore, CreateWithSchema, EvictSchema, RenameSchemaField},
+        uniques::{
+            CheckForUniqueKeys, DropUniqueKey, EvictUniqueKeys, RenameUniqueKey,
+            TruncateUniqueKeys, UpdateUniqueKeys,
+        },
+        wql::{CreateEntity, EvictEntity, EvictEntityId, TruncateEntity},
     },
-    schemas::tx::CreateEntityResponse,
+    schemas::tx::{CreateEntityResponse, DropFieldResponse, RenameFieldResponse},
 };
 use crate::{
     model::{error::Error, DataRegister},
@@ -37,7 +48,7 @@ use std::{
     sync::{atomic::Ordering, Arc, Mutex},
 };
 use uuid::Uuid;
-use wql::{Types, Wql};
+use wql::{apply_defaults, Entity, OnConflict, TypeTag, Types, Wql};
 
 fn pretty_config() -> PrettyConfig {
     PrettyConfig::new()
@@ -45,33 +56,112 @@ fn pretty_config() -> PrettyConfig {
         .with_new_line("".to_string())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn wql_handler(
     body: String,
     local_data: DataLocalContext,
     uniqueness: DataUniquenessContext,
     encryption: DataEncryptContext,
+    // Handlers take at most 10 extractors
+    (schemas, primary_keys): (DataSchemaContext, DataPrimaryKeyContext),
+    schema_defaults: DataSchemaDefaultsContext,
+    document_stores: DataDocumentStoreContext,
     bytes_counter: DataAtomicUsize,
-    hashing_cost: DataU32,
+    exec_options: DataExecOptions,
     actor: DataExecutor,
 ) -> impl Responder {
-    let query = wql::Wql::from_str(&body);
+    let query = wql::Wql::from_str(&body)
+        // Placeholders are never bound over HTTP, binding nothing rejects them.
+        .and_then(|wql| wql::bind(wql, &HashMap::new()))
+        .map_err(Error::from)
+        .and_then(|wql| exec_options.normalize_wql(wql));
     let response = match query {
-        Ok(Wql::CreateEntity(entity, uniques, encrypts)) => {
-            let _ = create_unique_controller(&entity, uniques, uniqueness, &actor).await;
-            let _ = create_encrypts_controller(&entity, encrypts, encryption, &actor).await;
-            create_controller(entity, local_data.into_inner(), bytes_counter, actor).await
+        Ok(Wql::CreateEntity(entity, uniques, encrypts, primary_key)) => {
+            create_entity_controller(
+                entity,
+                uniques,
+                encrypts,
+                primary_key,
+                local_data,
+                bytes_counter,
+                uniqueness,
+                encryption,
+                primary_keys,
+                actor,
+            )
+            .await
         }
-        Ok(Wql::Delete(entity, uuid)) => {
-            delete_controller(entity, uuid, local_data.into_inner(), bytes_counter, actor).await
+        Ok(Wql::CreateDocumentStore(entity)) => {
+            create_document_store_controller(
+                entity,
+                local_data,
+                bytes_counter,
+                document_stores,
+                actor,
+            )
+            .await
         }
-        Ok(Wql::Insert(entity, content)) => {
-            insert_controller(
-                InsertArgs::new(entity, content),
+        Ok(Wql::Delete(entity, uuid)) => {
+            delete_controller(
+                entity,
+                uuid,
                 local_data.into_inner(),
                 bytes_counter,
                 uniqueness,
+                actor,
+            )
+            .await
+        }
+        Ok(Wql::DeleteMany(entity, uuids)) => {
+            delete_many_controller(entity, uuids, local_data, bytes_counter, uniqueness, actor)
+                .await
+        }
+        Ok(Wql::Insert(entity, content, _, on_conflict)) => {
+            match schema_content(
+                &entity,
+                content,
+                &schemas,
+                &schema_defaults,
+                &document_stores,
+            ) {
+                Ok(content) => {
+                    insert_controller(
+                        InsertArgs::new(entity, content, on_conflict),
+                        local_data.into_inner(),
+                        bytes_counter,
+                        uniqueness,
+                        encryption,
+                        exec_options,
+                        actor,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Ok(Wql::NestedInsert(insert, sub_inserts)) => {
+            nested_insert_controller(
+                *insert,
+                sub_inserts,
+                local_data,
+                bytes_counter,
+                uniqueness,
                 encryption,
-                hashing_cost,
+                (schemas, schema_defaults, document_stores),
+                exec_options,
+                actor,
+            )
+            .await
+        }
+        Ok(Wql::CreateSchema(entity, schema, defaults)) => {
+            create_schema_controller(
+                entity,
+                schema,
+                defaults,
+                local_data,
+                schemas,
+                schema_defaults,
+                document_stores,
                 actor,
             )
             .await
@@ -94,7 +184,7 @@ pub async fn wql_handler(
                 bytes_counter,
                 uniqueness,
                 encryption,
-                hashing_cost,
+                exec_options,
                 actor,
             )
             .await
@@ -106,19 +196,109 @@ pub async fn wql_handler(
                 bytes_counter,
                 uniqueness,
                 encryption,
-                hashing_cost,
+                exec_options,
                 actor,
             )
             .await
         }
         Ok(Wql::Evict(entity, uuid)) => {
-            evict_controller(entity, uuid, local_data.into_inner(), bytes_counter, actor).await
+            let evicted = evict_controller(
+                entity.clone(),
+                uuid,
+                local_data.into_inner(),
+                bytes_counter,
+                uniqueness,
+                encryption,
+                actor.clone(),
+            )
+            .await;
+            match evicted {
+                Ok(resp) if uuid.is_none() => {
+                    let primary_key = SetPrimaryKey {
+                        entity: entity.clone(),
+                        primary_key: None,
+                        data: primary_keys,
+                    };
+                    match actor.send(primary_key).await.map_err(Error::from) {
+                        Ok(Ok(())) => actor
+                            .send(EvictSchema {
+                                entity,
+                                schemas,
+                                schema_defaults,
+                                document_stores,
+                            })
+                            .await
+                            .map_err(Error::from)
+                            .and_then(|evicted| evicted.map(|_| resp)),
+                        Ok(Err(e)) | Err(e) => Err(e),
+                    }
+                }
+                evicted => evicted,
+            }
+        }
+        Ok(Wql::Truncate(entity)) => {
+            truncate_controller(entity, local_data, bytes_counter, uniqueness, actor).await
+        }
+        Ok(Wql::RenameField(entity, from, to)) => {
+            let primary_key = RenamePrimaryKeyField {
+                entity: entity.clone(),
+                from: from.clone(),
+                to: Some(to.clone()),
+                data: primary_keys,
+            };
+            let schema = RenameSchemaField {
+                entity: entity.clone(),
+                from: from.clone(),
+                to: Some(to.clone()),
+                schemas,
+                schema_defaults,
+            };
+            let renamed = rename_field_controller(
+                RenameFieldArgs::new(entity, from, to),
+                local_data,
+                bytes_counter,
+                uniqueness,
+                encryption,
+                actor.clone(),
+            )
+            .await;
+            field_keys_controller(renamed, primary_key, schema, &actor).await
+        }
+        Ok(Wql::DropField(entity, field)) => {
+            let primary_key = RenamePrimaryKeyField {
+                entity: entity.clone(),
+                from: field.clone(),
+                to: None,
+                data: primary_keys,
+            };
+            let schema = RenameSchemaField {
+                entity: entity.clone(),
+                from: field.clone(),
+                to: None,
+                schemas,
+                schema_defaults,
+            };
+            let dropped = drop_field_controller(
+                entity,
+                field,
+                local_data,
+                bytes_counter,
+                uniqueness,
+                encryption,
+                actor.clone(),
+            )
+            .await;
+            field_keys_controller(dropped, primary_key, schema, &actor).await
         }
         Ok(Wql::CheckValue(entity, uuid, content)) => {
             check_value_controller(entity, uuid, content, local_data, encryption, actor).await
         }
+        // Statements are executed one at a time, transactions are not implemented for any level yet.
+        Ok(Wql::Begin(isolation)) => {
+            Err(Error::Unsupported(format!("BEGIN ISOLATION {}", isolation)))
+        }
         Ok(_) => Err(Error::SelectBadRequest),
-        Err(e) => Err(Error::QueryFormat(e)),
+        Err(e) => Err(e),
     };
 
     match response {
@@ -179,6 +359,48 @@ pub async fn check_value_controller(
     Ok(results)
 }
 
+/// Creates the entity tree key and only then its unique, encrypted and primary keys, so an entity
+/// that already exists keeps the keys it was created with.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_entity_controller(
+    entity: String,
+    mut uniques: Vec<String>,
+    encrypts: Vec<String>,
+    primary_key: Vec<String>,
+    local_data: DataLocalContext,
+    bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
+    encryption: DataEncryptContext,
+    primary_keys: DataPrimaryKeyContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let response = create_controller(
+        entity.clone(),
+        local_data.into_inner(),
+        bytes_counter,
+        actor.clone(),
+    )
+    .await?;
+
+    // The PRIMARY KEY is enforced as a unique key, a composite one by the tuple of its values
+    if !primary_key.is_empty() {
+        let key = unique_key_name(&primary_key);
+        if !uniques.contains(&key) {
+            uniques.push(key);
+        }
+        actor
+            .send(SetPrimaryKey {
+                entity: entity.clone(),
+                primary_key: Some(primary_key),
+                data: primary_keys,
+            })
+            .await??;
+    }
+    create_unique_controller(&entity, uniques, uniqueness, &actor).await?;
+    create_encrypts_controller(&entity, encrypts, encryption, &actor).await?;
+    Ok(response)
+}
+
 pub async fn create_controller(
     entity: String,
     local_data: Arc<Arc<Mutex<LocalContext>>>,
@@ -219,9 +441,20 @@ pub async fn evict_controller(
     uuid: Option<Uuid>,
     local_data: Arc<Arc<Mutex<LocalContext>>>,
     bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
+    encryption: DataEncryptContext,
     actor: DataExecutor,
 ) -> Result<String, Error> {
     if uuid.is_none() {
+        let is_created = if let Ok(guard) = local_data.lock() {
+            guard.contains_key(&entity)
+        } else {
+            return Err(Error::LockData);
+        };
+        if !is_created {
+            return Err(Error::EntityNotCreated(entity));
+        }
+
         let message = format!("Entity {} evicted", &entity);
         let (offset, is_empty) = actor.send(EvictEntity::new(&entity)).await??;
 
@@ -244,9 +477,32 @@ pub async fn evict_controller(
         };
 
         actor.send(LocalData::new(local_data)).await??;
+        actor
+            .send(EvictUniqueKeys::new(&entity, uniqueness))
+            .await??;
+        actor
+            .send(EvictEncryption::new(&entity, encryption))
+            .await??;
         Ok(DeleteOrEvictEntityResponse::new(entity, None, message).write())
     } else {
         let id = uuid.unwrap();
+        let registry = if let Ok(guard) = local_data.lock() {
+            guard.get(&entity).and_then(|ids| ids.get(&id)).cloned()
+        } else {
+            return Err(Error::LockData);
+        };
+        let has_uniques = if let Ok(guard) = uniqueness.lock() {
+            guard.contains_key(&entity)
+        } else {
+            return Err(Error::LockData);
+        };
+        let evicted_state = match registry {
+            Some(registry) if has_uniques => {
+                let state_str = actor.send(registry).await??;
+                actor.send(State(state_str)).await??
+            }
+            _ => HashMap::new(),
+        };
         let (offset, is_empty) = actor.send(EvictEntityId::new(&entity, id)).await??;
 
         if is_empty {
@@ -271,11 +527,66 @@ pub async fn evict_controller(
         };
         actor.send(LocalData::new(local_data)).await??;
 
+        actor
+            .send(UpdateUniqueKeys {
+                entity: entity.to_owned(),
+                previous: evicted_state,
+                current: HashMap::new(),
+                uniqueness,
+            })
+            .await??;
+
         let message = format!("Entity {} with id {} evicted", &entity, &id);
         Ok(DeleteOrEvictEntityResponse::new(entity, uuid, message).write())
     }
 }
 
+/// Deletes every entity of `entity`, unlike `EVICT` the entity stays created with its unique fields,
+/// encrypted keys and schema.
+pub async fn truncate_controller(
+    entity: String,
+    local_data: DataLocalContext,
+    bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let is_created = if let Ok(guard) = local_data.lock() {
+        guard.contains_key(&entity)
+    } else {
+        return Err(Error::LockData);
+    };
+    if !is_created {
+        return Err(Error::EntityNotCreated(entity));
+    }
+
+    let message = format!("Entity {} truncated", &entity);
+    let (offset, is_empty) = actor.send(TruncateEntity::new(&entity)).await??;
+
+    if is_empty {
+        bytes_counter.store(0, Ordering::SeqCst);
+    }
+    bytes_counter.fetch_add(offset, Ordering::SeqCst);
+    actor
+        .send(OffsetCounter::new(bytes_counter.load(Ordering::SeqCst)))
+        .await??;
+
+    let local_data = {
+        let mut local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        local_data.insert(entity.clone(), BTreeMap::new());
+        local_data.clone()
+    };
+
+    actor.send(LocalData::new(local_data)).await??;
+    actor
+        .send(TruncateUniqueKeys::new(&entity, uniqueness))
+        .await??;
+    Ok(DeleteOrEvictEntityResponse::new(entity, None, message).write())
+}
+
 pub async fn create_unique_controller(
     entity: &str,
     uniques: Vec<String>,
@@ -303,6 +614,20 @@ pub async fn create_unique_controller(
     }
 }
 
+/// Follows a renamed or dropped field in the `PRIMARY KEY`, the schema and its `OR DEFAULT` values
+/// once the field `response` succeeded, so later inserts are validated by the new field name.
+pub async fn field_keys_controller(
+    response: Result<String, Error>,
+    primary_key: RenamePrimaryKeyField,
+    schema: RenameSchemaField,
+    actor: &DataExecutor,
+) -> Result<String, Error> {
+    let response = response?;
+    actor.send(primary_key).await??;
+    actor.send(schema).await??;
+    Ok(response)
+}
+
 pub async fn create_encrypts_controller(
     entity: &str,
     encrypts: Vec<String>,
@@ -330,22 +655,302 @@ pub async fn create_encrypts_controller(
     }
 }
 
+pub async fn create_document_store_controller(
+    entity: String,
+    local_data: DataLocalContext,
+    bytes_counter: DataAtomicUsize,
+    document_stores: DataDocumentStoreContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    create_controller(
+        entity.clone(),
+        local_data.into_inner(),
+        bytes_counter,
+        actor.clone(),
+    )
+    .await?;
+    actor
+        .send(CreateDocumentStore {
+            entity: entity.to_owned(),
+            data: document_stores,
+        })
+        .await??;
+
+    let message = format!("Document store `{}` created", &entity);
+    Ok(CreateEntityResponse::new(entity, message).write())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create_schema_controller(
+    entity: String,
+    schema: HashMap<String, TypeTag>,
+    defaults: Entity,
+    local_data: DataLocalContext,
+    schemas: DataSchemaContext,
+    schema_defaults: DataSchemaDefaultsContext,
+    document_stores: DataDocumentStoreContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    {
+        let local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if !local_data.contains_key(&entity) {
+            return Err(Error::EntityNotCreated(entity));
+        }
+    }
+    if let Ok(guard) = document_stores.lock() {
+        if guard.contains(&entity) {
+            return Err(Error::DocumentStoreSchema(entity));
+        }
+    } else {
+        return Err(Error::LockData);
+    }
+
+    actor
+        .send(CreateWithSchema {
+            entity: entity.to_owned(),
+            schema,
+            defaults,
+            data: schemas,
+            defaults_data: schema_defaults,
+        })
+        .await??;
+
+    let message = format!("Schema for entity {} created", &entity);
+    Ok(CreateEntityResponse::new(entity, message).write())
+}
+
+/// Coerces an INSERT content toward the entity schema, when the entity has one, after setting the
+/// `OR DEFAULT` values of its missing fields. Document stores skip schema validation.
+fn schema_content(
+    entity: &str,
+    mut content: HashMap<String, Types>,
+    schemas: &DataSchemaContext,
+    schema_defaults: &DataSchemaDefaultsContext,
+    document_stores: &DataDocumentStoreContext,
+) -> Result<HashMap<String, Types>, Error> {
+    if let Ok(guard) = document_stores.lock() {
+        if guard.contains(entity) {
+            return Ok(content);
+        }
+    } else {
+        return Err(Error::LockData);
+    }
+    if let Ok(guard) = schema_defaults.lock() {
+        if let Some(defaults) = guard.get(entity) {
+            apply_defaults(&mut content, defaults);
+        }
+    } else {
+        return Err(Error::LockData);
+    }
+    let schemas = if let Ok(guard) = schemas.lock() {
+        guard
+    } else {
+        return Err(Error::LockData);
+    };
+    match schemas.get(entity) {
+        Some(schema) => coerce_to_schema(content, schema),
+        None => Ok(content),
+    }
+}
+
+/// Id of the entity written by an `INSERT`, a conflicting `DO UPDATE` responds with an `UpdateEntityResponse`.
+fn inserted_id(response: &str) -> Result<Uuid, Error> {
+    ron::de::from_str::<InsertEntityResponse>(response)
+        .map(|response| response.uuid)
+        .or_else(|_| {
+            ron::de::from_str::<UpdateEntityResponse>(response).map(|response| response.uuid)
+        })
+        .map_err(Error::Ron)
+}
+
+/// Runs the sub-inserts of a nested insert and then the outer insert with their ids set as `Types::Ref`.
+/// Every insert is checked first, so a failing nested insert writes none of them.
+#[allow(clippy::too_many_arguments)]
+async fn nested_insert_controller(
+    insert: Wql,
+    sub_inserts: Vec<(String, Wql)>,
+    local_data: DataLocalContext,
+    bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
+    encryption: DataEncryptContext,
+    (schemas, schema_defaults, document_stores): (
+        DataSchemaContext,
+        DataSchemaDefaultsContext,
+        DataDocumentStoreContext,
+    ),
+    exec_options: DataExecOptions,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let inserts = sub_inserts
+        .into_iter()
+        .map(|(field, sub_insert)| (Some(field), sub_insert))
+        .chain(std::iter::once((None, insert)))
+        .map(|(field, insert)| match insert {
+            Wql::Insert(entity, content, _, on_conflict) => {
+                Ok((field, InsertArgs::new(entity, content, on_conflict)))
+            }
+            _ => Err(Error::QueryFormat(String::from(
+                "Nested values can only be INSERT statements",
+            ))),
+        })
+        .collect::<Result<Vec<(Option<String>, InsertArgs)>, Error>>()?;
+    let prepare = |entity: &str, content: Entity| {
+        schema_content(
+            entity,
+            content,
+            &schemas,
+            &schema_defaults,
+            &document_stores,
+        )
+    };
+
+    // Sub-insert ids are not known yet, the outer insert is checked with nil ids
+    let placeholders = inserts
+        .iter()
+        .filter_map(|(field, args)| {
+            let field = field.clone()?;
+            Some((field, Types::Ref(args.entity.clone(), Uuid::nil())))
+        })
+        .collect::<Entity>();
+    let mut taken = HashSet::new();
+    for (field, args) in &inserts {
+        let mut content = args.content.clone();
+        if field.is_none() {
+            content.extend(placeholders.clone());
+        }
+        let content = prepare(&args.entity, content)?;
+        let guard = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if !guard.contains_key(&args.entity) {
+            return Err(Error::EntityNotCreated(args.entity.clone()));
+        }
+        if args.on_conflict.is_none() {
+            check_unique_keys(&args.entity, &content, &uniqueness, &mut taken)?;
+        }
+    }
+
+    let mut refs = Entity::new();
+    let mut response = String::new();
+    for (field, mut args) in inserts {
+        if field.is_none() {
+            args.content.extend(std::mem::take(&mut refs));
+        }
+        let content = prepare(&args.entity, args.content)?;
+        response = insert_controller(
+            InsertArgs::new(args.entity.clone(), content, args.on_conflict),
+            local_data.clone().into_inner(),
+            bytes_counter.clone(),
+            uniqueness.clone(),
+            encryption.clone(),
+            exec_options.clone(),
+            actor.clone(),
+        )
+        .await?;
+        if let Some(field) = field {
+            refs.insert(field, Types::Ref(args.entity, inserted_id(&response)?));
+        }
+    }
+    Ok(response)
+}
+
+/// Fails with `DuplicatedUnique` when a unique value of `content` is held by another entity or is in
+/// `taken`, the unique values of the entities checked before. Nil `Types::Ref` ids are never taken.
+fn check_unique_keys(
+    entity: &str,
+    content: &HashMap<String, Types>,
+    uniqueness: &DataUniquenessContext,
+    taken: &mut HashSet<(String, String, String)>,
+) -> Result<(), Error> {
+    let guard = if let Ok(guard) = uniqueness.lock() {
+        guard
+    } else {
+        return Err(Error::LockData);
+    };
+    let uniques = match guard.get(entity) {
+        Some(uniques) => uniques,
+        None => return Ok(()),
+    };
+    let is_placeholder = |value: &Types| matches!(value, Types::Ref(_, id) if id.is_nil());
+    for (key, values) in uniques {
+        let value = match unique_value(key, content) {
+            Some(Types::Vector(fields)) if fields.iter().any(is_placeholder) => continue,
+            Some(value) if is_placeholder(&value) => continue,
+            Some(value) => value,
+            None => continue,
+        };
+        let debug = format!("{:?}", value);
+        if values.contains(&debug) || !taken.insert((entity.to_owned(), key.to_owned(), debug)) {
+            return Err(Error::DuplicatedUnique(
+                entity.to_owned(),
+                key.to_owned(),
+                value,
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub async fn insert_controller(
     args: InsertArgs,
     local_data: Arc<Arc<Mutex<LocalContext>>>,
     bytes_counter: DataAtomicUsize,
     uniqueness: DataUniquenessContext,
     encryption: DataEncryptContext,
-    hashing_cost: DataU32,
+    exec_options: DataExecOptions,
     actor: DataExecutor,
 ) -> Result<String, Error> {
+    let conflict = match &args.on_conflict {
+        Some(OnConflict::Update(field)) | Some(OnConflict::Nothing(field)) => {
+            conflicting_id(
+                &args.entity,
+                field,
+                &args.content,
+                &local_data,
+                &uniqueness,
+                &actor,
+            )
+            .await?
+        }
+        None => None,
+    };
+    match (conflict, &args.on_conflict) {
+        (Some(id), Some(OnConflict::Nothing(field))) => {
+            let message = format!(
+                "Entity {} not inserted, `{}` conflicts with Uuid {}",
+                &args.entity, field, id
+            );
+            return Ok(InsertEntityResponse::new(args.entity, id, message).write());
+        }
+        (Some(id), _) => {
+            return update_set_controller(
+                UpdateArgs::new(args.entity, args.content, id),
+                local_data,
+                bytes_counter,
+                uniqueness,
+                encryption,
+                exec_options,
+                actor,
+            )
+            .await
+        }
+        (None, _) => (),
+    }
+
     let mut offset = bytes_counter.load(Ordering::SeqCst);
+    let uuid = exec_options.id_strategy.id_for(&args.entity, &args.content);
     let encrypted_content = actor
         .send(EncryptContent::new(
             &args.entity,
             args.content,
             encryption.into_inner(),
-            *hashing_cost.into_inner(),
+            exec_options.hashing_cost,
         ))
         .await??;
     let content_log =
@@ -357,8 +962,13 @@ pub async fn insert_controller(
         } else {
             return Err(Error::LockData);
         };
-        if !local_data.contains_key(&args.entity) {
-            return Err(Error::EntityNotCreated(args.entity));
+        match local_data.get(&args.entity) {
+            None => return Err(Error::EntityNotCreated(args.entity)),
+            // Content based ids are the same for the same content
+            Some(ids) if ids.contains_key(&uuid) => {
+                return Err(Error::DuplicatedId(args.entity, uuid))
+            }
+            Some(_) => (),
         }
     }
 
@@ -372,7 +982,7 @@ pub async fn insert_controller(
         .await??;
 
     let content_value = actor
-        .send(InsertEntityContent::new(&args.entity, &content_log))
+        .send(InsertEntityContent::new(&args.entity, &content_log, uuid))
         .await??;
 
     if content_value.3 {
@@ -412,13 +1022,62 @@ pub async fn insert_controller(
     Ok(InsertEntityResponse::new(args.entity, content_value.1, message).write())
 }
 
+/// Id of the entity tree `entity` holding the value of the unique `field` of `content`, entity
+/// states are only read when the uniqueness context already contains that value.
+async fn conflicting_id(
+    entity: &str,
+    field: &str,
+    content: &HashMap<String, Types>,
+    local_data: &Mutex<LocalContext>,
+    uniqueness: &DataUniquenessContext,
+    actor: &DataExecutor,
+) -> Result<Option<Uuid>, Error> {
+    let registries = if let Ok(guard) = local_data.lock() {
+        match guard.get(entity) {
+            Some(registries) => registries.clone(),
+            None => return Err(Error::EntityNotCreated(entity.to_owned())),
+        }
+    } else {
+        return Err(Error::LockData);
+    };
+    let value = match content.get(field) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let is_taken = if let Ok(guard) = uniqueness.lock() {
+        match guard.get(entity).and_then(|uniques| uniques.get(field)) {
+            Some(values) => values.contains(&format!("{:?}", value)),
+            None => {
+                return Err(Error::ConflictFieldNotUnique(
+                    entity.to_owned(),
+                    field.to_owned(),
+                ))
+            }
+        }
+    } else {
+        return Err(Error::LockData);
+    };
+    if !is_taken {
+        return Ok(None);
+    }
+
+    for (id, registry) in registries {
+        let state_str = actor.send(registry).await??;
+        let state = actor.send(State(state_str)).await??;
+        if state.get(field) == Some(value) {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
 pub async fn update_set_controller(
     args: UpdateArgs,
     local_data: Arc<Arc<Mutex<LocalContext>>>,
     bytes_counter: DataAtomicUsize,
     uniqueness: DataUniquenessContext,
     encryption: DataEncryptContext,
-    hashing_cost: DataU32,
+    exec_options: DataExecOptions,
     actor: DataExecutor,
 ) -> Result<String, Error> {
     let mut offset = bytes_counter.load(Ordering::SeqCst);
@@ -427,7 +1086,7 @@ pub async fn update_set_controller(
             &args.entity,
             args.content,
             encryption.into_inner(),
-            *hashing_cost.into_inner(),
+            exec_options.hashing_cost,
         ))
         .await??;
     let content_log =
@@ -448,15 +1107,6 @@ pub async fn update_set_controller(
         }
     }
 
-    let uniqueness = uniqueness.into_inner();
-    actor
-        .send(CheckForUniqueKeys {
-            entity: args.entity.to_owned(),
-            content: encrypted_content.to_owned(),
-            uniqueness,
-        })
-        .await??;
-
     let previous_entry = {
         let local_data = if let Ok(guard) = local_data.lock() {
             guard
@@ -469,12 +1119,22 @@ pub async fn update_set_controller(
 
     let previous_state_str = actor.send(previous_entry.to_owned()).await??;
     let mut previous_state = actor.send(State(previous_state_str)).await??;
+    let state_before = previous_state.clone();
 
     encrypted_content.into_iter().for_each(|(k, v)| {
         let local_state = previous_state.entry(k).or_insert_with(|| v.clone());
         *local_state = v;
     });
 
+    actor
+        .send(UpdateUniqueKeys {
+            entity: args.entity.to_owned(),
+            previous: state_before,
+            current: previous_state.clone(),
+            uniqueness,
+        })
+        .await??;
+
     let state_log =
         to_string_pretty(&previous_state, pretty_config()).map_err(Error::Serialization)?;
 
@@ -563,15 +1223,6 @@ pub async fn update_content_controller(
         }
     }
 
-    let uniqueness = uniqueness.into_inner();
-    actor
-        .send(CheckForUniqueKeys {
-            entity: args.entity.to_owned(),
-            content: args.content.to_owned(),
-            uniqueness,
-        })
-        .await??;
-
     let previous_entry = {
         let local_data = if let Ok(guard) = local_data.lock() {
             guard
@@ -584,11 +1235,21 @@ pub async fn update_content_controller(
 
     let previous_state_str = actor.send(previous_entry.to_owned()).await??;
     let mut previous_state = actor.send(State(previous_state_str)).await??;
+    let state_before = previous_state.clone();
 
     args.content
         .into_iter()
         .for_each(|(k, v)| update_content_state(&mut previous_state, k, v));
 
+    actor
+        .send(UpdateUniqueKeys {
+            entity: args.entity.to_owned(),
+            previous: state_before,
+            current: previous_state.clone(),
+            uniqueness,
+        })
+        .await??;
+
     let state_log =
         to_string_pretty(&previous_state, pretty_config()).map_err(Error::Serialization)?;
 
@@ -640,57 +1301,92 @@ pub async fn delete_controller(
     id: String,
     local_data: Arc<Arc<Mutex<LocalContext>>>,
     bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
     actor: DataExecutor,
 ) -> Result<String, Error> {
     let uuid = Uuid::from_str(&id)?;
     let message = format!("Entity {} with Uuid {} deleted", &entity, id);
-    let mut offset = bytes_counter.load(Ordering::SeqCst);
 
+    let deletion = deletion(&entity, uuid, &local_data, &actor).await?;
+    actor
+        .send(UpdateUniqueKeys {
+            entity: entity.to_owned(),
+            previous: deletion.state.clone(),
+            current: deletion.state_to_be.clone(),
+            uniqueness,
+        })
+        .await??;
+    write_deletion(&entity, deletion, &local_data, &bytes_counter, &actor).await?;
+
+    Ok(DeleteOrEvictEntityResponse::new(entity, Some(uuid), message).write())
+}
+
+/// Current state of a deleted entity id and the state, with its registry, it goes back to.
+struct Deletion {
+    uuid: Uuid,
+    state: HashMap<String, Types>,
+    state_to_be: HashMap<String, Types>,
+    registry_to_be: DataRegister,
+}
+
+async fn deletion(
+    entity: &str,
+    uuid: Uuid,
+    local_data: &Mutex<LocalContext>,
+    actor: &DataExecutor,
+) -> Result<Deletion, Error> {
     let previous_entry = {
         let local_data = if let Ok(guard) = local_data.lock() {
             guard
         } else {
             return Err(Error::LockData);
         };
-        if !local_data.contains_key(&entity) {
-            return Err(Error::EntityNotCreated(entity));
-        } else if local_data.contains_key(&entity)
-            && !local_data.get(&entity).unwrap().contains_key(&uuid)
-        {
-            return Err(Error::UuidNotCreatedForEntity(entity, uuid));
+        match local_data.get(entity) {
+            None => return Err(Error::EntityNotCreated(entity.to_owned())),
+            Some(map) => match map.get(&uuid) {
+                Some(previous_entry) => previous_entry.clone(),
+                None => return Err(Error::UuidNotCreatedForEntity(entity.to_owned(), uuid)),
+            },
         }
-
-        let previous_entry = local_data.get(&entity).unwrap().get(&uuid).unwrap();
-        previous_entry.clone()
     };
 
     let previous_state_str = actor.send(previous_entry.to_owned()).await??;
+    let state = actor.send(State(previous_state_str.clone())).await??;
     let two_registries_ago = actor.send(PreviousRegistry(previous_state_str)).await??;
 
-    let state_to_be = if let Some(reg) = two_registries_ago {
+    let (state_to_be, registry_to_be) = if let Some(reg) = two_registries_ago {
         let state_str = actor.send(reg.to_owned()).await??;
-        (actor.send(State(state_str)).await??, reg.to_owned())
+        (actor.send(State(state_str)).await??, reg)
     } else {
-        let local_data = if let Ok(guard) = local_data.lock() {
-            guard
-        } else {
-            return Err(Error::LockData);
-        };
-        let insert_reg = local_data.get(&entity).unwrap().get(&uuid).unwrap();
-        (HashMap::new(), insert_reg.to_owned())
+        (HashMap::new(), previous_entry)
     };
+    Ok(Deletion {
+        uuid,
+        state,
+        state_to_be,
+        registry_to_be,
+    })
+}
 
+async fn write_deletion(
+    entity: &str,
+    deletion: Deletion,
+    local_data: &Mutex<LocalContext>,
+    bytes_counter: &DataAtomicUsize,
+    actor: &DataExecutor,
+) -> Result<(), Error> {
+    let mut offset = bytes_counter.load(Ordering::SeqCst);
     let content_log =
-        to_string_pretty(&state_to_be.0, pretty_config()).map_err(Error::Serialization)?;
+        to_string_pretty(&deletion.state_to_be, pretty_config()).map_err(Error::Serialization)?;
 
-    let previous_register_log =
-        to_string_pretty(&state_to_be.1, pretty_config()).map_err(Error::Serialization)?;
+    let previous_register_log = to_string_pretty(&deletion.registry_to_be, pretty_config())
+        .map_err(Error::Serialization)?;
 
     let content_value = actor
         .send(DeleteId::new(
-            &entity,
+            entity,
             &content_log,
-            uuid,
+            deletion.uuid,
             &previous_register_log,
         ))
         .await??;
@@ -711,8 +1407,8 @@ pub async fn delete_controller(
         } else {
             return Err(Error::LockData);
         };
-        if let Some(map) = local_data.get_mut(&entity) {
-            if let Some(reg) = map.get_mut(&uuid) {
+        if let Some(map) = local_data.get_mut(entity) {
+            if let Some(reg) = map.get_mut(&deletion.uuid) {
                 *reg = local_data_register;
             }
         }
@@ -725,8 +1421,64 @@ pub async fn delete_controller(
     actor
         .send(OffsetCounter::new(bytes_counter.load(Ordering::SeqCst)))
         .await??;
+    Ok(())
+}
 
-    Ok(DeleteOrEvictEntityResponse::new(entity, Some(uuid), message).write())
+/// Deletes every id of `uuids` or none of them, repeated ids are deleted once. Every id is read and
+/// its unique values moved before the first delete is written, a unique value that cannot be moved
+/// puts the unique values of the previous ids back.
+pub async fn delete_many_controller(
+    entity: String,
+    uuids: Vec<Uuid>,
+    local_data: DataLocalContext,
+    bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let mut seen = HashSet::new();
+    let uuids = uuids
+        .into_iter()
+        .filter(|uuid| seen.insert(*uuid))
+        .collect::<Vec<Uuid>>();
+    let local_data = local_data.into_inner();
+
+    let mut deletions = Vec::new();
+    for uuid in &uuids {
+        deletions.push(deletion(&entity, *uuid, &local_data, &actor).await?);
+    }
+
+    for (i, deletion) in deletions.iter().enumerate() {
+        let moved = actor
+            .send(UpdateUniqueKeys {
+                entity: entity.to_owned(),
+                previous: deletion.state.clone(),
+                current: deletion.state_to_be.clone(),
+                uniqueness: uniqueness.clone(),
+            })
+            .await
+            .map_err(Error::from)
+            .and_then(|moved| moved);
+        if let Err(e) = moved {
+            for deletion in deletions[..i].iter().rev() {
+                actor
+                    .send(UpdateUniqueKeys {
+                        entity: entity.to_owned(),
+                        previous: deletion.state_to_be.clone(),
+                        current: deletion.state.clone(),
+                        uniqueness: uniqueness.clone(),
+                    })
+                    .await??;
+            }
+            return Err(e);
+        }
+    }
+
+    for deletion in deletions {
+        write_deletion(&entity, deletion, &local_data, &bytes_counter, &actor).await?;
+    }
+
+    let message = format!("Entity {} with Uuids {:?} deleted", &entity, uuids);
+    Ok(DeleteOrEvictEntityResponse::new(entity, None, message).write())
 }
 
 pub async fn match_update_set_controller(
@@ -735,7 +1487,7 @@ pub async fn match_update_set_controller(
     bytes_counter: DataAtomicUsize,
     uniqueness: DataUniquenessContext,
     encryption: DataEncryptContext,
-    hashing_cost: DataU32,
+    exec_options: DataExecOptions,
     actor: DataExecutor,
 ) -> Result<String, Error> {
     let previous_entry = {
@@ -772,26 +1524,27 @@ pub async fn match_update_set_controller(
             &args.entity,
             args.content.clone(),
             encryption.into_inner(),
-            *hashing_cost.into_inner(),
+            exec_options.hashing_cost,
         ))
         .await??;
     let content_log =
         to_string_pretty(&encrypted_content, pretty_config()).map_err(Error::Serialization)?;
 
-    let uniqueness = uniqueness.into_inner();
+    let state_before = previous_state.clone();
+    args.content.into_iter().for_each(|(k, v)| {
+        let local_state = previous_state.entry(k).or_insert_with(|| v.clone());
+        *local_state = v;
+    });
+
     actor
-        .send(CheckForUniqueKeys {
+        .send(UpdateUniqueKeys {
             entity: args.entity.to_owned(),
-            content: args.content.to_owned(),
+            previous: state_before,
+            current: previous_state.clone(),
             uniqueness,
         })
         .await??;
 
-    args.content.into_iter().for_each(|(k, v)| {
-        let local_state = previous_state.entry(k).or_insert_with(|| v.clone());
-        *local_state = v;
-    });
-
     let state_log =
         to_string_pretty(&previous_state, pretty_config()).map_err(Error::Serialization)?;
 
@@ -840,3 +1593,218 @@ pub async fn match_update_set_controller(
     let message = format!("Entity {} with Uuid {} updated", &args.entity, &args.id);
     Ok(UpdateEntityResponse::new(args.entity, args.id, state_log, message).write())
 }
+
+pub async fn rename_field_controller(
+    args: RenameFieldArgs,
+    local_data: DataLocalContext,
+    bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
+    encryption: DataEncryptContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let registries = {
+        let local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if let Some(map) = local_data.get(&args.entity) {
+            map.clone()
+        } else {
+            return Err(Error::EntityNotCreated(args.entity));
+        }
+    };
+
+    let mut states = Vec::new();
+    for (id, registry) in registries {
+        let state_str = actor.send(registry.to_owned()).await??;
+        let state = actor.send(State(state_str)).await??;
+        states.push((id, registry, state));
+    }
+
+    let has_field = |field: &str| states.iter().any(|(_, _, state)| state.contains_key(field));
+    if has_field(&args.from) && has_field(&args.to) {
+        return Err(Error::FieldAlreadyExists(args.entity, args.to));
+    }
+
+    let mut renamed = BTreeMap::new();
+    for (id, previous_entry, mut state) in states {
+        let value = if let Some(value) = state.remove(&args.from) {
+            value
+        } else {
+            continue;
+        };
+        let mut content = HashMap::new();
+        content.insert(args.to.to_owned(), value.clone());
+        state.insert(args.to.to_owned(), value);
+
+        let content_log =
+            to_string_pretty(&content, pretty_config()).map_err(Error::Serialization)?;
+        let state_log = to_string_pretty(&state, pretty_config()).map_err(Error::Serialization)?;
+
+        let mut offset = bytes_counter.load(Ordering::SeqCst);
+        let content_value = actor
+            .send(UpdateContentEntityContent::new(
+                &args.entity,
+                &state_log,
+                &content_log,
+                id,
+                &to_string_pretty(&previous_entry, pretty_config())
+                    .map_err(Error::Serialization)?,
+            ))
+            .await??;
+
+        if content_value.2 {
+            bytes_counter.store(0, Ordering::SeqCst);
+            offset = 0;
+        }
+        renamed.insert(
+            id,
+            DataRegister {
+                offset,
+                bytes_length: content_value.1,
+                file_name: content_value.0.format("data/%Y_%m_%d.log").to_string(),
+            },
+        );
+        bytes_counter.fetch_add(content_value.1, Ordering::SeqCst);
+    }
+
+    let local_data = {
+        let mut local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if let Some(map) = local_data.get_mut(&args.entity) {
+            map.extend(renamed.clone());
+        }
+        local_data.clone()
+    };
+    actor.send(LocalData::new(local_data)).await??;
+    actor
+        .send(OffsetCounter::new(bytes_counter.load(Ordering::SeqCst)))
+        .await??;
+
+    actor
+        .send(RenameUniqueKey {
+            entity: args.entity.to_owned(),
+            from: args.from.to_owned(),
+            to: args.to.to_owned(),
+            data: uniqueness,
+        })
+        .await??;
+    actor
+        .send(RenameEncryptedKey {
+            entity: args.entity.to_owned(),
+            from: args.from.to_owned(),
+            to: args.to.to_owned(),
+            data: encryption,
+        })
+        .await??;
+
+    let message = format!(
+        "Field {} renamed to {} for entity {}",
+        &args.from, &args.to, &args.entity
+    );
+    let uuids = renamed.into_keys().collect();
+    Ok(RenameFieldResponse::new(args.entity, uuids, message).write())
+}
+
+pub async fn drop_field_controller(
+    entity: String,
+    field: String,
+    local_data: DataLocalContext,
+    bytes_counter: DataAtomicUsize,
+    uniqueness: DataUniquenessContext,
+    encryption: DataEncryptContext,
+    actor: DataExecutor,
+) -> Result<String, Error> {
+    let registries = {
+        let local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if let Some(map) = local_data.get(&entity) {
+            map.clone()
+        } else {
+            return Err(Error::EntityNotCreated(entity));
+        }
+    };
+
+    let mut dropped = BTreeMap::new();
+    for (id, previous_entry) in registries {
+        let state_str = actor.send(previous_entry.to_owned()).await??;
+        let mut state = actor.send(State(state_str)).await??;
+        if state.remove(&field).is_none() {
+            continue;
+        }
+        let mut content = HashMap::new();
+        content.insert(field.to_owned(), Types::Nil);
+
+        let content_log =
+            to_string_pretty(&content, pretty_config()).map_err(Error::Serialization)?;
+        let state_log = to_string_pretty(&state, pretty_config()).map_err(Error::Serialization)?;
+
+        let mut offset = bytes_counter.load(Ordering::SeqCst);
+        let content_value = actor
+            .send(UpdateContentEntityContent::new(
+                &entity,
+                &state_log,
+                &content_log,
+                id,
+                &to_string_pretty(&previous_entry, pretty_config())
+                    .map_err(Error::Serialization)?,
+            ))
+            .await??;
+
+        if content_value.2 {
+            bytes_counter.store(0, Ordering::SeqCst);
+            offset = 0;
+        }
+        dropped.insert(
+            id,
+            DataRegister {
+                offset,
+                bytes_length: content_value.1,
+                file_name: content_value.0.format("data/%Y_%m_%d.log").to_string(),
+            },
+        );
+        bytes_counter.fetch_add(content_value.1, Ordering::SeqCst);
+    }
+
+    let local_data = {
+        let mut local_data = if let Ok(guard) = local_data.lock() {
+            guard
+        } else {
+            return Err(Error::LockData);
+        };
+        if let Some(map) = local_data.get_mut(&entity) {
+            map.extend(dropped.clone());
+        }
+        local_data.clone()
+    };
+    actor.send(LocalData::new(local_data)).await??;
+    actor
+        .send(OffsetCounter::new(bytes_counter.load(Ordering::SeqCst)))
+        .await??;
+
+    actor
+        .send(DropUniqueKey {
+            entity: entity.to_owned(),
+            field: field.to_owned(),
+            data: uniqueness,
+        })
+        .await??;
+    actor
+        .send(DropEncryptedKey {
+            entity: entity.to_owned(),
+            field: field.to_owned(),
+            data: encryption,
+        })
+        .await??;
+
+    let message = format!("Field {} dropped from entity {}", &field, &entity);
+    let uuids = dropped.into_keys().collect();
+    Ok(DropFieldResponse::new(entity, uuids, message).write())
+}
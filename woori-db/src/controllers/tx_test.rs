@@ -80,6 +80,68 @@ async fn test_create_encrypts_post_ok() {
     clear();
 }
 
+#[actix_rt::test]
+async fn test_insert_composite_primary_key_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_composite_pk PRIMARY KEY (tenant, email)")
+        .uri("/wql/tx")
+        .to_request();
+
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    read::assert_uniques("uniques: [\"(tenant, email)\",]");
+
+    for tenant in ["t1", "t2"] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!(
+                "INSERT {{tenant: \"{}\", email: \"a@b.c\",}} INTO test_composite_pk",
+                tenant
+            ))
+            .uri("/wql/tx")
+            .to_request();
+
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {tenant: \"t1\", email: \"a@b.c\",} INTO test_composite_pk")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_client_error());
+    let body = resp.take_body();
+    let body = body.as_ref().unwrap();
+    assert_eq!(
+        &Body::from(
+            "(\n error_type: \"DuplicatedUnique\",\n error_message: \"key `(tenant, email)` in entity `test_composite_pk` already contains value `Vector([String(\\\"t1\\\"), String(\\\"a@b.c\\\")])`\",\n)"
+        ),
+        body
+    );
+    clear();
+}
+
+#[actix_rt::test]
+async fn test_begin_unsupported_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("BEGIN ISOLATION READ COMMITTED")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_client_error());
+    let body = resp.take_body();
+    let body = body.as_ref().unwrap();
+    assert_eq!(&Body::from("(\n error_type: \"Unsupported\",\n error_message: \"BEGIN ISOLATION READ COMMITTED is not supported by WooriDB\",\n)"), body);
+}
+
 #[actix_rt::test]
 async fn test_create_post_duplicated_err() {
     let mut app = test::init_service(App::new().configure(routes)).await;
@@ -105,6 +167,41 @@ async fn test_create_post_duplicated_err() {
     clear();
 }
 
+#[actix_rt::test]
+async fn test_create_duplicated_keeps_keys_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_recreate_keys UNIQUES #{a,}")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let duplicated_req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_recreate_keys PRIMARY KEY (b) ENCRYPT #{a,}")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, duplicated_req).await;
+    assert!(resp.status().is_client_error());
+
+    for (a, is_ok) in [(1, true), (2, true), (1, false)] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!(
+                "INSERT {{a: {}, b: 7,}} INTO test_recreate_keys",
+                a
+            ))
+            .uri("/wql/tx")
+            .to_request();
+
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().is_success(), is_ok);
+    }
+    clear();
+}
+
 #[actix_rt::test]
 async fn test_create_post_bad_request() {
     let mut app = test::init_service(App::new().configure(routes)).await;
@@ -133,11 +230,30 @@ async fn test_unkwon_wql_post() {
     let body = resp.take_body();
     let body = body.as_ref().unwrap();
     assert_eq!(
-        &Body::from("(\n error_type: \"QueryFormat\",\n error_message: \"\\\"Symbol `DO` not implemented\\\"\",\n)"), 
+        &Body::from("(\n error_type: \"QueryFormat\",\n error_message: \"Symbol `DO` not implemented at offset 0\",\n)"), 
         body);
     clear();
 }
 
+#[actix_rt::test]
+async fn test_unbound_param_post() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: $1,} INTO test_unbound_param")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_client_error());
+    let body = resp.take_body().as_str().to_string();
+    assert_eq!(
+        body,
+        "(\n error_type: \"QueryFormat\",\n error_message: \"Parameter `$1` is not bound\",\n)"
+    );
+    clear();
+}
+
 #[actix_rt::test]
 async fn test_insert_post_ok() {
     let mut app = test::init_service(App::new().configure(routes)).await;
@@ -328,6 +444,230 @@ async fn test_update_uniqueness_set_post_ok() {
     );
     clear();
 }
+
+#[ignore]
+#[actix_rt::test]
+async fn test_insert_on_conflict_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_on_conflict UNIQUES #{email,}")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {email: \"julia@woori.db\", visits: 1,} INTO test_on_conflict")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp_insert = test::call_service(&mut app, req).await;
+    let body = resp_insert.take_body().as_str().to_string();
+    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+    let uuid = response.uuid;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {email: \"julia@woori.db\", visits: 2,} INTO test_on_conflict ON CONFLICT (email) DO NOTHING")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+    assert_eq!(response.uuid, uuid);
+    assert!(body.contains("not inserted, `email` conflicts"));
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {email: \"julia@woori.db\", visits: 3,} INTO test_on_conflict ON CONFLICT (email) DO UPDATE")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains(&uuid.to_string()));
+
+    read::assert_content("UPDATE_SET|");
+    read::assert_content("\"visits\": Integer(3),");
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_insert_nested_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for entity in &["test_nested_authors", "test_nested_posts"] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!("CREATE ENTITY {}", entity))
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {author: (INSERT {name: \"Ada\",} INTO test_nested_authors), title: \"x\",} INTO test_nested_posts")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains("test_nested_posts"));
+
+    read::assert_content("\"name\": String(\"Ada\"),");
+    read::assert_content("\"author\": Ref(\"test_nested_authors\",");
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_insert_nested_writes_nothing_post_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for payload in &[
+        "CREATE ENTITY test_nested_err_authors",
+        "CREATE ENTITY test_nested_err_posts UNIQUES #{title,}",
+        "INSERT {title: \"taken\",} INTO test_nested_err_posts",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(*payload)
+            .uri("/wql/tx")
+            .to_request();
+
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    for (payload, error) in &[
+        ("INSERT {author: (INSERT {name: \"Grace\",} INTO test_nested_err_authors), title: \"taken\",} INTO test_nested_err_posts", "DuplicatedUnique"),
+        ("INSERT {author: (INSERT {name: \"Grace\",} INTO test_nested_err_authors), title: \"new\",} INTO test_nested_err_missing", "EntityNotCreated"),
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(*payload)
+            .uri("/wql/tx")
+            .to_request();
+
+        let mut resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_client_error());
+        let body = resp.take_body().as_str().to_string();
+        assert!(body.contains(error));
+    }
+
+    assert_eq!(read::count_content("test_nested_err_authors"), 1);
+    clear();
+}
+
+#[actix_rt::test]
+async fn test_insert_on_conflict_not_unique_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_on_conflict_not_unique")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(
+            "INSERT {email: \"julia@woori.db\",} INTO test_on_conflict_not_unique ON CONFLICT (email) DO NOTHING",
+        )
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_client_error());
+    let body = resp.take_body();
+    let body = body.as_ref().unwrap();
+    assert_eq!(
+        &Body::from(
+            "(\n error_type: \"ConflictFieldNotUnique\",\n error_message: \"ON CONFLICT field `email` is not in the UNIQUES of entity `test_on_conflict_not_unique`\",\n)"
+        ),
+        body
+    );
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_uniques_follow_mutations_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_unique_follow UNIQUES #{ssn,}")
+        .uri("/wql/tx")
+        .to_request();
+    let _ = test::call_service(&mut app, req).await;
+
+    let insert = |ssn: usize| {
+        test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!("INSERT {{ssn: {},}} INTO test_unique_follow", ssn))
+            .uri("/wql/tx")
+            .to_request()
+    };
+    let mut resp = test::call_service(&mut app, insert(1)).await;
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    let first: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+
+    for payload in &[
+        format!(
+            "UPDATE test_unique_follow SET {{ssn: 2,}} INTO {}",
+            first.uuid
+        ),
+        format!(
+            "UPDATE test_unique_follow SET {{ssn: 2,}} INTO {}",
+            first.uuid
+        ),
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_owned())
+            .uri("/wql/tx")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    let resp = test::call_service(&mut app, insert(2)).await;
+    assert!(resp.status().is_client_error());
+    let mut resp = test::call_service(&mut app, insert(1)).await;
+    assert!(resp.status().is_success());
+    let body = resp.take_body().as_str().to_string();
+    let second: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(format!("DELETE {} FROM test_unique_follow", second.uuid))
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, insert(1)).await;
+    assert!(resp.status().is_success());
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(format!("EVICT {} FROM test_unique_follow", first.uuid))
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, insert(2)).await;
+    assert!(resp.status().is_success());
+    clear();
+}
+
 #[ignore]
 #[actix_rt::test]
 async fn test_update_content_post_ok() {
@@ -579,30 +919,33 @@ async fn test_delete_withput_update() {
 
 #[ignore]
 #[actix_rt::test]
-async fn test_match_all_update_post_ok() {
+async fn test_delete_many_post_ok() {
     let mut app = test::init_service(App::new().configure(routes)).await;
     let req = test::TestRequest::post()
         .header("Content-Type", "application/wql")
-        .set_payload("CREATE ENTITY test_match_all")
+        .set_payload("CREATE ENTITY test_delete_many")
         .uri("/wql/tx")
         .to_request();
 
     let _ = test::call_service(&mut app, req).await;
 
-    let req = test::TestRequest::post()
-        .header("Content-Type", "application/wql")
-        .set_payload("INSERT {a: 123, b: 12.3,} INTO test_match_all")
-        .uri("/wql/tx")
-        .to_request();
-
-    let mut resp_insert = test::call_service(&mut app, req).await;
-    let body = resp_insert.take_body().as_str().to_string();
-    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
-    let uuid = response.uuid;
+    let mut uuids = Vec::new();
+    for a in &[123, 456] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!("INSERT {{a: {},}} INTO test_delete_many", a))
+            .uri("/wql/tx")
+            .to_request();
+
+        let mut resp_insert = test::call_service(&mut app, req).await;
+        let body = resp_insert.take_body().as_str().to_string();
+        let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+        uuids.push(response.uuid);
+    }
 
     let payload = format!(
-        "MATCH ALL(a > 100, b <= 20.0) UPDATE test_match_all SET {{a: 43, c: Nil,}} INTO {}",
-        uuid
+        "DELETE FROM test_delete_many WHERE id IN ({}, {})",
+        uuids[0], uuids[1]
     );
     let req = test::TestRequest::post()
         .header("Content-Type", "application/wql")
@@ -613,41 +956,166 @@ async fn test_match_all_update_post_ok() {
     let resp = test::call_service(&mut app, req).await;
 
     assert!(resp.status().is_success());
+    read::assert_content(&format!("|{}|test_delete_many|{{}}|", uuids[0]));
+    read::assert_content(&format!("|{}|test_delete_many|{{}}|", uuids[1]));
     clear();
 }
 
 #[ignore]
 #[actix_rt::test]
-async fn test_match_any_update_post_ok() {
+async fn test_delete_many_all_or_nothing_post_err() {
     let mut app = test::init_service(App::new().configure(routes)).await;
     let req = test::TestRequest::post()
         .header("Content-Type", "application/wql")
-        .set_payload("CREATE ENTITY test_match_all")
+        .set_payload("CREATE ENTITY test_delete_many_atomic UNIQUES #{u,}")
         .uri("/wql/tx")
         .to_request();
 
     let _ = test::call_service(&mut app, req).await;
 
-    let req = test::TestRequest::post()
-        .header("Content-Type", "application/wql")
-        .set_payload("INSERT {a: 123, b: 12.3,} INTO test_match_all")
-        .uri("/wql/tx")
-        .to_request();
+    let mut uuids = Vec::new();
+    for u in &[1, 2, 3] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!("INSERT {{u: {},}} INTO test_delete_many_atomic", u))
+            .uri("/wql/tx")
+            .to_request();
+
+        let mut resp_insert = test::call_service(&mut app, req).await;
+        let body = resp_insert.take_body().as_str().to_string();
+        let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+        uuids.push(response.uuid);
+    }
+    let deleted = |uuid: &uuid::Uuid| {
+        read::count_content(&format!("|{}|test_delete_many_atomic|{{}}|", uuid))
+    };
+    let send = |payload: String| {
+        test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload)
+            .uri("/wql/tx")
+            .to_request()
+    };
+
+    // A repeated id is deleted once
+    let payload = format!(
+        "DELETE FROM test_delete_many_atomic WHERE id IN ({}, {})",
+        uuids[0], uuids[0]
+    );
+    let mut resp = test::call_service(&mut app, send(payload)).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_success());
+    assert!(body.contains(&format!("Uuids [{}] deleted", uuids[0])));
+    assert_eq!(deleted(&uuids[0]), 1);
 
-    let mut resp_insert = test::call_service(&mut app, req).await;
-    let body = resp_insert.take_body().as_str().to_string();
-    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
-    let uuid = response.uuid;
+    // A missing id deletes nothing
+    let payload = format!(
+        "DELETE FROM test_delete_many_atomic WHERE id IN ({}, 2df2b8cf-49da-474d-8a00-c596c0bb6fd1)",
+        uuids[1]
+    );
+    let resp = test::call_service(&mut app, send(payload)).await;
+    assert!(resp.status().is_client_error());
+    assert_eq!(deleted(&uuids[1]), 0);
 
+    // Id 3 goes back to `u: 3`, held by a new entity, so id 2 keeps `u: 2` too
     let payload = format!(
-        "MATCH ANY(a > 100, b <= 10.0) UPDATE test_match_all SET {{a: 43, c: Nil,}} INTO {}",
-        uuid
+        "UPDATE test_delete_many_atomic SET {{u: 4,}} INTO {}",
+        uuids[2]
     );
-    let req = test::TestRequest::post()
-        .header("Content-Type", "application/wql")
-        .set_payload(payload)
-        .uri("/wql/tx")
-        .to_request();
+    let resp = test::call_service(&mut app, send(payload)).await;
+    assert!(resp.status().is_success());
+    let payload = String::from("INSERT {u: 3,} INTO test_delete_many_atomic");
+    let resp = test::call_service(&mut app, send(payload)).await;
+    assert!(resp.status().is_success());
+
+    let payload = format!(
+        "DELETE FROM test_delete_many_atomic WHERE id IN ({}, {})",
+        uuids[1], uuids[2]
+    );
+    let mut resp = test::call_service(&mut app, send(payload)).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert!(body.contains("DuplicatedUnique"));
+    assert_eq!(deleted(&uuids[1]), 0);
+    assert_eq!(deleted(&uuids[2]), 0);
+
+    let payload = String::from("INSERT {u: 2,} INTO test_delete_many_atomic");
+    let resp = test::call_service(&mut app, send(payload)).await;
+    assert!(resp.status().is_client_error());
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_match_all_update_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_match_all")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: 123, b: 12.3,} INTO test_match_all")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp_insert = test::call_service(&mut app, req).await;
+    let body = resp_insert.take_body().as_str().to_string();
+    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+    let uuid = response.uuid;
+
+    let payload = format!(
+        "MATCH ALL(a > 100, b <= 20.0) UPDATE test_match_all SET {{a: 43, c: Nil,}} INTO {}",
+        uuid
+    );
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(payload)
+        .uri("/wql/tx")
+        .to_request();
+
+    let resp = test::call_service(&mut app, req).await;
+
+    assert!(resp.status().is_success());
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_match_any_update_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_match_all")
+        .uri("/wql/tx")
+        .to_request();
+
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: 123, b: 12.3,} INTO test_match_all")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp_insert = test::call_service(&mut app, req).await;
+    let body = resp_insert.take_body().as_str().to_string();
+    let response: InsertEntityResponse = ron::de::from_str(&body).unwrap();
+    let uuid = response.uuid;
+
+    let payload = format!(
+        "MATCH ANY(a > 100, b <= 10.0) UPDATE test_match_all SET {{a: 43, c: Nil,}} INTO {}",
+        uuid
+    );
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(payload)
+        .uri("/wql/tx")
+        .to_request();
 
     let resp = test::call_service(&mut app, req).await;
 
@@ -861,12 +1329,539 @@ async fn test_evict_entity_id_post_ok() {
     clear();
 }
 
+#[ignore]
+#[actix_rt::test]
+async fn test_evict_entity_removes_persisted_data() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_evict_files UNIQUES #{id,} ENCRYPT #{pswd,}")
+        .uri("/wql/tx")
+        .to_request();
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {id: 123, pswd: \"my-password\",} INTO test_evict_files")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    assert!(std::fs::read_to_string("data/local_data.log")
+        .unwrap()
+        .contains("test_evict_files"));
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("EVICT test_evict_files")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    for file in &[
+        "data/local_data.log",
+        "data/unique_data.log",
+        "data/encrypt.log",
+    ] {
+        assert!(!std::fs::read_to_string(file)
+            .unwrap()
+            .contains("test_evict_files"));
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("EVICT test_evict_files")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"EntityNotCreated\",\n error_message: \"Entity `test_evict_files` not created\",\n)", body);
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_truncate_entity_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_truncate UNIQUES #{id,}")
+        .uri("/wql/tx")
+        .to_request();
+    let _ = test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {id: 123, a: 1,} INTO test_truncate")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("TRUNCATE test_truncate")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_success());
+    assert!(body.contains("message: \"Entity test_truncate truncated\""));
+    read::assert_content("TRUNCATE_ENTITY|");
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("SELECT * FROM test_truncate")
+        .uri("/wql/query")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_success());
+    assert!(!body.contains("Integer(123)"));
+
+    // The unique values are cleared and the entity is still created
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {id: 123, a: 2,} INTO test_truncate")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {id: 123, a: 3,} INTO test_truncate")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_client_error());
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_truncate")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"EntityAlreadyCreated\",\n error_message: \"Entity `test_truncate` already created\",\n)", body);
+    clear();
+}
+
+#[actix_rt::test]
+async fn test_truncate_unknown_entity_post_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("TRUNCATE test_truncate_unknown")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"EntityNotCreated\",\n error_message: \"Entity `test_truncate_unknown` not created\",\n)", body);
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_rename_field_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_rename UNIQUES #{id,}")
+        .uri("/wql/tx")
+        .to_request();
+    let _ = test::call_service(&mut app, req).await;
+
+    for payload in &[
+        "INSERT {id: 123, name: \"julia\",} INTO test_rename",
+        "INSERT {name: \"naomi\",} INTO test_rename",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("ALTER ENTITY test_rename RENAME FIELD id TO code")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_success());
+    assert!(body.contains("message: \"Field id renamed to code for entity test_rename\""));
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("SELECT * FROM test_rename")
+        .uri("/wql/query")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains("\"code\": Integer(123)"));
+    assert!(!body.contains("\"id\": Integer(123)"));
+    assert!(std::fs::read_to_string("data/unique_data.log")
+        .unwrap()
+        .contains("\"code\""));
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {code: 123,} INTO test_rename")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_client_error());
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_rename_field_with_schema_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for payload in &[
+        "CREATE ENTITY test_rename_schema_field",
+        "CREATE SCHEMA test_rename_schema_field {name: String, tags: Vector OR DEFAULT [],}",
+        "INSERT {name: \"julia\",} INTO test_rename_schema_field",
+        "ALTER ENTITY test_rename_schema_field RENAME FIELD tags TO labels",
+        "INSERT {name: \"naomi\",} INTO test_rename_schema_field",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {name: \"otavio\", labels: 3,} INTO test_rename_schema_field")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert!(body.contains("SchemaMismatch"));
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("SELECT * FROM test_rename_schema_field")
+        .uri("/wql/query")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert_eq!(body.matches("\"labels\": Vector([])").count(), 2);
+    assert!(!body.contains("tags"));
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_rename_field_collision_post_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE ENTITY test_rename_collision")
+        .uri("/wql/tx")
+        .to_request();
+    let _ = test::call_service(&mut app, req).await;
+
+    for payload in &[
+        "INSERT {a: 1,} INTO test_rename_collision",
+        "INSERT {b: 2,} INTO test_rename_collision",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("ALTER ENTITY test_rename_collision RENAME FIELD a TO b")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"FieldAlreadyExists\",\n error_message: \"Entity `test_rename_collision` already contains field `b`, rename it first\",\n)", body);
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("ALTER ENTITY test_rename_collision RENAME FIELD c TO b")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_drop_field_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for payload in &[
+        "CREATE ENTITY test_drop_field UNIQUES #{ssn,}",
+        "INSERT {ssn: 123, name: \"julia\", middle_name: \"ann\",} INTO test_drop_field",
+        "INSERT {name: \"naomi\",} INTO test_drop_field",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    for field in &["middle_name", "ssn"] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(format!("ALTER ENTITY test_drop_field DROP FIELD {}", field))
+            .uri("/wql/tx")
+            .to_request();
+        let mut resp = test::call_service(&mut app, req).await;
+        let body = resp.take_body().as_str().to_string();
+        assert!(resp.status().is_success());
+        assert!(body.contains(&format!(
+            "message: \"Field {} dropped from entity test_drop_field\"",
+            field
+        )));
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("SELECT * FROM test_drop_field")
+        .uri("/wql/query")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains("\"name\": String(\"julia\")"));
+    assert!(!body.contains("middle_name"));
+    assert!(!body.contains("ssn"));
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {ssn: 123,} INTO test_drop_field")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("ALTER ENTITY test_drop_field DROP FIELD missing")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_success());
+    assert!(body.contains("uuids: []"));
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_drop_field_with_schema_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for payload in &[
+        "CREATE ENTITY test_drop_schema_field ENCRYPT #{pswd,}",
+        "CREATE SCHEMA test_drop_schema_field {name: String, tags: Vector OR DEFAULT [],}",
+        "INSERT {name: \"julia\", pswd: \"secret\",} INTO test_drop_schema_field",
+        "ALTER ENTITY test_drop_schema_field DROP FIELD tags",
+        "ALTER ENTITY test_drop_schema_field DROP FIELD pswd",
+        "INSERT {name: \"naomi\", pswd: \"plain\",} INTO test_drop_schema_field",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("SELECT * FROM test_drop_schema_field")
+        .uri("/wql/query")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(body.contains("\"name\": String(\"naomi\")"));
+    assert!(body.contains("\"pswd\": String(\"plain\")"));
+    assert!(!body.contains("tags"));
+    clear();
+}
+
+#[actix_rt::test]
+async fn test_drop_field_unknown_entity_post_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("ALTER ENTITY test_drop_field_unknown DROP FIELD a")
+        .uri("/wql/tx")
+        .to_request();
+
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"EntityNotCreated\",\n error_message: \"Entity `test_drop_field_unknown` not created\",\n)", body);
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_insert_with_schema_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for payload in &[
+        "CREATE ENTITY test_schema",
+        "CREATE SCHEMA test_schema {a: Float, id: Uuid,}",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload(
+            "INSERT {a: 3, id: \"48c7640e-9287-468a-a07c-2fb00da5eaed\", b: 4,} INTO test_schema",
+        )
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    read::assert_content("\"a\": Float(3)");
+    read::assert_content("\"id\": Uuid(\"48c7640e-9287-468a-a07c-2fb00da5eaed\")");
+    read::assert_content("\"b\": Integer(4)");
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_insert_with_schema_post_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for payload in &[
+        "CREATE ENTITY test_schema_err",
+        "CREATE SCHEMA test_schema_err {id: Uuid,}",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let _ = test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {id: \"not an uuid\",} INTO test_schema_err")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"SchemaMismatch\",\n error_message: \"Key `id` with value String(\\\"not an uuid\\\") cannot be coerced to Uuid\",\n)", body);
+    clear();
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn test_insert_with_schema_default_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    for payload in &[
+        "CREATE ENTITY test_schema_default",
+        "CREATE SCHEMA test_schema_default {name: String, tags: Vector OR DEFAULT [],}",
+    ] {
+        let req = test::TestRequest::post()
+            .header("Content-Type", "application/wql")
+            .set_payload(payload.to_string())
+            .uri("/wql/tx")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {name: \"julia\",} INTO test_schema_default")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    read::assert_content("\"tags\": Vector([])");
+    clear();
+}
+
+#[actix_rt::test]
+async fn test_create_schema_without_entity_post_err() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE SCHEMA test_schema_no_entity {a: Float,}")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"EntityNotCreated\",\n error_message: \"Entity `test_schema_no_entity` not created\",\n)", body);
+}
+
+#[actix_rt::test]
+async fn test_document_store_skips_schema_post_ok() {
+    let mut app = test::init_service(App::new().configure(routes)).await;
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE DOCUMENT STORE test_docs")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_success());
+    assert!(body.contains("message: \"Document store `test_docs` created\""));
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("CREATE SCHEMA test_docs {a: Integer,}")
+        .uri("/wql/tx")
+        .to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+    let body = resp.take_body().as_str().to_string();
+    assert!(resp.status().is_client_error());
+    assert_eq!("(\n error_type: \"DocumentStoreSchema\",\n error_message: \"Entity `test_docs` is a document store and cannot have a schema\",\n)", body);
+
+    let req = test::TestRequest::post()
+        .header("Content-Type", "application/wql")
+        .set_payload("INSERT {a: \"not an integer\", b: [1, 'c',],} INTO test_docs")
+        .uri("/wql/tx")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    clear();
+}
+
 #[actix_rt::test]
 async fn test_insert_encrypt_post_ok() {
     let mut app = test::init_service(App::new().configure(routes)).await;
     let req = test::TestRequest::post()
         .header("Content-Type", "application/wql")
-        .set_payload("CREATE ENTITY test_ok ENCRYPT #{pswd,}")
+        .set_payload("CREATE ENTITY test_insert_encrypt ENCRYPT #{pswd,}")
         .uri("/wql/tx")
         .to_request();
 
@@ -874,7 +1869,7 @@ async fn test_insert_encrypt_post_ok() {
 
     let req = test::TestRequest::post()
         .header("Content-Type", "application/wql")
-        .set_payload("INSERT {a: 123, pswd: \"my_password\",} INTO test_ok")
+        .set_payload("INSERT {a: 123, pswd: \"my_password\",} INTO test_insert_encrypt")
         .uri("/wql/tx")
         .to_request();
 
@@ -882,7 +1877,7 @@ async fn test_insert_encrypt_post_ok() {
     assert!(resp.status().is_success());
 
     read::assert_content("INSERT|");
-    read::assert_content("|test_ok|");
+    read::assert_content("|test_insert_encrypt|");
     read::assert_content("\"a\": Integer(123)");
     read::assert_not_content("my_password");
     clear();
@@ -1040,6 +2035,66 @@ impl BodyTest for ResponseBody<Body> {
     }
 }
 
+#[actix_rt::test]
+async fn test_insert_content_v5_duplicated_id_err() {
+    use crate::{
+        actors::wql::Executor,
+        controllers::tx::insert_controller,
+        model::{
+            options::{ExecOptions, IdStrategy},
+            wql::InsertArgs,
+        },
+        repository::local::{EncryptContext, LocalContext, UniquenessContext},
+    };
+    use actix::Actor;
+    use actix_web::web;
+    use std::{
+        collections::{BTreeMap, HashMap},
+        sync::{atomic::AtomicUsize, Arc, Mutex},
+    };
+    use wql::Types;
+
+    let mut local_context = LocalContext::new();
+    local_context.insert("test_content_v5".to_string(), BTreeMap::new());
+    let local_data = Arc::new(Arc::new(Mutex::new(local_context)));
+    let bytes_counter = web::Data::new(AtomicUsize::new(0));
+    let uniqueness = web::Data::new(Arc::new(Mutex::new(UniquenessContext::new())));
+    let encryption = web::Data::new(Arc::new(Mutex::new(EncryptContext::new())));
+    let exec_options = web::Data::new(ExecOptions {
+        hashing_cost: 4,
+        id_strategy: IdStrategy::ContentV5,
+        normalize_keys: None,
+    });
+    let actor = web::Data::new(Executor::new().start());
+    let mut content = HashMap::new();
+    content.insert("a".to_string(), Types::Integer(123));
+
+    let mut responses = Vec::new();
+    for _ in 0..2 {
+        let response = insert_controller(
+            InsertArgs::new("test_content_v5".to_string(), content.clone(), None),
+            local_data.clone(),
+            bytes_counter.clone(),
+            uniqueness.clone(),
+            encryption.clone(),
+            exec_options.clone(),
+            actor.clone(),
+        )
+        .await;
+        responses.push(response);
+    }
+
+    let inserted: InsertEntityResponse = ron::de::from_str(responses[0].as_ref().unwrap()).unwrap();
+    let err = responses[1].as_ref().unwrap_err().to_string();
+    assert!(err.contains("DuplicatedId"));
+    assert!(err.contains(&format!(
+        "Entity `test_content_v5` already contains Uuid {}",
+        inserted.uuid
+    )));
+    assert_eq!(local_data.lock().unwrap()["test_content_v5"].len(), 1);
+    clear();
+}
+
 pub fn clear() {
     std::process::Command::new("rm")
         .arg("-rf")
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use wql::Types;
+
+/// Name of the unique key enforcing `fields` together, a single field is its own name and a composite
+/// `PRIMARY KEY (tenant, email)` is named `(tenant, email)`.
+pub fn unique_key_name(fields: &[String]) -> String {
+    match fields {
+        [field] => field.to_owned(),
+        fields => format!("({})", fields.join(", ")),
+    }
+}
+
+/// Fields of a composite unique key named `(tenant, email)`, in declared order.
+pub fn composite_fields(key: &str) -> Option<Vec<&str>> {
+    let fields = key.strip_prefix('(')?.strip_suffix(')')?;
+    Some(fields.split(", ").collect())
+}
+
+/// Value of the unique `key` in `content`, a composite key has the vector of its field values and
+/// only when every field is set.
+pub fn unique_value(key: &str, content: &HashMap<String, Types>) -> Option<Types> {
+    match composite_fields(key) {
+        Some(fields) => fields
+            .iter()
+            .map(|field| content.get(*field).cloned())
+            .collect::<Option<Vec<Types>>>()
+            .map(Types::Vector),
+        None => content.get(key).cloned(),
+    }
+}
+
+/// Unique key named `key` with its field `from` renamed to `to`.
+pub fn rename_in_key(key: &str, from: &str, to: &str) -> String {
+    match composite_fields(key) {
+        Some(fields) => {
+            let fields = fields
+                .into_iter()
+                .map(|field| if field == from { to } else { field })
+                .map(String::from)
+                .collect::<Vec<String>>();
+            unique_key_name(&fields)
+        }
+        None if key == from => to.to_owned(),
+        None => key.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn composite_key_values() {
+        let key = unique_key_name(&["tenant".to_string(), "email".to_string()]);
+        let mut content = HashMap::new();
+        content.insert("email".to_string(), Types::String("a@b.c".to_string()));
+
+        assert_eq!(key, "(tenant, email)");
+        assert_eq!(unique_value(&key, &content), None);
+        content.insert("tenant".to_string(), Types::Integer(1));
+        assert_eq!(
+            unique_value(&key, &content),
+            Some(Types::Vector(vec![
+                Types::Integer(1),
+                Types::String("a@b.c".to_string())
+            ]))
+        );
+        assert_eq!(unique_value("tenant", &content), Some(Types::Integer(1)));
+        assert_eq!(rename_in_key(&key, "email", "mail"), "(tenant, mail)");
+        assert_eq!(rename_in_key("email", "email", "mail"), "mail");
+    }
+}
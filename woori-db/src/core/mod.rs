@@ -1,6 +1,9 @@
 use ron::ser::PrettyConfig;
 
+pub(crate) mod keys;
+pub(crate) mod planner;
 pub(crate) mod registry;
+pub(crate) mod sample;
 pub(crate) mod wql;
 
 pub fn pretty_config_output() -> PrettyConfig {
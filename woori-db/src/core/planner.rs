@@ -0,0 +1,625 @@
+use std::collections::{HashMap, HashSet};
+
+use wql::{Clause, Function, Types, Value};
+
+use crate::{
+    core::keys::{composite_fields, unique_value},
+    repository::local::UniquenessContext,
+};
+
+/// Value statistics of one field of an entity tree, `min` and `max` are only known for numeric and
+/// temporal fields.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnStats {
+    pub distinct: usize,
+    pub min: Option<Types>,
+    pub max: Option<Types>,
+}
+
+impl ColumnStats {
+    fn eq(&self) -> f64 {
+        if self.distinct > 0 {
+            1f64 / self.distinct as f64
+        } else {
+            EQ_SELECTIVITY
+        }
+    }
+
+    /// Whether `value` is out of the `[min, max]` range.
+    fn excludes(&self, value: &Types) -> bool {
+        match (
+            as_f64(value),
+            self.min.as_ref().and_then(as_f64),
+            self.max.as_ref().and_then(as_f64),
+        ) {
+            (Some(value), Some(min), Some(max)) => value < min || value > max,
+            _ => false,
+        }
+    }
+
+    /// Part of the `[min, max]` range between `start` and `end`, `None` bounds are open.
+    fn range(&self, start: Option<&Types>, end: Option<&Types>) -> Option<f64> {
+        let min = self.min.as_ref().and_then(as_f64)?;
+        let max = self.max.as_ref().and_then(as_f64)?;
+        let start = start.map_or(Some(min), as_f64)?.max(min);
+        let end = end.map_or(Some(max), as_f64)?.min(max);
+        Some(if start > end {
+            0f64
+        } else if max > min {
+            (end - start) / (max - min)
+        } else {
+            1f64
+        })
+    }
+}
+
+/// Statistics of the indexed fields of an entity tree, `UNIQUES` and composite `PRIMARY KEY`s are the
+/// only indexes for now.
+#[derive(Debug, Clone, Default)]
+pub struct IndexStats(HashMap<String, ColumnStats>);
+
+impl IndexStats {
+    pub fn from_uniques(entity: &str, uniqueness: &UniquenessContext) -> Self {
+        Self(
+            uniqueness
+                .get(entity)
+                .map(|uniques| {
+                    uniques
+                        .iter()
+                        .map(|(field, values)| {
+                            let stats = ColumnStats {
+                                distinct: values.len(),
+                                ..ColumnStats::default()
+                            };
+                            (field.to_owned(), stats)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        )
+    }
+
+    #[cfg(test)]
+    pub fn with_index(mut self, field: &str, distinct: usize) -> Self {
+        let stats = ColumnStats {
+            distinct,
+            ..ColumnStats::default()
+        };
+        self.0.insert(field.to_owned(), stats);
+        self
+    }
+
+    fn column(&self, field: &str) -> Option<&ColumnStats> {
+        self.0.get(field)
+    }
+}
+
+/// Fraction of the entity tree matched by an equality on a field that is not indexed.
+const EQ_SELECTIVITY: f64 = 0.1;
+/// Fraction of the entity tree matched by `>`, `>=`, `<` and `<=`.
+const RANGE_SELECTIVITY: f64 = 0.33;
+const BETWEEN_SELECTIVITY: f64 = 0.25;
+const LIKE_SELECTIVITY: f64 = 0.5;
+
+/// Rough number of entities matched by the WHERE `clauses` in an entity tree with `store_size` entities,
+/// used to choose between an index lookup and a full scan. No clauses estimates the full size and
+/// a `Clause::Error`, that never matches, estimates `0`.
+pub fn estimate_cardinality(clauses: &[Clause], store_size: usize, indexes: &IndexStats) -> usize {
+    let args_to_key = args_to_key(clauses);
+    let selectivity = clauses
+        .iter()
+        .map(|clause| indexed_selectivity(clause, indexes, &args_to_key))
+        .product::<f64>();
+
+    ((store_size as f64 * selectivity).ceil() as usize).min(store_size)
+}
+
+fn indexed_selectivity(
+    clause: &Clause,
+    indexes: &IndexStats,
+    args_to_key: &HashMap<&str, &str>,
+) -> f64 {
+    let unknown = ColumnStats::default();
+    let column = |key: &str| {
+        indexes
+            .column(args_to_key.get(key).unwrap_or(&key))
+            .unwrap_or(&unknown)
+    };
+    match clause {
+        Clause::ContainsKeyValue(_, key, _)
+        | Clause::SimpleComparisonFunction(_, key, _)
+        | Clause::ComplexComparisonFunctions(_, key, _) => selectivity(clause, column(key)),
+        Clause::TupleEq(keys, _) => {
+            let fields = tuple_fields(keys, args_to_key);
+            match composite_key(&fields, indexes.0.keys()) {
+                Some(key) => indexes.0[key].eq(),
+                None => keys.iter().map(|key| column(key).eq()).product(),
+            }
+        }
+        Clause::Or(Function::And, clauses) => clauses
+            .iter()
+            .map(|clause| indexed_selectivity(clause, indexes, args_to_key))
+            .product(),
+        Clause::Or(Function::Not, clauses) => {
+            1f64 - clauses
+                .iter()
+                .map(|clause| indexed_selectivity(clause, indexes, args_to_key))
+                .product::<f64>()
+        }
+        Clause::Or(_, clauses) => clauses
+            .iter()
+            .map(|clause| indexed_selectivity(clause, indexes, args_to_key))
+            .sum::<f64>()
+            .min(1f64),
+        _ => selectivity(clause, &unknown),
+    }
+}
+
+/// Estimated fraction, from `0.0` to `1.0`, of the entities matched by `clause` on a field with
+/// `stats`. Equalities match `1 / distinct` of the entities and ranges the part of `[min, max]`
+/// they cover, without `min` and `max` ranges use a fixed estimate.
+pub fn selectivity(clause: &Clause, stats: &ColumnStats) -> f64 {
+    let range = |start: Option<&Types>, end: Option<&Types>, default: f64| {
+        stats.range(start, end).unwrap_or(default)
+    };
+    let eq = |value: &Types| {
+        if stats.excludes(value) {
+            0f64
+        } else {
+            stats.eq()
+        }
+    };
+    match clause {
+        Clause::ValueAttribution(_, _, _) => 1f64,
+        Clause::ContainsKeyValue(_, _, value) => eq(value),
+        Clause::SimpleComparisonFunction(f, _, value) => match f {
+            Function::Eq => eq(value),
+            Function::NotEq => 1f64 - eq(value),
+            Function::G | Function::GEq => range(Some(value), None, RANGE_SELECTIVITY),
+            Function::L | Function::LEq => range(None, Some(value), RANGE_SELECTIVITY),
+            Function::Like | Function::ILike => LIKE_SELECTIVITY,
+            _ => 1f64,
+        },
+        Clause::ComplexComparisonFunctions(Function::In, _, values) => {
+            values.iter().map(eq).sum::<f64>().min(1f64)
+        }
+        Clause::ComplexComparisonFunctions(Function::Between, _, bounds) if bounds.len() == 2 => {
+            range(Some(&bounds[0]), Some(&bounds[1]), BETWEEN_SELECTIVITY)
+        }
+        Clause::ComplexComparisonFunctions(_, _, _) => BETWEEN_SELECTIVITY,
+        Clause::TupleEq(keys, _) => stats.eq().powi(keys.len() as i32),
+        Clause::IsEmpty(_) | Clause::IsNotEmpty(_) | Clause::Missing(_) => 1f64,
+        Clause::Or(Function::And, clauses) => clauses
+            .iter()
+            .map(|clause| selectivity(clause, stats))
+            .product(),
+        Clause::Or(Function::Not, clauses) => {
+            1f64 - clauses
+                .iter()
+                .map(|clause| selectivity(clause, stats))
+                .product::<f64>()
+        }
+        Clause::Or(_, clauses) => clauses
+            .iter()
+            .map(|clause| selectivity(clause, stats))
+            .sum::<f64>()
+            .min(1f64),
+        Clause::Error => 0f64,
+    }
+}
+
+fn as_f64(value: &Types) -> Option<f64> {
+    match value {
+        Types::Integer(i) => Some(*i as f64),
+        Types::Float(f) if f.is_finite() => Some(*f),
+        Types::DateTime(date) => Some(date.timestamp_millis() as f64),
+        Types::Duration(millis) => Some(*millis as f64),
+        _ => None,
+    }
+}
+
+/// Splits the WHERE `clauses`, that must all match, into the clauses an index on `indexed_fields` can
+/// answer and the residual clauses evaluated on the entities found by the index. Only equalities,
+/// `?* e:key value`, `(== ?var value)` and `(in ?var ...)`, over an indexed field and tuple equalities
+/// over the fields of a composite key use the index, `?* e:key ?var` bindings are kept in every part
+/// that uses their variable.
+pub fn split_for_index(
+    clauses: &[Clause],
+    indexed_fields: &HashSet<String>,
+) -> (Option<Vec<Clause>>, Option<Vec<Clause>>) {
+    let args_to_key = args_to_key(clauses);
+    let (indexed, residual): (Vec<&Clause>, Vec<&Clause>) = clauses
+        .iter()
+        .filter(|clause| !matches!(clause, Clause::ValueAttribution(..)))
+        .partition(|clause| {
+            indexed_values(clause, &args_to_key)
+                .is_some_and(|(field, _)| indexed_fields.contains(field))
+                || tuple_value(clause, &args_to_key, indexed_fields).is_some()
+        });
+
+    let with_bindings = |part: Vec<&Clause>| {
+        if part.is_empty() {
+            return None;
+        }
+        let used = part
+            .iter()
+            .flat_map(|clause| clause_keys(clause))
+            .collect::<HashSet<&str>>();
+        Some(
+            clauses
+                .iter()
+                .filter(|clause| match clause {
+                    Clause::ValueAttribution(_, _, Value(arg)) => used.contains(arg.as_str()),
+                    _ => false,
+                })
+                .chain(part)
+                .cloned()
+                .collect::<Vec<Clause>>(),
+        )
+    };
+    (with_bindings(indexed), with_bindings(residual))
+}
+
+/// Whether the unique values of `entity` hold every value required by the indexed equalities of
+/// `clauses`, when they don't no entity can match and the entity tree does not need to be read.
+pub fn unique_values_match(
+    entity: &str,
+    clauses: &[Clause],
+    uniqueness: &UniquenessContext,
+) -> bool {
+    let uniques = match uniqueness.get(entity) {
+        Some(uniques) => uniques,
+        None => return true,
+    };
+    let fields = uniques.keys().cloned().collect::<HashSet<String>>();
+    let indexed = match split_for_index(clauses, &fields) {
+        (Some(indexed), _) => indexed,
+        (None, _) => return true,
+    };
+
+    let args_to_key = args_to_key(&indexed);
+    indexed.iter().all(|clause| {
+        match (
+            indexed_values(clause, &args_to_key),
+            tuple_value(clause, &args_to_key, &fields),
+        ) {
+            (Some((field, values)), _) => values
+                .iter()
+                .any(|value| uniques[field].contains(&format!("{:?}", value))),
+            (None, Some((key, value))) => uniques[key].contains(&format!("{:?}", value)),
+            (None, None) => true,
+        }
+    })
+}
+
+fn args_to_key(clauses: &[Clause]) -> HashMap<&str, &str> {
+    clauses
+        .iter()
+        .filter_map(|clause| {
+            if let Clause::ValueAttribution(_, key, Value(arg)) = clause {
+                Some((arg.as_str(), key.as_str()))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Field and accepted values of an equality clause.
+fn indexed_values<'a>(
+    clause: &'a Clause,
+    args_to_key: &HashMap<&str, &'a str>,
+) -> Option<(&'a str, Vec<&'a Types>)> {
+    let field = |key: &'a str| *args_to_key.get(key).unwrap_or(&key);
+    match clause {
+        Clause::ContainsKeyValue(_, key, value) => Some((key, vec![value])),
+        Clause::SimpleComparisonFunction(Function::Eq, key, value) => {
+            Some((field(key), vec![value]))
+        }
+        Clause::ComplexComparisonFunctions(Function::In, key, values) => {
+            Some((field(key), values.iter().collect()))
+        }
+        _ => None,
+    }
+}
+
+/// Composite unique key of `indexed_fields` over the fields of a tuple equality, in any order, and the
+/// value of the tuple ordered as the key fields.
+fn tuple_value<'a>(
+    clause: &Clause,
+    args_to_key: &HashMap<&str, &str>,
+    indexed_fields: &'a HashSet<String>,
+) -> Option<(&'a String, Types)> {
+    let (keys, values) = match clause {
+        Clause::TupleEq(keys, values) => (keys, values),
+        _ => return None,
+    };
+    let fields = tuple_fields(keys, args_to_key);
+    let key = composite_key(&fields, indexed_fields.iter())?;
+    let content = fields
+        .into_iter()
+        .map(String::from)
+        .zip(values.iter().cloned())
+        .collect::<HashMap<String, Types>>();
+    Some((key, unique_value(key, &content)?))
+}
+
+fn tuple_fields<'a>(keys: &'a [String], args_to_key: &HashMap<&str, &'a str>) -> Vec<&'a str> {
+    keys.iter()
+        .map(|key| *args_to_key.get(key.as_str()).unwrap_or(&key.as_str()))
+        .collect()
+}
+
+fn composite_key<'a>(
+    fields: &[&str],
+    mut keys: impl Iterator<Item = &'a String>,
+) -> Option<&'a String> {
+    keys.find(|key| {
+        composite_fields(key).is_some_and(|composite| {
+            composite.len() == fields.len() && composite.iter().all(|f| fields.contains(f))
+        })
+    })
+}
+
+fn clause_keys(clause: &Clause) -> Vec<&str> {
+    match clause {
+        Clause::SimpleComparisonFunction(_, key, _)
+        | Clause::ComplexComparisonFunctions(_, key, _)
+        | Clause::IsEmpty(key)
+        | Clause::IsNotEmpty(key)
+        | Clause::Missing(key) => vec![key.as_str()],
+        Clause::TupleEq(keys, _) => keys.iter().map(String::as_str).collect(),
+        Clause::Or(_, clauses) => clauses.iter().flat_map(clause_keys).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn eq(key: &str) -> Clause {
+        Clause::SimpleComparisonFunction(Function::Eq, key.to_string(), Types::Integer(1))
+    }
+
+    fn range(key: &str) -> Clause {
+        Clause::SimpleComparisonFunction(Function::GEq, key.to_string(), Types::Integer(1))
+    }
+
+    #[test]
+    fn no_filter_is_full_size() {
+        assert_eq!(
+            estimate_cardinality(&[], 1000, &IndexStats::default()),
+            1000
+        );
+    }
+
+    #[test]
+    fn error_clause_is_empty() {
+        let clauses = [eq("a"), Clause::Error];
+
+        assert_eq!(
+            estimate_cardinality(&clauses, 1000, &IndexStats::default()),
+            0
+        );
+    }
+
+    #[test]
+    fn estimates_order_across_clauses() {
+        let indexes = IndexStats::default().with_index("id", 1000);
+        let estimate = |clauses: &[Clause]| estimate_cardinality(clauses, 1000, &indexes);
+
+        let indexed_eq = estimate(&[eq("id")]);
+        let eq = estimate(&[eq("a")]);
+        let range = estimate(&[range("a")]);
+        let like = estimate(&[Clause::SimpleComparisonFunction(
+            Function::Like,
+            "a".to_string(),
+            Types::String("%a%".to_string()),
+        )]);
+        let full = estimate(&[]);
+
+        assert_eq!(indexed_eq, 1);
+        assert!(indexed_eq < eq);
+        assert!(eq < range);
+        assert!(range < like);
+        assert!(like < full);
+    }
+
+    #[test]
+    fn variables_resolve_to_indexed_fields() {
+        let indexes = IndexStats::default().with_index("id", 500);
+        let clauses = [
+            Clause::ValueAttribution("e".to_string(), "id".to_string(), Value("?id".to_string())),
+            eq("?id"),
+        ];
+
+        assert_eq!(estimate_cardinality(&clauses, 1000, &indexes), 2);
+    }
+
+    #[test]
+    fn and_narrows_or_widens() {
+        let indexes = IndexStats::default();
+        let estimate = |clauses: &[Clause]| estimate_cardinality(clauses, 1000, &indexes);
+
+        assert!(estimate(&[eq("a"), range("b")]) < estimate(&[eq("a")]));
+        assert!(
+            estimate(&[Clause::Or(Function::Or, vec![eq("a"), range("b")])])
+                > estimate(&[range("b")])
+        );
+    }
+
+    #[test]
+    fn in_grows_with_values() {
+        let indexes = IndexStats::default();
+        let in_values = |n: isize| {
+            Clause::ComplexComparisonFunctions(
+                Function::In,
+                "a".to_string(),
+                (0..n).map(Types::Integer).collect(),
+            )
+        };
+
+        assert!(
+            estimate_cardinality(&[in_values(1)], 1000, &indexes)
+                < estimate_cardinality(&[in_values(5)], 1000, &indexes)
+        );
+        assert_eq!(estimate_cardinality(&[in_values(50)], 1000, &indexes), 1000);
+    }
+
+    fn age_stats() -> ColumnStats {
+        ColumnStats {
+            distinct: 50,
+            min: Some(Types::Integer(0)),
+            max: Some(Types::Integer(100)),
+        }
+    }
+
+    fn cmp(function: Function, value: isize) -> Clause {
+        Clause::SimpleComparisonFunction(function, "age".to_string(), Types::Integer(value))
+    }
+
+    #[test]
+    fn selectivity_from_column_stats() {
+        let stats = age_stats();
+        let between = |start: isize, end: isize| {
+            Clause::ComplexComparisonFunctions(
+                Function::Between,
+                "age".to_string(),
+                vec![Types::Integer(start), Types::Integer(end)],
+            )
+        };
+
+        assert_eq!(selectivity(&cmp(Function::Eq, 30), &stats), 0.02);
+        assert_eq!(selectivity(&cmp(Function::Eq, 130), &stats), 0f64);
+        assert_eq!(selectivity(&cmp(Function::GEq, 75), &stats), 0.25);
+        assert_eq!(selectivity(&cmp(Function::L, 10), &stats), 0.1);
+        assert_eq!(selectivity(&cmp(Function::G, 200), &stats), 0f64);
+        assert_eq!(selectivity(&cmp(Function::LEq, 200), &stats), 1f64);
+        assert_eq!(selectivity(&between(20, 60), &stats), 0.4);
+        assert_eq!(selectivity(&between(60, 20), &stats), 0f64);
+    }
+
+    #[test]
+    fn selectivity_orders_equality_and_ranges() {
+        let stats = age_stats();
+        let eq = selectivity(&cmp(Function::Eq, 30), &stats);
+        let narrow = selectivity(&cmp(Function::G, 90), &stats);
+        let wide = selectivity(&cmp(Function::G, 10), &stats);
+        let not_eq = selectivity(&cmp(Function::NotEq, 30), &stats);
+
+        assert!(eq < narrow);
+        assert!(narrow < wide);
+        assert!(wide < not_eq);
+        assert!(not_eq < 1f64);
+    }
+
+    #[test]
+    fn selectivity_without_min_max() {
+        let stats = ColumnStats {
+            distinct: 4,
+            ..ColumnStats::default()
+        };
+
+        assert_eq!(selectivity(&cmp(Function::Eq, 30), &stats), 0.25);
+        assert_eq!(
+            selectivity(&cmp(Function::GEq, 30), &stats),
+            RANGE_SELECTIVITY
+        );
+        assert_eq!(
+            selectivity(&cmp(Function::Eq, 30), &ColumnStats::default()),
+            EQ_SELECTIVITY
+        );
+    }
+
+    fn bind(key: &str) -> Clause {
+        Clause::ValueAttribution("e".to_string(), key.to_string(), Value(format!("?{}", key)))
+    }
+
+    #[test]
+    fn split_indexed_and_residual() {
+        let indexed_fields = ["ssn".to_string()]
+            .iter()
+            .cloned()
+            .collect::<HashSet<String>>();
+        let clauses = [bind("ssn"), bind("age"), eq("?ssn"), range("?age")];
+
+        assert_eq!(
+            split_for_index(&clauses, &indexed_fields),
+            (
+                Some(vec![bind("ssn"), eq("?ssn")]),
+                Some(vec![bind("age"), range("?age")])
+            )
+        );
+    }
+
+    #[test]
+    fn split_without_indexable_clauses() {
+        let indexed_fields = ["ssn".to_string()]
+            .iter()
+            .cloned()
+            .collect::<HashSet<String>>();
+        let clauses = [bind("ssn"), range("?ssn"), eq("name")];
+
+        assert_eq!(
+            split_for_index(&clauses, &indexed_fields),
+            (None, Some(clauses.to_vec()))
+        );
+        assert_eq!(split_for_index(&[], &indexed_fields), (None, None));
+    }
+
+    #[test]
+    fn missing_unique_value_cannot_match() {
+        let mut uniqueness = UniquenessContext::new();
+        let mut uniques = HashMap::new();
+        uniques.insert(
+            "ssn".to_string(),
+            ["Integer(1)".to_string()]
+                .iter()
+                .cloned()
+                .collect::<HashSet<String>>(),
+        );
+        uniqueness.insert("e".to_string(), uniques);
+
+        assert!(unique_values_match(
+            "e",
+            &[bind("ssn"), eq("?ssn")],
+            &uniqueness
+        ));
+        assert!(!unique_values_match(
+            "e",
+            &[Clause::ContainsKeyValue(
+                "e".to_string(),
+                "ssn".to_string(),
+                Types::Integer(2)
+            )],
+            &uniqueness
+        ));
+        assert!(unique_values_match("other", &[eq("ssn")], &uniqueness));
+    }
+
+    #[test]
+    fn tuple_equality_uses_composite_key() {
+        let mut uniqueness = UniquenessContext::new();
+        let mut uniques = HashMap::new();
+        uniques.insert(
+            "(tenant, email)".to_string(),
+            ["Vector([Integer(1), String(\"a@b.c\")])".to_string()]
+                .iter()
+                .cloned()
+                .collect::<HashSet<String>>(),
+        );
+        uniqueness.insert("e".to_string(), uniques);
+        let tuple = |tenant: isize| {
+            Clause::TupleEq(
+                vec!["email".to_string(), "tenant".to_string()],
+                vec![Types::String("a@b.c".to_string()), Types::Integer(tenant)],
+            )
+        };
+        let indexes = IndexStats::from_uniques("e", &uniqueness).with_index("(tenant, email)", 100);
+
+        assert!(unique_values_match("e", &[tuple(1)], &uniqueness));
+        assert!(!unique_values_match("e", &[tuple(2)], &uniqueness));
+        assert_eq!(estimate_cardinality(&[tuple(1)], 1000, &indexes), 10);
+    }
+}
@@ -0,0 +1,62 @@
+/// Picks up to `size` items of `items` uniformly at random in a single pass, with reservoir sampling.
+/// The same `seed` and items always produce the same sample, in the order the items were read.
+pub fn reservoir_sample<T>(items: impl Iterator<Item = T>, size: usize, seed: u64) -> Vec<T> {
+    let mut rng = SplitMix64(seed);
+    // The reservoir never holds more than the items read
+    let mut reservoir: Vec<(usize, T)> = Vec::with_capacity(size.min(items.size_hint().0));
+    for (read, item) in items.enumerate() {
+        if reservoir.len() < size {
+            reservoir.push((read, item));
+        } else {
+            let slot = (rng.next() % (read as u64 + 1)) as usize;
+            if slot < size {
+                reservoir[slot] = (read, item);
+            }
+        }
+    }
+    reservoir.sort_by_key(|(read, _)| *read);
+    reservoir.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Seed for a `SAMPLE` without `SEED`.
+pub fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// SplitMix64 generator, enough for sampling and without a dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample_size() {
+        assert_eq!(reservoir_sample(0..1000, 100, 7).len(), 100);
+        assert_eq!(reservoir_sample(0..3, 100, 7), vec![0, 1, 2]);
+        assert!(reservoir_sample(0..10, 0, 7).is_empty());
+        assert_eq!(reservoir_sample(0..3, usize::MAX, 7), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn seeded_sample_is_deterministic() {
+        let sample = reservoir_sample(0..1000, 10, 42);
+
+        assert_eq!(sample, reservoir_sample(0..1000, 10, 42));
+        assert_ne!(sample, reservoir_sample(0..1000, 10, 43));
+        assert_ne!(sample, (0..10).collect::<Vec<_>>());
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
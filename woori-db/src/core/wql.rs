@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use wql::Types;
+use wql::{TypeTag, Types};
 
 use crate::{
     actors::wql::{
@@ -10,7 +10,7 @@ use crate::{
         UpdateSetEntityContent,
     },
     core::pretty_config_inner,
-    model::wql::Action,
+    model::{error::Error, wql::Action},
 };
 use ron::ser::to_string_pretty;
 
@@ -24,6 +24,12 @@ pub fn evict_entity_content(entity: &str) -> String {
     format!("{}|{}|{};", Action::EvictEntity, date, entity)
 }
 
+pub fn truncate_entity_content(entity: &str) -> String {
+    let date: DateTime<Utc> = Utc::now();
+    let date = to_string_pretty(&date, pretty_config_inner()).unwrap();
+    format!("{}|{}|{};", Action::TruncateEntity, date, entity)
+}
+
 pub fn evict_entity_id_content(entity: &EvictEntityId) -> String {
     let date: DateTime<Utc> = Utc::now();
     let date = to_string_pretty(&date, pretty_config_inner()).unwrap();
@@ -37,7 +43,7 @@ pub fn evict_entity_id_content(entity: &EvictEntityId) -> String {
 }
 
 pub fn insert_entity_content(content: &InsertEntityContent) -> (DateTime<Utc>, Uuid, String) {
-    let uuid = Uuid::new_v4();
+    let uuid = content.uuid;
     let date: DateTime<Utc> = Utc::now();
     let date_str = to_string_pretty(&date, pretty_config_inner()).unwrap();
     let log = format!(
@@ -128,6 +134,20 @@ pub fn update_content_state(previous_state: &mut HashMap<String, Types>, k: Stri
         Types::Uuid(uuid) => {
             *local_state = Types::Uuid(uuid);
         }
+        Types::DateTime(date) => {
+            *local_state = Types::DateTime(date);
+        }
+        Types::Duration(d) => {
+            if let Ok(shifted) = local_state.add_temporal(&Types::Duration(d)) {
+                *local_state = shifted;
+            }
+        }
+        Types::Ref(entity, uuid) => {
+            *local_state = Types::Ref(entity, uuid);
+        }
+        Types::Range(start, end, inclusive) => {
+            *local_state = Types::Range(start, end, inclusive);
+        }
         Types::Float(f) => {
             if let Types::Float(local) = *local_state {
                 *local_state = Types::Float(local + f);
@@ -140,13 +160,19 @@ pub fn update_content_state(previous_state: &mut HashMap<String, Types>, k: Stri
         Types::Boolean(b) => {
             *local_state = Types::Boolean(b);
         }
-        Types::Hash(_) => {}
+        Types::Hash(_) | Types::Param(_) | Types::SelfRef(_) => {}
         Types::Vector(mut v) => {
             if let Types::Vector(local) = local_state {
                 local.append(&mut v);
                 *local_state = Types::Vector(local.to_owned());
             }
         }
+        Types::Pairs(mut pairs) => {
+            if let Types::Pairs(local) = local_state {
+                local.append(&mut pairs);
+                *local_state = Types::Pairs(local.to_owned());
+            }
+        }
         Types::Map(m) => {
             if let Types::Map(local) = local_state {
                 m.iter().for_each(|(key, value)| {
@@ -167,6 +193,23 @@ pub fn update_content_state(previous_state: &mut HashMap<String, Types>, k: Stri
     }
 }
 
+/// Coerces every field of `content` toward the type registered by `CREATE SCHEMA`, fields out of the schema are kept as they are.
+pub fn coerce_to_schema(
+    content: HashMap<String, Types>,
+    schema: &HashMap<String, TypeTag>,
+) -> Result<HashMap<String, Types>, Error> {
+    content
+        .into_iter()
+        .map(|(key, value)| match schema.get(&key) {
+            Some(tag) => match value.coerce(*tag) {
+                Some(coerced) => Ok((key, coerced)),
+                None => Err(Error::SchemaMismatch(key, *tag, value)),
+            },
+            None => Ok((key, value)),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -185,6 +228,7 @@ mod test {
         let entity = InsertEntityContent {
             name: "my_entity".to_string(),
             content: "suppose this is a log".to_string(),
+            uuid: Uuid::new_v4(),
         };
         let (_, _, s) = insert_entity_content(&entity);
 
@@ -258,6 +302,20 @@ mod test {
         assert!(actual.contains("hello"))
     }
 
+    #[test]
+    fn truncate_entity_test() {
+        let actual = truncate_entity_content("hello");
+
+        assert!(actual.starts_with("TRUNCATE_ENTITY|"));
+        assert!(actual.ends_with("|hello;"))
+    }
+
+    fn entity(a: isize) -> HashMap<String, Types> {
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(a));
+        hm
+    }
+
     #[test]
     fn evict_entity_id_test() {
         let uuid = Uuid::new_v4();
@@ -272,4 +330,34 @@ mod test {
         assert!(actual.contains("hello"));
         assert!(actual.contains(&uuid.to_string()));
     }
+
+    #[test]
+    fn coerce_to_schema_ok() {
+        let mut schema = HashMap::new();
+        schema.insert("a".to_string(), TypeTag::Float);
+        schema.insert("id".to_string(), TypeTag::Uuid);
+        let id = Uuid::new_v4();
+        let mut content = entity(3);
+        content.insert("id".to_string(), Types::String(id.to_string()));
+        content.insert("b".to_string(), Types::Integer(4));
+
+        let coerced = coerce_to_schema(content, &schema).unwrap();
+
+        assert_eq!(coerced["a"], Types::Float(3.0));
+        assert_eq!(coerced["id"], Types::Uuid(id));
+        assert_eq!(coerced["b"], Types::Integer(4));
+    }
+
+    #[test]
+    fn coerce_to_schema_mismatch() {
+        let mut schema = HashMap::new();
+        schema.insert("a".to_string(), TypeTag::Boolean);
+
+        let coerced = coerce_to_schema(entity(3), &schema);
+
+        assert!(
+            matches!(coerced, Err(Error::SchemaMismatch(key, TypeTag::Boolean, Types::Integer(3)))
+            if key == "a")
+        );
+    }
 }
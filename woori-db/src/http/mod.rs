@@ -5,8 +5,15 @@ use actix_web_httpauth::middleware::HttpAuthentication;
 
 use crate::{
     actors::wql::Executor,
-    io::read::{encryption, local_data, offset, unique_data},
-    repository::local::{LocalContext, SessionContext, UniquenessContext},
+    io::read::{
+        document_stores, encryption, local_data, offset, primary_keys, schema_defaults, schemas,
+        unique_data,
+    },
+    model::options::ExecOptions,
+    repository::local::{
+        DocumentStoreContext, LocalContext, PrimaryKeyContext, SchemaContext,
+        SchemaDefaultsContext, SessionContext, UniquenessContext,
+    },
 };
 use crate::{
     controllers::{query, tx},
@@ -42,10 +49,19 @@ pub fn routes(config: &mut web::ServiceConfig) {
     let wql_context = Arc::new(Mutex::new(local_context));
     let unique_context = Arc::new(Mutex::new(uniqueness));
     let encrypt_context = Arc::new(Mutex::new(encrypt_context));
+    let schema_context = Arc::new(Mutex::new(schemas().map_or(SchemaContext::new(), |s| s)));
+    let schema_defaults_context = Arc::new(Mutex::new(
+        schema_defaults().map_or(SchemaDefaultsContext::new(), |s| s),
+    ));
+    let primary_key_context = Arc::new(Mutex::new(
+        primary_keys().map_or(PrimaryKeyContext::new(), |p| p),
+    ));
+    let document_store_context = Arc::new(Mutex::new(
+        document_stores().map_or(DocumentStoreContext::new(), |s| s),
+    ));
     let write_offset = AtomicUsize::new(offset().map_or(0_usize, |o| o));
     let actor = Executor::new().start();
-    let env_cost = std::env::var("HASHING_COST").unwrap_or_else(|_| "14".to_owned());
-    let cost = env_cost.parse::<u32>().expect("HASHING_COST must be a u32");
+    let exec_options = ExecOptions::from_env();
 
     let session_context = Arc::new(Mutex::new(SessionContext::new()));
 
@@ -70,9 +86,13 @@ pub fn routes(config: &mut web::ServiceConfig) {
             web::scope("/wql")
                 .guard(guard::Header("Content-Type", "application/wql"))
                 .data(wql_context)
-                .data(cost)
+                .data(exec_options)
                 .data(unique_context)
                 .data(encrypt_context)
+                .data(schema_context)
+                .data(schema_defaults_context)
+                .data(primary_key_context)
+                .data(document_store_context)
                 .data(write_offset)
                 .data(actor)
                 .wrap(wql_auth)
@@ -88,9 +108,13 @@ pub fn routes(config: &mut web::ServiceConfig) {
             web::scope("/wql")
                 .guard(guard::Header("Content-Type", "application/wql"))
                 .data(wql_context)
-                .data(cost)
+                .data(exec_options)
                 .data(unique_context)
                 .data(encrypt_context)
+                .data(schema_context)
+                .data(schema_defaults_context)
+                .data(primary_key_context)
+                .data(document_store_context)
                 .data(write_offset)
                 .data(actor)
                 .route("/tx", web::post().to(tx::wql_handler))
@@ -7,7 +7,13 @@ use std::{
 use uuid::Uuid;
 
 use crate::model::error;
-use crate::{actors::encrypts::WriteWithEncryption, model::DataRegister};
+use crate::{
+    actors::encrypts::WriteWithEncryption,
+    model::DataRegister,
+    repository::local::{
+        DocumentStoreContext, PrimaryKeyContext, SchemaContext, SchemaDefaultsContext,
+    },
+};
 
 #[cfg(test)]
 pub fn assert_content(pat: &str) {
@@ -22,6 +28,19 @@ pub fn assert_content(pat: &str) {
     assert!(s.contains(pat));
 }
 
+#[cfg(test)]
+pub fn count_content(pat: &str) -> usize {
+    use chrono::prelude::*;
+    let utc: DateTime<Utc> = Utc::now();
+    let date_log = utc.format("data/%Y_%m_%d.log").to_string();
+
+    let mut file = OpenOptions::new().read(true).open(date_log).unwrap();
+    let mut s = String::new();
+    file.read_to_string(&mut s).unwrap();
+
+    s.matches(pat).count()
+}
+
 #[cfg(test)]
 pub fn assert_not_content(pat: &str) {
     use chrono::prelude::*;
@@ -163,6 +182,54 @@ pub fn unique_data() -> Result<BTreeMap<String, HashMap<String, HashSet<String>>
     data
 }
 
+pub fn schemas() -> Result<SchemaContext, error::Error> {
+    #[cfg(not(feature = "test_read"))]
+    let path = "data/schemas.log";
+    #[cfg(feature = "test_read")]
+    let path = "data/schemas.txt";
+    let mut file = OpenOptions::new().read(true).open(path)?;
+    let mut s = String::new();
+    file.read_to_string(&mut s)?;
+
+    ron::de::from_str(&s).map_err(|_| error::Error::FailedToParseState)
+}
+
+pub fn schema_defaults() -> Result<SchemaDefaultsContext, error::Error> {
+    #[cfg(not(feature = "test_read"))]
+    let path = "data/schema_defaults.log";
+    #[cfg(feature = "test_read")]
+    let path = "data/schema_defaults.txt";
+    let mut file = OpenOptions::new().read(true).open(path)?;
+    let mut s = String::new();
+    file.read_to_string(&mut s)?;
+
+    ron::de::from_str(&s).map_err(|_| error::Error::FailedToParseState)
+}
+
+pub fn primary_keys() -> Result<PrimaryKeyContext, error::Error> {
+    #[cfg(not(feature = "test_read"))]
+    let path = "data/primary_keys.log";
+    #[cfg(feature = "test_read")]
+    let path = "data/primary_keys.txt";
+    let mut file = OpenOptions::new().read(true).open(path)?;
+    let mut s = String::new();
+    file.read_to_string(&mut s)?;
+
+    ron::de::from_str(&s).map_err(|_| error::Error::FailedToParseState)
+}
+
+pub fn document_stores() -> Result<DocumentStoreContext, error::Error> {
+    #[cfg(not(feature = "test_read"))]
+    let path = "data/document_stores.log";
+    #[cfg(feature = "test_read")]
+    let path = "data/document_stores.txt";
+    let mut file = OpenOptions::new().read(true).open(path)?;
+    let mut s = String::new();
+    file.read_to_string(&mut s)?;
+
+    ron::de::from_str(&s).map_err(|_| error::Error::FailedToParseState)
+}
+
 pub fn encryption() -> Result<BTreeMap<String, HashSet<String>>, error::Error> {
     #[cfg(not(feature = "test_read"))]
     let path = "data/encrypt.log";
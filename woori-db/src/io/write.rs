@@ -32,6 +32,7 @@ pub fn local_data(log: &str) -> Result<(), Error> {
         .write(true)
         .append(false)
         .create(true)
+        .truncate(true)
         .open("data/local_data.log")?;
 
     let _ = file.seek(SeekFrom::Start(0));
@@ -45,6 +46,7 @@ pub fn unique_data(log: &str) -> Result<(), Error> {
         .write(true)
         .append(false)
         .create(true)
+        .truncate(true)
         .open("data/unique_data.log")?;
 
     let _ = file.seek(SeekFrom::Start(0));
@@ -53,6 +55,62 @@ pub fn unique_data(log: &str) -> Result<(), Error> {
     Ok(())
 }
 
+pub fn schema_data(log: &str) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .append(false)
+        .create(true)
+        .truncate(true)
+        .open("data/schemas.log")?;
+
+    let _ = file.seek(SeekFrom::Start(0));
+    file.write_all(log.as_bytes())?;
+
+    Ok(())
+}
+
+pub fn schema_defaults_data(log: &str) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .append(false)
+        .create(true)
+        .truncate(true)
+        .open("data/schema_defaults.log")?;
+
+    let _ = file.seek(SeekFrom::Start(0));
+    file.write_all(log.as_bytes())?;
+
+    Ok(())
+}
+
+pub fn primary_key_data(log: &str) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .append(false)
+        .create(true)
+        .truncate(true)
+        .open("data/primary_keys.log")?;
+
+    let _ = file.seek(SeekFrom::Start(0));
+    file.write_all(log.as_bytes())?;
+
+    Ok(())
+}
+
+pub fn document_store_data(log: &str) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .append(false)
+        .create(true)
+        .truncate(true)
+        .open("data/document_stores.log")?;
+
+    let _ = file.seek(SeekFrom::Start(0));
+    file.write_all(log.as_bytes())?;
+
+    Ok(())
+}
+
 pub fn offset_counter(log: usize) -> Result<(), Error> {
     let mut file = OpenOptions::new()
         .write(true)
@@ -77,6 +135,18 @@ pub fn write_to_encrypts(log: &str) -> Result<(), Error> {
     Ok(())
 }
 
+pub fn encrypt_data(log: &str) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open("data/encrypt.log")?;
+
+    file.write_all(log.as_bytes())?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
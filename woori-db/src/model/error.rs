@@ -3,7 +3,7 @@ use actix_web::error;
 use std::io;
 
 use uuid::Uuid;
-use wql::Types;
+use wql::{TypeTag, Types};
 
 use crate::schemas::error::Response;
 
@@ -34,6 +34,14 @@ pub enum Error {
     AdminNotConfigured,
     AuthBadRequest,
     FailedToCreateUser,
+    DuplicatedId(String, Uuid),
+    NonNumericValue(String, Types),
+    FieldAlreadyExists(String, String),
+    SchemaMismatch(String, TypeTag, Types),
+    DocumentStoreSchema(String),
+    ConflictFieldNotUnique(String, String),
+    KeyCollision(String, String, String),
+    Unsupported(String),
     Unknown,
 }
 
@@ -41,7 +49,7 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::QueryFormat(s) => {
-                Response::new(String::from("QueryFormat"), format!("{:?}", s)).write(f)
+                Response::new(String::from("QueryFormat"), s.to_owned()).write(f)
             }
             Error::Io(e) => Response::new(String::from("IO"), format!("{:?}", e)).write(f),
             Error::EntityAlreadyCreated(e) => Response::new(
@@ -155,6 +163,61 @@ impl std::fmt::Display for Error {
                 "Failed to create requested user".to_string(),
             )
             .write(f),
+            Error::DuplicatedId(entity, id) => Response::new(
+                String::from("DuplicatedId"),
+                format!("Entity `{}` already contains Uuid {}", entity, id),
+            )
+            .write(f),
+            Error::NonNumericValue(key, value) => Response::new(
+                String::from("NonNumericValue"),
+                format!("Key `{}` contains non numeric value {:?}", key, value),
+            )
+            .write(f),
+            Error::FieldAlreadyExists(entity, field) => Response::new(
+                String::from("FieldAlreadyExists"),
+                format!(
+                    "Entity `{}` already contains field `{}`, rename it first",
+                    entity, field
+                ),
+            )
+            .write(f),
+            Error::SchemaMismatch(key, tag, value) => Response::new(
+                String::from("SchemaMismatch"),
+                format!(
+                    "Key `{}` with value {:?} cannot be coerced to {}",
+                    key, value, tag
+                ),
+            )
+            .write(f),
+            Error::DocumentStoreSchema(entity) => Response::new(
+                String::from("DocumentStoreSchema"),
+                format!(
+                    "Entity `{}` is a document store and cannot have a schema",
+                    entity
+                ),
+            )
+            .write(f),
+            Error::ConflictFieldNotUnique(entity, field) => Response::new(
+                String::from("ConflictFieldNotUnique"),
+                format!(
+                    "ON CONFLICT field `{}` is not in the UNIQUES of entity `{}`",
+                    field, entity
+                ),
+            )
+            .write(f),
+            Error::KeyCollision(key, other, normalized) => Response::new(
+                String::from("KeyCollision"),
+                format!(
+                    "Keys `{}` and `{}` are both normalized to `{}`",
+                    key, other, normalized
+                ),
+            )
+            .write(f),
+            Error::Unsupported(feature) => Response::new(
+                String::from("Unsupported"),
+                format!("{} is not supported by WooriDB", feature),
+            )
+            .write(f),
             Error::Unknown => Response::new(
                 String::from("Unknown"),
                 "Request credentials failed".to_string(),
@@ -164,6 +227,13 @@ impl std::fmt::Display for Error {
     }
 }
 
+/// WQL parser errors, so `Wql::from_str(..)?` returns `Error::QueryFormat`.
+impl From<String> for Error {
+    fn from(error: String) -> Self {
+        Error::QueryFormat(error)
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
         Error::Io(error)
@@ -189,3 +259,25 @@ impl From<uuid::Error> for Error {
 }
 
 impl error::ResponseError for Error {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+    use wql::Wql;
+
+    fn parse(query: &str) -> Result<Wql, Error> {
+        Ok(Wql::from_str(query)?)
+    }
+
+    #[test]
+    fn parse_error_is_query_format() {
+        let error = parse("DO SOMETHIG weird").unwrap_err();
+
+        assert!(matches!(error, Error::QueryFormat(_)));
+        assert_eq!(
+            error.to_string(),
+            "(\n error_type: \"QueryFormat\",\n error_message: \"Symbol `DO` not implemented at offset 0\",\n)"
+        );
+    }
+}
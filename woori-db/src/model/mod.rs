@@ -1,4 +1,5 @@
 pub(crate) mod error;
+pub(crate) mod options;
 pub(crate) mod wql;
 
 use actix::prelude::*;
@@ -11,14 +12,22 @@ use std::{
 
 use crate::{
     actors::wql::Executor,
-    repository::local::{EncryptContext, LocalContext, UniquenessContext},
+    model::options::ExecOptions,
+    repository::local::{
+        DocumentStoreContext, EncryptContext, LocalContext, PrimaryKeyContext, SchemaContext,
+        SchemaDefaultsContext, UniquenessContext,
+    },
 };
 
 pub type DataLocalContext = web::Data<Arc<Mutex<LocalContext>>>;
 pub type DataUniquenessContext = web::Data<Arc<Mutex<UniquenessContext>>>;
 pub type DataEncryptContext = web::Data<Arc<Mutex<EncryptContext>>>;
+pub type DataSchemaContext = web::Data<Arc<Mutex<SchemaContext>>>;
+pub type DataSchemaDefaultsContext = web::Data<Arc<Mutex<SchemaDefaultsContext>>>;
+pub type DataPrimaryKeyContext = web::Data<Arc<Mutex<PrimaryKeyContext>>>;
+pub type DataDocumentStoreContext = web::Data<Arc<Mutex<DocumentStoreContext>>>;
 pub type DataAtomicUsize = web::Data<AtomicUsize>;
-pub type DataU32 = web::Data<u32>;
+pub type DataExecOptions = web::Data<ExecOptions>;
 pub type DataExecutor = web::Data<Addr<Executor>>;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -0,0 +1,464 @@
+use std::{collections::HashMap, str::FromStr};
+
+use uuid::Uuid;
+use wql::{Entity, MatchCondition, OnConflict, Types, Wql};
+
+use crate::model::error::Error;
+
+/// Executor configuration, read from the environment when the routes are created.
+#[derive(Debug, Clone)]
+pub struct ExecOptions {
+    pub hashing_cost: u32,
+    pub id_strategy: IdStrategy,
+    /// Case the keys of inserted entity maps are renamed to, `firstName` is stored as `first_name` with `SnakeCase`.
+    pub normalize_keys: Option<KeyCase>,
+}
+
+impl ExecOptions {
+    pub fn from_env() -> Self {
+        let env_cost = std::env::var("HASHING_COST").unwrap_or_else(|_| "14".to_owned());
+        let hashing_cost = env_cost.parse::<u32>().expect("HASHING_COST must be a u32");
+        let env_strategy = std::env::var("ID_STRATEGY").unwrap_or_else(|_| "random_v4".to_owned());
+        let id_strategy = env_strategy
+            .parse::<IdStrategy>()
+            .expect("ID_STRATEGY must be `random_v4` or `content_v5`");
+        let normalize_keys = std::env::var("NORMALIZE_KEYS").ok().map(|case| {
+            case.parse::<KeyCase>()
+                .expect("NORMALIZE_KEYS must be `snake_case` or `camel_case`")
+        });
+
+        Self {
+            hashing_cost,
+            id_strategy,
+            normalize_keys,
+        }
+    }
+
+    /// Renames the keys of `content` to `normalize_keys`, keys that are renamed to the same key fail.
+    pub fn normalize<V>(&self, content: HashMap<String, V>) -> Result<HashMap<String, V>, Error> {
+        let case = if let Some(case) = self.normalize_keys {
+            case
+        } else {
+            return Ok(content);
+        };
+
+        let mut entries = content.into_iter().collect::<Vec<(String, V)>>();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut original_keys: HashMap<String, String> = HashMap::new();
+        let mut normalized = HashMap::new();
+        for (key, value) in entries {
+            let normalized_key = case.normalize(&key);
+            if let Some(other) = original_keys.insert(normalized_key.clone(), key.clone()) {
+                return Err(Error::KeyCollision(other, key, normalized_key));
+            }
+            normalized.insert(normalized_key, value);
+        }
+        Ok(normalized)
+    }
+
+    /// Renames a list of fields to `normalize_keys` in order, different fields renamed to the same key fail.
+    pub fn normalize_fields(&self, fields: Vec<String>) -> Result<Vec<String>, Error> {
+        let mut original_keys: HashMap<String, String> = HashMap::new();
+        fields
+            .into_iter()
+            .map(|field| {
+                let normalized_key = self.normalize_key(&field);
+                match original_keys.insert(normalized_key.clone(), field.clone()) {
+                    Some(other) if other != field => {
+                        Err(Error::KeyCollision(other, field, normalized_key))
+                    }
+                    _ => Ok(normalized_key),
+                }
+            })
+            .collect()
+    }
+
+    pub fn normalize_key(&self, key: &str) -> String {
+        match self.normalize_keys {
+            Some(case) => case.normalize(key),
+            None => key.to_owned(),
+        }
+    }
+
+    /// `wql` with the fields it writes, declares or matches renamed to `normalize_keys`, so every
+    /// statement sees the keys the way inserts store them.
+    pub fn normalize_wql(&self, wql: Wql) -> Result<Wql, Error> {
+        if self.normalize_keys.is_none() {
+            return Ok(wql);
+        }
+        let on_conflict = |on_conflict: Option<OnConflict>| match on_conflict {
+            Some(OnConflict::Update(field)) => Some(OnConflict::Update(self.normalize_key(&field))),
+            Some(OnConflict::Nothing(field)) => {
+                Some(OnConflict::Nothing(self.normalize_key(&field)))
+            }
+            None => None,
+        };
+        Ok(match wql {
+            Wql::CreateEntity(entity, uniques, encrypts, primary_key) => Wql::CreateEntity(
+                entity,
+                self.normalize_fields(uniques)?,
+                self.normalize_fields(encrypts)?,
+                self.normalize_fields(primary_key)?,
+            ),
+            Wql::Insert(entity, content, version, conflict) => Wql::Insert(
+                entity,
+                self.normalize(content)?,
+                version,
+                on_conflict(conflict),
+            ),
+            Wql::NestedInsert(insert, sub_inserts) => {
+                if let Wql::Insert(_, content, _, _) = insert.as_ref() {
+                    let fields = content.keys().cloned();
+                    let sub_fields = sub_inserts.iter().map(|(field, _)| field.clone());
+                    self.normalize_fields(fields.chain(sub_fields).collect())?;
+                }
+                let sub_inserts = sub_inserts
+                    .into_iter()
+                    .map(|(field, sub_insert)| {
+                        Ok((self.normalize_key(&field), self.normalize_wql(sub_insert)?))
+                    })
+                    .collect::<Result<Vec<(String, Wql)>, Error>>()?;
+                Wql::NestedInsert(Box::new(self.normalize_wql(*insert)?), sub_inserts)
+            }
+            Wql::InsertMany(entity, contents) => Wql::InsertMany(
+                entity,
+                contents
+                    .into_iter()
+                    .map(|content| self.normalize(content))
+                    .collect::<Result<Vec<Entity>, Error>>()?,
+            ),
+            Wql::UpdateContent(entity, content, uuid) => {
+                Wql::UpdateContent(entity, self.normalize(content)?, uuid)
+            }
+            Wql::UpdateSet(entity, content, uuid) => {
+                Wql::UpdateSet(entity, self.normalize(content)?, uuid)
+            }
+            Wql::MatchUpdate(entity, content, uuid, condition) => Wql::MatchUpdate(
+                entity,
+                self.normalize(content)?,
+                uuid,
+                self.normalize_condition(condition),
+            ),
+            Wql::CreateSchema(entity, schema, defaults) => {
+                Wql::CreateSchema(entity, self.normalize(schema)?, self.normalize(defaults)?)
+            }
+            Wql::CheckValue(entity, uuid, content) => {
+                Wql::CheckValue(entity, uuid, self.normalize(content)?)
+            }
+            Wql::RenameField(entity, from, to) => {
+                Wql::RenameField(entity, self.normalize_key(&from), self.normalize_key(&to))
+            }
+            Wql::DropField(entity, field) => Wql::DropField(entity, self.normalize_key(&field)),
+            wql => wql,
+        })
+    }
+
+    fn normalize_condition(&self, condition: MatchCondition) -> MatchCondition {
+        let key = |key: String| self.normalize_key(&key);
+        match condition {
+            MatchCondition::All(conditions) => MatchCondition::All(
+                conditions
+                    .into_iter()
+                    .map(|condition| self.normalize_condition(condition))
+                    .collect(),
+            ),
+            MatchCondition::Any(conditions) => MatchCondition::Any(
+                conditions
+                    .into_iter()
+                    .map(|condition| self.normalize_condition(condition))
+                    .collect(),
+            ),
+            MatchCondition::Eq(k, v) => MatchCondition::Eq(key(k), v),
+            MatchCondition::NotEq(k, v) => MatchCondition::NotEq(key(k), v),
+            MatchCondition::GEq(k, v) => MatchCondition::GEq(key(k), v),
+            MatchCondition::G(k, v) => MatchCondition::G(key(k), v),
+            MatchCondition::LEq(k, v) => MatchCondition::LEq(key(k), v),
+            MatchCondition::L(k, v) => MatchCondition::L(key(k), v),
+        }
+    }
+}
+
+/// Case of the keys of an entity map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyCase {
+    /// `first_name`
+    SnakeCase,
+    /// `firstName`
+    CamelCase,
+}
+
+impl FromStr for KeyCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "snake_case" => Ok(KeyCase::SnakeCase),
+            "camel_case" => Ok(KeyCase::CamelCase),
+            _ => Err(format!("Unknown key case `{}`", s)),
+        }
+    }
+}
+
+impl KeyCase {
+    pub fn normalize(self, key: &str) -> String {
+        let words = key_words(key);
+        match self {
+            KeyCase::SnakeCase => words.join("_"),
+            KeyCase::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) if i > 0 => first.to_uppercase().chain(chars).collect(),
+                        _ => word.to_owned(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Lowercase words of a key, split at `_`, `-` and where the case changes, `HTTPServer` is `http` and `server`.
+fn key_words(key: &str) -> Vec<String> {
+    let chars = key.chars().collect::<Vec<char>>();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '_' || *c == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && (previous.is_some_and(|p| p.is_lowercase() || p.is_numeric())
+                || (previous.is_some_and(char::is_uppercase)
+                    && next.is_some_and(|n| n.is_lowercase())));
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// How the Uuid of an inserted entity is generated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdStrategy {
+    RandomV4,
+    /// Name based Uuid from the entity tree key and its content, so identical entities share an id.
+    ContentV5,
+}
+
+impl FromStr for IdStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "random_v4" => Ok(IdStrategy::RandomV4),
+            "content_v5" => Ok(IdStrategy::ContentV5),
+            _ => Err(format!("Unknown id strategy `{}`", s)),
+        }
+    }
+}
+
+impl IdStrategy {
+    pub fn id_for(self, entity: &str, content: &Entity) -> Uuid {
+        match self {
+            IdStrategy::RandomV4 => Uuid::new_v4(),
+            IdStrategy::ContentV5 => {
+                let namespace = Uuid::new_v5(&Uuid::NAMESPACE_OID, entity.as_bytes());
+                Uuid::new_v5(&namespace, content_hash(content).as_bytes())
+            }
+        }
+    }
+}
+
+/// Canonical representation of an entity content, with map keys sorted at every level.
+pub fn content_hash(content: &Entity) -> String {
+    let mut keys = content.keys().collect::<Vec<&String>>();
+    keys.sort();
+    let entries = keys
+        .into_iter()
+        .map(|k| format!("{:?}:{}", k, canonical_value(&content[k])))
+        .collect::<Vec<String>>();
+    format!("{{{}}}", entries.join(","))
+}
+
+fn canonical_value(value: &Types) -> String {
+    match value {
+        Types::Map(map) => content_hash(map),
+        Types::Vector(vec) => format!(
+            "[{}]",
+            vec.iter()
+                .map(canonical_value)
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        _ => format!("{:?}", value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entity(a: isize) -> Entity {
+        let mut inner = HashMap::new();
+        inner.insert("x".to_string(), Types::Boolean(true));
+        inner.insert("y".to_string(), Types::Char('y'));
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(a));
+        hm.insert("b".to_string(), Types::String("hello".to_string()));
+        hm.insert("c".to_string(), Types::Map(inner));
+        hm
+    }
+
+    #[test]
+    fn content_v5_identical_entities() {
+        let strategy = IdStrategy::ContentV5;
+
+        assert_eq!(
+            strategy.id_for("my_entity", &entity(1)),
+            strategy.id_for("my_entity", &entity(1))
+        );
+        assert_ne!(
+            strategy.id_for("my_entity", &entity(1)),
+            strategy.id_for("my_entity", &entity(2))
+        );
+        assert_ne!(
+            strategy.id_for("my_entity", &entity(1)),
+            strategy.id_for("other_entity", &entity(1))
+        );
+    }
+
+    #[test]
+    fn random_v4_identical_entities() {
+        let strategy = IdStrategy::RandomV4;
+
+        assert_ne!(
+            strategy.id_for("my_entity", &entity(1)),
+            strategy.id_for("my_entity", &entity(1))
+        );
+    }
+
+    #[test]
+    fn normalize_keys() {
+        let options = ExecOptions {
+            hashing_cost: 4,
+            id_strategy: IdStrategy::RandomV4,
+            normalize_keys: Some(KeyCase::SnakeCase),
+        };
+        let mut hm = HashMap::new();
+        hm.insert("firstName".to_string(), Types::String("Julia".to_string()));
+        hm.insert("HTTPServer".to_string(), Types::Boolean(true));
+        hm.insert("age".to_string(), Types::Integer(30));
+
+        let normalized = options.normalize(hm).unwrap();
+        let mut keys = normalized.keys().cloned().collect::<Vec<String>>();
+        keys.sort();
+        assert_eq!(keys, vec!["age", "first_name", "http_server"]);
+        assert_eq!(normalized["first_name"], Types::String("Julia".to_string()));
+        assert_eq!(KeyCase::CamelCase.normalize("first_name"), "firstName");
+        assert_eq!(KeyCase::CamelCase.normalize("user-id"), "userId");
+        assert_eq!(
+            KeyCase::SnakeCase.normalize("address2Line"),
+            "address2_line"
+        );
+    }
+
+    #[test]
+    fn normalize_keys_collision() {
+        let options = ExecOptions {
+            hashing_cost: 4,
+            id_strategy: IdStrategy::RandomV4,
+            normalize_keys: Some(KeyCase::SnakeCase),
+        };
+        let mut hm = HashMap::new();
+        hm.insert("firstName".to_string(), Types::Nil);
+        hm.insert("first_name".to_string(), Types::Nil);
+
+        let err = options.normalize(hm).unwrap_err().to_string();
+        assert!(err.contains("KeyCollision"));
+        assert!(
+            err.contains("Keys `firstName` and `first_name` are both normalized to `first_name`")
+        );
+    }
+
+    #[test]
+    fn normalize_wql_fields() {
+        let options = ExecOptions {
+            hashing_cost: 4,
+            id_strategy: IdStrategy::RandomV4,
+            normalize_keys: Some(KeyCase::SnakeCase),
+        };
+        let normalize = |wql: &str| options.normalize_wql(wql.parse::<Wql>().unwrap());
+
+        assert_eq!(
+            normalize("CREATE ENTITY users UNIQUES #{userName,} ENCRYPT #{passWord,}").unwrap(),
+            Wql::CreateEntity(
+                "users".to_string(),
+                vec!["user_name".to_string()],
+                vec!["pass_word".to_string()],
+                Vec::new()
+            )
+        );
+        let uuid = Uuid::new_v4();
+        let mut content = Entity::new();
+        content.insert("first_name".to_string(), Types::String("Julia".to_string()));
+        assert_eq!(
+            normalize(&format!(
+                "UPDATE users SET {{firstName: \"Julia\",}} INTO {}",
+                uuid
+            ))
+            .unwrap(),
+            Wql::UpdateSet("users".to_string(), content.clone(), uuid)
+        );
+        assert_eq!(
+            normalize(&format!(
+                "MATCH ALL(lastName == \"x\") UPDATE users SET {{firstName: \"Julia\",}} INTO {}",
+                uuid
+            ))
+            .unwrap(),
+            Wql::MatchUpdate(
+                "users".to_string(),
+                content,
+                uuid,
+                MatchCondition::All(vec![MatchCondition::Eq(
+                    "last_name".to_string(),
+                    Types::String("x".to_string())
+                )])
+            )
+        );
+        assert_eq!(
+            normalize("ALTER ENTITY users RENAME FIELD firstName TO givenName").unwrap(),
+            Wql::RenameField(
+                "users".to_string(),
+                "first_name".to_string(),
+                "given_name".to_string()
+            )
+        );
+        assert!(
+            normalize("CREATE ENTITY users UNIQUES #{userName, user_name,}")
+                .unwrap_err()
+                .to_string()
+                .contains("KeyCollision")
+        );
+    }
+
+    #[test]
+    fn id_strategy_from_str() {
+        assert_eq!(
+            "content_v5".parse::<IdStrategy>(),
+            Ok(IdStrategy::ContentV5)
+        );
+        assert!("v7".parse::<IdStrategy>().is_err());
+    }
+}
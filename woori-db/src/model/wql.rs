@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use uuid::Uuid;
-use wql::{MatchCondition, Types};
+use wql::{MatchCondition, OnConflict, Types};
 
 #[derive(Debug, PartialEq)]
 pub enum Action {
@@ -13,6 +13,7 @@ pub enum Action {
     Delete,
     EvictEntity,
     EvictEntityId,
+    TruncateEntity,
     Error,
 }
 
@@ -27,6 +28,7 @@ impl std::fmt::Display for Action {
             Action::Delete => write!(f, "DELETE"),
             Action::EvictEntity => write!(f, "EVICT_ENTITY"),
             Action::EvictEntityId => write!(f, "EVICT_ENTITY_ID"),
+            Action::TruncateEntity => write!(f, "TRUNCATE_ENTITY"),
             Action::Error => write!(f, "Error"),
         }
     }
@@ -43,6 +45,7 @@ impl From<String> for Action {
             "UPDATE_CONTENT" => Action::UpdateContent,
             "EVICT_ENTITY" => Action::EvictEntity,
             "EVICT_ENTITY_ID" => Action::EvictEntityId,
+            "TRUNCATE_ENTITY" => Action::TruncateEntity,
             _ => Action::Error,
         }
     }
@@ -90,11 +93,32 @@ impl UpdateArgs {
 pub struct InsertArgs {
     pub entity: String,
     pub content: HashMap<String, Types>,
+    pub on_conflict: Option<OnConflict>,
 }
 
 impl InsertArgs {
-    pub fn new(entity: String, content: HashMap<String, Types>) -> Self {
-        Self { entity, content }
+    pub fn new(
+        entity: String,
+        content: HashMap<String, Types>,
+        on_conflict: Option<OnConflict>,
+    ) -> Self {
+        Self {
+            entity,
+            content,
+            on_conflict,
+        }
+    }
+}
+
+pub struct RenameFieldArgs {
+    pub entity: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl RenameFieldArgs {
+    pub fn new(entity: String, from: String, to: String) -> Self {
+        Self { entity, from, to }
     }
 }
 
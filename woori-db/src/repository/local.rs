@@ -1,14 +1,21 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use wql::{TypeTag, Types};
 
 use crate::{auth::schemas::Role, model::DataRegister};
 
 pub type LocalContext = BTreeMap<String, BTreeMap<Uuid, DataRegister>>;
 pub type UniquenessContext = BTreeMap<String, HashMap<String, HashSet<String>>>;
 pub type EncryptContext = BTreeMap<String, HashSet<String>>;
+pub type SchemaContext = BTreeMap<String, HashMap<String, TypeTag>>;
+/// `OR DEFAULT` values of the schema fields of each entity.
+pub type SchemaDefaultsContext = BTreeMap<String, HashMap<String, Types>>;
+pub type DocumentStoreContext = BTreeSet<String>;
+/// Declared `PRIMARY KEY` fields of each entity.
+pub type PrimaryKeyContext = BTreeMap<String, Vec<String>>;
 pub type SessionContext = BTreeMap<String, SessionInfo>;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
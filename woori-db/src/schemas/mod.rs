@@ -1,6 +1,7 @@
 use ron::ser::PrettyConfig;
 
 pub mod error;
+pub mod query;
 pub mod tx;
 
 pub fn pretty_config() -> PrettyConfig {
@@ -0,0 +1,24 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wql::Types;
+
+use crate::core::pretty_config_output;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageResponse {
+    pub(crate) entities: BTreeMap<Uuid, HashMap<String, Types>>,
+    /// Cursor for `AFTER` to read the next page, `None` on the last page.
+    pub(crate) cursor: Option<String>,
+}
+
+impl PageResponse {
+    pub fn new(entities: BTreeMap<Uuid, HashMap<String, Types>>, cursor: Option<String>) -> Self {
+        Self { entities, cursor }
+    }
+
+    pub fn write(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, pretty_config_output())
+    }
+}
@@ -63,10 +63,54 @@ impl DeleteOrEvictEntityResponse {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameFieldResponse {
+    entity: String,
+    uuids: Vec<Uuid>,
+    message: String,
+}
+
+impl RenameFieldResponse {
+    pub fn new(entity: String, uuids: Vec<Uuid>, message: String) -> Self {
+        Self {
+            entity,
+            uuids,
+            message,
+        }
+    }
+
+    pub fn write(&self) -> String {
+        ron::ser::to_string_pretty(self, pretty_config())
+            .unwrap_or_else(|_| "SERVER ERROR".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropFieldResponse {
+    entity: String,
+    uuids: Vec<Uuid>,
+    message: String,
+}
+
+impl DropFieldResponse {
+    pub fn new(entity: String, uuids: Vec<Uuid>, message: String) -> Self {
+        Self {
+            entity,
+            uuids,
+            message,
+        }
+    }
+
+    pub fn write(&self) -> String {
+        ron::ser::to_string_pretty(self, pretty_config())
+            .unwrap_or_else(|_| "SERVER ERROR".to_string())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateEntityResponse {
     entity: String,
-    uuid: Uuid,
+    pub(crate) uuid: Uuid,
     state: String,
     message: String,
 }
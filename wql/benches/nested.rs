@@ -0,0 +1,100 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::collections::HashMap;
+use wql::{parse_single_value, parse_types, Types};
+
+/// Recursive map reader the iterative `read_nested` replaced, kept here as the baseline it is compared to.
+fn recursive_map(chars: &mut std::str::Chars) -> Result<HashMap<String, Types>, String> {
+    let mut res = HashMap::new();
+    let mut key: Option<String> = None;
+    loop {
+        let value = match chars.next() {
+            Some('}') => return Ok(res),
+            Some('{') if key.is_some() => Types::Map(recursive_map(chars)?),
+            Some('[') if key.is_some() => Types::Vector(recursive_vec(chars)?),
+            Some(c) if c.is_whitespace() || c == ',' => continue,
+            Some(c) if key.is_some() => parse_types(c, chars)?,
+            Some(c) if c.is_alphanumeric() || c == '_' => {
+                let rest = chars
+                    .take_while(|c| c.is_alphanumeric() || c == &'_')
+                    .collect::<String>();
+                key = Some(format!("{}{}", c, rest));
+                continue;
+            }
+            _ => return Err(String::from("Entity HashMap could not be created")),
+        };
+        if let Some(key) = key.take() {
+            res.insert(key, value);
+        }
+    }
+}
+
+fn recursive_vec(chars: &mut std::str::Chars) -> Result<Vec<Types>, String> {
+    let mut res = Vec::new();
+    loop {
+        match chars.next() {
+            Some(']') => return Ok(res),
+            Some('[') => res.push(Types::Vector(recursive_vec(chars)?)),
+            Some('{') => res.push(Types::Map(recursive_map(chars)?)),
+            Some(c) if c.is_whitespace() || c == ',' => (),
+            Some(c) => res.push(parse_types(c, chars)?),
+            None => return Err(String::from("Vector is not closed")),
+        }
+    }
+}
+
+fn recursive(s: &str) -> Result<Types, String> {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some('{') => recursive_map(&mut chars).map(Types::Map),
+        Some('[') => recursive_vec(&mut chars).map(Types::Vector),
+        _ => Err(String::from("A map or vector is required")),
+    }
+}
+
+/// `{a: {a: ... {a: 1, b: [1, 2, ], }, ... }, }` nested `depth` maps deep.
+fn nested_maps(depth: usize) -> String {
+    format!(
+        "{}{{a: 1, b: [1, 2, ], }}{}",
+        "{a: ".repeat(depth - 1),
+        ", }".repeat(depth - 1)
+    )
+}
+
+/// One map of `width` fields, each with a small map and vector, to compare the cost on shallow input.
+fn wide_map(width: usize) -> String {
+    let fields = (0..width)
+        .map(|i| format!("f{}: {{a: {}, b: [1, 2, ], }}, ", i, i))
+        .collect::<String>();
+    format!("{{{}}}", fields)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nested_maps");
+    for depth in [8, 32, 120].iter() {
+        let input = nested_maps(*depth);
+        assert_eq!(parse_single_value(&input), recursive(&input));
+        group.bench_with_input(BenchmarkId::new("iterative", depth), &input, |b, input| {
+            b.iter(|| parse_single_value(input))
+        });
+        group.bench_with_input(BenchmarkId::new("recursive", depth), &input, |b, input| {
+            b.iter(|| recursive(input))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("wide_map");
+    for width in [10, 100].iter() {
+        let input = wide_map(*width);
+        assert_eq!(parse_single_value(&input), recursive(&input));
+        group.bench_with_input(BenchmarkId::new("iterative", width), &input, |b, input| {
+            b.iter(|| parse_single_value(input))
+        });
+        group.bench_with_input(BenchmarkId::new("recursive", width), &input, |b, input| {
+            b.iter(|| recursive(input))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
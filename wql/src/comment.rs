@@ -0,0 +1,120 @@
+use crate::{ErrorKind, WqlError};
+
+/// Replaces the `/* ... */` block comments that are outside of strings with spaces, so they are skipped
+/// wherever whitespace is allowed and error offsets still point into the original query.
+/// Block comments nest, `/* a /* b */ c */` is a single comment.
+pub(crate) fn strip_block_comments(s: &str, offset: usize) -> Result<String, WqlError> {
+    if !s.contains("/*") {
+        return Ok(s.to_string());
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.char_indices().peekable();
+    let mut depth = 0usize;
+    let mut opened_at = 0usize;
+    let mut inside_string = false;
+    let mut escaped = false;
+
+    while let Some((i, c)) = chars.next() {
+        if inside_string {
+            match c {
+                '\\' => escaped = !escaped,
+                '"' if !escaped => inside_string = false,
+                _ => escaped = false,
+            }
+            out.push(c);
+            continue;
+        }
+        match (c, chars.peek().map(|(_, next)| *next)) {
+            ('/', Some('*')) => {
+                chars.next();
+                if depth == 0 {
+                    opened_at = i;
+                }
+                depth += 1;
+                out.push_str("  ");
+            }
+            ('*', Some('/')) if depth > 0 => {
+                chars.next();
+                depth -= 1;
+                out.push_str("  ");
+            }
+            ('\n', _) => out.push(c),
+            (_, _) if depth > 0 => out.push_str(&" ".repeat(c.len_utf8())),
+            ('"', _) => {
+                inside_string = true;
+                out.push(c);
+            }
+            (_, _) => out.push(c),
+        }
+    }
+
+    if depth > 0 {
+        return Err(WqlError::new(
+            ErrorKind::UnclosedComment,
+            format!(
+                "Block comment opened at offset {} is not closed with `*/`",
+                offset + opened_at
+            ),
+            offset + opened_at,
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Wql;
+    use std::str::FromStr;
+
+    #[test]
+    fn comment_between_map_entries() {
+        let wql = Wql::from_str(
+            "INSERT {a: 123, /* the name\n of the entity */ b: \"hello\",} /* target */ INTO my_entity",
+        );
+
+        assert_eq!(
+            wql,
+            Wql::from_str("INSERT {a: 123, b: \"hello\",} INTO my_entity")
+        );
+    }
+
+    #[test]
+    fn nested_comment() {
+        let wql = Wql::from_str("/* outer /* inner */ still outer */ SELECT * FROM my_entity");
+
+        assert_eq!(wql, Wql::from_str("SELECT * FROM my_entity"));
+    }
+
+    #[test]
+    fn comment_inside_string_is_kept() {
+        let wql = Wql::from_str("INSERT {a: \"/* not \\\" a comment */\",} INTO my_entity");
+
+        assert_eq!(
+            wql,
+            Wql::from_str("INSERT {a: \"/* not \\\" a comment */\",} INTO my_entity")
+        );
+        assert_eq!(
+            strip_block_comments("{a: \"/* kept */\", /* gone */}", 0).unwrap(),
+            "{a: \"/* kept */\",           }"
+        );
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        assert_eq!(
+            Wql::from_str("SELECT * /* a /* b */ FROM my_entity"),
+            Err(String::from(
+                "Block comment opened at offset 9 is not closed with `*/`"
+            ))
+        );
+    }
+
+    #[test]
+    fn offsets_are_kept() {
+        assert_eq!(
+            Wql::from_str("/* é */ DO SOMETHING"),
+            Err(String::from("Symbol `DO` not implemented at offset 9"))
+        );
+    }
+}
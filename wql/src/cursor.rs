@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+/// Length of the unpadded base64 of the 16 bytes of a Uuid.
+const ENCODED_LEN: usize = 22;
+
+/// Opaque position of a paginated `SELECT ... AFTER <cursor>`, the id of the last entity of the previous page.
+/// Entities are paginated in id order, so a cursor is formatted as the url safe, unpadded base64 of that id.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cursor(Uuid);
+
+impl Cursor {
+    pub fn new(id: Uuid) -> Self {
+        Self(id)
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bytes = self.0.as_bytes();
+        let mut encoded = String::with_capacity(ENCODED_LEN);
+        for chunk in bytes.chunks(3) {
+            let block = chunk.iter().enumerate().fold(0u32, |block, (i, b)| {
+                block | (u32::from(*b) << (16 - 8 * i))
+            });
+            (0..=chunk.len()).for_each(|i| {
+                let sextet = (block >> (18 - 6 * i)) & 0b11_1111;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            });
+        }
+        write!(f, "{}", encoded)
+    }
+}
+
+impl std::str::FromStr for Cursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Cursor `{}` is not a valid pagination cursor", s);
+        if s.len() != ENCODED_LEN {
+            return Err(invalid());
+        }
+        let sextets = s
+            .bytes()
+            .map(|c| ALPHABET.iter().position(|a| *a == c).map(|p| p as u32))
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(invalid)?;
+
+        let mut bytes = Vec::with_capacity(16);
+        for chunk in sextets.chunks(4) {
+            let block = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |block, (i, s)| block | (s << (18 - 6 * i)));
+            (0..chunk.len() - 1).for_each(|i| bytes.push((block >> (16 - 8 * i)) as u8));
+            // The bits after the last byte are always zero for a cursor formatted by `Cursor`
+            if block & (0xFF_FFFF >> (8 * (chunk.len() - 1))) != 0 {
+                return Err(invalid());
+            }
+        }
+
+        Uuid::from_slice(&bytes).map(Cursor).map_err(|_| invalid())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn cursor_round_trip() {
+        let id = Uuid::from_str("2df2b8cf-49da-474d-8a00-c596c0bb6fd1").unwrap();
+        let cursor = Cursor::new(id);
+
+        assert_eq!(cursor.to_string(), "LfK4z0naR02KAMWWwLtv0Q");
+        assert_eq!(Cursor::from_str(&cursor.to_string()), Ok(cursor));
+        assert_eq!(
+            Cursor::from_str(&Cursor::new(Uuid::nil()).to_string())
+                .unwrap()
+                .id(),
+            Uuid::nil()
+        );
+    }
+
+    #[test]
+    fn malformed_cursor() {
+        for cursor in &[
+            "LfK4z0naR02KAMWWwLtv0",
+            "LfK4z0naR02KAMWWwLtv0=",
+            "LfK4z0naR02KAMWWwLtv0R",
+        ] {
+            assert_eq!(
+                Cursor::from_str(cursor),
+                Err(format!(
+                    "Cursor `{}` is not a valid pagination cursor",
+                    cursor
+                ))
+            );
+        }
+    }
+}
@@ -0,0 +1,687 @@
+use std::{collections::BTreeMap, fmt};
+
+use chrono::SecondsFormat;
+
+use crate::{
+    Clause, Entity, Expr, Function, IsolationLevel, MatchCondition, OnConflict, Operator, ToSelect,
+    TypeTag, Types, Value, Wql,
+};
+
+/// Renders the value as a WQL literal that parses back into the same value,
+/// except `Hash` that is rendered as a `String` because hashes have no literal.
+impl fmt::Display for Types {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Types::Char(c) => match c {
+                '\t' => write!(f, "'\\t'"),
+                '\r' => write!(f, "'\\r'"),
+                '\n' => write!(f, "'\\n'"),
+                '\\' => write!(f, "'\\\\'"),
+                '\'' => write!(f, "'\\''"),
+                c => write!(f, "'{}'", c),
+            },
+            Types::Integer(i) => write!(f, "{}", i),
+            Types::String(s) | Types::Hash(s) => write_str(f, s),
+            Types::Uuid(id) => write!(f, "{}", id),
+            Types::Float(float) => write!(f, "{:?}", float),
+            Types::Boolean(b) => write!(f, "{}", b),
+            Types::Vector(vec) => {
+                write!(f, "[")?;
+                vec.iter().try_for_each(|v| write!(f, "{}, ", v))?;
+                write!(f, "]")
+            }
+            Types::Map(map) => write_entity(f, map),
+            Types::Pairs(pairs) => {
+                write!(f, "&{{")?;
+                pairs
+                    .iter()
+                    .try_for_each(|(k, v)| write!(f, "{}: {}, ", k, v))?;
+                write!(f, "}}")
+            }
+            Types::Precise(p) => write!(f, "{}P", p),
+            Types::Ref(entity, id) => write!(f, "@{}/{}", entity, id),
+            Types::Param(param) => write!(f, "${}", param.key()),
+            Types::SelfRef(field) => write!(f, "$self.{}", field),
+            Types::DateTime(date) => {
+                write!(f, "{}", date.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+            }
+            Types::Duration(ms) => write_duration(f, *ms),
+            Types::Range(start, end, true) => write!(f, "{}..={}", start, end),
+            Types::Range(start, end, false) => write!(f, "{}..{}", start, end),
+            Types::Nil => write!(f, "Nil"),
+        }
+    }
+}
+
+/// Renders the canonical WQL of the statement, that parses back into the same statement. Maps are
+/// rendered with their keys sorted, so equal statements render equally.
+/// `InsertMany` has no syntax and is rendered as the `;` separated program of its single inserts.
+impl fmt::Display for Wql {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Wql::CreateEntity(entity, uniques, encrypts, primary_key) => {
+                write!(f, "CREATE ENTITY {}", entity)?;
+                if !primary_key.is_empty() {
+                    write!(f, " PRIMARY KEY ({})", primary_key.join(", "))?;
+                }
+                if !uniques.is_empty() {
+                    write!(f, " UNIQUES ")?;
+                    write_set(f, uniques)?;
+                }
+                if !encrypts.is_empty() {
+                    write!(f, " ENCRYPT ")?;
+                    write_set(f, encrypts)?;
+                }
+                Ok(())
+            }
+            Wql::Insert(entity, content, version, on_conflict) => {
+                write_insert(f, entity, content, *version, on_conflict, &[])
+            }
+            Wql::NestedInsert(insert, sub_inserts) => match &**insert {
+                Wql::Insert(entity, content, version, on_conflict) => {
+                    write_insert(f, entity, content, *version, on_conflict, sub_inserts)
+                }
+                insert => write!(f, "{}", insert),
+            },
+            Wql::InsertMany(entity, entities) => {
+                entities.iter().enumerate().try_for_each(|(i, content)| {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write_insert(f, entity, content, None, &None, &[])
+                })
+            }
+            Wql::UpdateContent(entity, content, id) => {
+                write!(
+                    f,
+                    "UPDATE {} CONTENT {} INTO {}",
+                    entity,
+                    Sorted(content),
+                    id
+                )
+            }
+            Wql::UpdateSet(entity, content, id) => {
+                write!(f, "UPDATE {} SET {} INTO {}", entity, Sorted(content), id)
+            }
+            Wql::Delete(entity, id) => write!(f, "DELETE {} FROM {}", id, entity),
+            Wql::DeleteMany(entity, ids) => {
+                write!(f, "DELETE FROM {} WHERE id IN (", entity)?;
+                write_joined(f, ids)?;
+                write!(f, ")")
+            }
+            Wql::MatchUpdate(entity, content, id, condition) => write!(
+                f,
+                "MATCH {} UPDATE {} SET {} INTO {}",
+                condition,
+                entity,
+                Sorted(content),
+                id
+            ),
+            Wql::Evict(entity, None) => write!(f, "EVICT {}", entity),
+            Wql::Evict(entity, Some(id)) => write!(f, "EVICT {} FROM {}", id, entity),
+            Wql::Truncate(entity) => write!(f, "TRUNCATE {}", entity),
+            Wql::Select(entity, to_select, id) => {
+                write!(f, "SELECT {} FROM {}", to_select, entity)?;
+                id.iter().try_for_each(|id| write!(f, " ID {}", id))
+            }
+            Wql::SelectWhen(entity, to_select, id, date) => {
+                write!(f, "SELECT {} FROM {}", to_select, entity)?;
+                id.iter().try_for_each(|id| write!(f, " ID {}", id))?;
+                write!(f, " WHEN AT {}", date)
+            }
+            Wql::SelectWhenRange(entity, id, start, end) => write!(
+                f,
+                "SELECT * FROM {} ID {} WHEN START {} END {}",
+                entity, id, start, end
+            ),
+            Wql::SelectIds(entity, to_select, ids) => {
+                write!(f, "SELECT {} FROM {} IDS IN ", to_select, entity)?;
+                write_set(f, ids)
+            }
+            Wql::SelectPage(entity, to_select, cursor, limit) => {
+                write!(f, "SELECT {} FROM {}", to_select, entity)?;
+                cursor
+                    .iter()
+                    .try_for_each(|cursor| write!(f, " AFTER {}", cursor))?;
+                limit
+                    .iter()
+                    .try_for_each(|limit| write!(f, " LIMIT {}", limit))
+            }
+            Wql::SelectSample(entity, to_select, n, seed) => {
+                write!(f, "SELECT {} FROM {} SAMPLE {}", to_select, entity, n)?;
+                seed.iter().try_for_each(|seed| write!(f, " SEED {}", seed))
+            }
+            Wql::SelectWhere(entity, to_select, clauses) => {
+                write!(f, "SELECT {} FROM {}", to_select, entity)?;
+                write_where(f, clauses)
+            }
+            Wql::SelectCount(entity, clauses) => {
+                write!(f, "SELECT COUNT FROM {}", entity)?;
+                write_where(f, clauses)
+            }
+            Wql::SelectSum(entity, field, clauses) => {
+                write!(f, "SELECT SUM({}) FROM {}", field, entity)?;
+                write_where(f, clauses)
+            }
+            Wql::SelectAvg(entity, field, clauses) => {
+                write!(f, "SELECT AVG({}) FROM {}", field, entity)?;
+                write_where(f, clauses)
+            }
+            Wql::SelectHistory(entity, id) => {
+                write!(f, "SELECT HISTORY FROM {} WHERE id = {}", entity, id)
+            }
+            Wql::SelectLatest(entity, id) => {
+                write!(f, "SELECT LATEST FROM {} WHERE id = {}", entity, id)
+            }
+            Wql::CheckValue(entity, id, values) => {
+                write!(f, "CHECK {{")?;
+                values
+                    .iter()
+                    .collect::<BTreeMap<&String, &String>>()
+                    .into_iter()
+                    .try_for_each(|(k, v)| {
+                        write!(f, "{}: ", k)?;
+                        write_str(f, v)?;
+                        write!(f, ", ")
+                    })?;
+                write!(f, "}} FROM {} ID {}", entity, id)
+            }
+            Wql::Comment(entity, field, text) => {
+                write!(f, "COMMENT ON {}", entity)?;
+                field.iter().try_for_each(|field| write!(f, ".{}", field))?;
+                write!(f, " IS ")?;
+                write_str(f, text)
+            }
+            Wql::Use(entity) => write!(f, "USE {}", entity),
+            Wql::Let(name, value) => write!(f, "LET {} = {}", name, value),
+            Wql::RenameField(entity, from, to) => {
+                write!(f, "ALTER ENTITY {} RENAME FIELD {} TO {}", entity, from, to)
+            }
+            Wql::DropField(entity, field) => {
+                write!(f, "ALTER ENTITY {} DROP FIELD {}", entity, field)
+            }
+            Wql::CreateSchema(entity, schema, defaults) => {
+                write!(f, "CREATE SCHEMA {} {{", entity)?;
+                schema
+                    .iter()
+                    .collect::<BTreeMap<&String, &TypeTag>>()
+                    .into_iter()
+                    .try_for_each(|(field, tag)| {
+                        write!(f, "{}: {}", field, tag)?;
+                        defaults
+                            .get(field)
+                            .iter()
+                            .try_for_each(|default| write!(f, " OR DEFAULT {}", default))?;
+                        write!(f, ", ")
+                    })?;
+                write!(f, "}}")
+            }
+            Wql::CreateDocumentStore(entity) => write!(f, "CREATE DOCUMENT STORE {}", entity),
+            Wql::Begin(level) => write!(f, "BEGIN ISOLATION {}", level),
+        }
+    }
+}
+
+impl fmt::Display for ToSelect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToSelect::All => write!(f, "*"),
+            ToSelect::Keys(keys) => write_set(f, keys),
+            ToSelect::Computed(keys, computed) => {
+                write!(f, "#{{")?;
+                keys.iter().try_for_each(|key| write!(f, "{}, ", key))?;
+                computed.iter().try_for_each(|(alias, expr)| match expr {
+                    Expr::Case(..) => write!(f, "{} AS {}, ", expr, alias),
+                    expr => write!(f, "({}) AS {}, ", expr, alias),
+                })?;
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Renders the expression with every nested operation in parentheses.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter<'_>, expr: &Expr| match expr {
+            Expr::Operation(..) => write!(f, "({})", expr),
+            expr => write!(f, "{}", expr),
+        };
+        match self {
+            Expr::Field(field) => write!(f, "{}", field),
+            Expr::Value(value) => write!(f, "{}", value),
+            Expr::Operation(op, lhs, rhs) => {
+                operand(f, lhs)?;
+                write!(f, " {} ", op)?;
+                operand(f, rhs)
+            }
+            Expr::Case(branches, otherwise) => {
+                write!(f, "CASE")?;
+                branches.iter().try_for_each(|(condition, expr)| {
+                    write!(f, " WHEN ")?;
+                    match condition {
+                        Clause::SimpleComparisonFunction(function, key, value) => {
+                            write!(f, "{} {} {}", key, function, value)?
+                        }
+                        condition => write!(f, "{}", condition)?,
+                    }
+                    write!(f, " THEN ")?;
+                    operand(f, expr)
+                })?;
+                if let Some(expr) = otherwise {
+                    write!(f, " ELSE ")?;
+                    operand(f, expr)?;
+                }
+                write!(f, " END")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+        }
+    }
+}
+
+/// Renders the clause as written inside `WHERE { ... }`, `Clause::Missing` as `(missing ?a)`.
+impl fmt::Display for Clause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Clause::ContainsKeyValue(entity, key, value) => {
+                write!(f, "?* {}:{} {}", entity, key, value)
+            }
+            Clause::ValueAttribution(entity, key, Value(arg)) => {
+                write!(f, "?* {}:{} {}", entity, key, arg)
+            }
+            Clause::SimpleComparisonFunction(function, key, value) => {
+                write!(f, "({} {} {})", function, key, value)
+            }
+            Clause::ComplexComparisonFunctions(function, key, values) => {
+                write!(f, "({} {}", function, key)?;
+                values
+                    .iter()
+                    .try_for_each(|value| write!(f, " {}", value))?;
+                write!(f, ")")
+            }
+            Clause::Or(function, clauses) => {
+                write!(f, "({}", function)?;
+                clauses
+                    .iter()
+                    .try_for_each(|clause| write!(f, " {}", clause))?;
+                write!(f, ")")
+            }
+            Clause::TupleEq(keys, values) => {
+                write!(f, "({}) == (", keys.join(", "))?;
+                write_joined(f, values)?;
+                write!(f, ")")
+            }
+            Clause::IsEmpty(key) => write!(f, "{} IS EMPTY", key),
+            Clause::IsNotEmpty(key) => write!(f, "{} IS NOT EMPTY", key),
+            Clause::Missing(key) => write!(f, "(missing {})", key),
+            Clause::Error => write!(f, "(error)"),
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let function = match self {
+            Function::Eq => "==",
+            Function::GEq => ">=",
+            Function::G => ">",
+            Function::LEq => "<=",
+            Function::L => "<",
+            Function::NotEq => "!=",
+            Function::Like => "like",
+            Function::ILike => "ilike",
+            Function::Between => "between",
+            Function::Or => "or",
+            Function::Xor => "xor",
+            Function::And => "and",
+            Function::Not => "not",
+            Function::In => "in",
+            Function::Contains => "contains",
+            Function::Error => "error",
+        };
+        write!(f, "{}", function)
+    }
+}
+
+/// Renders the condition as written after `MATCH`, `ALL(a == 1, b >= 3)`.
+impl fmt::Display for MatchCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchCondition::All(conditions) | MatchCondition::Any(conditions) => {
+                let name = if let MatchCondition::All(_) = self {
+                    "ALL"
+                } else {
+                    "ANY"
+                };
+                write!(f, "{}(", name)?;
+                write_joined(f, conditions)?;
+                write!(f, ")")
+            }
+            MatchCondition::Eq(key, value) => write!(f, "{} == {}", key, value),
+            MatchCondition::NotEq(key, value) => write!(f, "{} != {}", key, value),
+            MatchCondition::GEq(key, value) => write!(f, "{} >= {}", key, value),
+            MatchCondition::G(key, value) => write!(f, "{} > {}", key, value),
+            MatchCondition::LEq(key, value) => write!(f, "{} <= {}", key, value),
+            MatchCondition::L(key, value) => write!(f, "{} < {}", key, value),
+        }
+    }
+}
+
+/// `INSERT` of `content` with the `(INSERT ... INTO sub)` of each of the `sub_inserts` as a value.
+fn write_insert(
+    f: &mut fmt::Formatter<'_>,
+    entity: &str,
+    content: &Entity,
+    version: Option<u32>,
+    on_conflict: &Option<OnConflict>,
+    sub_inserts: &[(String, Wql)],
+) -> fmt::Result {
+    write!(f, "INSERT ")?;
+    version
+        .iter()
+        .try_for_each(|version| write!(f, "@v{} ", version))?;
+
+    let mut values = content
+        .iter()
+        .map(|(k, v)| (k, v as &dyn fmt::Display))
+        .collect::<BTreeMap<&String, &dyn fmt::Display>>();
+    values.extend(
+        sub_inserts
+            .iter()
+            .map(|(k, insert)| (k, insert as &dyn fmt::Display)),
+    );
+    write!(f, "{{")?;
+    values.into_iter().try_for_each(|(k, v)| {
+        if sub_inserts.iter().any(|(field, _)| field == k) {
+            write!(f, "{}: ({}), ", k, v)
+        } else {
+            write!(f, "{}: {}, ", k, v)
+        }
+    })?;
+    write!(f, "}} INTO {}", entity)?;
+
+    match on_conflict {
+        Some(OnConflict::Update(field)) => write!(f, " ON CONFLICT ({}) DO UPDATE", field),
+        Some(OnConflict::Nothing(field)) => write!(f, " ON CONFLICT ({}) DO NOTHING", field),
+        None => Ok(()),
+    }
+}
+
+fn write_where(f: &mut fmt::Formatter<'_>, clauses: &[Clause]) -> fmt::Result {
+    if clauses.is_empty() {
+        return Ok(());
+    }
+    write!(f, " WHERE {{ ")?;
+    clauses
+        .iter()
+        .try_for_each(|clause| write!(f, "{}, ", clause))?;
+    write!(f, "}}")
+}
+
+/// Values separated by `, ` without a trailing separator, `1, 2`.
+fn write_joined<T: fmt::Display>(f: &mut fmt::Formatter<'_>, values: &[T]) -> fmt::Result {
+    values
+        .iter()
+        .enumerate()
+        .try_for_each(|(i, v)| write!(f, "{}{}", if i > 0 { ", " } else { "" }, v))
+}
+
+fn write_set<T: fmt::Display>(f: &mut fmt::Formatter<'_>, values: &[T]) -> fmt::Result {
+    write!(f, "#{{")?;
+    values.iter().try_for_each(|v| write!(f, "{}, ", v))?;
+    write!(f, "}}")
+}
+
+fn write_entity(f: &mut fmt::Formatter<'_>, entity: &Entity) -> fmt::Result {
+    write!(f, "{{")?;
+    entity
+        .iter()
+        .collect::<BTreeMap<&String, &Types>>()
+        .into_iter()
+        .try_for_each(|(k, v)| write!(f, "{}: {}, ", k, v))?;
+    write!(f, "}}")
+}
+
+/// Entity map rendered with its keys sorted.
+struct Sorted<'a>(&'a Entity);
+
+impl fmt::Display for Sorted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_entity(f, self.0)
+    }
+}
+
+/// Renders the level as written after `BEGIN ISOLATION`.
+impl fmt::Display for IsolationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IsolationLevel::ReadUncommitted => write!(f, "READ UNCOMMITTED"),
+            IsolationLevel::ReadCommitted => write!(f, "READ COMMITTED"),
+            IsolationLevel::RepeatableRead => write!(f, "REPEATABLE READ"),
+            IsolationLevel::Serializable => write!(f, "SERIALIZABLE"),
+        }
+    }
+}
+
+/// ISO 8601 duration from days to seconds, `P1DT2H0.5S`, a zero duration is `PT0S`.
+fn write_duration(f: &mut fmt::Formatter<'_>, ms: i64) -> fmt::Result {
+    let sign = if ms < 0 { "-" } else { "" };
+    let ms = ms.unsigned_abs();
+    let (days, hours, minutes) = (ms / 86_400_000, ms / 3_600_000 % 24, ms / 60_000 % 60);
+    let (seconds, millis) = (ms / 1_000 % 60, ms % 1_000);
+
+    write!(f, "{}P", sign)?;
+    if days > 0 {
+        write!(f, "{}D", days)?;
+    }
+    if days > 0 && hours == 0 && minutes == 0 && seconds == 0 && millis == 0 {
+        return Ok(());
+    }
+    write!(f, "T")?;
+    if hours > 0 {
+        write!(f, "{}H", hours)?;
+    }
+    if minutes > 0 {
+        write!(f, "{}M", minutes)?;
+    }
+    match (seconds, millis) {
+        (0, 0) if hours > 0 || minutes > 0 => Ok(()),
+        (seconds, 0) => write!(f, "{}S", seconds),
+        (seconds, millis) => write!(
+            f,
+            "{}.{}S",
+            seconds,
+            format!("{:03}", millis).trim_end_matches('0')
+        ),
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    s.chars().try_for_each(|c| match c {
+        '\t' => write!(f, "\\t"),
+        '\r' => write!(f, "\\r"),
+        '\n' => write!(f, "\\n"),
+        '\\' => write!(f, "\\\\"),
+        '\"' => write!(f, "\\\""),
+        c => write!(f, "{}", c),
+    })?;
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Entity, Wql};
+    use std::{collections::HashMap, str::FromStr};
+    use uuid::Uuid;
+
+    /// Xorshift generator, a fixed seed makes every failing case reproducible.
+    struct Gen(u64);
+
+    impl Gen {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        fn ident(&mut self) -> String {
+            let len = 1 + self.below(8) as usize;
+            (0..len)
+                .map(|i| match self.below(if i == 0 { 26 } else { 37 }) {
+                    n @ 0..=25 => (b'a' + n as u8) as char,
+                    n @ 26..=35 => (b'0' + (n - 26) as u8) as char,
+                    _ => '_',
+                })
+                .collect()
+        }
+
+        /// Chars that are delimiters or escaped somewhere in WQL.
+        fn char(&mut self) -> char {
+            const CHARS: [char; 13] = [
+                'a', 'Z', '9', ' ', ',', '{', ']', '\t', '\n', '\\', '"', '\'', 'é',
+            ];
+            CHARS[self.below(CHARS.len() as u64) as usize]
+        }
+
+        fn string(&mut self) -> String {
+            let len = self.below(10) as usize;
+            (0..len).map(|_| self.char()).collect()
+        }
+
+        fn uuid(&mut self) -> Uuid {
+            let bytes = ((self.next() as u128) << 64) | self.next() as u128;
+            uuid::Builder::from_bytes(bytes.to_be_bytes())
+                .set_version(uuid::Version::Random)
+                .build()
+        }
+
+        /// Finite values only, non finite floats are rejected by the parser by default.
+        fn float(&mut self) -> f64 {
+            let value = (self.next() as i64) as f64 / (1 + self.below(1_000_000)) as f64;
+            if value.fract() == 0.0 {
+                value + 0.5
+            } else {
+                value
+            }
+        }
+
+        fn types(&mut self, depth: usize) -> Types {
+            let variants = if depth == 0 { 9 } else { 11 };
+            match self.below(variants) {
+                0 => Types::Char(self.char()),
+                1 => Types::Integer(self.next() as isize),
+                2 => Types::String(self.string()),
+                3 => Types::Uuid(self.uuid()),
+                4 => Types::Float(self.float()),
+                5 => Types::Boolean(self.below(2) == 0),
+                6 => Types::Precise(format!("{}{}", self.next(), self.next())),
+                7 => Types::Ref(self.ident(), self.uuid()),
+                8 => Types::Nil,
+                9 => Types::Vector((0..self.below(4)).map(|_| self.types(depth - 1)).collect()),
+                _ => Types::Map(self.entity(depth - 1)),
+            }
+        }
+
+        fn entity(&mut self, depth: usize) -> Entity {
+            (0..1 + self.below(5))
+                .map(|_| (self.ident(), self.types(depth)))
+                .collect::<HashMap<String, Types>>()
+        }
+    }
+
+    #[test]
+    fn display_parse_round_trip() {
+        for seed in 1..=500u64 {
+            let mut gen = Gen(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let entity = gen.entity(3);
+            let query = format!("INSERT {} INTO my_entity", Types::Map(entity.clone()));
+
+            assert_eq!(
+                Wql::from_str(&query),
+                Ok(Wql::Insert("my_entity".to_string(), entity, None, None)),
+                "seed {} rendered `{}`",
+                seed,
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn statement_display_round_trip() {
+        [
+            "CREATE ENTITY my_entity PRIMARY KEY (a, b) UNIQUES #{c,} ENCRYPT #{d,}",
+            "CREATE SCHEMA my_entity {a: Float, tags: Vector OR DEFAULT [],}",
+            "INSERT @v2 {b: [1, {c: \"x, y\",},], a: 1,} INTO my_entity ON CONFLICT (a) DO UPDATE",
+            "INSERT {a: 1, b: (INSERT {c: 2,} INTO other),} INTO my_entity",
+            "UPDATE my_entity CONTENT {a: 1,} INTO 2df2b8cf-49da-474d-8a00-c596c0bb6fd1",
+            "DELETE FROM my_entity WHERE id IN (2df2b8cf-49da-474d-8a00-c596c0bb6fd1, 3df2b8cf-49da-474d-8a00-c596c0bb6fd1)",
+            "MATCH ANY(a < 1, b != \"x\") UPDATE my_entity SET {a: 2,} INTO 2df2b8cf-49da-474d-8a00-c596c0bb6fd1",
+            "EVICT 2df2b8cf-49da-474d-8a00-c596c0bb6fd1 FROM my_entity",
+            "SELECT #{a, (b + c * 2) AS total, CASE WHEN a > 3 THEN \"big\" WHEN b like \"x%\" THEN b - 1 ELSE 0 END AS size,} FROM my_entity",
+            "SELECT * FROM my_entity ID 2df2b8cf-49da-474d-8a00-c596c0bb6fd1 WHEN AT 2020-01-01T00:00:00Z",
+            "SELECT * FROM my_entity AFTER AAAAAAAAAAAAAAAAAAAAAA LIMIT 2",
+            "SELECT * FROM my_entity SAMPLE 3 SEED 4",
+            "SELECT #{a,} FROM my_entity WHERE { ?* my_entity:a ?a, (between ?a 1 5), (or (== ?a 1) (not (like ?b \"x%\"))), (?a, ?b) == (1, 2), ?b IS NOT EMPTY, }",
+            "SELECT AVG(a) FROM my_entity WHERE { (> ?a 3), }",
+            "CHECK {pswd: \"my password\",} FROM my_entity ID 2df2b8cf-49da-474d-8a00-c596c0bb6fd1",
+            "COMMENT ON my_entity.a IS \"the \\\"a\\\" field\"",
+            "ALTER ENTITY my_entity RENAME FIELD a TO b",
+            "BEGIN ISOLATION READ COMMITTED",
+        ]
+        .iter()
+        .for_each(|query| {
+            let wql = Wql::from_str(query).unwrap();
+            let rendered = wql.to_string();
+
+            assert_eq!(Wql::from_str(&rendered), Ok(wql), "rendered `{}`", rendered);
+        });
+    }
+
+    #[test]
+    fn statement_display_sorts_keys() {
+        let wql = Wql::from_str("INSERT {b: {d: 1, c: 2,}, a: 1,} INTO my_entity").unwrap();
+
+        assert_eq!(
+            wql.to_string(),
+            "INSERT {a: 1, b: {c: 2, d: 1, }, } INTO my_entity"
+        );
+        assert_eq!(
+            Wql::from_str("MATCH ALL(a == 1, b >= 3) UPDATE my_entity SET {a: 2,} INTO 2df2b8cf-49da-474d-8a00-c596c0bb6fd1")
+                .unwrap()
+                .to_string(),
+            "MATCH ALL(a == 1, b >= 3) UPDATE my_entity SET {a: 2, } INTO 2df2b8cf-49da-474d-8a00-c596c0bb6fd1"
+        );
+    }
+
+    #[test]
+    fn display_literals() {
+        assert_eq!(Types::Float(3.0).to_string(), "3.0");
+        assert_eq!(Types::Char('\n').to_string(), "'\\n'");
+        assert_eq!(Types::Char('é').to_string(), "'é'");
+        assert_eq!(
+            Types::String("a \"b\"\n".to_string()).to_string(),
+            "\"a \\\"b\\\"\\n\""
+        );
+        assert_eq!(
+            Types::Vector(vec![Types::Integer(1), Types::Nil]).to_string(),
+            "[1, Nil, ]"
+        );
+    }
+}
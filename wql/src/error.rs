@@ -0,0 +1,136 @@
+use std::fmt;
+
+/// What stage of the parsing rejected a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A `/* ...` block comment is never closed.
+    UnclosedComment,
+    /// The query has no statement.
+    Empty,
+    /// The query does not start with a WQL statement keyword.
+    UnknownStatement,
+    /// The statement is malformed.
+    Syntax,
+    /// The statement is complete and followed by more input.
+    TrailingInput,
+    /// The statement is valid WQL but rejected by its `ParseOptions`.
+    Rejected,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::UnclosedComment => "unclosed_comment",
+            ErrorKind::Empty => "empty",
+            ErrorKind::UnknownStatement => "unknown_statement",
+            ErrorKind::Syntax => "syntax",
+            ErrorKind::TrailingInput => "trailing_input",
+            ErrorKind::Rejected => "rejected",
+        }
+    }
+}
+
+/// Parse error of `parse_with_diagnostics`. `message` is the error returned by `Wql::from_str` and
+/// `offset` is the byte where the error was found or, when the parser does not track it, where the
+/// statement starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WqlError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub offset: usize,
+}
+
+impl WqlError {
+    pub(crate) fn new(kind: ErrorKind, message: String, offset: usize) -> Self {
+        Self {
+            kind,
+            message,
+            offset,
+        }
+    }
+
+    /// Diagnostic for editors, `{"kind": "syntax", "message": "...", "offset": 0}`.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"kind\": \"{}\", \"message\": \"{}\", \"offset\": {}}}",
+            self.kind.as_str(),
+            escape_json(&self.message),
+            self.offset
+        )
+    }
+}
+
+impl fmt::Display for WqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut out, c| {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+            out
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parse_with_diagnostics, ParseOptions};
+
+    fn diagnostic(query: &str) -> String {
+        parse_with_diagnostics(query, &ParseOptions::default())
+            .unwrap_err()
+            .to_json()
+    }
+
+    #[test]
+    fn syntax_error_json() {
+        assert_eq!(
+            diagnostic("  INSERT {a: 1,} my_entity"),
+            "{\"kind\": \"syntax\", \"message\": \"Keyword INTO is required for INSERT\", \"offset\": 2}"
+        );
+    }
+
+    #[test]
+    fn positioned_errors_json() {
+        assert_eq!(
+            diagnostic("DO SOMETHING"),
+            "{\"kind\": \"unknown_statement\", \"message\": \"Symbol `DO` not implemented at offset 0\", \"offset\": 0}"
+        );
+        assert_eq!(
+            diagnostic("SELECT * /* a FROM my_entity"),
+            "{\"kind\": \"unclosed_comment\", \"message\": \"Block comment opened at offset 9 is not closed with `*/`\", \"offset\": 9}"
+        );
+        assert_eq!(
+            diagnostic("EVICT my_entity  extra"),
+            "{\"kind\": \"trailing_input\", \"message\": \"Unexpected trailing input: `extra`\", \"offset\": 17}"
+        );
+    }
+
+    #[test]
+    fn rejected_and_empty_json() {
+        let options = ParseOptions {
+            allow_nil: false,
+            ..ParseOptions::default()
+        };
+        let error = parse_with_diagnostics("INSERT {a: Nil,} INTO e", &options).unwrap_err();
+
+        assert_eq!(
+            error.to_json(),
+            "{\"kind\": \"rejected\", \"message\": \"Nil values are not permitted\", \"offset\": 0}"
+        );
+        assert_eq!(
+            diagnostic("   "),
+            "{\"kind\": \"empty\", \"message\": \"Empty WQL\", \"offset\": 3}"
+        );
+    }
+}
@@ -0,0 +1,615 @@
+use serde::{Deserialize, Serialize};
+
+use std::str::FromStr;
+
+use crate::{eval_filter, logic::read_duration, project, Clause, Entity, Function, Types};
+
+/// Arithmetic expression of a computed field, `SELECT #{a, (b + c) AS total,} FROM my_entity`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
+    Field(String),
+    Value(Types),
+    Operation(Operator, Box<Expr>, Box<Expr>),
+    /// `CASE WHEN age < 18 THEN "minor" ELSE "adult" END`, the first matching `WHEN` is evaluated.
+    Case(Vec<(Clause, Expr)>, Option<Box<Expr>>),
+}
+
+/// Alias and expression of a computed field.
+pub type ComputedField = (String, Expr);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Expr {
+    /// Evaluates the expression over the fields of `entity`. An absent or non numeric operand,
+    /// an `Integer` overflow and a division by zero evaluate to `Types::Nil` instead of failing the row,
+    /// as does a `CASE` without a matching `WHEN` nor an `ELSE`.
+    pub fn eval(&self, entity: &Entity) -> Types {
+        match self {
+            Expr::Field(field) => entity.get(field).cloned().unwrap_or(Types::Nil),
+            Expr::Value(value) => value.to_owned(),
+            Expr::Operation(op, lhs, rhs) => op.apply(&lhs.eval(entity), &rhs.eval(entity)),
+            Expr::Case(branches, otherwise) => branches
+                .iter()
+                .find(|(condition, _)| eval_filter(std::slice::from_ref(condition), entity))
+                .map(|(_, expr)| expr.eval(entity))
+                .or_else(|| otherwise.as_ref().map(|expr| expr.eval(entity)))
+                .unwrap_or(Types::Nil),
+        }
+    }
+}
+
+impl Operator {
+    /// `Integer`s keep integer arithmetic, so `/` truncates, any other numeric pair is computed as `Float`.
+    /// `DateTime`s and `Duration`s follow `Types::add_temporal` and `Types::sub_temporal`.
+    fn apply(self, lhs: &Types, rhs: &Types) -> Types {
+        if lhs.is_temporal() || rhs.is_temporal() {
+            let result = match self {
+                Operator::Add => lhs.add_temporal(rhs),
+                Operator::Sub => lhs.sub_temporal(rhs),
+                Operator::Mul | Operator::Div => Err(String::new()),
+            };
+            return result.unwrap_or(Types::Nil);
+        }
+        if let (Types::Integer(a), Types::Integer(b)) = (lhs, rhs) {
+            let result = match self {
+                Operator::Add => a.checked_add(*b),
+                Operator::Sub => a.checked_sub(*b),
+                Operator::Mul => a.checked_mul(*b),
+                Operator::Div => a.checked_div(*b),
+            };
+            return result.map_or(Types::Nil, Types::Integer);
+        }
+
+        match (lhs.as_f64(), rhs.as_f64()) {
+            (Some(a), Some(b)) => {
+                let result = match self {
+                    Operator::Add => a + b,
+                    Operator::Sub => a - b,
+                    Operator::Mul => a * b,
+                    Operator::Div => a / b,
+                };
+                if result.is_finite() {
+                    Types::Float(result)
+                } else {
+                    Types::Nil
+                }
+            }
+            _ => Types::Nil,
+        }
+    }
+}
+
+impl Types {
+    fn is_temporal(&self) -> bool {
+        matches!(self, Types::DateTime(_) | Types::Duration(_))
+    }
+
+    /// `DateTime + Duration`, `Duration + DateTime` and `Duration + Duration`, any other pair fails.
+    pub fn add_temporal(&self, rhs: &Types) -> Result<Types, String> {
+        match (self, rhs) {
+            (Types::DateTime(date), Types::Duration(ms))
+            | (Types::Duration(ms), Types::DateTime(date)) => date
+                .checked_add_signed(chrono::Duration::milliseconds(*ms))
+                .map(Types::DateTime)
+                .ok_or_else(|| format!("{} + {} is out of the DateTime range", self, rhs)),
+            (Types::Duration(a), Types::Duration(b)) => a
+                .checked_add(*b)
+                .map(Types::Duration)
+                .ok_or_else(|| format!("{} + {} is out of the Duration range", self, rhs)),
+            _ => Err(format!(
+                "{} cannot be added to {}",
+                rhs.type_tag(),
+                self.type_tag()
+            )),
+        }
+    }
+
+    /// `DateTime - Duration`, `DateTime - DateTime`, which is a `Duration`, and `Duration - Duration`,
+    /// any other pair fails.
+    pub fn sub_temporal(&self, rhs: &Types) -> Result<Types, String> {
+        match (self, rhs) {
+            (Types::DateTime(date), Types::Duration(ms)) => date
+                .checked_sub_signed(chrono::Duration::milliseconds(*ms))
+                .map(Types::DateTime)
+                .ok_or_else(|| format!("{} - {} is out of the DateTime range", self, rhs)),
+            (Types::DateTime(a), Types::DateTime(b)) => Ok(Types::Duration(
+                a.signed_duration_since(*b).num_milliseconds(),
+            )),
+            (Types::Duration(a), Types::Duration(b)) => a
+                .checked_sub(*b)
+                .map(Types::Duration)
+                .ok_or_else(|| format!("{} - {} is out of the Duration range", self, rhs)),
+            _ => Err(format!(
+                "{} cannot be subtracted from {}",
+                rhs.type_tag(),
+                self.type_tag()
+            )),
+        }
+    }
+}
+
+/// Projects the `keys` of `entity` and adds the value of every computed field under its alias.
+pub fn project_computed(entity: &Entity, keys: &[String], computed: &[ComputedField]) -> Entity {
+    let mut projected = project(entity, keys);
+    computed.iter().for_each(|(alias, expr)| {
+        projected.insert(alias.to_owned(), expr.eval(entity));
+    });
+    projected
+}
+
+/// Reads a computed field after its opening `(`, `b + c) AS total`.
+pub(crate) fn read_computed_field(chars: &mut std::str::Chars) -> Result<ComputedField, String> {
+    let mut depth = 1;
+    let mut source = String::new();
+    for c in chars.by_ref() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 1 => {
+                depth = 0;
+                break;
+            }
+            ')' => depth -= 1,
+            _ => (),
+        }
+        source.push(c);
+    }
+    if depth != 0 {
+        return Err(String::from(
+            "Computed field expression should end with `)`",
+        ));
+    }
+
+    let alias = read_alias(chars, &format!("({})", source))?;
+    Ok((alias, parse_expr(&source)?))
+}
+
+/// Reads a conditional field after its `CASE` keyword, `WHEN age < 18 THEN "minor" END AS group`.
+pub(crate) fn read_case_field(chars: &mut std::str::Chars) -> Result<ComputedField, String> {
+    let mut source = String::from("CASE ");
+    let mut word = String::new();
+    let mut in_str = false;
+    let mut escaped = false;
+    loop {
+        match chars.next() {
+            Some(c) if in_str || !(c.is_whitespace() || c == ',' || c == '}') => {
+                if c == '"' && !escaped {
+                    in_str = !in_str;
+                }
+                escaped = c == '\\' && !escaped;
+                word.push(c);
+                source.push(c);
+            }
+            _ if word.eq_ignore_ascii_case("END") => break,
+            Some(c) if c.is_whitespace() => {
+                word.clear();
+                source.push(c);
+            }
+            _ => {
+                return Err(format!(
+                    "CASE expression `{}` requires END",
+                    source.trim_end()
+                ))
+            }
+        }
+    }
+
+    let alias = read_alias(chars, &source)?;
+    Ok((alias, parse_expr(&source)?))
+}
+
+fn read_alias(chars: &mut std::str::Chars, source: &str) -> Result<String, String> {
+    let keyword = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+    let alias = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || c == &'_')
+        .collect::<String>();
+    if keyword.to_uppercase() != "AS" || alias.is_empty() {
+        return Err(format!(
+            "Computed field `{}` requires `AS field_name`",
+            source
+        ));
+    }
+    Ok(alias)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Field(String),
+    Str(String),
+    Op(Operator),
+    Cmp(Function),
+    Open,
+    Close,
+}
+
+fn parse_expr(source: &str) -> Result<Expr, String> {
+    let tokens = tokenize(source)?;
+    let mut tokens = tokens.iter().peekable();
+    let expr = sum(&mut tokens, source)?;
+
+    match tokens.next() {
+        None => Ok(expr),
+        Some(token) => Err(format!(
+            "Expression `{}` has unexpected token {:?}",
+            source, token
+        )),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '+' => Token::Op(Operator::Add),
+            '*' => Token::Op(Operator::Mul),
+            '/' => Token::Op(Operator::Div),
+            '"' => Token::Str(read_str(source, &mut chars)?),
+            '<' | '>' | '=' | '!' => {
+                let op = read_while(c, &mut chars, |c| matches!(c, '<' | '>' | '='));
+                match &op[..] {
+                    "=" => Token::Cmp(Function::Eq),
+                    op => match Function::from_str(op) {
+                        Ok(Function::Error) | Err(_) => {
+                            return Err(format!(
+                                "Expression `{}` has unexpected operator `{}`",
+                                source, op
+                            ))
+                        }
+                        Ok(function) => Token::Cmp(function),
+                    },
+                }
+            }
+            '-' if !matches!(
+                tokens.last(),
+                Some(Token::Number(_)) | Some(Token::Field(_)) | Some(Token::Close)
+            ) && chars.peek().is_some_and(|c| c.is_ascii_digit()) =>
+            {
+                Token::Number(read_while(c, &mut chars, |c| {
+                    c.is_ascii_digit() || c == '.'
+                }))
+            }
+            '-' => Token::Op(Operator::Sub),
+            c if c.is_ascii_digit() => Token::Number(read_while(c, &mut chars, |c| {
+                c.is_ascii_digit() || c == '.'
+            })),
+            c if c.is_alphanumeric() || c == '_' => Token::Field(read_while(c, &mut chars, |c| {
+                c.is_alphanumeric() || c == '_'
+            })),
+            c => {
+                return Err(format!(
+                    "Expression `{}` has unexpected character `{}`",
+                    source, c
+                ))
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn read_str(
+    source: &str,
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Result<String, String> {
+    let mut s = String::new();
+    let mut escaped = false;
+    for c in chars.by_ref() {
+        match c {
+            '"' if !escaped => return Ok(s),
+            '\\' if !escaped => escaped = true,
+            c => {
+                escaped = false;
+                s.push(c)
+            }
+        }
+    }
+    Err(format!(
+        "Expression `{}` has an unterminated String",
+        source
+    ))
+}
+
+fn read_while<F: Fn(char) -> bool>(
+    first: char,
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    f: F,
+) -> String {
+    let mut s = first.to_string();
+    while let Some(c) = chars.peek().copied().filter(|c| f(*c)) {
+        s.push(c);
+        chars.next();
+    }
+    s
+}
+
+type Tokens<'a> = std::iter::Peekable<std::slice::Iter<'a, Token>>;
+
+fn sum(tokens: &mut Tokens, source: &str) -> Result<Expr, String> {
+    let mut expr = product(tokens, source)?;
+    while let Some(Token::Op(op @ (Operator::Add | Operator::Sub))) = tokens.peek() {
+        tokens.next();
+        expr = Expr::Operation(*op, Box::new(expr), Box::new(product(tokens, source)?));
+    }
+    Ok(expr)
+}
+
+fn product(tokens: &mut Tokens, source: &str) -> Result<Expr, String> {
+    let mut expr = operand(tokens, source)?;
+    while let Some(Token::Op(op @ (Operator::Mul | Operator::Div))) = tokens.peek() {
+        tokens.next();
+        expr = Expr::Operation(*op, Box::new(expr), Box::new(operand(tokens, source)?));
+    }
+    Ok(expr)
+}
+
+const CASE_KEYWORDS: [&str; 4] = ["WHEN", "THEN", "ELSE", "END"];
+
+fn is_keyword(token: &Token, keyword: &str) -> bool {
+    matches!(token, Token::Field(field) if field.eq_ignore_ascii_case(keyword))
+}
+
+fn operand(tokens: &mut Tokens, source: &str) -> Result<Expr, String> {
+    match tokens.next() {
+        Some(Token::Field(field)) if field.eq_ignore_ascii_case("CASE") => case(tokens, source),
+        Some(Token::Field(field)) if read_duration(field).is_some() => {
+            Ok(Expr::Value(read_duration(field).unwrap()))
+        }
+        Some(Token::Field(field))
+            if !CASE_KEYWORDS.iter().any(|k| field.eq_ignore_ascii_case(k)) =>
+        {
+            Ok(Expr::Field(field.to_owned()))
+        }
+        Some(Token::Str(s)) => Ok(Expr::Value(Types::String(s.to_owned()))),
+        Some(Token::Number(n)) => n
+            .parse::<isize>()
+            .map(Types::Integer)
+            .or_else(|_| n.parse::<f64>().map(Types::Float))
+            .map(Expr::Value)
+            .map_err(|_| format!("Expression `{}` has invalid number `{}`", source, n)),
+        Some(Token::Open) => {
+            let expr = sum(tokens, source)?;
+            if tokens.next() == Some(&Token::Close) {
+                Ok(expr)
+            } else {
+                Err(format!(
+                    "Expression `{}` has unbalanced parentheses",
+                    source
+                ))
+            }
+        }
+        _ => Err(format!("Expression `{}` is missing an operand", source)),
+    }
+}
+
+/// Reads the `WHEN <field> <comparison> <value> THEN <expr>` branches of a `CASE` until `END`.
+fn case(tokens: &mut Tokens, source: &str) -> Result<Expr, String> {
+    let mut branches = Vec::new();
+    let mut otherwise = None;
+    loop {
+        match tokens.next() {
+            Some(token) if is_keyword(token, "WHEN") && otherwise.is_none() => {
+                let condition = condition(tokens, source)?;
+                if !tokens.next().is_some_and(|token| is_keyword(token, "THEN")) {
+                    return Err(format!(
+                        "CASE expression `{}` requires THEN after WHEN",
+                        source
+                    ));
+                }
+                branches.push((condition, sum(tokens, source)?));
+            }
+            Some(token) if is_keyword(token, "ELSE") && otherwise.is_none() => {
+                otherwise = Some(Box::new(sum(tokens, source)?));
+            }
+            Some(token) if is_keyword(token, "END") => break,
+            Some(token) => {
+                return Err(format!(
+                    "CASE expression `{}` has unexpected token {:?}",
+                    source, token
+                ))
+            }
+            None => return Err(format!("CASE expression `{}` requires END", source)),
+        }
+    }
+
+    if branches.is_empty() {
+        Err(format!(
+            "CASE expression `{}` requires at least one WHEN",
+            source
+        ))
+    } else {
+        Ok(Expr::Case(branches, otherwise))
+    }
+}
+
+fn condition(tokens: &mut Tokens, source: &str) -> Result<Clause, String> {
+    let field = match tokens.next() {
+        Some(Token::Field(field)) => field.to_owned(),
+        _ => {
+            return Err(format!(
+                "CASE condition in `{}` should start with a field",
+                source
+            ))
+        }
+    };
+    let function = match tokens.next() {
+        Some(Token::Cmp(function)) => function.to_owned(),
+        Some(token) if is_keyword(token, "LIKE") => Function::Like,
+        Some(token) if is_keyword(token, "ILIKE") => Function::ILike,
+        _ => {
+            return Err(format!(
+                "CASE condition on `{}` requires a comparison operator",
+                field
+            ))
+        }
+    };
+    match operand(tokens, source)? {
+        Expr::Value(value) => Ok(Clause::SimpleComparisonFunction(function, field, value)),
+        _ => Err(format!(
+            "CASE condition on `{}` should compare to a value",
+            field
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn field(name: &str) -> Box<Expr> {
+        Box::new(Expr::Field(name.to_string()))
+    }
+
+    #[test]
+    fn precedence_and_parentheses() {
+        assert_eq!(
+            parse_expr("a + b * 2").unwrap(),
+            Expr::Operation(
+                Operator::Add,
+                field("a"),
+                Box::new(Expr::Operation(
+                    Operator::Mul,
+                    field("b"),
+                    Box::new(Expr::Value(Types::Integer(2)))
+                ))
+            )
+        );
+        assert_eq!(
+            parse_expr("(a - b) / -2.5").unwrap(),
+            Expr::Operation(
+                Operator::Div,
+                Box::new(Expr::Operation(Operator::Sub, field("a"), field("b"))),
+                Box::new(Expr::Value(Types::Float(-2.5)))
+            )
+        );
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        assert_eq!(
+            parse_expr("a +").err(),
+            Some(String::from("Expression `a +` is missing an operand"))
+        );
+        assert_eq!(
+            parse_expr("a % b").err(),
+            Some(String::from(
+                "Expression `a % b` has unexpected character `%`"
+            ))
+        );
+    }
+
+    #[test]
+    fn eval_numeric_and_nil() {
+        let mut entity = HashMap::new();
+        entity.insert("a".to_string(), Types::Integer(7));
+        entity.insert("b".to_string(), Types::Float(0.5));
+        entity.insert("s".to_string(), Types::String("hello".to_string()));
+
+        let eval = |s: &str| parse_expr(s).unwrap().eval(&entity);
+        assert_eq!(eval("a / 2"), Types::Integer(3));
+        assert_eq!(eval("a * b"), Types::Float(3.5));
+        assert_eq!(eval("a + s"), Types::Nil);
+        assert_eq!(eval("a + missing"), Types::Nil);
+        assert_eq!(eval("a / 0"), Types::Nil);
+    }
+
+    #[test]
+    fn temporal_arithmetic() {
+        use chrono::{DateTime, Utc};
+        use std::str::FromStr;
+
+        let utc = |s: &str| Types::DateTime(DateTime::<Utc>::from_str(s).unwrap());
+        let hour = Types::Duration(60 * 60 * 1000);
+        let start = utc("2020-01-01T00:00:00Z");
+        let end = utc("2020-01-01T01:00:00Z");
+
+        assert_eq!(start.add_temporal(&hour), Ok(end.clone()));
+        assert_eq!(hour.add_temporal(&start), Ok(end.clone()));
+        assert_eq!(
+            hour.add_temporal(&hour),
+            Ok(Types::Duration(2 * 60 * 60 * 1000))
+        );
+        assert_eq!(end.sub_temporal(&hour), Ok(start.clone()));
+        assert_eq!(end.sub_temporal(&start), Ok(hour.clone()));
+        assert_eq!(
+            start.sub_temporal(&end),
+            Ok(Types::Duration(-60 * 60 * 1000))
+        );
+        assert_eq!(hour.sub_temporal(&hour), Ok(Types::Duration(0)));
+
+        assert_eq!(
+            hour.add_temporal(&Types::String("1h".to_string())),
+            Err(String::from("String cannot be added to Duration"))
+        );
+        assert_eq!(
+            hour.sub_temporal(&start),
+            Err(String::from("DateTime cannot be subtracted from Duration"))
+        );
+        assert_eq!(
+            Types::Duration(i64::MAX).add_temporal(&Types::Duration(1)),
+            Err(format!(
+                "{} + PT0.001S is out of the Duration range",
+                Types::Duration(i64::MAX)
+            ))
+        );
+
+        let mut entity = HashMap::new();
+        entity.insert("created_at".to_string(), start);
+        entity.insert("ttl".to_string(), hour);
+        let eval = |s: &str| parse_expr(s).unwrap().eval(&entity);
+        assert_eq!(eval("created_at + PT1H"), end);
+        assert_eq!(eval("created_at + ttl - created_at"), entity["ttl"]);
+        assert_eq!(eval("ttl * 2"), Types::Nil);
+        assert_eq!(eval("created_at + 1"), Types::Nil);
+    }
+
+    #[test]
+    fn case_branches_and_errors() {
+        let mut entity = HashMap::new();
+        entity.insert("age".to_string(), Types::Integer(70));
+
+        let eval = |s: &str| parse_expr(s).unwrap().eval(&entity);
+        assert_eq!(
+            eval("CASE WHEN age < 18 THEN 0 WHEN age >= 65 THEN age * 2 END"),
+            Types::Integer(140)
+        );
+        assert_eq!(eval("CASE WHEN age == 1 THEN 1 END"), Types::Nil);
+        assert_eq!(
+            eval("(CASE WHEN age != 70 THEN 1 ELSE 2 END) + 1"),
+            Types::Integer(3)
+        );
+
+        assert_eq!(
+            parse_expr("CASE WHEN age < 18 THEN 1").err(),
+            Some(String::from(
+                "CASE expression `CASE WHEN age < 18 THEN 1` requires END"
+            ))
+        );
+        assert_eq!(
+            parse_expr("CASE WHEN age < b THEN 1 END").err(),
+            Some(String::from(
+                "CASE condition on `age` should compare to a value"
+            ))
+        );
+        assert_eq!(
+            parse_expr("CASE ELSE 1 END").err(),
+            Some(String::from(
+                "CASE expression `CASE ELSE 1 END` requires at least one WHEN"
+            ))
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use crate::{node_id::statement_name, Clause, Entity, Expr, MatchCondition, ToSelect, Wql};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash of the shape of `wql`, its statement, entity, keys, fields and comparison functions, with every
+/// literal value, id, date, limit and seed normalized out. `WHERE {?* e:age ?a, (== ?a 18),}` and
+/// `WHERE {?* e:age ?a, (== ?a 25),}` share a fingerprint while `(> ?a 18)` does not.
+/// Map keys are hashed in sorted order and the hash is FNV-1a, so fingerprints are stable across runs.
+pub fn query_fingerprint(wql: &Wql) -> u64 {
+    let mut shape = Shape(FNV_OFFSET);
+    shape.statement(wql);
+    shape.0
+}
+
+struct Shape(u64);
+
+impl Shape {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |hash, b| {
+            (hash ^ u64::from(*b)).wrapping_mul(FNV_PRIME)
+        });
+    }
+
+    fn tag(&mut self, tag: u8) {
+        self.bytes(&[tag]);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.bytes(&(s.len() as u64).to_be_bytes());
+        self.bytes(s.as_bytes());
+    }
+
+    fn strs<'a>(&mut self, strs: impl IntoIterator<Item = &'a String>) {
+        let strs = strs.into_iter().collect::<Vec<&String>>();
+        self.bytes(&(strs.len() as u64).to_be_bytes());
+        strs.into_iter().for_each(|s| self.str(s));
+    }
+
+    fn debug<T: std::fmt::Debug>(&mut self, value: &T) {
+        self.str(&format!("{:?}", value));
+    }
+
+    fn is_some<T>(&mut self, option: &Option<T>) {
+        self.tag(option.is_some() as u8);
+    }
+
+    fn statement(&mut self, wql: &Wql) {
+        self.str(statement_name(wql));
+        self.debug(&std::mem::discriminant(wql));
+        if let Some(entity) = wql.entity_name() {
+            self.str(entity);
+        }
+
+        match wql {
+            Wql::CreateEntity(_, uniques, encrypts, primary_key) => {
+                self.strs(uniques);
+                self.strs(encrypts);
+                self.strs(primary_key);
+            }
+            Wql::Insert(_, entity, version, on_conflict) => {
+                self.entity(entity);
+                self.debug(version);
+                self.debug(on_conflict);
+            }
+            Wql::InsertMany(_, entities) => entities.iter().for_each(|entity| self.entity(entity)),
+            Wql::NestedInsert(insert, sub_inserts) => {
+                self.statement(insert);
+                sub_inserts.iter().for_each(|(field, sub_insert)| {
+                    self.str(field);
+                    self.statement(sub_insert);
+                });
+            }
+            Wql::UpdateContent(_, entity, _) | Wql::UpdateSet(_, entity, _) => self.entity(entity),
+            Wql::MatchUpdate(_, entity, _, conditions) => {
+                self.entity(entity);
+                self.condition(conditions);
+            }
+            Wql::Evict(_, id) => self.is_some(id),
+            Wql::Select(_, to_select, id) | Wql::SelectWhen(_, to_select, id, _) => {
+                self.selection(to_select);
+                self.is_some(id);
+            }
+            Wql::SelectIds(_, to_select, _) => self.selection(to_select),
+            Wql::SelectPage(_, to_select, cursor, limit) => {
+                self.selection(to_select);
+                self.is_some(cursor);
+                self.is_some(limit);
+            }
+            Wql::SelectSample(_, to_select, _, seed) => {
+                self.selection(to_select);
+                self.is_some(seed);
+            }
+            Wql::SelectWhere(_, to_select, clauses) => {
+                self.selection(to_select);
+                self.clauses(clauses);
+            }
+            Wql::SelectCount(_, clauses) => self.clauses(clauses),
+            Wql::SelectSum(_, field, clauses) | Wql::SelectAvg(_, field, clauses) => {
+                self.str(field);
+                self.clauses(clauses);
+            }
+            Wql::CheckValue(_, _, map) => self.sorted_keys(map),
+            Wql::Comment(_, field, _) => {
+                self.is_some(field);
+                field.iter().for_each(|field| self.str(field));
+            }
+            Wql::Let(name, _) => self.str(name),
+            Wql::RenameField(_, from, to) => {
+                self.str(from);
+                self.str(to);
+            }
+            Wql::DropField(_, field) => self.str(field),
+            Wql::CreateSchema(_, schema, defaults) => {
+                let mut fields = schema.iter().collect::<Vec<_>>();
+                fields.sort_by(|a, b| a.0.cmp(b.0));
+                fields.into_iter().for_each(|(field, tag)| {
+                    self.str(field);
+                    self.debug(tag);
+                });
+                self.sorted_keys(defaults);
+            }
+            Wql::Begin(isolation) => self.debug(isolation),
+            Wql::Delete(_, _)
+            | Wql::DeleteMany(_, _)
+            | Wql::Truncate(_)
+            | Wql::SelectWhenRange(_, _, _, _)
+            | Wql::SelectHistory(_, _)
+            | Wql::SelectLatest(_, _)
+            | Wql::Use(_)
+            | Wql::CreateDocumentStore(_) => (),
+        }
+    }
+
+    /// Keys of the entity, its values are literals.
+    fn entity(&mut self, entity: &Entity) {
+        self.sorted_keys(entity);
+    }
+
+    fn sorted_keys<V>(&mut self, map: &HashMap<String, V>) {
+        let mut keys = map.keys().collect::<Vec<&String>>();
+        keys.sort();
+        self.strs(keys);
+    }
+
+    fn selection(&mut self, to_select: &ToSelect) {
+        match to_select {
+            ToSelect::All => self.tag(0),
+            ToSelect::Keys(keys) => {
+                self.tag(1);
+                self.strs(keys);
+            }
+            ToSelect::Computed(keys, computed) => {
+                self.tag(2);
+                self.strs(keys);
+                computed.iter().for_each(|(alias, expr)| {
+                    self.str(alias);
+                    self.expr(expr);
+                });
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Field(field) => {
+                self.tag(0);
+                self.str(field);
+            }
+            Expr::Value(_) => self.tag(1),
+            Expr::Operation(operator, lhs, rhs) => {
+                self.tag(2);
+                self.debug(operator);
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            Expr::Case(whens, otherwise) => {
+                self.tag(3);
+                self.bytes(&(whens.len() as u64).to_be_bytes());
+                whens.iter().for_each(|(clause, then)| {
+                    self.clauses(std::slice::from_ref(clause));
+                    self.expr(then);
+                });
+                self.is_some(otherwise);
+                otherwise.iter().for_each(|otherwise| self.expr(otherwise));
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &MatchCondition) {
+        self.debug(&std::mem::discriminant(condition));
+        match condition {
+            MatchCondition::All(conditions) | MatchCondition::Any(conditions) => {
+                self.bytes(&(conditions.len() as u64).to_be_bytes());
+                conditions.iter().for_each(|c| self.condition(c));
+            }
+            MatchCondition::Eq(field, _)
+            | MatchCondition::NotEq(field, _)
+            | MatchCondition::GEq(field, _)
+            | MatchCondition::G(field, _)
+            | MatchCondition::LEq(field, _)
+            | MatchCondition::L(field, _) => self.str(field),
+        }
+    }
+
+    fn clauses(&mut self, clauses: &[Clause]) {
+        self.bytes(&(clauses.len() as u64).to_be_bytes());
+        clauses.iter().for_each(|clause| {
+            self.debug(&std::mem::discriminant(clause));
+            match clause {
+                Clause::ContainsKeyValue(var, key, _) => {
+                    self.str(var);
+                    self.str(key);
+                }
+                Clause::ValueAttribution(var, key, value) => {
+                    self.str(var);
+                    self.str(key);
+                    self.str(&value.0);
+                }
+                Clause::SimpleComparisonFunction(function, var, _)
+                | Clause::ComplexComparisonFunctions(function, var, _) => {
+                    self.debug(function);
+                    self.str(var);
+                }
+                Clause::Or(function, clauses) => {
+                    self.debug(function);
+                    self.clauses(clauses);
+                }
+                Clause::TupleEq(fields, _) => self.strs(fields),
+                Clause::IsEmpty(field) | Clause::IsNotEmpty(field) | Clause::Missing(field) => {
+                    self.str(field)
+                }
+                Clause::Error => (),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn fingerprint(query: &str) -> u64 {
+        query_fingerprint(&Wql::from_str(query).unwrap())
+    }
+
+    #[test]
+    fn same_shape_same_fingerprint() {
+        assert_eq!(
+            fingerprint("SELECT * FROM e WHERE {?* e:age ?a, (== ?a 18),}"),
+            fingerprint("SELECT * FROM e WHERE {?* e:age ?a, (== ?a 25),}")
+        );
+        assert_eq!(
+            fingerprint("INSERT {a: 1, b: \"x\",} INTO e"),
+            fingerprint("INSERT {b: \"other\", a: 2,} INTO e")
+        );
+        assert_eq!(
+            fingerprint("SELECT #{a,} FROM e ID 2df2b8cf-49da-474d-8a00-c596c0bb6fd1"),
+            fingerprint("SELECT #{a,} FROM e ID 53315090-e14d-4738-a4d2-f1ec2a93664c")
+        );
+        assert_eq!(
+            fingerprint("SELECT * FROM e SAMPLE 3 SEED 42"),
+            fingerprint("SELECT * FROM e SAMPLE 100 SEED 7")
+        );
+    }
+
+    #[test]
+    fn different_shape_different_fingerprint() {
+        let eq = fingerprint("SELECT * FROM e WHERE {?* e:age ?a, (== ?a 18),}");
+
+        assert_ne!(
+            eq,
+            fingerprint("SELECT * FROM e WHERE {?* e:age ?a, (> ?a 18),}")
+        );
+        assert_ne!(
+            eq,
+            fingerprint("SELECT * FROM e WHERE {?* e:height ?a, (== ?a 18),}")
+        );
+        assert_ne!(
+            eq,
+            fingerprint("SELECT * FROM other WHERE {?* other:age ?a, (== ?a 18),}")
+        );
+        assert_ne!(
+            eq,
+            fingerprint("SELECT #{age,} FROM e WHERE {?* e:age ?a, (== ?a 18),}")
+        );
+        assert_ne!(
+            fingerprint("INSERT {a: 1,} INTO e"),
+            fingerprint("INSERT {a: 1, b: 2,} INTO e")
+        );
+        assert_ne!(
+            fingerprint("SELECT * FROM e SAMPLE 3"),
+            fingerprint("SELECT * FROM e SAMPLE 3 SEED 42")
+        );
+        assert_ne!(fingerprint("SELECT * FROM e"), fingerprint("TRUNCATE e"));
+    }
+}
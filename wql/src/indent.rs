@@ -0,0 +1,177 @@
+/// Rewrites indentation blocks as brace blocks so the regular parser can read them.
+/// A line followed by a more indented line opens a map, unless it already ends with an opening
+/// delimiter or a `,`, in which case the block is kept as it is written. The lines of a multi-line
+/// string are part of the line opening the string and are kept as they are written.
+pub(crate) fn desugar(s: &str) -> String {
+    let lines = logical_lines(s)
+        .into_iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| (l.len() - l.trim_start().len(), l.trim()))
+        .collect::<Vec<(usize, &str)>>();
+    let mut blocks: Vec<(usize, Option<&str>)> = Vec::new();
+    let mut out = Vec::new();
+
+    for (i, (indent, line)) in lines.iter().enumerate() {
+        while let Some((block_indent, closing)) = blocks.last() {
+            if indent >= block_indent {
+                break;
+            }
+            if let Some(closing) = closing {
+                out.push((*closing).to_string());
+            }
+            blocks.pop();
+        }
+
+        let in_indent_block = blocks.last().is_some_and(|(_, c)| c.is_some());
+        let next_indent = lines.get(i + 1).map(|(n, _)| *n).filter(|n| n > indent);
+        let explicit = line.ends_with(['{', '[', '(', ',']);
+
+        match next_indent {
+            Some(next) if !explicit => {
+                out.push(format!("{} {{", line));
+                blocks.push((next, Some(if in_indent_block { "}," } else { "}" })));
+            }
+            Some(next) => {
+                out.push((*line).to_string());
+                blocks.push((next, None));
+            }
+            None if in_indent_block && !line.ends_with(',') => out.push(format!("{},", line)),
+            None => out.push((*line).to_string()),
+        }
+    }
+
+    while let Some((_, closing)) = blocks.pop() {
+        if let Some(closing) = closing {
+            out.push(closing.to_string());
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Lines of `s` outside of string literals, a line break inside a `"` string or a char literal
+/// does not end the line.
+fn logical_lines(s: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '\n') => {
+                lines.push(&s[start..i]);
+                start = i + 1;
+            }
+            (None, _) => (),
+        }
+    }
+    lines.push(&s[start..]);
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_with_options, ParseOptions, Types, Wql};
+    use std::{collections::HashMap, str::FromStr};
+
+    fn indent_options() -> ParseOptions {
+        ParseOptions {
+            significant_indent: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn desugar_nested_map() {
+        let query = "INSERT\n  a: 1\n  b:\n    c: 2\nINTO my_entity";
+
+        assert_eq!(
+            desugar(query),
+            "INSERT {\na: 1,\nb: {\nc: 2,\n},\n}\nINTO my_entity"
+        );
+    }
+
+    #[test]
+    fn desugar_keeps_multi_line_strings() {
+        let query =
+            "INSERT\n  a: \"first\n    second:\n\n  third\"\n  b: '\"'\n  c: 1\nINTO my_entity";
+
+        assert_eq!(
+            desugar(query),
+            "INSERT {\na: \"first\n    second:\n\n  third\",\nb: '\"',\nc: 1,\n}\nINTO my_entity"
+        );
+
+        let mut hm = HashMap::new();
+        hm.insert(
+            "a".to_string(),
+            Types::String("first\n    second:\n\n  third".to_string()),
+        );
+        hm.insert("b".to_string(), Types::Char('"'));
+        hm.insert("c".to_string(), Types::Integer(1));
+        assert_eq!(
+            parse_with_options(query, &indent_options()),
+            Ok(Wql::Insert("my_entity".to_string(), hm, None, None))
+        );
+    }
+
+    #[test]
+    fn indented_nested_structure() {
+        let query = "INSERT
+    a: 123
+    b:
+        c: \"hello\"
+        d:
+            e: true
+INTO my_entity";
+        let wql = parse_with_options(query, &indent_options());
+
+        let mut d = HashMap::new();
+        d.insert("e".to_string(), Types::Boolean(true));
+        let mut b = HashMap::new();
+        b.insert("c".to_string(), Types::String("hello".to_string()));
+        b.insert("d".to_string(), Types::Map(d));
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert("b".to_string(), Types::Map(b));
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
+        );
+    }
+
+    #[test]
+    fn brace_syntax_with_indent_on() {
+        let query = "INSERT {
+            a: 123,
+            b: {
+                c: \"hello\",
+                d: [1, 2,],
+            },
+        } INTO my_entity";
+
+        assert_eq!(
+            parse_with_options(query, &indent_options()),
+            Wql::from_str(query)
+        );
+        assert_eq!(
+            parse_with_options("SELECT * FROM my_entity", &indent_options()),
+            Wql::from_str("SELECT * FROM my_entity")
+        );
+    }
+
+    #[test]
+    fn indent_off_by_default() {
+        let query = "INSERT
+    a: 123
+INTO my_entity";
+
+        assert!(parse_with_options(query, &ParseOptions::default()).is_err());
+    }
+}
@@ -1,50 +1,348 @@
 use crate::{
-    logic::{read_args, read_map_as_str},
-    select::{select_all, select_args},
+    logic::{
+        parse_bound_value, parse_key, parse_value_with, read_args, read_field_list,
+        read_insert_map, read_map_as_str, read_nested, read_str_with,
+    },
+    select::{select_all, select_args, select_function},
 };
 
-use super::{read_map, read_match_args, FromStr, MatchCondition, Uuid, Wql};
+use super::{
+    read_map, read_match_args, Entity, FromStr, HashMap, IsolationLevel, MatchCondition,
+    OnConflict, ParseOptions, TypeTag, Types, Uuid, Wql,
+};
+
+pub(crate) fn read_symbol(
+    a: char,
+    chars: &mut std::str::Chars,
+    offset: usize,
+    options: &ParseOptions,
+) -> Result<Wql, String> {
+    let symbol = format!(
+        "{}{}",
+        a,
+        chars.take_while(|c| !c.is_whitespace()).collect::<String>()
+    );
+
+    match &symbol[..] {
+        s if match_keyword("CREATE", s) => create_entity(chars, options),
+        s if match_keyword("INSERT", s) => insert(chars, options),
+        s if match_keyword("UPDATE", s) => update(chars, options),
+        s if match_keyword("DELETE", s) => delete(chars, options),
+        s if match_keyword("MATCH", s) => match_update(chars, options),
+        s if match_keyword("EVICT", s) => evict(chars),
+        s if match_keyword("TRUNCATE", s) => truncate(chars, options),
+        s if match_keyword("SELECT", s) => select(chars),
+        s if match_keyword("CHECK", s) => check(chars, options),
+        s if match_keyword("COMMENT", s) => comment(chars, options),
+        s if match_keyword("USE", s) => use_entity(chars, options),
+        s if match_keyword("LET", s) => let_binding(chars, options),
+        s if match_keyword("ALTER", s) => alter_entity(chars, options),
+        s if match_keyword("BEGIN", s) => begin(chars, options),
+        s if options.compact_insert && compact_entity(s).is_some() => {
+            compact_insert(compact_entity(s).unwrap(), chars, options)
+        }
+        _ => Err(options.messages.symbol_not_implemented(&symbol, offset)),
+    }
+}
+
+/// Whether `symbol` starts one of the statements of `read_symbol`.
+pub(crate) fn is_statement(symbol: &str) -> bool {
+    [
+        "CREATE", "INSERT", "UPDATE", "DELETE", "MATCH", "EVICT", "TRUNCATE", "SELECT", "CHECK",
+        "COMMENT", "USE", "LET", "ALTER", "BEGIN",
+    ]
+    .iter()
+    .any(|keyword| match_keyword(keyword, symbol))
+}
+
+/// Keywords are matched ignoring case, `INTO`, `into` and `Into` are the same keyword, while the
+/// entity names and keys around them are kept as they are written.
+pub(crate) fn match_keyword(expected: &str, actual: &str) -> bool {
+    actual.eq_ignore_ascii_case(expected)
+}
+
+/// Entity of a compact insert, `my_entity:` is the symbol before its `key=value` pairs.
+pub(crate) fn compact_entity(symbol: &str) -> Option<&str> {
+    symbol
+        .strip_suffix(':')
+        .filter(|entity| !entity.is_empty())
+        .filter(|entity| entity.chars().all(|c| c.is_alphanumeric() || c == '_'))
+}
+
+/// Reads the whitespace separated `key=value` pairs of `my_entity: a=1 b=2` into an `INSERT`.
+fn compact_insert(
+    entity_name: &str,
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<Wql, String> {
+    let mut entity_map = HashMap::new();
+    loop {
+        let pair = chars.as_str().trim_start();
+        if pair.is_empty() {
+            break;
+        }
+        *chars = pair.chars();
+
+        let key = chars
+            .clone()
+            .take_while(|c| c.is_alphanumeric() || c == &'_')
+            .collect::<String>();
+        chars.take(key.chars().count()).for_each(drop);
+        if key.is_empty() || chars.next() != Some('=') {
+            return Err(format!(
+                "Compact insert pairs should be written as `key=value`, found `{}`",
+                pair.split_whitespace().next().unwrap_or_default()
+            ));
+        }
 
-pub(crate) fn read_symbol(a: char, chars: &mut std::str::Chars) -> Result<Wql, String> {
-    let symbol = chars.take_while(|c| !c.is_whitespace()).collect::<String>();
+        let value = match chars.next() {
+            Some(c @ ('{' | '[' | '(' | '&')) => read_nested(chars, c, options)?,
+            Some(c) if !c.is_whitespace() => parse_bound_value(c, chars, options)?,
+            _ => return Err(format!("Value is required for key `{}`", key)),
+        };
+        if entity_map.insert(key.clone(), value).is_some() {
+            return Err(format!("Duplicate key `{}` in entity map", key));
+        }
+    }
 
-    match (a, &symbol.to_uppercase()[..]) {
-        ('c', "REATE") | ('C', "REATE") => create_entity(chars),
-        ('i', "NSERT") | ('I', "NSERT") => insert(chars),
-        ('u', "PDATE") | ('U', "PDATE") => update(chars),
-        ('d', "ELETE") | ('D', "ELETE") => delete(chars),
-        ('m', "ATCH") | ('M', "ATCH") => match_update(chars),
-        ('e', "VICT") | ('E', "VICT") => evict(chars),
-        ('s', "ELECT") | ('S', "ELECT") => select(chars),
-        ('c', "HECK") | ('C', "HECK") => check(chars),
-        _ => Err(format!("Symbol `{}{}` not implemented", a, symbol)),
+    if entity_map.is_empty() {
+        return Err(format!(
+            "Compact insert into `{}` requires at least one `key=value` pair",
+            entity_name
+        ));
     }
+    Ok(Wql::Insert(entity_name.to_string(), entity_map, None, None))
 }
 
-fn create_entity(chars: &mut std::str::Chars) -> Result<Wql, String> {
-    let entity_symbol = chars.take_while(|c| !c.is_whitespace()).collect::<String>();
+fn create_entity(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let entity_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
 
-    if entity_symbol.to_uppercase() != "ENTITY" {
-        return Err(String::from("Keyword ENTITY is required for CREATE"));
+    if match_keyword("SCHEMA", &entity_symbol) {
+        return create_schema(chars, options);
     }
+    if match_keyword("DOCUMENT", &entity_symbol) {
+        return create_document_store(chars, options);
+    }
+    if !match_keyword("ENTITY", &entity_symbol) {
+        return Err(options.messages.keyword_required("ENTITY", "CREATE"));
+    }
+
+    let entity_name = read_entity_name(chars);
 
+    let next_symbol = |chars: &mut std::str::Chars| {
+        chars
+            .skip_while(|c| c.is_whitespace())
+            .take_while(|c| !c.is_whitespace())
+            .collect::<String>()
+    };
+    let mut symbol = next_symbol(chars);
+    let mut primary_key = Vec::new();
+    if match_keyword("PRIMARY", &symbol) {
+        if !match_keyword("KEY", &next_symbol(chars)) {
+            return Err(options.messages.keyword_required("KEY", "PRIMARY"));
+        }
+        primary_key = read_field_list(chars)?;
+        if let Some(key) = primary_key
+            .iter()
+            .enumerate()
+            .find_map(|(i, key)| primary_key[..i].contains(key).then_some(key))
+        {
+            return Err(format!("Duplicate field `{}` in PRIMARY KEY", key));
+        }
+        symbol = next_symbol(chars);
+    }
+
+    let (uniques, encrypts) = if match_keyword("UNIQUES", &symbol) {
+        create_uniques_and_encrypts(chars, "ENCRYPT")?
+    } else if match_keyword("ENCRYPT", &symbol) {
+        let (encrypts, uniques) = create_uniques_and_encrypts(chars, "UNIQUES")?;
+        (uniques, encrypts)
+    } else if !symbol.is_empty() {
+        let trailing = format!("{} {}", symbol, chars.as_str());
+        return Err(format!("Unexpected trailing input: `{}`", trailing.trim()));
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    if primary_key.iter().any(|key| encrypts.contains(key)) {
+        return Err(String::from(
+            "Encrypted arguments cannot be set to PRIMARY KEY",
+        ));
+    }
+    Ok(Wql::CreateEntity(
+        entity_name,
+        uniques,
+        encrypts,
+        primary_key,
+    ))
+}
+
+fn create_schema(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
     let entity_name = chars
+        .skip_while(|c| c.is_whitespace())
         .take_while(|c| c.is_alphanumeric() || c == &'_')
-        .collect::<String>()
-        .trim()
-        .to_string();
+        .collect::<String>();
 
-    let next_symbol = chars.take_while(|c| !c.is_whitespace()).collect::<String>();
-    if next_symbol.to_uppercase() == "UNIQUES" {
-        let (uniques, encrypts) = create_uniques_and_encrypts(chars, "ENCRYPT")?;
+    if entity_name.is_empty() {
+        return Err(options.messages.entity_name_required("CREATE SCHEMA"));
+    }
 
-        Ok(Wql::CreateEntity(entity_name, uniques, encrypts))
-    } else if next_symbol.to_uppercase() == "ENCRYPT" {
-        let (encrypts, uniques) = create_uniques_and_encrypts(chars, "UNIQUES")?;
+    let (schema, defaults) = read_schema_map(chars, options)?;
 
-        Ok(Wql::CreateEntity(entity_name, uniques, encrypts))
-    } else {
-        Ok(Wql::CreateEntity(entity_name, Vec::new(), Vec::new()))
+    if schema.is_empty() {
+        return Err(String::from(
+            "CREATE SCHEMA requires at least one field type",
+        ));
+    }
+
+    Ok(Wql::CreateSchema(entity_name, schema, defaults))
+}
+
+/// Reads `{a: Float, tags: Vector OR DEFAULT [],}`, the `OR DEFAULT` values are read as entity map values
+/// and must have the type of their field.
+fn read_schema_map(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<(HashMap<String, TypeTag>, Entity), String> {
+    let mut schema = HashMap::new();
+    let mut defaults = HashMap::new();
+    if chars.find(|c| !c.is_whitespace()) != Some('{') {
+        return Err(String::from(
+            "Entity map should start with `{` and end with `}`",
+        ));
+    }
+
+    loop {
+        let field = match chars.find(|c| !c.is_whitespace() && *c != ',') {
+            Some('}') => return Ok((schema, defaults)),
+            Some(c) => parse_key(c, chars),
+            None => return Err(String::from("Entity HashMap could not be created")),
+        };
+        let rest = chars.as_str().trim_start();
+        let tag_end = rest
+            .find(|c: char| c.is_whitespace() || c == ',' || c == '}')
+            .unwrap_or(rest.len());
+        let tag = rest[..tag_end]
+            .parse::<TypeTag>()
+            .map_err(|e| format!("{} in CREATE SCHEMA", e))?;
+        *chars = rest[tag_end..].chars();
+
+        let rest = chars.as_str().trim_start();
+        let or_symbol = rest
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or_default();
+        if match_keyword("OR", or_symbol) {
+            *chars = rest[or_symbol.len()..].chars();
+            let default_symbol = chars
+                .skip_while(|c| c.is_whitespace())
+                .take_while(|c| !c.is_whitespace())
+                .collect::<String>();
+            if !match_keyword("DEFAULT", &default_symbol) {
+                return Err(options
+                    .messages
+                    .keyword_required("DEFAULT", "OR in CREATE SCHEMA"));
+            }
+            let default = match chars.find(|c| !c.is_whitespace()) {
+                Some(c @ ('{' | '[' | '&')) => read_nested(chars, c, options)?,
+                Some(c) => parse_value_with(c, chars, options)?,
+                None => return Err(format!("Value is required for the DEFAULT of `{}`", field)),
+            };
+            if default.type_tag() != tag {
+                return Err(format!(
+                    "DEFAULT {} of `{}` should be a {}, found {}",
+                    default,
+                    field,
+                    tag,
+                    default.type_tag()
+                ));
+            }
+            defaults.insert(field.to_owned(), default);
+        }
+        schema.insert(field, tag);
+    }
+}
+
+fn create_document_store(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<Wql, String> {
+    let store_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+    if !match_keyword("STORE", &store_symbol) {
+        return Err(options
+            .messages
+            .keyword_required("STORE", "CREATE DOCUMENT"));
+    }
+
+    let store_name = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || c == &'_')
+        .collect::<String>();
+    if store_name.is_empty() {
+        return Err(options
+            .messages
+            .entity_name_required("CREATE DOCUMENT STORE"));
+    }
+
+    let rest = chars.collect::<String>();
+    if !rest.trim().is_empty() {
+        return Err(format!(
+            "Unexpected `{}` after CREATE DOCUMENT STORE {}, document stores have no UNIQUES nor ENCRYPT",
+            rest.trim(),
+            store_name
+        ));
+    }
+
+    Ok(Wql::CreateDocumentStore(store_name))
+}
+
+fn comment(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let on_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    if !match_keyword("ON", &on_symbol) {
+        return Err(options.messages.keyword_required("ON", "COMMENT"));
+    }
+
+    let target = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || c == &'_' || c == &'.')
+        .collect::<String>();
+
+    let (entity_name, field) = match target.split_once('.') {
+        Some((entity, field)) => (entity.to_string(), Some(field.to_string())),
+        None => (target, None),
+    };
+
+    if entity_name.is_empty() || field.as_ref().is_some_and(String::is_empty) {
+        return Err(String::from(
+            "Entity name or `entity.field` is required for COMMENT ON",
+        ));
+    }
+
+    let is_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    if !match_keyword("IS", &is_symbol) {
+        return Err(options.messages.keyword_required("IS", "COMMENT"));
+    }
+
+    match chars.find(|c| !c.is_whitespace()) {
+        Some('"') => match read_str_with(chars, options)? {
+            Types::String(text) => Ok(Wql::Comment(entity_name, field, text)),
+            _ => Err(String::from("COMMENT text must be a String")),
+        },
+        _ => Err(String::from("COMMENT text must be a String")),
     }
 }
 
@@ -53,7 +351,7 @@ fn create_uniques_and_encrypts(
     next_element: &str,
 ) -> Result<(Vec<String>, Vec<String>), String> {
     let mut aux_vec = Vec::new();
-    if chars.next() != Some('#') {
+    if chars.find(|c| !c.is_whitespace()) != Some('#') {
         return Err(String::from(
             "Arguments set should start with `#{` and end with `}`",
         ));
@@ -63,13 +361,16 @@ fn create_uniques_and_encrypts(
         .skip_while(|c| c.is_whitespace())
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
-    if encrypt_symbol.to_uppercase() == next_element {
-        if chars.next() != Some('#') {
+    if match_keyword(next_element, &encrypt_symbol) {
+        if chars.find(|c| !c.is_whitespace()) != Some('#') {
             return Err(String::from(
                 "Arguments set should start with `#{` and end with `}`",
             ));
         }
         aux_vec = read_args(chars)?;
+    } else if !encrypt_symbol.is_empty() {
+        let trailing = format!("{} {}", encrypt_symbol, chars.as_str());
+        return Err(format!("Unexpected trailing input: `{}`", trailing.trim()));
     }
 
     if aux_vec.iter().any(|e| main_vec.contains(e)) {
@@ -81,22 +382,26 @@ fn create_uniques_and_encrypts(
 fn select(chars: &mut std::str::Chars) -> Result<Wql, String> {
     loop {
         match chars.next() {
-            Some(' ') => (),
+            Some(c) if c.is_whitespace() => (),
             Some('*') => return select_all(chars),
             Some('#') => return select_args(chars),
+            Some(c) if c.is_alphabetic() => return select_function(c, chars),
             _ => return Err(String::from("SELECT expression should be followed by `*` for ALL keys or `#{key_names...}` for some keys"))
         }
     }
 }
 
-fn delete(chars: &mut std::str::Chars) -> Result<Wql, String> {
+fn delete(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
     let entity_id = chars
+        .skip_while(|c| c.is_whitespace())
         .take_while(|c| c.is_alphanumeric() || c == &'-')
-        .collect::<String>()
-        .trim()
-        .to_string();
+        .collect::<String>();
+
+    if match_keyword("FROM", &entity_id) {
+        return delete_many(chars);
+    }
 
-    if entity_id.is_empty() || entity_id == "FROM" {
+    if entity_id.is_empty() {
         return Err(String::from("Entity UUID is required for DELETE"));
     }
 
@@ -105,66 +410,388 @@ fn delete(chars: &mut std::str::Chars) -> Result<Wql, String> {
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
 
-    if entity_symbol.to_uppercase() != "FROM" {
+    if !match_keyword("FROM", &entity_symbol) {
         return Err(String::from("Keyword FROM is required for DELETE"));
     }
 
-    let entity_name = chars
-        .take_while(|c| c.is_alphanumeric() || c == &'_')
-        .collect::<String>()
-        .trim()
-        .to_string();
+    let entity_name = read_entity_name(chars);
 
     if entity_name.is_empty() {
-        return Err(String::from("Entity name is required after FROM"));
+        return Err(options.messages.entity_name_required_after("FROM"));
     }
 
     Ok(Wql::Delete(entity_name, entity_id))
 }
 
-fn insert(chars: &mut std::str::Chars) -> Result<Wql, String> {
-    let entity_map = read_map(chars)?;
-    let entity_symbol = chars
+fn delete_many(chars: &mut std::str::Chars) -> Result<Wql, String> {
+    let entity_name = chars
         .skip_while(|c| c.is_whitespace())
-        .take_while(|c| !c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || c == &'_')
         .collect::<String>();
 
-    if entity_symbol.to_uppercase() != "INTO" {
-        return Err(String::from("Keyword INTO is required for INSERT"));
+    let where_id_in = (0..3)
+        .map(|_| {
+            chars
+                .skip_while(|c| c.is_whitespace())
+                .take_while(|c| !c.is_whitespace())
+                .collect::<String>()
+                .to_uppercase()
+        })
+        .collect::<Vec<String>>();
+
+    if entity_name.is_empty() || where_id_in.iter().all(String::is_empty) {
+        return Err(String::from("Entity UUID is required for DELETE"));
     }
 
+    if where_id_in != ["WHERE", "ID", "IN"] {
+        return Err(String::from(
+            "Keywords `WHERE id IN (...)` are required to DELETE many ids",
+        ));
+    }
+
+    if chars.find(|c| !c.is_whitespace()) != Some('(') {
+        return Err(String::from(
+            "Uuids in `WHERE id IN` are required to be inside `(` and `)`",
+        ));
+    }
+
+    let ids = match chars.as_str().find(')') {
+        Some(end) => {
+            let ids = chars.as_str()[..end].to_string();
+            chars.nth(ids.chars().count());
+            ids
+        }
+        None => {
+            return Err(String::from(
+                "Uuids in `WHERE id IN` are required to be inside `(` and `)`",
+            ))
+        }
+    };
+    let uuids = ids
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            Uuid::from_str(id)
+                .map_err(|e| format!("Couldn't creat an Uuid from {:?}. Error {:?}", id, e))
+        })
+        .collect::<Result<Vec<Uuid>, String>>()?;
+
+    if uuids.is_empty() {
+        return Err(String::from(
+            "At least one Uuid is required in `WHERE id IN (...)`",
+        ));
+    }
+
+    Ok(Wql::DeleteMany(entity_name, uuids))
+}
+
+fn use_entity(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
     let entity_name = chars
+        .skip_while(|c| c.is_whitespace())
         .take_while(|c| c.is_alphanumeric() || c == &'_')
+        .collect::<String>();
+
+    if entity_name.is_empty() {
+        return Err(options.messages.entity_name_required("USE"));
+    }
+
+    Ok(Wql::Use(entity_name))
+}
+
+fn truncate(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let entity_name = read_entity_name(chars);
+
+    if entity_name.is_empty() {
+        return Err(options.messages.entity_name_required("TRUNCATE"));
+    }
+    match chars.as_str().trim() {
+        "" => Ok(Wql::Truncate(entity_name)),
+        trailing => Err(format!("Unexpected trailing input: `{}`", trailing)),
+    }
+}
+
+fn alter_entity(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let mut next_symbol = || {
+        chars
+            .skip_while(|c| c.is_whitespace())
+            .take_while(|c| !c.is_whitespace())
+            .collect::<String>()
+    };
+
+    if !match_keyword("ENTITY", &next_symbol()) {
+        return Err(options.messages.keyword_required("ENTITY", "ALTER"));
+    }
+
+    let entity_name = next_symbol();
+    if !is_identifier(&entity_name) {
+        return Err(options.messages.entity_name_required("ALTER ENTITY"));
+    }
+
+    let action = next_symbol();
+    if match_keyword("DROP", &action) {
+        if !match_keyword("FIELD", &next_symbol()) {
+            return Err(options.messages.keyword_required("FIELD", "DROP"));
+        }
+        let field = next_symbol();
+        if !is_identifier(&field) {
+            return Err(String::from("Field name is required for DROP FIELD"));
+        }
+        return Ok(Wql::DropField(entity_name, field));
+    }
+
+    if !match_keyword("RENAME", &action) || !match_keyword("FIELD", &next_symbol()) {
+        return Err(options
+            .messages
+            .keyword_required("RENAME FIELD or DROP FIELD", "ALTER ENTITY"));
+    }
+
+    let from = next_symbol();
+    if !is_identifier(&from) {
+        return Err(String::from("Field name is required for RENAME FIELD"));
+    }
+
+    if !match_keyword("TO", &next_symbol()) {
+        return Err(options.messages.keyword_required("TO", "RENAME FIELD"));
+    }
+
+    let to = next_symbol();
+    if !is_identifier(&to) {
+        return Err(String::from("New field name is required after TO"));
+    }
+
+    if from == to {
+        return Err(format!("Field `{}` cannot be renamed to itself", from));
+    }
+
+    Ok(Wql::RenameField(entity_name, from, to))
+}
+
+fn begin(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let isolation_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+    if isolation_symbol.is_empty() {
+        return Ok(Wql::Begin(IsolationLevel::default()));
+    }
+    if !match_keyword("ISOLATION", &isolation_symbol) {
+        return Err(options.messages.keyword_required("ISOLATION", "BEGIN"));
+    }
+
+    let level = chars
         .collect::<String>()
-        .trim()
-        .to_string();
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase();
+    let isolation = match &level[..] {
+        "READ UNCOMMITTED" => IsolationLevel::ReadUncommitted,
+        "READ COMMITTED" => IsolationLevel::ReadCommitted,
+        "REPEATABLE READ" => IsolationLevel::RepeatableRead,
+        "SERIALIZABLE" => IsolationLevel::Serializable,
+        "" => return Err(String::from("Isolation level is required after ISOLATION")),
+        _ => {
+            return Err(format!(
+                "Unknown isolation level `{}`, expected READ UNCOMMITTED, READ COMMITTED, REPEATABLE READ or SERIALIZABLE",
+                level
+            ))
+        }
+    };
+    Ok(Wql::Begin(isolation))
+}
+
+fn is_identifier(symbol: &str) -> bool {
+    !symbol.is_empty() && symbol.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn let_binding(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let name = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || c == &'_')
+        .collect::<String>();
+
+    if name.is_empty() {
+        return Err(String::from("Binding name is required for LET"));
+    }
+
+    let eq_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    if eq_symbol != "=" {
+        return Err(String::from("Keyword `=` is required for LET"));
+    }
+
+    let value = match chars.clone().find(|c| !c.is_whitespace()) {
+        Some('{') => Types::Map(read_map(chars, options)?),
+        Some(_) => match chars.find(|c| !c.is_whitespace()) {
+            Some(c @ ('[' | '&')) => read_nested(chars, c, options)?,
+            Some(c) => parse_bound_value(c, chars, options)?,
+            None => unreachable!(),
+        },
+        None => return Err(String::from("Value is required for LET")),
+    };
+
+    Ok(Wql::Let(name, value))
+}
+
+fn insert(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let version = read_version(chars)?;
+    let (entity_map, sub_inserts) = read_insert_map(chars, options)?;
+    let entity_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    if entity_symbol.is_empty() {
+        if let Some(entity_name) = &options.default_entity {
+            let insert = Wql::Insert(entity_name.to_owned(), entity_map, version, None);
+            return Ok(nest_sub_inserts(insert, sub_inserts));
+        }
+    }
+
+    if !match_keyword("INTO", &entity_symbol) {
+        return Err(options.messages.keyword_required("INTO", "INSERT"));
+    }
+
+    let entity_name = read_entity_name(chars);
 
     if entity_name.is_empty() {
-        return Err(String::from("Entity name is required after INTO"));
+        return Err(options.messages.entity_name_required_after("INTO"));
+    }
+
+    let next_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+    let on_conflict = if match_keyword("ON", &next_symbol) {
+        Some(read_on_conflict(chars, options)?)
+    } else if !next_symbol.is_empty() {
+        let trailing = format!("{} {}", next_symbol, chars.as_str());
+        return Err(format!("Unexpected trailing input: `{}`", trailing.trim()));
+    } else {
+        None
+    };
+
+    let insert = Wql::Insert(entity_name, entity_map, version, on_conflict);
+    Ok(nest_sub_inserts(insert, sub_inserts))
+}
+
+fn nest_sub_inserts(insert: Wql, sub_inserts: Vec<(String, Wql)>) -> Wql {
+    if sub_inserts.is_empty() {
+        insert
+    } else {
+        Wql::NestedInsert(Box::new(insert), sub_inserts)
+    }
+}
+
+/// Reads `CONFLICT (field) DO UPDATE|NOTHING` after the `ON` of an `INSERT`.
+fn read_on_conflict(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<OnConflict, String> {
+    let conflict_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+    if !match_keyword("CONFLICT", &conflict_symbol) {
+        return Err(options.messages.keyword_required("CONFLICT", "ON"));
+    }
+
+    let mut fields = read_field_list(chars)?;
+    if fields.len() != 1 {
+        return Err(String::from("ON CONFLICT requires a single unique field"));
+    }
+    let field = fields.remove(0);
+
+    let do_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+    if !match_keyword("DO", &do_symbol) {
+        return Err(options.messages.keyword_required("DO", "ON CONFLICT"));
+    }
+
+    let action = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+    if match_keyword("UPDATE", &action) {
+        Ok(OnConflict::Update(field))
+    } else if match_keyword("NOTHING", &action) {
+        Ok(OnConflict::Nothing(field))
+    } else {
+        Err(format!(
+            "Unknown ON CONFLICT action `{}`, expected UPDATE or NOTHING",
+            action
+        ))
+    }
+}
+
+fn read_entity_name(chars: &mut std::str::Chars) -> String {
+    read_while(chars, |c| c.is_alphanumeric() || c == '_')
+}
+
+fn read_id(chars: &mut std::str::Chars) -> String {
+    read_while(chars, |c| c.is_alphanumeric() || c == '-')
+}
+
+/// Skips the whitespaces and reads the characters matching `pred`, the first character that
+/// does not match is left in `chars` for the trailing input check.
+fn read_while(chars: &mut std::str::Chars, pred: impl Fn(char) -> bool) -> String {
+    let whitespaces = chars.clone().take_while(|c| c.is_whitespace()).count();
+    let word = chars
+        .clone()
+        .skip(whitespaces)
+        .take_while(|c| pred(*c))
+        .collect::<String>();
+    chars
+        .by_ref()
+        .take(whitespaces + word.chars().count())
+        .for_each(drop);
+    word
+}
+
+/// Reads an optional `@v<number>` schema version tag before the entity map.
+fn read_version(chars: &mut std::str::Chars) -> Result<Option<u32>, String> {
+    let mut tag_chars = chars.clone();
+    if tag_chars.find(|c| !c.is_whitespace()) != Some('@') {
+        return Ok(None);
     }
 
-    Ok(Wql::Insert(entity_name, entity_map))
+    let tag = tag_chars
+        .clone()
+        .take_while(|c| !c.is_whitespace() && c != &'{')
+        .collect::<String>();
+    tag_chars.by_ref().take(tag.chars().count()).for_each(drop);
+    *chars = tag_chars;
+
+    tag.strip_prefix('v')
+        .and_then(|version| version.parse::<u32>().ok())
+        .map(Some)
+        .ok_or_else(|| format!("Version tag `@{}` must be formatted as `@v<number>`", tag))
 }
 
-fn check(chars: &mut std::str::Chars) -> Result<Wql, String> {
+fn check(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
     let entity_map = read_map_as_str(chars)?;
     let entity_symbol = chars
         .skip_while(|c| c.is_whitespace())
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
 
-    if entity_symbol.to_uppercase() != "FROM" {
-        return Err(String::from("Keyword FROM is required for CHECK"));
+    if !match_keyword("FROM", &entity_symbol) {
+        return Err(options.messages.keyword_required("FROM", "CHECK"));
     }
 
     let entity_name = chars
+        .skip_while(|c| c.is_whitespace())
         .take_while(|c| c.is_alphanumeric() || c == &'_')
-        .collect::<String>()
-        .trim()
-        .to_string();
+        .collect::<String>();
 
     if entity_name.is_empty() {
-        return Err(String::from("Entity name is required after FROM"));
+        return Err(options.messages.entity_name_required_after("FROM"));
     }
 
     let id_symbol = chars
@@ -172,28 +799,27 @@ fn check(chars: &mut std::str::Chars) -> Result<Wql, String> {
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
 
-    if id_symbol.to_uppercase() != "ID" {
-        return Err(String::from("Keyword FROM is required for CHECK"));
+    if !match_keyword("ID", &id_symbol) {
+        return Err(options.messages.keyword_required("FROM", "CHECK"));
     }
-    let entity_id = chars
-        .take_while(|c| c.is_alphanumeric() || c == &'-')
-        .collect::<String>()
-        .trim()
-        .to_owned();
+    let entity_id = read_id(chars);
     let id = Uuid::from_str(&entity_id).map_err(|e| format!("{:?}", e))?;
 
     Ok(Wql::CheckValue(entity_name, id, entity_map))
 }
 
-fn update(chars: &mut std::str::Chars) -> Result<Wql, String> {
+fn update(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
     let entity_name = chars
+        .skip_while(|c| c.is_whitespace())
         .take_while(|c| c.is_alphanumeric() || c == &'_')
-        .collect::<String>()
-        .trim()
-        .to_string();
+        .collect::<String>();
 
-    if entity_name.is_empty() {
-        return Err(String::from("Entity name is required for UPDATE"));
+    if entity_name.is_empty()
+        || ["SET", "CONTENT"]
+            .iter()
+            .any(|keyword| match_keyword(keyword, &entity_name))
+    {
+        return Err(options.messages.entity_name_required("UPDATE"));
     };
 
     let entity_symbol = chars
@@ -201,54 +827,53 @@ fn update(chars: &mut std::str::Chars) -> Result<Wql, String> {
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
 
-    if entity_symbol.to_uppercase() != "SET" && entity_symbol.to_uppercase() != "CONTENT" {
+    if !match_keyword("SET", &entity_symbol) && !match_keyword("CONTENT", &entity_symbol) {
         return Err(String::from(
             "UPDATE type is required after entity. Keywords are SET or CONTENT",
         ));
     };
 
-    let entity_map = read_map(chars)?;
+    let entity_map = read_map(chars, options)?;
 
     let into_symbol = chars
         .skip_while(|c| c.is_whitespace())
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
 
-    if into_symbol.to_uppercase() != "INTO" {
-        return Err(String::from("Keyword INTO is required for UPDATE"));
+    if !match_keyword("INTO", &into_symbol) {
+        return Err(options.messages.keyword_required("INTO", "UPDATE"));
     };
 
-    let uuid_str = chars
-        .take_while(|c| c.is_alphanumeric() || c == &'-')
-        .collect::<String>()
-        .trim()
-        .to_string();
+    let uuid_str = read_id(chars);
+    if uuid_str.is_empty() {
+        return Err(String::from("Entity UUID is required for UPDATE"));
+    }
 
     let uuid = Uuid::from_str(&uuid_str)
         .map_err(|e| format!("Couldn't create uuid from {}. Error: {:?}", uuid_str, e))?;
 
-    match &entity_symbol.to_uppercase()[..] {
-        "SET" => Ok(Wql::UpdateSet(entity_name, entity_map, uuid)),
-        "CONTENT" => Ok(Wql::UpdateContent(entity_name, entity_map, uuid)),
+    match &entity_symbol[..] {
+        s if match_keyword("SET", s) => Ok(Wql::UpdateSet(entity_name, entity_map, uuid)),
+        s if match_keyword("CONTENT", s) => Ok(Wql::UpdateContent(entity_name, entity_map, uuid)),
         _ => Err("Couldn't parse UPDATE query".to_string()),
     }
 }
 
-fn match_update(chars: &mut std::str::Chars) -> Result<Wql, String> {
+fn match_update(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
     let match_arg_symbol = chars
         .skip_while(|c| c.is_whitespace())
         .take_while(|c| c.is_alphabetic())
         .collect::<String>();
 
-    if &match_arg_symbol.to_uppercase() != "ALL" && &match_arg_symbol.to_uppercase() != "ANY" {
+    if !match_keyword("ALL", &match_arg_symbol) && !match_keyword("ANY", &match_arg_symbol) {
         return Err(String::from("MATCH requires ALL or ANY symbols"));
     }
 
     let logical_args = read_match_args(chars)?;
 
-    let match_args = if match_arg_symbol.to_uppercase().eq("ALL") {
+    let match_args = if match_keyword("ALL", &match_arg_symbol) {
         Ok(MatchCondition::All(logical_args))
-    } else if match_arg_symbol.to_uppercase().eq("ANY") {
+    } else if match_keyword("ANY", &match_arg_symbol) {
         Ok(MatchCondition::Any(logical_args))
     } else {
         Err(String::from("MATCH requires ALL or ANY symbols"))
@@ -259,18 +884,17 @@ fn match_update(chars: &mut std::str::Chars) -> Result<Wql, String> {
         .take_while(|c| c.is_alphabetic())
         .collect::<String>();
 
-    if update_symbol.to_uppercase() != "UPDATE" {
+    if !match_keyword("UPDATE", &update_symbol) {
         return Err(String::from("UPDATE keyword is required for MATCH UPDATE"));
     };
 
     let entity_name = chars
+        .skip_while(|c| c.is_whitespace())
         .take_while(|c| c.is_alphanumeric() || c == &'_')
-        .collect::<String>()
-        .trim()
-        .to_string();
+        .collect::<String>();
 
-    if entity_name.is_empty() {
-        return Err(String::from("Entity name is required for MATCH UPDATE"));
+    if entity_name.is_empty() || match_keyword("SET", &entity_name) {
+        return Err(options.messages.entity_name_required("MATCH UPDATE"));
     };
 
     let entity_symbol = chars
@@ -278,44 +902,38 @@ fn match_update(chars: &mut std::str::Chars) -> Result<Wql, String> {
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
 
-    if entity_symbol.to_uppercase() != "SET" {
+    if !match_keyword("SET", &entity_symbol) {
         return Err(String::from(
             "MATCH UPDATE type is required after entity. Keyword is SET",
         ));
     };
 
-    let entity_map = read_map(chars)?;
+    let entity_map = read_map(chars, options)?;
 
     let into_symbol = chars
         .skip_while(|c| c.is_whitespace())
         .take_while(|c| !c.is_whitespace())
         .collect::<String>();
 
-    if into_symbol.to_uppercase() != "INTO" {
-        return Err(String::from("Keyword INTO is required for MATCH UPDATE"));
+    if !match_keyword("INTO", &into_symbol) {
+        return Err(options.messages.keyword_required("INTO", "MATCH UPDATE"));
     };
 
-    let uuid_str = chars
-        .take_while(|c| c.is_alphanumeric() || c == &'-')
-        .collect::<String>()
-        .trim()
-        .to_string();
+    let uuid_str = read_id(chars);
 
     let uuid = Uuid::from_str(&uuid_str)
         .map_err(|e| format!("Couldn't create uuid from {}, Error: {:?}", uuid_str, e))?;
 
-    match &entity_symbol.to_uppercase()[..] {
-        "SET" => Ok(Wql::MatchUpdate(entity_name, entity_map, uuid, match_args?)),
+    match &entity_symbol[..] {
+        s if match_keyword("SET", s) => {
+            Ok(Wql::MatchUpdate(entity_name, entity_map, uuid, match_args?))
+        }
         _ => Err("Couldn't parse MATCH UPDATE query".to_string()),
     }
 }
 
 fn evict(chars: &mut std::str::Chars) -> Result<Wql, String> {
-    let info = chars
-        .take_while(|c| c.is_alphanumeric() || c == &'-' || c == &'_')
-        .collect::<String>()
-        .trim()
-        .to_string();
+    let info = read_while(chars, |c| c.is_alphanumeric() || c == '-' || c == '_');
 
     let uuid = Uuid::from_str(&info);
     if uuid.is_err() {
@@ -331,14 +949,10 @@ fn evict(chars: &mut std::str::Chars) -> Result<Wql, String> {
             .trim()
             .to_string();
 
-        if from_symbol.to_uppercase() != "FROM" {
+        if !match_keyword("FROM", &from_symbol) {
             return Err(String::from("FROM keyword is required to EVICT an UUID"));
         }
-        let name = chars
-            .take_while(|c| c.is_alphanumeric() || c == &'_')
-            .collect::<String>()
-            .trim()
-            .to_string();
+        let name = read_entity_name(chars);
 
         if name.is_empty() {
             return Err(String::from("Entity name is required"));
@@ -0,0 +1,238 @@
+use crate::{
+    logic::{read_char, read_str},
+    Types,
+};
+
+/// Lexical unit of a WQL statement, see `Tokens`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// Keywords, entity names, keys, variables and unquoted values, `INSERT`, `my_entity`, `?name` or `42`.
+    Word(String),
+    /// Content of a quoted string with its escapes expanded.
+    Str(String),
+    /// Content of a char literal, `'a'` or `'\n'`.
+    Char(char),
+    /// One of `{ } [ ] ( ) # , :`.
+    Punct(char),
+}
+
+/// Lazy iterator over the tokens of a WQL statement, `/* ... */` block comments are skipped. The first
+/// error, like an unterminated string, ends the iteration.
+pub struct Tokens<'a> {
+    source: &'a str,
+    chars: std::str::Chars<'a>,
+    failed: bool,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            chars: source.chars(),
+            failed: false,
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.source.len() - self.chars.as_str().len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    fn skip_comment(&mut self) -> Result<(), String> {
+        let opened_at = self.offset() - 1;
+        self.chars.next();
+        let mut depth = 1usize;
+        while depth > 0 {
+            match (self.chars.next(), self.peek()) {
+                (Some('/'), Some('*')) => {
+                    self.chars.next();
+                    depth += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    self.chars.next();
+                    depth -= 1;
+                }
+                (Some(_), _) => (),
+                (None, _) => {
+                    return Err(format!(
+                        "Block comment opened at offset {} is not closed with `*/`",
+                        opened_at
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_char_literal(&mut self) -> Result<Token, String> {
+        let rest = self.chars.as_str();
+        let mut escaped = false;
+        let end = rest.char_indices().find(|(_, c)| match c {
+            '\\' if !escaped => {
+                escaped = true;
+                false
+            }
+            '\'' if !escaped => true,
+            _ => {
+                escaped = false;
+                false
+            }
+        });
+        match end {
+            Some((end, _)) => {
+                self.chars = rest[end + 1..].chars();
+                match read_char(&rest[..end])? {
+                    Types::Char(c) => Ok(Token::Char(c)),
+                    _ => Err(format!("Char literal `'{}'` is not a char", &rest[..end])),
+                }
+            }
+            None => Err(String::from("Unterminated char literal")),
+        }
+    }
+
+    fn read_word(&mut self, first: char) -> String {
+        let mut word = first.to_string();
+        while let Some(c) = self.peek() {
+            // `:` ends keys and `e:key` bindings but is part of values like `21:00:09`
+            let identifier = word.chars().all(|c| c.is_alphanumeric() || c == '_');
+            let comment = c == '/' && self.chars.as_str()[1..].starts_with('*');
+            if c.is_whitespace() || c == '"' || comment || (is_punct(c) && (c != ':' || identifier))
+            {
+                break;
+            }
+            word.push(c);
+            self.chars.next();
+        }
+        word
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let token = loop {
+            match self.chars.next()? {
+                c if c.is_whitespace() => (),
+                '/' if self.peek() == Some('*') => match self.skip_comment() {
+                    Ok(()) => (),
+                    Err(e) => break Err(e),
+                },
+                '"' => {
+                    break read_str(&mut self.chars).and_then(|value| match value {
+                        Types::String(s) => Ok(Token::Str(s)),
+                        value => Err(format!("{} is not a String", value)),
+                    })
+                }
+                '\'' => break self.read_char_literal(),
+                c if is_punct(c) => break Ok(Token::Punct(c)),
+                c => break Ok(Token::Word(self.read_word(c))),
+            }
+        };
+        self.failed = token.is_err();
+        Some(token)
+    }
+}
+
+fn is_punct(c: char) -> bool {
+    matches!(c, '{' | '}' | '[' | ']' | '(' | ')' | '#' | ',' | ':')
+}
+
+/// Every token of `source`, see `Tokens` to read them lazily.
+pub fn lex(source: &str) -> Result<Vec<Token>, String> {
+    Tokens::new(source).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn word(w: &str) -> Token {
+        Token::Word(w.to_string())
+    }
+
+    #[test]
+    fn insert_tokens() {
+        let tokens = lex("INSERT {a: 1, b: \"two words\", c: 'x', d: [2020-01-01T00:00:00Z,],} /* note */ INTO my_entity");
+
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                word("INSERT"),
+                Token::Punct('{'),
+                word("a"),
+                Token::Punct(':'),
+                word("1"),
+                Token::Punct(','),
+                word("b"),
+                Token::Punct(':'),
+                Token::Str("two words".to_string()),
+                Token::Punct(','),
+                word("c"),
+                Token::Punct(':'),
+                Token::Char('x'),
+                Token::Punct(','),
+                word("d"),
+                Token::Punct(':'),
+                Token::Punct('['),
+                word("2020-01-01T00:00:00Z"),
+                Token::Punct(','),
+                Token::Punct(']'),
+                Token::Punct(','),
+                Token::Punct('}'),
+                word("INTO"),
+                word("my_entity"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens_are_lazy() {
+        let mut tokens = Tokens::new("SELECT * FROM my_entity \"never closed");
+
+        assert_eq!(tokens.next(), Some(Ok(word("SELECT"))));
+        assert_eq!(tokens.next(), Some(Ok(word("*"))));
+        assert_eq!(
+            Tokens::new("SELECT #{a, b,} FROM e")
+                .take(3)
+                .collect::<Vec<_>>(),
+            vec![
+                Ok(word("SELECT")),
+                Ok(Token::Punct('#')),
+                Ok(Token::Punct('{'))
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_end_iteration() {
+        let mut tokens = Tokens::new("INSERT {a: \"unterminated,} INTO my_entity");
+
+        assert_eq!(tokens.next(), Some(Ok(word("INSERT"))));
+        assert_eq!(tokens.next(), Some(Ok(Token::Punct('{'))));
+        assert_eq!(tokens.next(), Some(Ok(word("a"))));
+        assert_eq!(tokens.next(), Some(Ok(Token::Punct(':'))));
+        assert_eq!(
+            tokens.next(),
+            Some(Err(String::from("Unterminated string")))
+        );
+        assert_eq!(tokens.next(), None);
+
+        assert_eq!(
+            lex("INSERT {a: '\\x',} INTO e"),
+            Err(String::from("Invalid escape sequence \\x"))
+        );
+        assert_eq!(
+            lex("SELECT * /* open FROM e"),
+            Err(String::from(
+                "Block comment opened at offset 9 is not closed with `*/`"
+            ))
+        );
+    }
+}
@@ -1,45 +1,170 @@
+use chrono::{DateTime, Utc};
 use language_parser::read_symbol;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 use uuid::Uuid;
 
+mod comment;
+mod cursor;
+mod display;
+mod error;
+mod expression;
+mod fingerprint;
+mod indent;
 mod language_parser;
+mod lexer;
 mod logic;
+mod messages;
+mod node_id;
+mod options;
+mod params;
+mod program;
+mod schema;
 mod select;
+mod self_ref;
+mod serialize;
+mod sort;
+mod statement;
 #[cfg(test)]
 mod test;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+mod validate;
+mod walk;
 mod where_clause;
 
-pub use logic::parse_value as parse_types;
+pub use cursor::Cursor;
+pub use error::{ErrorKind, WqlError};
+pub use expression::{project_computed, ComputedField, Expr, Operator};
+pub use fingerprint::query_fingerprint;
+pub use lexer::{lex, Token, Tokens};
+pub use logic::{parse_single_value, parse_value as parse_types};
 use logic::{read_map, read_match_args};
-pub use where_clause::{Clause, Function, Value};
+pub use messages::{English, ErrorMessages, Messages};
+pub use node_id::{Node, NodeArena, NodeId, NodeKind};
+pub use options::{OverflowMode, ParseOptions, ValueParser};
+pub use params::{bind, ParamRef};
+pub use program::{execute_file, parse_program, validate_program, ExecStats, Executor, Statements};
+pub use schema::{
+    apply_defaults, check_fields, infer_schema, migration_script, result_schema, schema_drift,
+    SchemaDrift, TypeTag,
+};
+pub use serialize::{entity_from_str, entity_to_string, CanonicalEntity};
+pub use sort::SortKey;
+pub use statement::{can_batch, collect_strings, referenced_entities, suggest_indexes};
+pub use walk::PathSeg;
+pub use where_clause::{describe_clauses, eval_filter, push_not, Clause, Function, Value};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum Wql {
-    CreateEntity(String, Vec<String>, Vec<String>),
-    Insert(String, Entity),
+    /// Entity name, `UNIQUES`, `ENCRYPT` and `PRIMARY KEY` fields.
+    CreateEntity(String, Vec<String>, Vec<String>, Vec<String>),
+    /// Entity tree key, entity map, the optional schema version of `INSERT @v2 {...} INTO` and
+    /// the optional `ON CONFLICT (field) DO UPDATE|NOTHING`.
+    Insert(String, Entity, Option<u32>, Option<OnConflict>),
+    /// `Insert` with `(INSERT ... INTO entity)` values and the field each of them is set in. The sub-inserts
+    /// run first and their ids are set in the fields of the outer insert as `Types::Ref`.
+    NestedInsert(Box<Wql>, Vec<(String, Wql)>),
+    /// Entity tree key and the entity maps inserted in it, in order. It has no WQL syntax, clients that
+    /// batch inserts build it and send it with `Wql::to_wire`, see `Wql::explode_insert_many`.
+    InsertMany(String, Vec<Entity>),
     UpdateContent(String, Entity, Uuid),
     UpdateSet(String, Entity, Uuid),
     Delete(String, String),
+    DeleteMany(String, Vec<Uuid>),
     MatchUpdate(String, Entity, Uuid, MatchCondition),
     Evict(String, Option<Uuid>),
+    /// Entity tree key whose entities are all deleted, `TRUNCATE e`, the entity, its uniques,
+    /// encrypted keys and schema are kept.
+    Truncate(String),
     Select(String, ToSelect, Option<Uuid>),
     SelectWhen(String, ToSelect, Option<Uuid>, String),
     SelectWhenRange(String, Uuid, String, String),
     SelectIds(String, ToSelect, Vec<Uuid>),
+    /// Page of `SELECT ... [AFTER <cursor>] [LIMIT <n>]`, entities in id order after the cursor position.
+    SelectPage(String, ToSelect, Option<Cursor>, Option<usize>),
+    /// Up to `n` random entities of `SELECT ... SAMPLE <n> [SEED <seed>]`, the seed makes the sample reproducible.
+    SelectSample(String, ToSelect, usize, Option<u64>),
     SelectWhere(String, ToSelect, Vec<Clause>),
+    SelectCount(String, Vec<Clause>),
+    SelectSum(String, String, Vec<Clause>),
+    SelectAvg(String, String, Vec<Clause>),
+    SelectHistory(String, Uuid),
+    SelectLatest(String, Uuid),
     CheckValue(String, Uuid, HashMap<String, String>),
+    Comment(String, Option<String>, String),
+    Use(String),
+    Let(String, Types),
+    /// Entity tree key, field to rename and its new name, `ALTER ENTITY e RENAME FIELD old TO new`.
+    RenameField(String, String, String),
+    /// Entity tree key and field removed from every entity id, `ALTER ENTITY e DROP FIELD middle_name`.
+    DropField(String, String),
+    /// Entity tree key, the type of each field and the default values of the fields declared with
+    /// `OR DEFAULT`, `CREATE SCHEMA e {a: Float, tags: Vector OR DEFAULT [],}`.
+    CreateSchema(String, HashMap<String, TypeTag>, Entity),
+    /// Entity tree key of a schema-less store, `CREATE DOCUMENT STORE e`, its inserts skip schema validation.
+    CreateDocumentStore(String),
+    /// Starts a transaction, `BEGIN ISOLATION SERIALIZABLE`, without `ISOLATION` it is `Serializable`.
+    Begin(IsolationLevel),
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum ToSelect {
     All,
     Keys(Vec<String>),
+    /// Selected keys and computed fields, `#{a, (b + c) AS total,}`.
+    Computed(Vec<String>, Vec<ComputedField>),
 }
 
 pub type Entity = HashMap<String, Types>;
 
+/// Projects the `fields` of `entity` into a new `Entity`, only the selected values are cloned and absent fields are skipped.
+pub fn project(entity: &Entity, fields: &[String]) -> Entity {
+    fields
+        .iter()
+        .filter_map(|field| {
+            entity
+                .get(field)
+                .map(|value| (field.to_owned(), value.to_owned()))
+        })
+        .collect()
+}
+
+/// Compares every field of `a` and `b` except the `ignore`d ones, a field present in only one of them makes them different.
+pub fn structurally_eq(a: &Entity, b: &Entity, ignore: &HashSet<String>) -> bool {
+    let compared = |entity: &Entity| entity.keys().filter(|k| !ignore.contains(*k)).count();
+
+    compared(a) == compared(b)
+        && a.iter()
+            .filter(|(k, _)| !ignore.contains(*k))
+            .all(|(k, v)| b.get(k) == Some(v))
+}
+
+/// Minimal `UPDATE entity SET {...} INTO id` turning `old` into `new`, it only contains the added and changed fields.
+/// `SET` cannot remove a key, so a field absent from `new` is set to `Types::Nil`. `None` if nothing changed.
+pub fn make_update(old: &Entity, new: &Entity, entity: &str, id: Uuid) -> Option<Wql> {
+    let mut patch = new
+        .iter()
+        .filter(|(k, v)| old.get(*k) != Some(v))
+        .map(|(k, v)| (k.to_owned(), v.to_owned()))
+        .collect::<Entity>();
+    old.iter()
+        .filter(|(k, v)| !new.contains_key(*k) && **v != Types::Nil)
+        .for_each(|(k, _)| {
+            patch.insert(k.to_owned(), Types::Nil);
+        });
+
+    if patch.is_empty() {
+        None
+    } else {
+        Some(Wql::UpdateSet(entity.to_owned(), patch, id))
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum MatchCondition {
     All(Vec<MatchCondition>),
@@ -52,7 +177,26 @@ pub enum MatchCondition {
     L(String, Types),
 }
 
-pub(crate) fn tokenize(wql: &str) -> std::str::Chars {
+/// Action of an `INSERT ... ON CONFLICT (field)` when the unique `field` already holds the inserted value.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum OnConflict {
+    /// `DO UPDATE` sets the inserted entity map into the entity holding the value.
+    Update(String),
+    /// `DO NOTHING` keeps the entity holding the value and inserts nothing.
+    Nothing(String),
+}
+
+/// Isolation level of a transaction started by `BEGIN ISOLATION <level>`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    #[default]
+    Serializable,
+}
+
+pub(crate) fn tokenize(wql: &str) -> std::str::Chars<'_> {
     wql.chars()
 }
 
@@ -61,16 +205,79 @@ impl std::str::FromStr for Wql {
 
     /// Parses a `&str` that contains an Edn into `Result<Edn, EdnError>`
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tokens = tokenize(s.trim_start());
-        let wql = parse(tokens.next(), &mut tokens)?;
-        Ok(wql)
+        parse_at(s, 0, &ParseOptions::default())
+    }
+}
+
+/// Parses a `&str` into a `Wql` with the behavior defined by `ParseOptions`.
+pub fn parse_with_options(s: &str, options: &ParseOptions) -> Result<Wql, String> {
+    parse_at(s, 0, options)
+}
+
+/// Same as `parse_with_options`, the error tells which stage rejected the query and where, for editors.
+pub fn parse_with_diagnostics(s: &str, options: &ParseOptions) -> Result<Wql, WqlError> {
+    parse_spanned(s, 0, options)
+}
+
+/// Parses a statement that starts at byte `offset` of a program, so error positions are relative to the program.
+pub(crate) fn parse_at(s: &str, offset: usize, options: &ParseOptions) -> Result<Wql, String> {
+    parse_spanned(s, offset, options).map_err(|e| e.message)
+}
+
+fn parse_spanned(s: &str, offset: usize, options: &ParseOptions) -> Result<Wql, WqlError> {
+    let s = comment::strip_block_comments(s, offset)?;
+    let s = if options.significant_indent {
+        indent::desugar(&s)
+    } else {
+        s
+    };
+    let trimmed = s.trim_start();
+    let start = offset + s.len() - trimmed.len();
+    let mut tokens = tokenize(trimmed);
+    let wql = parse(tokens.next(), &mut tokens, start, options).map_err(|e| {
+        let kind = match trimmed.split_whitespace().next() {
+            None => ErrorKind::Empty,
+            Some(symbol)
+                if options.compact_insert && language_parser::compact_entity(symbol).is_some() =>
+            {
+                ErrorKind::Syntax
+            }
+            Some(symbol) if !language_parser::is_statement(symbol) => ErrorKind::UnknownStatement,
+            Some(_) => ErrorKind::Syntax,
+        };
+        WqlError::new(kind, e, start)
+    })?;
+    let rest = tokens.as_str();
+    if !rest.trim().is_empty() {
+        return Err(WqlError::new(
+            ErrorKind::TrailingInput,
+            format!("Unexpected trailing input: `{}`", rest.trim()),
+            offset + s.len() - rest.trim_start().len(),
+        ));
     }
+    self_ref::unresolved(&wql).map_err(|e| WqlError::new(ErrorKind::Syntax, e, start))?;
+    options
+        .check(&wql)
+        .map_err(|e| WqlError::new(ErrorKind::Rejected, e, start))?;
+    Ok(wql)
 }
 
-pub(crate) fn parse(c: Option<char>, chars: &mut std::str::Chars) -> Result<Wql, String> {
+/// Parses a `&str` into a `Wql` together with a `NodeArena` assigning a `NodeId` to every AST node.
+pub fn parse_with_ids(s: &str) -> Result<(Wql, NodeArena), String> {
+    let wql = Wql::from_str(s)?;
+    let arena = NodeArena::from_wql(&wql);
+    Ok((wql, arena))
+}
+
+pub(crate) fn parse(
+    c: Option<char>,
+    chars: &mut std::str::Chars,
+    offset: usize,
+    options: &ParseOptions,
+) -> Result<Wql, String> {
     c.map_or_else(
         || Err(String::from("Empty WQL")),
-        |ch| read_symbol(ch, chars),
+        |ch| read_symbol(ch, chars, offset, options),
     )
 }
 
@@ -83,10 +290,22 @@ pub enum Types {
     Float(f64),
     Boolean(bool),
     Vector(Vec<Types>),
-    Map(HashMap<String, Types>),
+    Map(#[serde(serialize_with = "serialize::sorted_map")] HashMap<String, Types>),
+    /// Ordered key-value pairs that keep duplicated keys, `&{a: 1, a: 2,}`, like HTTP headers.
+    Pairs(Vec<(String, Box<Types>)>),
     Hash(String),
     Precise(String),
-    //DateTime
+    Ref(String, Uuid),
+    /// Placeholder replaced by `bind`, `$1` or `$name`.
+    Param(ParamRef),
+    /// Field of the same entity map, `$self.a`, replaced by the value of `a` once the map is parsed.
+    SelfRef(String),
+    /// RFC 3339 timestamp, `2014-11-28T21:00:09+09:00`, kept in UTC.
+    DateTime(DateTime<Utc>),
+    /// Milliseconds of an ISO 8601 duration, `P1DT2H30M` or `-PT0.5S`.
+    Duration(i64),
+    /// Start, end and whether the end is included of a numeric range, `18..65` or `18..=65`.
+    Range(Box<Types>, Box<Types>, bool),
     Nil,
 }
 
@@ -101,8 +320,19 @@ impl Types {
             Types::Boolean(_) => Types::Boolean(false),
             Types::Vector(_) => Types::Vector(Vec::new()),
             Types::Map(_) => Types::Map(HashMap::new()),
+            Types::Pairs(_) => Types::Pairs(Vec::new()),
             Types::Hash(_) => Types::Hash(String::new()),
             Types::Precise(_) => Types::Precise(String::from("0")),
+            Types::Ref(entity, _) => Types::Ref(entity.to_owned(), Uuid::new_v4()),
+            Types::Param(param) => Types::Param(param.to_owned()),
+            Types::SelfRef(field) => Types::SelfRef(field.to_owned()),
+            Types::DateTime(_) => Types::DateTime(std::time::UNIX_EPOCH.into()),
+            Types::Duration(_) => Types::Duration(0),
+            Types::Range(start, end, inclusive) => Types::Range(
+                Box::new(start.default_values()),
+                Box::new(end.default_values()),
+                *inclusive,
+            ),
             Types::Nil => Types::Nil,
         }
     }
@@ -118,8 +348,14 @@ impl Types {
             Types::Boolean(b) => format!("{}", b),
             Types::Vector(vec) => format!("{:?}", vec),
             Types::Map(map) => format!("{:?}", map),
+            Types::Pairs(pairs) => format!("{:?}", pairs),
             Types::Precise(p) => p.to_string(),
+            Types::Ref(entity, id) => format!("@{}/{}", entity, id),
+            Types::DateTime(date) => date.to_rfc3339(),
+            Types::Duration(_) | Types::Range(_, _, _) => self.to_string(),
             Types::Hash(_) => return Err(String::from("Hash cannot be hashed")),
+            Types::Param(_) => return Err(String::from("Param cannot be hashed")),
+            Types::SelfRef(_) => return Err(String::from("SelfRef cannot be hashed")),
             Types::Nil => return Err(String::from("Nil cannot be hashed")),
         };
         match hash(&value, cost.map_or(DEFAULT_COST, |c| c)) {
@@ -131,6 +367,52 @@ impl Types {
     pub fn is_hash(&self) -> bool {
         matches!(self, Types::Hash(_))
     }
+
+    /// Whether a collection, `Vector`, `Map` or `Pairs`, or a `String` is empty, `None` for every other variant.
+    pub fn is_empty(&self) -> Option<bool> {
+        match self {
+            Types::Vector(vec) => Some(vec.is_empty()),
+            Types::Map(map) => Some(map.is_empty()),
+            Types::Pairs(pairs) => Some(pairs.is_empty()),
+            Types::String(s) => Some(s.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// Equality where numeric values, `Integer`, `Float` and `Precise`, are equal if they differ by at most `epsilon`.
+    /// `Vector`s, `Map`s and `Pairs` are compared element by element, every other variant falls back to `==`.
+    pub fn approx_eq(&self, other: &Types, epsilon: f64) -> bool {
+        match (self, other) {
+            (Types::Vector(a), Types::Vector(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Types::Map(a), Types::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(k, v)| b.get(k).is_some_and(|o| v.approx_eq(o, epsilon)))
+            }
+            (Types::Pairs(a), Types::Pairs(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.approx_eq(vb, epsilon))
+            }
+            (Types::Integer(a), Types::Integer(b)) => a == b,
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(a), Some(b)) => (a - b).abs() <= epsilon,
+                _ => self == other,
+            },
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Types::Integer(i) => Some(*i as f64),
+            Types::Float(f) => Some(*f),
+            Types::Precise(p) => p.parse().ok(),
+            _ => None,
+        }
+    }
 }
 
 impl Eq for Types {}
@@ -159,6 +441,8 @@ impl PartialOrd for Types {
             }
             (Types::Uuid(a), Types::Uuid(b)) => Some(a.cmp(b)),
             (Types::Boolean(a), Types::Boolean(b)) => Some(a.cmp(b)),
+            (Types::DateTime(a), Types::DateTime(b)) => Some(a.cmp(b)),
+            (Types::Duration(a), Types::Duration(b)) => Some(a.cmp(b)),
             (Types::Vector(a), Types::Vector(b)) => Some(a.len().cmp(&b.len())),
             _ => None,
         }
@@ -1,6 +1,12 @@
+use std::collections::HashSet;
 use uuid::Uuid;
 
-use super::{FromStr, HashMap, MatchCondition, Types};
+use super::{Entity, FromStr, HashMap, MatchCondition, ParseOptions, Types, Wql};
+use crate::expression::{read_case_field, read_computed_field, ComputedField};
+use crate::language_parser::match_keyword;
+use crate::params::read_param;
+use crate::schema::TypeTag;
+use crate::self_ref::resolve_self_refs;
 
 pub(crate) fn read_match_args(chars: &mut std::str::Chars) -> Result<Vec<MatchCondition>, String> {
     let base = chars
@@ -12,10 +18,10 @@ pub(crate) fn read_match_args(chars: &mut std::str::Chars) -> Result<Vec<MatchCo
     let mut conditions: Vec<MatchCondition> = Vec::new();
     base.split(',')
         .map(|l| {
-            let k = l
-                .split(' ')
-                .filter(|f| !f.is_empty())
-                .collect::<Vec<&str>>();
+            let k = l.split_whitespace().collect::<Vec<&str>>();
+            if k.len() < 3 {
+                return Err(String::from("Not able to parse match argument"));
+            }
             let mut c = k[2].chars();
             match k.get(1) {
                 Some(&"==") => Ok(MatchCondition::Eq(
@@ -77,13 +83,37 @@ pub(crate) fn read_match_args(chars: &mut std::str::Chars) -> Result<Vec<MatchCo
     Ok(conditions)
 }
 
-pub(crate) fn read_map(chars: &mut std::str::Chars) -> Result<HashMap<String, Types>, String> {
+pub(crate) fn read_map(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<HashMap<String, Types>, String> {
+    read_entity_map(chars, options, None)
+}
+
+/// Reads the entity map of an `INSERT`, where a value can be a parenthesized `(INSERT ... INTO entity)`.
+/// The sub-inserts are returned with the field they are set in and are not part of the map.
+pub(crate) fn read_insert_map(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<(Entity, Vec<(String, Wql)>), String> {
+    let mut sub_inserts = Vec::new();
+    let map = read_entity_map(chars, options, Some(&mut sub_inserts))?;
+    Ok((map, sub_inserts))
+}
+
+fn read_entity_map(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+    mut sub_inserts: Option<&mut Vec<(String, Wql)>>,
+) -> Result<HashMap<String, Types>, String> {
     let mut res: HashMap<String, Types> = HashMap::new();
+    // Keys written in this map, spread keys can be overridden but a written key cannot be repeated.
+    let mut written: HashSet<String> = HashSet::new();
     let mut key: Option<String> = None;
     let mut val: Option<Types> = None;
     loop {
         match chars.next() {
-            Some(' ') => (),
+            Some(c) if c.is_whitespace() => (),
             Some('{') => break,
             _ => {
                 return Err(String::from(
@@ -95,40 +125,141 @@ pub(crate) fn read_map(chars: &mut std::str::Chars) -> Result<HashMap<String, Ty
 
     loop {
         match chars.next() {
-            Some('}') => return Ok(res),
-            Some('{') => {
-                if key.is_some() {
-                    val = Some(Types::Map(read_inner_map(chars)?));
-                } else {
-                    return Err(String::from("Key must be an alphanumeric value"));
-                }
+            Some('}') => return resolve_self_refs(res),
+            Some('{') | Some('[') if key.is_none() => {
+                return Err(String::from("Key must be an alphanumeric value"))
             }
-            Some('[') => {
-                if key.is_some() {
-                    val = Some(Types::Vector(read_vec(chars)?));
-                } else {
-                    return Err(String::from("Key must be an alphanumeric value"));
+            Some('(') if key.is_some() && is_sub_insert(chars) => {
+                let sub_insert = read_sub_insert(chars, options)?;
+                let key = key.take().unwrap();
+                if !written.insert(key.clone()) {
+                    return Err(format!("Duplicate key `{}` in entity map", key));
                 }
+                match sub_inserts.as_mut() {
+                    Some(sub_inserts) => sub_inserts.push((key, sub_insert)),
+                    None => {
+                        return Err(String::from(
+                            "Sub-inserts are only allowed in the entity map of an INSERT",
+                        ))
+                    }
+                }
+            }
+            Some(c @ ('{' | '[' | '(' | '&')) if key.is_some() => {
+                val = Some(read_nested(chars, c, options)?)
             }
+            Some('.') if key.is_none() => spread(chars, options, &mut res)?,
             Some(c) if !c.is_whitespace() && c != ',' => {
                 if key.is_some() {
-                    val = Some(parse_value(c, chars)?);
+                    val = Some(parse_bound_value(c, chars, options)?);
                 } else {
                     key = Some(parse_key(c, chars));
                 }
             }
             Some(c) if c.is_whitespace() || c == ',' => (),
+            None if options.lenient_unterminated_map => match key {
+                Some(key) => return Err(format!("Value is required for key `{}`", key)),
+                None => return resolve_self_refs(res),
+            },
             _ => return Err(String::from("Entity HashMap could not be created")),
         }
 
         if key.is_some() && val.is_some() {
-            res.insert(key.unwrap().to_string(), val.unwrap());
-            key = None;
-            val = None;
+            let key = key.take().unwrap();
+            if !written.insert(key.clone()) {
+                return Err(format!("Duplicate key `{}` in entity map", key));
+            }
+            res.insert(key, val.take().unwrap());
         }
     }
 }
 
+/// Whether the value after a `(` is an `INSERT` statement instead of a type assertion.
+fn is_sub_insert(chars: &std::str::Chars) -> bool {
+    let symbol = chars
+        .as_str()
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphabetic())
+        .collect::<String>();
+    match_keyword("INSERT", &symbol)
+}
+
+/// Reads `INSERT ... INTO entity)` after its `(`, the statement ends at the `)` closing the `(`.
+fn read_sub_insert(chars: &mut std::str::Chars, options: &ParseOptions) -> Result<Wql, String> {
+    let s = chars.as_str();
+    let mut depth = 1usize;
+    let mut inside_string = false;
+    let mut escaped = false;
+    let end = s
+        .char_indices()
+        .find(|(_, c)| {
+            if inside_string {
+                match c {
+                    '\\' => escaped = !escaped,
+                    '"' if !escaped => inside_string = false,
+                    _ => escaped = false,
+                }
+                return false;
+            }
+            match c {
+                '"' => inside_string = true,
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => (),
+            }
+            depth == 0
+        })
+        .map(|(i, _)| i)
+        .ok_or_else(|| String::from("Sub-insert should start with `(` and end with `)`"))?;
+
+    let statement = s[..end].trim_start();
+    let mut tokens = statement.chars();
+    let sub_insert = crate::parse(tokens.next(), &mut tokens, 0, options)?;
+    let rest = tokens.as_str().trim();
+    if !rest.is_empty() {
+        return Err(format!("Unexpected trailing input: `{}`", rest));
+    }
+    chars.nth(s[..end].chars().count());
+
+    match sub_insert {
+        Wql::Insert(_, _, _, _) => Ok(sub_insert),
+        _ => Err(String::from("Sub-inserts cannot contain sub-inserts")),
+    }
+}
+
+/// Reads `...name` after its first `.` and inserts the fields of the `LET` bound map `name` into `res`.
+fn spread(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+    res: &mut HashMap<String, Types>,
+) -> Result<(), String> {
+    if chars.next() != Some('.') || chars.next() != Some('.') {
+        return Err(String::from(
+            "Spread must be formatted as `...binding_name`",
+        ));
+    }
+    let name = chars
+        .clone()
+        .take_while(|c| c.is_alphanumeric() || c == &'_')
+        .collect::<String>();
+    chars.nth(name.chars().count().saturating_sub(1));
+
+    match options.bindings.get(&name) {
+        Some(Types::Map(map)) => {
+            res.extend(map.iter().map(|(k, v)| (k.to_owned(), v.to_owned())));
+            Ok(())
+        }
+        Some(_) => Err(format!(
+            "Binding `{}` is not a map and cannot be spread",
+            name
+        )),
+        None if name.is_empty() => Err(String::from(
+            "Spread must be formatted as `...binding_name`",
+        )),
+        None => Err(format!("Binding `{}` used in spread is not defined", name)),
+    }
+}
+
 pub(crate) fn read_map_as_str(
     chars: &mut std::str::Chars,
 ) -> Result<HashMap<String, String>, String> {
@@ -138,7 +269,7 @@ pub(crate) fn read_map_as_str(
 
     loop {
         match chars.next() {
-            Some(' ') => (),
+            Some(c) if c.is_whitespace() => (),
             Some('{') => break,
             _ => {
                 return Err(String::from(
@@ -170,67 +301,182 @@ pub(crate) fn read_map_as_str(
     }
 }
 
-pub(crate) fn read_inner_map(
+/// Map, vector or type assertion opened by `read_nested` and not closed yet.
+enum Frame {
+    /// Fields read so far and the key waiting for its value.
+    Map(HashMap<String, Types>, Option<String>),
+    /// Pairs read so far, in order and with duplicated keys, and the key waiting for its value.
+    Pairs(Vec<(String, Box<Types>)>, Option<String>),
+    Vector(Vec<Types>),
+    /// Tag of a type assertion, `(tag)`, waiting for its map or vector value.
+    Asserted(String),
+}
+
+/// What `read_nested` found after reading from the innermost `Frame`.
+enum Step {
+    Skip,
+    Value(Types),
+    Open(char),
+    Close,
+}
+
+/// Reads the value after its opening char, `{` of a map, `&` of `&{` pairs, `[` of a vector or `(` of a
+/// type assertion.
+/// Nested values are kept in an explicit stack instead of recursive calls, so the call stack does not
+/// grow with the input, and maps and vectors nested deeper than `options.max_depth` are an error.
+pub(crate) fn read_nested(
     chars: &mut std::str::Chars,
-) -> Result<HashMap<String, Types>, String> {
-    let mut res: HashMap<String, Types> = HashMap::new();
-    let mut key: Option<String> = None;
-    let mut val: Option<Types> = None;
+    open: char,
+    options: &ParseOptions,
+) -> Result<Types, String> {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut depth = 0usize;
+    let mut value = open_nested(chars, open, options, &mut stack, &mut depth)?;
 
     loop {
-        match chars.next() {
-            Some('}') => return Ok(res),
-            Some('{') => {
-                if key.is_some() {
-                    val = Some(Types::Map(read_inner_map(chars)?));
-                } else {
-                    return Err(String::from("Key must be an alphanumeric value"));
+        if let Some(v) = value.take() {
+            match stack.last_mut() {
+                None => return Ok(v),
+                Some(Frame::Map(map, key)) => {
+                    if let Some(key) = key.take() {
+                        if map.contains_key(&key) {
+                            return Err(format!("Duplicate key `{}` in entity map", key));
+                        }
+                        map.insert(key, v);
+                    }
                 }
-            }
-            Some('[') => {
-                if key.is_some() {
-                    val = Some(Types::Vector(read_vec(chars)?));
-                } else {
-                    return Err(String::from("Key must be an alphanumeric value"));
+                Some(Frame::Pairs(pairs, key)) => {
+                    if let Some(key) = key.take() {
+                        pairs.push((key, Box::new(v)));
+                    }
                 }
-            }
-            Some(c) if !c.is_whitespace() && c != ',' => {
-                if key.is_some() {
-                    val = Some(parse_value(c, chars)?);
-                } else {
-                    key = Some(parse_key(c, chars));
+                Some(Frame::Vector(vec)) => vec.push(v),
+                Some(Frame::Asserted(tag)) => {
+                    value = Some(assert_type(tag, v)?);
+                    stack.pop();
+                    continue;
                 }
             }
-            Some(c) if c.is_whitespace() || c == ',' => (),
-            _ => return Err(String::from("Entity HashMap could not be created")),
         }
 
-        if key.is_some() && val.is_some() {
-            res.insert(key.unwrap().to_string(), val.unwrap());
-            key = None;
-            val = None;
-        }
+        let step = match stack.last_mut() {
+            Some(Frame::Map(_, key)) | Some(Frame::Pairs(_, key)) => match chars.next() {
+                Some('}') => Step::Close,
+                Some(c @ ('{' | '[' | '&')) if key.is_some() => Step::Open(c),
+                Some('{') | Some('[') => {
+                    return Err(String::from("Key must be an alphanumeric value"))
+                }
+                Some('(') if key.is_some() => Step::Open('('),
+                Some(c) if !c.is_whitespace() && c != ',' => {
+                    if key.is_some() {
+                        Step::Value(parse_bound_value(c, chars, options)?)
+                    } else {
+                        *key = Some(parse_key(c, chars));
+                        Step::Skip
+                    }
+                }
+                Some(c) if c.is_whitespace() || c == ',' => Step::Skip,
+                _ => return Err(String::from("Entity HashMap could not be created")),
+            },
+            Some(Frame::Vector(_)) => match chars.next() {
+                Some(']') => Step::Close,
+                Some(c @ ('[' | '{' | '&')) => Step::Open(c),
+                Some(c) if !c.is_whitespace() && c != ',' => {
+                    Step::Value(parse_bound_value(c, chars, options)?)
+                }
+                Some(c) if c.is_whitespace() || c == ',' => Step::Skip,
+                err => return Err(format!("{:?} could not be parsed at char", err)),
+            },
+            // A type assertion is never the innermost frame, its map or vector is opened with it.
+            Some(Frame::Asserted(_)) | None => unreachable!(),
+        };
+
+        value = match step {
+            Step::Skip => None,
+            Step::Value(v) => Some(v),
+            Step::Open(c) => open_nested(chars, c, options, &mut stack, &mut depth)?,
+            Step::Close => {
+                depth -= 1;
+                match stack.pop() {
+                    Some(Frame::Map(map, _)) => Some(Types::Map(map)),
+                    Some(Frame::Pairs(pairs, _)) => Some(Types::Pairs(pairs)),
+                    Some(Frame::Vector(vec)) => Some(Types::Vector(vec)),
+                    _ => unreachable!(),
+                }
+            }
+        };
     }
 }
 
-fn read_vec(chars: &mut std::str::Chars) -> Result<Vec<Types>, String> {
-    let mut res: Vec<Types> = vec![];
-    loop {
-        match chars.next() {
-            Some(']') => return Ok(res),
-            Some('[') => res.push(Types::Vector(read_vec(chars)?)),
-            Some('{') => res.push(Types::Map(read_inner_map(chars)?)),
-            Some(c) if !c.is_whitespace() && c != ',' => {
-                res.push(parse_value(c, chars)?);
+/// Pushes the map, pairs or vector started by `open` into `stack`, a type assertion of a single value is
+/// read right away instead.
+fn open_nested(
+    chars: &mut std::str::Chars,
+    open: char,
+    options: &ParseOptions,
+    stack: &mut Vec<Frame>,
+    depth: &mut usize,
+) -> Result<Option<Types>, String> {
+    let open = if open == '(' {
+        let tag = chars
+            .take_while(|c| c != &')')
+            .collect::<String>()
+            .trim()
+            .to_string();
+        match chars.find(|c| !c.is_whitespace()) {
+            Some(c @ ('{' | '[' | '&')) => {
+                stack.push(Frame::Asserted(tag));
+                c
             }
-            Some(c) if c.is_whitespace() || c == ',' => (),
-            err => return Err(format!("{:?} could not be parsed at char", err)),
+            Some(c) => return assert_type(&tag, parse_bound_value(c, chars, options)?).map(Some),
+            None => {
+                return Err(format!(
+                    "Value is required after type assertion `({})`",
+                    tag
+                ))
+            }
+        }
+    } else {
+        open
+    };
+
+    if *depth >= options.max_depth {
+        return Err(format!(
+            "Maps and vectors cannot be nested deeper than {} levels",
+            options.max_depth
+        ));
+    }
+    *depth += 1;
+    stack.push(match open {
+        '{' => Frame::Map(HashMap::new(), None),
+        '&' if chars.next() == Some('{') => Frame::Pairs(Vec::new(), None),
+        '&' => {
+            return Err(String::from(
+                "Pairs should start with `&{` and end with `}`",
+            ))
         }
+        _ => Frame::Vector(Vec::new()),
+    });
+    Ok(None)
+}
+
+fn assert_type(tag: &str, value: Types) -> Result<Types, String> {
+    match tag.parse::<TypeTag>() {
+        Ok(asserted) if asserted == value.type_tag() => Ok(value),
+        Ok(_) => Err(format!(
+            "Type assertion `({})` failed, value is {}",
+            tag,
+            value.type_tag()
+        )),
+        Err(_) => Err(format!("Unknown type `{}` in type assertion", tag)),
     }
 }
 
-pub(crate) fn read_select_args(chars: &mut std::str::Chars) -> Result<Vec<String>, String> {
+pub(crate) fn read_select_args(
+    chars: &mut std::str::Chars,
+) -> Result<(Vec<String>, Vec<ComputedField>), String> {
     let mut res = Vec::new();
+    let mut computed = Vec::new();
     if chars.next() != Some('{') {
         return Err(String::from(
             "SELECT arguments set should start with `#{` and end with `}`",
@@ -239,14 +485,19 @@ pub(crate) fn read_select_args(chars: &mut std::str::Chars) -> Result<Vec<String
 
     loop {
         match chars.next() {
-            Some('}') => return Ok(res),
+            Some('}') => return Ok((res, computed)),
+            Some('(') => computed.push(read_computed_field(chars)?),
             Some(c) if !c.is_whitespace() && c != ',' => {
                 let key_rest = chars
                     .take_while(|c| c.is_alphanumeric() || c == &'_')
                     .collect::<String>();
 
                 let key = format!("{}{}", c, key_rest);
-                res.push(key);
+                if key.eq_ignore_ascii_case("CASE") {
+                    computed.push(read_case_field(chars)?);
+                } else {
+                    res.push(key);
+                }
             }
             Some(c) if c.is_whitespace() || c == ',' => (),
             err => return Err(format!("{:?} could not be parsed at char", err)),
@@ -282,6 +533,44 @@ pub(crate) fn read_args(chars: &mut std::str::Chars) -> Result<Vec<String>, Stri
     }
 }
 
+/// Reads a parenthesized list of field names, `(tenant, email)`.
+pub(crate) fn read_field_list(chars: &mut std::str::Chars) -> Result<Vec<String>, String> {
+    if chars.find(|c| !c.is_whitespace()) != Some('(') {
+        return Err(String::from(
+            "Fields list should start with `(` and end with `)`",
+        ));
+    }
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    loop {
+        match chars.next() {
+            Some(')') => break,
+            Some(c) if c.is_alphanumeric() || c == '_' => field.push(c),
+            Some(c) if c.is_whitespace() || c == ',' => {
+                if !field.is_empty() {
+                    fields.push(std::mem::take(&mut field));
+                }
+            }
+            Some(c) => return Err(format!("Invalid character `{}` in fields list", c)),
+            None => {
+                return Err(String::from(
+                    "Fields list should start with `(` and end with `)`",
+                ))
+            }
+        }
+    }
+    if !field.is_empty() {
+        fields.push(field);
+    }
+
+    if fields.is_empty() {
+        Err(String::from("Fields list cannot be empty"))
+    } else {
+        Ok(fields)
+    }
+}
+
 pub(crate) fn parse_key(c: char, chars: &mut std::str::Chars) -> String {
     let key_rest = chars
         .take_while(|c| c.is_alphanumeric() || c == &'_')
@@ -290,36 +579,346 @@ pub(crate) fn parse_key(c: char, chars: &mut std::str::Chars) -> String {
 }
 
 pub fn parse_value(c: char, chars: &mut std::str::Chars) -> Result<Types, String> {
+    parse_value_with(c, chars, &ParseOptions::default())
+}
+
+/// Same as `parse_value`, integer literals out of the `isize` range and unrecognized values are handled
+/// by `options`.
+pub(crate) fn parse_value_with(
+    c: char,
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<Types, String> {
     if c == '"' {
-        return read_str(chars);
+        return read_str_with(chars, options);
     }
 
-    let value = format!(
-        "{}{}",
-        c,
-        chars
-            .take_while(|c| !c.is_whitespace() && c != &',')
-            .collect::<String>()
-    );
+    let value = value_token(c, chars);
+    value_from_str(value, options)
+}
+
+/// Unquoted value starting with `c`, up to a whitespace or a `,`. A char literal is read up to its
+/// closing `'`, so `' '` and `','` are one value.
+fn value_token(c: char, chars: &mut std::str::Chars) -> String {
+    let mut value = String::from(c);
+    if c == '\'' {
+        let mut escaped = false;
+        for c in chars.by_ref() {
+            value.push(c);
+            match c {
+                '\\' if !escaped => escaped = true,
+                '\'' if !escaped => break,
+                _ => escaped = false,
+            }
+        }
+    }
+    value.extend(chars.take_while(|c| !c.is_whitespace() && c != &','));
+    value
+}
+
+/// Parses one standalone value, like `42`, `"hello"`, `[1, 2,]` or `{a: 1,}`, without statement syntax.
+/// Anything after the value is an error.
+pub fn parse_single_value(s: &str) -> Result<Types, String> {
+    let s = s.trim();
+    let mut chars = s.chars();
+    let value = match chars.next() {
+        Some(c @ ('{' | '[' | '&')) => read_nested(&mut chars, c, &ParseOptions::default())?,
+        Some('"') => read_str(&mut chars)?,
+        Some('\'') => {
+            let value = value_token('\'', &mut chars);
+            value_from_str(value, &ParseOptions::default())?
+        }
+        Some(_) if s.contains(|c: char| c.is_whitespace() || c == ',') => {
+            let value_end = s
+                .find(|c: char| c.is_whitespace() || c == ',')
+                .unwrap_or(s.len());
+            return Err(format!(
+                "Unexpected input `{}` after value",
+                s[value_end..].trim()
+            ));
+        }
+        Some(_) => return value_from_str(s.to_string(), &ParseOptions::default()),
+        None => return Err(String::from("A value is required")),
+    };
+
+    match chars.as_str().trim() {
+        "" => Ok(value),
+        rest => Err(format!("Unexpected input `{}` after value", rest)),
+    }
+}
+
+/// Same as `parse_value`, but a bare identifier bound by `LET` is replaced by its bound value.
+pub(crate) fn parse_bound_value(
+    c: char,
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<Types, String> {
+    if c == '"' {
+        return read_str_with(chars, options);
+    }
+
+    let value = value_token(c, chars);
+    match options.bindings.get(&value) {
+        Some(bound) => value_from_str(value, options).or_else(|_| Ok(bound.to_owned())),
+        None => value_from_str(value, options),
+    }
+}
+
+fn value_from_str(value: String, options: &ParseOptions) -> Result<Types, String> {
     if value.ends_with('P') && value[..value.len() - 1].parse::<f64>().is_ok() {
         Ok(Types::Precise(value[..value.len() - 1].to_string()))
     } else if value.parse::<isize>().is_ok() {
         Ok(Types::Integer(value.parse().unwrap()))
+    } else if is_integer_literal(&value) {
+        options.integer_overflow.integer(&value).map(Types::Integer)
     } else if value.parse::<f64>().is_ok() {
         Ok(Types::Float(value.parse().unwrap()))
+    } else if let Some((start, end, inclusive)) = range_bounds(&value) {
+        read_range(start, end, inclusive, options)
     } else if uuid::Uuid::from_str(&value).is_ok() {
         Ok(Types::Uuid(uuid::Uuid::from_str(&value).unwrap()))
+    } else if is_almost_uuid(&value) {
+        Err(format!(
+            "Malformed UUID: {}, hyphens should split its 32 hex digits as 8-4-4-4-12",
+            value
+        ))
+    } else if is_date_like(&value) {
+        read_datetime(&value).ok_or_else(|| {
+            format!(
+                "Malformed DateTime: {}, expected an RFC 3339 timestamp like 2020-01-01T00:00:00Z",
+                value
+            )
+        })
+    } else if is_duration_like(&value) {
+        read_duration(&value).ok_or_else(|| {
+            format!(
+                "Malformed Duration: {}, expected an ISO 8601 duration like P1DT2H30M",
+                value
+            )
+        })
     } else if value.parse::<bool>().is_ok() {
         Ok(Types::Boolean(value.parse().unwrap()))
     } else if &value.to_lowercase() == "nil" {
         Ok(Types::Nil)
-    } else if value.starts_with('\'') && value.ends_with('\'') && value.len() == 3 {
-        Ok(Types::Char(value.chars().nth(1).unwrap()))
+    } else if value.starts_with('\'') && value.ends_with('\'') && value.len() >= 2 {
+        read_char(&value[1..value.len() - 1])
+    } else if let Some(reference) = value.strip_prefix('@') {
+        read_ref(reference)
+    } else if let Some(param) = value.strip_prefix('$') {
+        read_param(param)
+    } else if let Some(custom) = options.custom_value_parser.as_ref().and_then(|p| p(&value)) {
+        Ok(custom)
     } else {
         Err(format!("Value Type could not be created from {}", value))
     }
 }
 
+/// Splits `18..65` and `18..=65` in their bounds, both must start like a number.
+fn range_bounds(value: &str) -> Option<(&str, &str, bool)> {
+    let (start, end, inclusive) = match value.split_once("..=") {
+        Some((start, end)) => (start, end, true),
+        None => value
+            .split_once("..")
+            .map(|(start, end)| (start, end, false))?,
+    };
+    let numeric = |bound: &str| {
+        bound
+            .strip_prefix(&['-', '+'][..])
+            .unwrap_or(bound)
+            .starts_with(|c: char| c.is_ascii_digit())
+    };
+    if numeric(start) && numeric(end) {
+        Some((start, end, inclusive))
+    } else {
+        None
+    }
+}
+
+fn read_range(
+    start: &str,
+    end: &str,
+    inclusive: bool,
+    options: &ParseOptions,
+) -> Result<Types, String> {
+    let separator = if inclusive { "..=" } else { ".." };
+    let start_value = value_from_str(start.to_string(), options)?;
+    let end_value = value_from_str(end.to_string(), options)?;
+    let numeric = |value: &Types| {
+        matches!(
+            value,
+            Types::Integer(_) | Types::Float(_) | Types::Precise(_)
+        )
+    };
+    if !numeric(&start_value) || !numeric(&end_value) {
+        Err(format!(
+            "Range `{}{}{}` bounds should be Integer, Float or Precise",
+            start, separator, end
+        ))
+    } else if start_value.type_tag() != end_value.type_tag() {
+        Err(format!(
+            "Range `{}{}{}` bounds should have the same numeric type, found {} and {}",
+            start,
+            separator,
+            end,
+            start_value.type_tag(),
+            end_value.type_tag()
+        ))
+    } else {
+        Ok(Types::Range(
+            Box::new(start_value),
+            Box::new(end_value),
+            inclusive,
+        ))
+    }
+}
+
+fn is_integer_literal(value: &str) -> bool {
+    let digits = value.strip_prefix(&['-', '+'][..]).unwrap_or(value);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Starts as a `YYYY-` date, a bare year like `2020` is an `Integer`.
+fn is_date_like(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() > 5 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-'
+}
+
+pub(crate) fn read_datetime(value: &str) -> Option<Types> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|date| Types::DateTime(date.with_timezone(&chrono::Utc)))
+}
+
+/// Starts as a `P1` or `PT` duration, optionally negative.
+fn is_duration_like(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    value
+        .strip_prefix('P')
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit() || c == 'T'))
+}
+
+/// ISO 8601 duration of weeks, days, hours, minutes and seconds up to milliseconds, `P1DT2H30M` or `-PT0.25S`.
+/// Years and months have no fixed length and are not supported.
+pub(crate) fn read_duration(value: &str) -> Option<Types> {
+    let (negative, duration) = match value.strip_prefix('-') {
+        Some(duration) => (true, duration),
+        None => (false, value),
+    };
+    let duration = duration.strip_prefix('P')?;
+    let (date, time) = match duration.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (duration, None),
+    };
+    if (date.is_empty() && time.is_none()) || time == Some("") {
+        return None;
+    }
+
+    let ms = duration_components(date, &[('W', 604_800_000), ('D', 86_400_000)], 0)?;
+    let ms = match time {
+        Some(time) => {
+            duration_components(time, &[('H', 3_600_000), ('M', 60_000), ('S', 1_000)], ms)?
+        }
+        None => ms,
+    };
+    Some(Types::Duration(if negative { -ms } else { ms }))
+}
+
+/// Adds the `<number><designator>` components of `part` to `ms`, designators follow the order of `units`
+/// and only seconds can have a fraction.
+fn duration_components(part: &str, units: &[(char, i64)], mut ms: i64) -> Option<i64> {
+    let mut units = units.iter();
+    let mut number = String::new();
+    for c in part.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let (designator, unit) = units.find(|(designator, _)| *designator == c)?;
+        let component = if *designator == 'S' {
+            let (seconds, fraction) = number.split_once('.').unwrap_or((&number, "0"));
+            if fraction.is_empty() || fraction.len() > 3 {
+                return None;
+            }
+            let millis = format!("{:0<3}", fraction).parse::<i64>().ok()?;
+            seconds
+                .parse::<i64>()
+                .ok()?
+                .checked_mul(*unit)?
+                .checked_add(millis)?
+        } else {
+            number.parse::<i64>().ok()?.checked_mul(*unit)?
+        };
+        ms = ms.checked_add(component)?;
+        number.clear();
+    }
+    number.is_empty().then_some(ms)
+}
+
+/// The 32 hex digits of an Uuid with hyphens in the wrong places, like `d6ca73c041ff-4975-8a60-fc4a061ce536`.
+fn is_almost_uuid(value: &str) -> bool {
+    value.contains('-')
+        && value.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        && value.chars().filter(char::is_ascii_hexdigit).count() == 32
+}
+
+fn read_ref(reference: &str) -> Result<Types, String> {
+    let mut parts = reference.splitn(2, '/');
+    match (parts.next(), parts.next()) {
+        (Some(entity), Some(id)) if !entity.is_empty() => uuid::Uuid::from_str(id)
+            .map(|id| Types::Ref(entity.to_string(), id))
+            .map_err(|_| format!("Ref `@{}` must have a valid Uuid", reference)),
+        _ => Err(format!(
+            "Ref `@{}` must be formatted as `@entity_name/<uuid>`",
+            reference
+        )),
+    }
+}
+
+/// Reads the content of a char literal, a single unicode scalar, a `\u{1F600}` escape or one of the
+/// `read_str` escapes where `\'` replaces `\"`.
+pub(crate) fn read_char(content: &str) -> Result<Types, String> {
+    if let Some(hex) = content
+        .strip_prefix("\\u{")
+        .and_then(|escape| escape.strip_suffix('}'))
+    {
+        return read_unicode_escape(hex).map(Types::Char);
+    }
+    let mut chars = content.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some('\\'), Some(escaped), None) => match escaped {
+            't' => Ok(Types::Char('\t')),
+            'r' => Ok(Types::Char('\r')),
+            'n' => Ok(Types::Char('\n')),
+            '\\' => Ok(Types::Char('\\')),
+            '\'' => Ok(Types::Char('\'')),
+            _ => Err(format!("Invalid escape sequence \\{}", escaped)),
+        },
+        (Some(c), None, _) => Ok(Types::Char(c)),
+        (None, _, _) => Err(String::from("Char literal cannot be empty")),
+        _ => Err(format!(
+            "Char literal `'{}'` must contain a single character",
+            content
+        )),
+    }
+}
+
+/// Char of a `\\u{1F600}` escape, surrogates and code points above `10FFFF` are not Unicode scalars.
+fn read_unicode_escape(hex: &str) -> Result<char, String> {
+    if hex.is_empty() || hex.len() > 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Invalid unicode escape \\u{{{}}}, expected 1 to 6 hex digits",
+            hex
+        ));
+    }
+    let code = u32::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
+    char::from_u32(code).ok_or_else(|| {
+        format!(
+            "Unicode escape \\u{{{}}} is not a Unicode scalar value, surrogates D800 to DFFF and values above 10FFFF are not chars",
+            hex
+        )
+    })
+}
+
 pub(crate) fn parse_str_value(c: char, chars: &mut std::str::Chars) -> String {
     format!(
         "{}{}",
@@ -331,7 +930,16 @@ pub(crate) fn parse_str_value(c: char, chars: &mut std::str::Chars) -> String {
     .replace('\"', "")
 }
 
+/// Quoted values are always `Types::String`, Uuid shaped content is never converted to `Types::Uuid`.
 pub(crate) fn read_str(chars: &mut std::str::Chars) -> Result<Types, String> {
+    read_str_with(chars, &ParseOptions::default())
+}
+
+/// Same as `read_str`, unknown escapes are kept as written when `options.lenient_escapes` is set.
+pub(crate) fn read_str_with(
+    chars: &mut std::str::Chars,
+    options: &ParseOptions,
+) -> Result<Types, String> {
     let result = chars.try_fold((false, String::new()), |(last_was_escape, mut s), c| {
         if last_was_escape {
             // Supported escape characters, per https://github.com/edn-format/edn#strings
@@ -341,6 +949,10 @@ pub(crate) fn read_str(chars: &mut std::str::Chars) -> Result<Types, String> {
                 'n' => s.push('\n'),
                 '\\' => s.push('\\'),
                 '\"' => s.push('\"'),
+                _ if options.lenient_escapes => {
+                    s.push('\\');
+                    s.push(c);
+                }
                 _ => return Err(Err(format!("Invalid escape sequence \\{}", c))),
             };
 
@@ -370,7 +982,7 @@ pub(crate) fn read_uuids(chars: &mut std::str::Chars) -> Result<Vec<Uuid>, Strin
     let mut uuid = String::new();
     loop {
         match chars.next() {
-            Some(' ') | Some('#') | Some('{') => (),
+            Some(c) if c.is_whitespace() || c == '#' || c == '{' => (),
             Some(l) if l.is_alphanumeric() => uuid.push(l),
             Some(dash) if dash == '-' => uuid.push(dash),
             Some(',') => {
@@ -0,0 +1,121 @@
+use std::{fmt, sync::Arc};
+
+/// Wording of the parser error messages, override any method to translate or rephrase them.
+/// Every method defaults to the English message returned by `Wql::from_str`.
+pub trait ErrorMessages: Send + Sync {
+    /// A required `keyword` is missing from a `statement`, e.g. INTO in INSERT.
+    fn keyword_required(&self, keyword: &str, statement: &str) -> String {
+        format!("Keyword {} is required for {}", keyword, statement)
+    }
+
+    /// The entity name is missing from a `statement`.
+    fn entity_name_required(&self, statement: &str) -> String {
+        format!("Entity name is required for {}", statement)
+    }
+
+    /// The entity name is missing after a `keyword`, e.g. `INSERT {...} INTO`.
+    fn entity_name_required_after(&self, keyword: &str) -> String {
+        format!("Entity name is required after {}", keyword)
+    }
+
+    /// The query starts with an unknown `symbol`.
+    fn symbol_not_implemented(&self, symbol: &str, offset: usize) -> String {
+        format!("Symbol `{}` not implemented at offset {}", symbol, offset)
+    }
+}
+
+/// The default English messages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+impl ErrorMessages for English {}
+
+/// `ErrorMessages` used by `ParseOptions`, `Messages::default()` is `English`.
+/// Two custom `Messages` are equal when they share the same implementation.
+#[derive(Clone, Default)]
+pub struct Messages(Option<Arc<dyn ErrorMessages>>);
+
+impl Messages {
+    pub fn new(messages: impl ErrorMessages + 'static) -> Self {
+        Self(Some(Arc::new(messages)))
+    }
+}
+
+impl std::ops::Deref for Messages {
+    type Target = dyn ErrorMessages;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            Some(messages) => messages.as_ref(),
+            None => &English,
+        }
+    }
+}
+
+impl fmt::Debug for Messages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Messages(custom)"),
+            None => f.write_str("Messages(English)"),
+        }
+    }
+}
+
+impl PartialEq for Messages {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ParseOptions;
+
+    struct Portuguese;
+
+    impl ErrorMessages for Portuguese {
+        fn keyword_required(&self, keyword: &str, statement: &str) -> String {
+            format!("Palavra-chave {} é obrigatória em {}", keyword, statement)
+        }
+    }
+
+    fn options() -> ParseOptions {
+        ParseOptions {
+            messages: Messages::new(Portuguese),
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn overridden_message() {
+        let wql = crate::parse_with_options("INSERT {a: 1,} my_entity", &options());
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Palavra-chave INTO é obrigatória em INSERT"))
+        );
+    }
+
+    #[test]
+    fn not_overridden_message_is_english() {
+        let wql = crate::parse_with_options("INSERT {a: 1,} INTO", &options());
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Entity name is required after INTO"))
+        );
+    }
+
+    #[test]
+    fn default_messages_are_equal() {
+        assert_eq!(ParseOptions::default(), ParseOptions::default());
+        let options = options();
+        assert_eq!(options.clone(), options);
+        assert_ne!(options.messages, Messages::default());
+    }
+}
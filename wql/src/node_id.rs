@@ -0,0 +1,276 @@
+use crate::{Clause, MatchCondition, ToSelect, Types, Wql};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Statement(String),
+    Key(String),
+    Field(String),
+    Element(usize),
+    Condition(usize),
+    Clause(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: NodeId,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+}
+
+/// Nodes of a parsed `Wql` in depth-first order, so `NodeId(n)` is always the n-th node visited.
+/// Map keys are visited in sorted order, making ids stable across re-parses of the same input.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NodeArena {
+    nodes: Vec<Node>,
+}
+
+impl NodeArena {
+    pub fn from_wql(wql: &Wql) -> Self {
+        let mut arena = NodeArena::default();
+        let root = arena.push(None, NodeKind::Statement(statement_name(wql).to_string()));
+
+        match wql {
+            Wql::Insert(_, entity, _, _)
+            | Wql::UpdateContent(_, entity, _)
+            | Wql::UpdateSet(_, entity, _) => arena.push_map(root, entity),
+            Wql::NestedInsert(insert, sub_inserts) => {
+                if let Wql::Insert(_, entity, _, _) = insert.as_ref() {
+                    arena.push_map(root, entity);
+                }
+                sub_inserts.iter().for_each(|(field, sub_insert)| {
+                    if let Wql::Insert(_, entity, _, _) = sub_insert {
+                        let id = arena.push(Some(root), NodeKind::Field(field.to_owned()));
+                        arena.push_map(id, entity);
+                    }
+                });
+            }
+            Wql::InsertMany(_, entities) => entities.iter().enumerate().for_each(|(i, entity)| {
+                let id = arena.push(Some(root), NodeKind::Element(i));
+                arena.push_map(id, entity);
+            }),
+            Wql::MatchUpdate(_, entity, _, conditions) => {
+                arena.push_conditions(root, conditions);
+                arena.push_map(root, entity);
+            }
+            Wql::Select(_, to_select, _)
+            | Wql::SelectWhen(_, to_select, _, _)
+            | Wql::SelectIds(_, to_select, _)
+            | Wql::SelectPage(_, to_select, _, _)
+            | Wql::SelectSample(_, to_select, _, _) => arena.push_keys(root, to_select),
+            Wql::SelectWhere(_, to_select, clauses) => {
+                arena.push_keys(root, to_select);
+                arena.push_clauses(root, clauses);
+            }
+            Wql::SelectCount(_, clauses) => arena.push_clauses(root, clauses),
+            Wql::SelectSum(_, field, clauses) | Wql::SelectAvg(_, field, clauses) => {
+                arena.push(Some(root), NodeKind::Key(field.to_owned()));
+                arena.push_clauses(root, clauses);
+            }
+            Wql::CheckValue(_, _, map) => {
+                let mut keys = map.keys().collect::<Vec<&String>>();
+                keys.sort();
+                keys.into_iter().for_each(|k| {
+                    arena.push(Some(root), NodeKind::Field(k.to_owned()));
+                });
+            }
+            Wql::CreateEntity(_, uniques, encrypts, primary_key) => {
+                uniques
+                    .iter()
+                    .chain(encrypts.iter())
+                    .chain(primary_key.iter())
+                    .for_each(|k| {
+                        arena.push(Some(root), NodeKind::Key(k.to_owned()));
+                    });
+            }
+            Wql::Delete(_, _)
+            | Wql::DeleteMany(_, _)
+            | Wql::Evict(_, _)
+            | Wql::Truncate(_)
+            | Wql::SelectWhenRange(_, _, _, _)
+            | Wql::SelectHistory(_, _)
+            | Wql::SelectLatest(_, _)
+            | Wql::Use(_)
+            | Wql::CreateDocumentStore(_)
+            | Wql::Begin(_) => (),
+            Wql::CreateSchema(_, schema, _) => {
+                schema.keys().for_each(|k| {
+                    arena.push(Some(root), NodeKind::Field(k.to_owned()));
+                });
+            }
+            Wql::RenameField(_, from, to) => {
+                arena.push(Some(root), NodeKind::Field(from.to_owned()));
+                arena.push(Some(root), NodeKind::Field(to.to_owned()));
+            }
+            Wql::DropField(_, field) => {
+                arena.push(Some(root), NodeKind::Field(field.to_owned()));
+            }
+            Wql::Let(_, value) => arena.push_value(root, value),
+            Wql::Comment(_, field, _) => {
+                if let Some(field) = field {
+                    arena.push(Some(root), NodeKind::Field(field.to_owned()));
+                }
+            }
+        }
+
+        arena
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0)
+    }
+
+    pub fn children(&self, id: NodeId) -> Vec<&Node> {
+        self.nodes.iter().filter(|n| n.parent == Some(id)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Node> {
+        self.nodes.iter()
+    }
+
+    fn push(&mut self, parent: Option<NodeId>, kind: NodeKind) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node { id, parent, kind });
+        id
+    }
+
+    fn push_map(&mut self, parent: NodeId, map: &HashMap<String, Types>) {
+        let mut entries = map.iter().collect::<Vec<(&String, &Types)>>();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries.into_iter().for_each(|(k, v)| {
+            let id = self.push(Some(parent), NodeKind::Field(k.to_owned()));
+            self.push_value(id, v);
+        });
+    }
+
+    fn push_value(&mut self, parent: NodeId, value: &Types) {
+        match value {
+            Types::Map(map) => self.push_map(parent, map),
+            Types::Pairs(pairs) => pairs.iter().for_each(|(k, v)| {
+                let id = self.push(Some(parent), NodeKind::Field(k.to_owned()));
+                self.push_value(id, v);
+            }),
+            Types::Vector(vec) => vec.iter().enumerate().for_each(|(i, v)| {
+                let id = self.push(Some(parent), NodeKind::Element(i));
+                self.push_value(id, v);
+            }),
+            _ => (),
+        }
+    }
+
+    fn push_keys(&mut self, parent: NodeId, to_select: &ToSelect) {
+        match to_select {
+            ToSelect::Keys(keys) => keys.iter().for_each(|k| {
+                self.push(Some(parent), NodeKind::Key(k.to_owned()));
+            }),
+            ToSelect::Computed(keys, computed) => keys
+                .iter()
+                .chain(computed.iter().map(|(alias, _)| alias))
+                .for_each(|k| {
+                    self.push(Some(parent), NodeKind::Key(k.to_owned()));
+                }),
+            ToSelect::All => (),
+        }
+    }
+
+    fn push_conditions(&mut self, parent: NodeId, conditions: &MatchCondition) {
+        if let MatchCondition::All(conds) | MatchCondition::Any(conds) = conditions {
+            conds.iter().enumerate().for_each(|(i, c)| {
+                let id = self.push(Some(parent), NodeKind::Condition(i));
+                self.push_conditions(id, c);
+            });
+        }
+    }
+
+    fn push_clauses(&mut self, parent: NodeId, clauses: &[Clause]) {
+        clauses.iter().enumerate().for_each(|(i, c)| {
+            let id = self.push(Some(parent), NodeKind::Clause(i));
+            if let Clause::Or(_, inner) = c {
+                self.push_clauses(id, inner);
+            }
+        });
+    }
+}
+
+pub(crate) fn statement_name(wql: &Wql) -> &'static str {
+    match wql {
+        Wql::CreateEntity(_, _, _, _) => "CREATE",
+        Wql::Insert(_, _, _, _) | Wql::NestedInsert(_, _) | Wql::InsertMany(_, _) => "INSERT",
+        Wql::UpdateContent(_, _, _) => "UPDATE CONTENT",
+        Wql::UpdateSet(_, _, _) => "UPDATE SET",
+        Wql::Delete(_, _) | Wql::DeleteMany(_, _) => "DELETE",
+        Wql::MatchUpdate(_, _, _, _) => "MATCH UPDATE",
+        Wql::Evict(_, _) => "EVICT",
+        Wql::Truncate(_) => "TRUNCATE",
+        Wql::Select(_, _, _)
+        | Wql::SelectWhen(_, _, _, _)
+        | Wql::SelectWhenRange(_, _, _, _)
+        | Wql::SelectIds(_, _, _)
+        | Wql::SelectPage(_, _, _, _)
+        | Wql::SelectSample(_, _, _, _)
+        | Wql::SelectWhere(_, _, _) => "SELECT",
+        Wql::SelectCount(_, _) => "SELECT COUNT",
+        Wql::SelectSum(_, _, _) => "SELECT SUM",
+        Wql::SelectAvg(_, _, _) => "SELECT AVG",
+        Wql::SelectHistory(_, _) => "SELECT HISTORY",
+        Wql::SelectLatest(_, _) => "SELECT LATEST",
+        Wql::CheckValue(_, _, _) => "CHECK",
+        Wql::Comment(_, _, _) => "COMMENT",
+        Wql::Use(_) => "USE",
+        Wql::Let(_, _) => "LET",
+        Wql::RenameField(_, _, _) | Wql::DropField(_, _) => "ALTER ENTITY",
+        Wql::CreateSchema(_, _, _) => "CREATE SCHEMA",
+        Wql::CreateDocumentStore(_) => "CREATE DOCUMENT STORE",
+        Wql::Begin(_) => "BEGIN",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_with_ids;
+
+    #[test]
+    fn ids_are_depth_first() {
+        let (_, arena) = parse_with_ids("INSERT {b: [1, {c: 2,},], a: 3,} INTO my_entity").unwrap();
+        let kinds = arena
+            .iter()
+            .map(|n| n.kind.clone())
+            .collect::<Vec<NodeKind>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                NodeKind::Statement("INSERT".to_string()),
+                NodeKind::Field("a".to_string()),
+                NodeKind::Field("b".to_string()),
+                NodeKind::Element(0),
+                NodeKind::Element(1),
+                NodeKind::Field("c".to_string()),
+            ]
+        );
+        assert_eq!(arena.get(NodeId(5)).unwrap().parent, Some(NodeId(4)));
+        assert_eq!(arena.children(NodeId(2)).len(), 2);
+    }
+
+    #[test]
+    fn ids_are_stable_across_reparses() {
+        let query = "INSERT {a: 1, b: {c: 2, d: [3, 4,],}, e: \"hello\",} INTO my_entity";
+        let (wql1, arena1) = parse_with_ids(query).unwrap();
+        let (wql2, arena2) = parse_with_ids(query).unwrap();
+
+        assert_eq!(wql1, wql2);
+        assert_eq!(arena1, arena2);
+    }
+}
@@ -0,0 +1,677 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+use crate::{Clause, Messages, TypeTag, Types, Wql};
+
+/// Options to customize how a `&str` is parsed into a `Wql`.
+/// `ParseOptions::default()` parses exactly like `Wql::from_str`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// Experimental: nested maps can be expressed by indentation instead of `{...}`.
+    pub significant_indent: bool,
+    /// When set, only these fields accept unquoted Uuids. Other fields must quote them as `String`.
+    pub uuid_fields: Option<HashSet<String>>,
+    /// Entity used by an `INSERT` without `INTO`, set by a previous `USE my_entity` statement.
+    pub default_entity: Option<String>,
+    /// Accepts `inf`, `-inf` and `NaN` as `Float` and `Precise` values, by default they are rejected.
+    pub allow_non_finite: bool,
+    /// Values bound by previous `LET name = value` statements, a later `LET` with the same name shadows
+    /// the previous value. Bare names are replaced by their value and maps can be spread with `...name`.
+    pub bindings: HashMap<String, Types>,
+    /// Rejects vectors containing elements of different `TypeTag`s, `Integer` and `Float` are compatible.
+    pub homogeneous_vectors: bool,
+    /// With `homogeneous_vectors`, also rejects vectors mixing `Integer` and `Float`.
+    pub strict_numeric_vectors: bool,
+    /// Wording of the parser error messages, defaults to English.
+    pub messages: Messages,
+    /// How integer literals out of the `isize` range are parsed, by default they are rejected.
+    pub integer_overflow: OverflowMode,
+    /// Accepts `Nil` values in entity maps, when disabled every inserted or updated value must be concrete.
+    pub allow_nil: bool,
+    /// Maximum nesting of maps and vectors inside a value, `{a: [{b: 1,},],}` nests 2 levels in `a`.
+    pub max_depth: usize,
+    /// Recognizes domain specific literals, it is called with every unquoted value that is not a WQL value
+    /// and a `None` keeps the `Value Type could not be created` error.
+    pub custom_value_parser: Option<ValueParser>,
+    /// Accepts `my_entity: a=1 b="two"` as a shorthand for `INSERT {a: 1, b: "two",} INTO my_entity`.
+    pub compact_insert: bool,
+    /// Closes an entity map at the end of the input instead of rejecting it, to recover truncated logs.
+    /// Only the statement's own map is closed, nested maps and vectors still have to be closed, and a key
+    /// without its value is an error. A truncated `INSERT` never reaches its `INTO`, so it is only
+    /// recovered with a `default_entity`, otherwise INTO is still required.
+    pub lenient_unterminated_map: bool,
+    /// Keeps unknown string escapes like `\d` as the two characters instead of rejecting them, for pasted regexes.
+    pub lenient_escapes: bool,
+}
+
+/// Nesting levels accepted by `ParseOptions::default()`.
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 128;
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            significant_indent: false,
+            uuid_fields: None,
+            default_entity: None,
+            allow_non_finite: false,
+            bindings: HashMap::new(),
+            homogeneous_vectors: false,
+            strict_numeric_vectors: false,
+            messages: Messages::default(),
+            integer_overflow: OverflowMode::default(),
+            allow_nil: true,
+            max_depth: DEFAULT_MAX_DEPTH,
+            custom_value_parser: None,
+            compact_insert: false,
+            lenient_unterminated_map: false,
+            lenient_escapes: false,
+        }
+    }
+}
+
+/// Handling of integer literals that do not fit in an `isize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowMode {
+    /// Rejects the literal.
+    #[default]
+    Error,
+    /// Clamps the literal to `isize::MAX` or `isize::MIN`.
+    Saturate,
+    /// Keeps the low bits of the literal, like `isize::wrapping_mul` and `isize::wrapping_add`.
+    Wrap,
+}
+
+impl OverflowMode {
+    /// Parses an integer `literal`, optionally signed, that does not fit in an `isize`.
+    pub(crate) fn integer(self, literal: &str) -> Result<isize, String> {
+        let negative = literal.starts_with('-');
+        match self {
+            OverflowMode::Error => Err(format!(
+                "Integer `{}` does not fit in an isize, set `integer_overflow` to `Saturate` or `Wrap` to accept it",
+                literal
+            )),
+            OverflowMode::Saturate if negative => Ok(isize::MIN),
+            OverflowMode::Saturate => Ok(isize::MAX),
+            OverflowMode::Wrap => {
+                let wrapped = literal
+                    .trim_start_matches(&['-', '+'][..])
+                    .bytes()
+                    .fold(0isize, |acc, digit| {
+                        acc.wrapping_mul(10).wrapping_add((digit - b'0') as isize)
+                    });
+                Ok(if negative {
+                    wrapped.wrapping_neg()
+                } else {
+                    wrapped
+                })
+            }
+        }
+    }
+}
+
+/// Hook of `ParseOptions::custom_value_parser`, dereferences to the parsing function.
+/// Two `ValueParser`s are equal when they share the same function.
+#[derive(Clone)]
+pub struct ValueParser(Arc<ParseFn>);
+
+type ParseFn = dyn Fn(&str) -> Option<Types> + Send + Sync;
+
+impl ValueParser {
+    pub fn new(parser: impl Fn(&str) -> Option<Types> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(parser))
+    }
+}
+
+impl std::ops::Deref for ValueParser {
+    type Target = ParseFn;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for ValueParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ValueParser")
+    }
+}
+
+impl PartialEq for ValueParser {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl ParseOptions {
+    pub(crate) fn check(&self, wql: &Wql) -> Result<(), String> {
+        match wql {
+            Wql::Insert(_, entity, _, _)
+            | Wql::UpdateContent(_, entity, _)
+            | Wql::UpdateSet(_, entity, _)
+            | Wql::MatchUpdate(_, entity, _, _) => entity
+                .iter()
+                .try_for_each(|(key, value)| self.check_value(key, value)),
+            Wql::InsertMany(_, entities) => entities.iter().try_for_each(|entity| {
+                entity
+                    .iter()
+                    .try_for_each(|(key, value)| self.check_value(key, value))
+            }),
+            Wql::NestedInsert(insert, sub_inserts) => {
+                self.check(insert)?;
+                sub_inserts
+                    .iter()
+                    .try_for_each(|(_, sub_insert)| self.check(sub_insert))
+            }
+            Wql::SelectWhere(_, _, clauses)
+            | Wql::SelectCount(_, clauses)
+            | Wql::SelectSum(_, _, clauses)
+            | Wql::SelectAvg(_, _, clauses) => self.check_clauses(clauses),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_clauses(&self, clauses: &[Clause]) -> Result<(), String> {
+        clauses.iter().try_for_each(|clause| match clause {
+            Clause::ContainsKeyValue(_, key, value)
+            | Clause::SimpleComparisonFunction(_, key, value) => self.check_finite(key, value),
+            Clause::ComplexComparisonFunctions(_, key, values) => values
+                .iter()
+                .try_for_each(|value| self.check_finite(key, value)),
+            Clause::TupleEq(keys, values) => keys
+                .iter()
+                .zip(values)
+                .try_for_each(|(key, value)| self.check_finite(key, value)),
+            Clause::Or(_, clauses) => self.check_clauses(clauses),
+            _ => Ok(()),
+        })
+    }
+
+    fn check_finite(&self, key: &str, value: &Types) -> Result<(), String> {
+        let non_finite = match value {
+            Types::Float(f) => !f.is_finite(),
+            Types::Precise(p) => ["inf", "infinity", "nan"].contains(
+                &p.trim_start_matches(&['+', '-'][..])
+                    .to_lowercase()
+                    .as_str(),
+            ),
+            _ => false,
+        };
+
+        if non_finite && !self.allow_non_finite {
+            Err(format!(
+                "Field `{}` contains non finite value {:?}, enable `allow_non_finite` to accept it",
+                key, value
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_homogeneous(&self, key: &str, vec: &[Types]) -> Result<(), String> {
+        if !self.homogeneous_vectors {
+            return Ok(());
+        }
+        let numeric = |tag: TypeTag| {
+            if !self.strict_numeric_vectors && tag == TypeTag::Float {
+                TypeTag::Integer
+            } else {
+                tag
+            }
+        };
+
+        let mut tags = vec.iter().map(Types::type_tag);
+        match tags.next() {
+            Some(first) => match tags.find(|tag| numeric(*tag) != numeric(first)) {
+                Some(other) => Err(format!(
+                    "Field `{}` contains a vector mixing {} and {}, disable `homogeneous_vectors` to accept it",
+                    key, first, other
+                )),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    fn check_value(&self, key: &str, value: &Types) -> Result<(), String> {
+        // Nested values are checked from a stack, so deeply nested values don't grow the call stack.
+        let mut pending = vec![(key, value)];
+        while let Some((key, value)) = pending.pop() {
+            match value {
+                Types::Uuid(_) => match &self.uuid_fields {
+                    Some(fields) if !fields.contains(key) => {
+                        return Err(format!(
+                            "Field `{}` does not accept unquoted Uuids, use a String instead",
+                            key
+                        ))
+                    }
+                    _ => (),
+                },
+                Types::Vector(vec) => {
+                    self.check_homogeneous(key, vec)?;
+                    pending.extend(vec.iter().rev().map(|v| (key, v)));
+                }
+                Types::Map(map) => pending.extend(map.iter().map(|(k, v)| (k.as_str(), v))),
+                Types::Pairs(pairs) => {
+                    pending.extend(pairs.iter().rev().map(|(k, v)| (k.as_str(), v.as_ref())))
+                }
+                Types::Nil if !self.allow_nil => {
+                    return Err(String::from("Nil values are not permitted"))
+                }
+                _ => self.check_finite(key, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_with_options;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    fn uuid_options() -> ParseOptions {
+        let mut fields = HashSet::new();
+        fields.insert("id".to_string());
+        ParseOptions {
+            uuid_fields: Some(fields),
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn uuid_only_on_configured_fields() {
+        let query = "INSERT {id: 2df2b8cf-49da-474d-8a00-c596c0bb6fd1, code: \"53315090-e14d-4738-a4d2-f1ec2a93664c\",} INTO my_entity";
+        let wql = parse_with_options(query, &uuid_options());
+
+        let mut hm = HashMap::new();
+        hm.insert(
+            "id".to_string(),
+            Types::Uuid(Uuid::parse_str("2df2b8cf-49da-474d-8a00-c596c0bb6fd1").unwrap()),
+        );
+        hm.insert(
+            "code".to_string(),
+            Types::String("53315090-e14d-4738-a4d2-f1ec2a93664c".to_string()),
+        );
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
+        );
+    }
+
+    #[test]
+    fn unquoted_uuid_on_other_field() {
+        let query = "INSERT {id: 2df2b8cf-49da-474d-8a00-c596c0bb6fd1, code: 53315090-e14d-4738-a4d2-f1ec2a93664c,} INTO my_entity";
+
+        assert_eq!(
+            parse_with_options(query, &uuid_options()).err(),
+            Some(String::from(
+                "Field `code` does not accept unquoted Uuids, use a String instead"
+            ))
+        );
+    }
+
+    #[test]
+    fn nested_fields_use_their_own_key() {
+        let query = "INSERT {a: {id: 2df2b8cf-49da-474d-8a00-c596c0bb6fd1,}, b: [{code: 53315090-e14d-4738-a4d2-f1ec2a93664c,},],} INTO my_entity";
+
+        assert_eq!(
+            parse_with_options(query, &uuid_options()).err(),
+            Some(String::from(
+                "Field `code` does not accept unquoted Uuids, use a String instead"
+            ))
+        );
+    }
+
+    #[test]
+    fn every_field_detects_uuids_by_default() {
+        let query = "INSERT {code: 53315090-e14d-4738-a4d2-f1ec2a93664c,} INTO my_entity";
+
+        assert!(parse_with_options(query, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn non_finite_floats_are_rejected_by_default() {
+        ["inf", "-inf", "NaN", "infinityP"]
+            .iter()
+            .for_each(|value| {
+                let query = format!("INSERT {{a: [{},],}} INTO my_entity", value);
+                let err = parse_with_options(&query, &ParseOptions::default()).unwrap_err();
+
+                assert!(
+                    err.starts_with("Field `a` contains non finite value"),
+                    "{}",
+                    err
+                );
+            });
+
+        let query = "SELECT * FROM my_entity WHERE { ?* my_entity:a ?a, (< ?a inf), }";
+        assert!(parse_with_options(query, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn non_finite_floats_are_accepted_when_allowed() {
+        let options = ParseOptions {
+            allow_non_finite: true,
+            ..ParseOptions::default()
+        };
+        let wql = parse_with_options("INSERT {a: inf, b: -inf, c: NaN,} INTO my_entity", &options);
+
+        if let Ok(Wql::Insert(_, entity, _, _)) = wql {
+            assert_eq!(entity["a"], Types::Float(f64::INFINITY));
+            assert_eq!(entity["b"], Types::Float(f64::NEG_INFINITY));
+            assert!(matches!(entity["c"], Types::Float(f) if f.is_nan()));
+        } else {
+            panic!("expected INSERT, got {:?}", wql);
+        }
+    }
+
+    #[test]
+    fn nil_allowed_by_default() {
+        let wql = parse_with_options(
+            "INSERT {a: nil, b: [1, Nil,],} INTO my_entity",
+            &ParseOptions::default(),
+        );
+
+        assert!(wql.is_ok());
+    }
+
+    #[test]
+    fn nil_rejected_when_not_allowed() {
+        let options = ParseOptions {
+            allow_nil: false,
+            ..ParseOptions::default()
+        };
+        let nil_error = Err(String::from("Nil values are not permitted"));
+
+        assert_eq!(
+            parse_with_options("INSERT {a: 1, b: nil,} INTO my_entity", &options),
+            nil_error
+        );
+        assert_eq!(
+            parse_with_options("INSERT {a: [1, Nil,],} INTO my_entity", &options),
+            nil_error
+        );
+        assert_eq!(
+            parse_with_options(
+                "UPDATE my_entity SET {a: {b: nil,},} INTO 2df2b8cf-49da-474d-8a00-c596c0bb6fd1",
+                &options
+            ),
+            nil_error
+        );
+        assert!(parse_with_options("INSERT {a: 1,} INTO my_entity", &options).is_ok());
+    }
+
+    #[test]
+    fn mixed_vector_rejected_when_homogeneous() {
+        let options = ParseOptions {
+            homogeneous_vectors: true,
+            ..ParseOptions::default()
+        };
+        let wql = parse_with_options("INSERT {a: [1, \"two\", 3,],} INTO my_entity", &options);
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Field `a` contains a vector mixing Integer and String, disable `homogeneous_vectors` to accept it"))
+        );
+        assert!(parse_with_options(
+            "INSERT {a: [1, \"two\", 3,],} INTO my_entity",
+            &ParseOptions::default()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn uniform_vector_accepted_when_homogeneous() {
+        let options = ParseOptions {
+            homogeneous_vectors: true,
+            ..ParseOptions::default()
+        };
+        let wql = parse_with_options(
+            "INSERT {a: [1, 2.5, 3,], b: {c: [\"x\", \"y\",],},} INTO my_entity",
+            &options,
+        );
+
+        assert!(wql.is_ok(), "{:?}", wql);
+    }
+
+    #[test]
+    fn strict_numeric_vectors_rejects_integer_and_float() {
+        let options = ParseOptions {
+            homogeneous_vectors: true,
+            strict_numeric_vectors: true,
+            ..ParseOptions::default()
+        };
+        let wql = parse_with_options("INSERT {a: {b: [1, 2.5,],},} INTO my_entity", &options);
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Field `b` contains a vector mixing Integer and Float, disable `homogeneous_vectors` to accept it"))
+        );
+    }
+
+    fn overflow_options(integer_overflow: OverflowMode) -> ParseOptions {
+        ParseOptions {
+            integer_overflow,
+            ..ParseOptions::default()
+        }
+    }
+
+    fn inserted_a(query: &str, options: &ParseOptions) -> Result<Types, String> {
+        match parse_with_options(query, options)? {
+            Wql::Insert(_, mut entity, _, _) => Ok(entity.remove("a").unwrap()),
+            wql => panic!("expected INSERT, got {:?}", wql),
+        }
+    }
+
+    #[test]
+    fn integer_overflow_is_rejected_by_default() {
+        let query = "INSERT {a: 9223372036854775808,} INTO my_entity";
+
+        assert_eq!(
+            inserted_a(query, &ParseOptions::default()),
+            Err(String::from("Integer `9223372036854775808` does not fit in an isize, set `integer_overflow` to `Saturate` or `Wrap` to accept it"))
+        );
+    }
+
+    #[test]
+    fn integer_overflow_saturates() {
+        let options = overflow_options(OverflowMode::Saturate);
+
+        assert_eq!(
+            inserted_a("INSERT {a: 99999999999999999999999,} INTO e", &options),
+            Ok(Types::Integer(isize::MAX))
+        );
+        assert_eq!(
+            inserted_a("INSERT {a: [-99999999999999999999999,],} INTO e", &options),
+            Ok(Types::Vector(vec![Types::Integer(isize::MIN)]))
+        );
+    }
+
+    #[test]
+    fn integer_overflow_wraps() {
+        let options = overflow_options(OverflowMode::Wrap);
+
+        assert_eq!(
+            inserted_a("INSERT {a: 9223372036854775808,} INTO e", &options),
+            Ok(Types::Integer(isize::MIN))
+        );
+        assert_eq!(
+            inserted_a("INSERT {a: 18446744073709551617,} INTO e", &options),
+            Ok(Types::Integer(1))
+        );
+        assert_eq!(
+            inserted_a("INSERT {a: -9223372036854775809,} INTO e", &options),
+            Ok(Types::Integer(isize::MAX))
+        );
+    }
+
+    #[test]
+    fn custom_value_parser() {
+        let options = ParseOptions {
+            custom_value_parser: Some(ValueParser::new(|value| {
+                value
+                    .strip_prefix("USD$")
+                    .filter(|amount| amount.parse::<f64>().is_ok())
+                    .map(|amount| Types::Precise(amount.to_string()))
+            })),
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(
+            inserted_a("INSERT {a: USD$5,} INTO e", &options),
+            Ok(Types::Precise("5".to_string()))
+        );
+        assert_eq!(
+            inserted_a("INSERT {a: [USD$5, 3,],} INTO e", &options),
+            Ok(Types::Vector(vec![
+                Types::Precise("5".to_string()),
+                Types::Integer(3)
+            ]))
+        );
+        assert_eq!(
+            inserted_a("INSERT {a: 5,} INTO e", &options),
+            Ok(Types::Integer(5))
+        );
+        assert_eq!(
+            inserted_a("INSERT {a: EUR$5,} INTO e", &options),
+            Err(String::from("Value Type could not be created from EUR$5"))
+        );
+        assert_eq!(
+            inserted_a("INSERT {a: USD$5,} INTO e", &ParseOptions::default()),
+            Err(String::from("Value Type could not be created from USD$5"))
+        );
+        assert_eq!(options, options.clone());
+    }
+
+    #[test]
+    fn compact_insert_same_as_verbose() {
+        let options = ParseOptions {
+            compact_insert: true,
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(
+            parse_with_options("e: a=1 b=2", &options),
+            Wql::from_str("INSERT { a: 1, b: 2 } INTO e")
+        );
+        assert_eq!(
+            parse_with_options(
+                "my_entity: name=\"ada lovelace\" tags=[1, 2,] score=7.5 ok=true",
+                &options
+            ),
+            Wql::from_str(
+                "INSERT {name: \"ada lovelace\", tags: [1, 2,], score: 7.5, ok: true,} INTO my_entity"
+            )
+        );
+        assert_eq!(
+            parse_with_options("e: a=1", &ParseOptions::default()),
+            Err(String::from("Symbol `e:` not implemented at offset 0"))
+        );
+    }
+
+    #[test]
+    fn compact_insert_errors() {
+        let options = ParseOptions {
+            compact_insert: true,
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(
+            parse_with_options("e: a=1 b", &options),
+            Err(String::from(
+                "Compact insert pairs should be written as `key=value`, found `b`"
+            ))
+        );
+        assert_eq!(
+            parse_with_options("e: a=1 a=2", &options),
+            Err(String::from("Duplicate key `a` in entity map"))
+        );
+        assert_eq!(
+            parse_with_options("e:", &options),
+            Err(String::from(
+                "Compact insert into `e` requires at least one `key=value` pair"
+            ))
+        );
+        assert_eq!(
+            parse_with_options("e: a=", &options),
+            Err(String::from("Value is required for key `a`"))
+        );
+    }
+
+    #[test]
+    fn unterminated_map_rejected_by_default() {
+        let options = ParseOptions {
+            default_entity: Some("e".to_string()),
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(
+            parse_with_options("INSERT {a: 1, b: 2", &options),
+            Err(String::from("Entity HashMap could not be created"))
+        );
+    }
+
+    #[test]
+    fn unterminated_map_recovered_when_lenient() {
+        let options = ParseOptions {
+            default_entity: Some("e".to_string()),
+            lenient_unterminated_map: true,
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(
+            parse_with_options("INSERT {a: 1, b: \"two\"", &options),
+            Wql::from_str("INSERT {a: 1, b: \"two\",} INTO e")
+        );
+        assert_eq!(
+            parse_with_options("INSERT {a: 1, b:", &options),
+            Err(String::from("Value is required for key `b`"))
+        );
+        assert_eq!(
+            parse_with_options("INSERT {a: 1, b: [1, 2", &options),
+            Err(String::from("None could not be parsed at char"))
+        );
+    }
+
+    #[test]
+    fn unterminated_map_requires_default_entity() {
+        let options = ParseOptions {
+            lenient_unterminated_map: true,
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(
+            parse_with_options("INSERT {a: 1, b: 2", &options),
+            Err(String::from("Keyword INTO is required for INSERT"))
+        );
+    }
+
+    #[test]
+    fn unknown_escape_rejected_by_default() {
+        assert_eq!(
+            Wql::from_str("INSERT {pattern: \"\\d+\",} INTO e"),
+            Err(String::from("Invalid escape sequence \\d"))
+        );
+    }
+
+    #[test]
+    fn unknown_escape_kept_when_lenient() {
+        let options = ParseOptions {
+            lenient_escapes: true,
+            ..ParseOptions::default()
+        };
+        let mut hm = HashMap::new();
+        hm.insert("pattern".to_string(), Types::String("\\d+\n".to_string()));
+
+        assert_eq!(
+            parse_with_options("INSERT {pattern: \"\\d+\\n\",} INTO e", &options),
+            Ok(Wql::Insert("e".to_string(), hm, None, None))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{self_ref::read_self_ref, Types, Wql};
+
+/// Placeholder of a prepared statement value, positional `$1` or named `$name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ParamRef {
+    Position(usize),
+    Name(String),
+}
+
+impl ParamRef {
+    /// Key of the placeholder in the `bind` params, `"1"` for `$1` and `"name"` for `$name`.
+    pub fn key(&self) -> String {
+        match self {
+            ParamRef::Position(position) => position.to_string(),
+            ParamRef::Name(name) => name.to_owned(),
+        }
+    }
+}
+
+pub(crate) fn read_param(param: &str) -> Result<Types, String> {
+    if let Some(field) = param.strip_prefix("self.") {
+        return read_self_ref(field);
+    }
+    let mut chars = param.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_digit() && chars.all(|c| c.is_ascii_digit()) => {
+            match param.parse::<usize>() {
+                Ok(position) if position > 0 => Ok(Types::Param(ParamRef::Position(position))),
+                _ => Err(format!(
+                    "Positional parameter `${}` must be a number starting at `$1`",
+                    param
+                )),
+            }
+        }
+        Some(c) if c.is_alphabetic() && chars.all(|c| c.is_alphanumeric() || c == '_') => {
+            Ok(Types::Param(ParamRef::Name(param.to_string())))
+        }
+        _ => Err(format!(
+            "Parameter `${}` must be formatted as `$1` or `$name`",
+            param
+        )),
+    }
+}
+
+/// Replaces every placeholder of `wql` by its value in `params`, keyed by `ParamRef::key`.
+/// Fails with the first placeholder that has no value in `params`.
+pub fn bind(mut wql: Wql, params: &HashMap<String, Types>) -> Result<Wql, String> {
+    let mut unbound = None;
+    wql.values_mut().into_iter().for_each(|value| {
+        value.walk_mut(&mut |_, v| {
+            if let Types::Param(param) = v {
+                match params.get(&param.key()) {
+                    Some(bound) => *v = bound.to_owned(),
+                    None => {
+                        unbound.get_or_insert_with(|| param.to_owned());
+                    }
+                }
+            }
+        })
+    });
+
+    match unbound {
+        Some(param) => Err(format!("Parameter `${}` is not bound", param.key())),
+        None => Ok(wql),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn positional_params() {
+        let wql = Wql::from_str("INSERT {a: $1, b: [$2, 3,],} INTO my_entity").unwrap();
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Param(ParamRef::Position(1)));
+        hm.insert(
+            "b".to_string(),
+            Types::Vector(vec![Types::Param(ParamRef::Position(2)), Types::Integer(3)]),
+        );
+        assert_eq!(wql, Wql::Insert("my_entity".to_string(), hm, None, None));
+
+        let mut params = HashMap::new();
+        params.insert("1".to_string(), Types::String("hello".to_string()));
+        params.insert("2".to_string(), Types::Boolean(true));
+        let mut bound = HashMap::new();
+        bound.insert("a".to_string(), Types::String("hello".to_string()));
+        bound.insert(
+            "b".to_string(),
+            Types::Vector(vec![Types::Boolean(true), Types::Integer(3)]),
+        );
+        assert_eq!(
+            bind(wql, &params),
+            Ok(Wql::Insert("my_entity".to_string(), bound, None, None))
+        );
+    }
+
+    #[test]
+    fn named_params_in_where() {
+        let wql = Wql::from_str(
+            "SELECT * FROM my_entity WHERE { ?* my_entity:age ?age, (>= ?age $min_age), }",
+        )
+        .unwrap();
+
+        let mut params = HashMap::new();
+        params.insert("min_age".to_string(), Types::Integer(18));
+        let bound = bind(wql, &params).unwrap();
+        let expected =
+            Wql::from_str("SELECT * FROM my_entity WHERE { ?* my_entity:age ?age, (>= ?age 18), }")
+                .unwrap();
+        assert_eq!(bound, expected);
+    }
+
+    #[test]
+    fn unbound_param() {
+        let wql = Wql::from_str("INSERT {a: $1, b: $name,} INTO my_entity").unwrap();
+
+        let mut params = HashMap::new();
+        params.insert("1".to_string(), Types::Integer(1));
+        assert_eq!(
+            bind(wql, &params).err(),
+            Some(String::from("Parameter `$name` is not bound"))
+        );
+    }
+
+    #[test]
+    fn malformed_params() {
+        assert_eq!(
+            Wql::from_str("INSERT {a: $0,} INTO my_entity").err(),
+            Some(String::from(
+                "Positional parameter `$0` must be a number starting at `$1`"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: $1a,} INTO my_entity").err(),
+            Some(String::from(
+                "Parameter `$1a` must be formatted as `$1` or `$name`"
+            ))
+        );
+    }
+}
@@ -0,0 +1,433 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{parse_at, ParseOptions, Wql};
+
+/// Executes a single parsed `Wql` statement.
+pub trait Executor {
+    fn execute(&mut self, wql: Wql) -> Result<(), String>;
+}
+
+/// Counters returned by `execute_file`. `errors` contains the statement index and its parse or execution error.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecStats {
+    pub created: usize,
+    pub inserted: usize,
+    pub executed: usize,
+    pub failed: usize,
+    pub errors: Vec<(usize, String)>,
+}
+
+/// Lazily reads `;` separated statements from a `BufRead`, so only one statement is kept in memory at a time.
+/// A `;` inside a `String` value, a char literal or a `/* ... */` comment does not end a statement and the last statement may omit the `;`.
+/// Each statement is returned with the byte offset where it starts in the source.
+pub struct Statements<R> {
+    reader: R,
+    offset: usize,
+}
+
+impl<R: BufRead> Statements<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, offset: 0 }
+    }
+}
+
+impl<R: BufRead> Iterator for Statements<R> {
+    type Item = std::io::Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut start = self.offset;
+        let mut buf = Vec::new();
+        loop {
+            match self.reader.read_until(b';', &mut buf) {
+                Err(e) => return Some(Err(e)),
+                Ok(0) => return to_statement(start, buf),
+                Ok(read) => {
+                    self.offset += read;
+                    if buf.last() == Some(&b';') && !inside_literal(&buf) {
+                        buf.pop();
+                        if buf.iter().all(u8::is_ascii_whitespace) {
+                            start = self.offset;
+                            buf.clear();
+                            continue;
+                        }
+                        return to_statement(start, buf);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn to_statement(start: usize, buf: Vec<u8>) -> Option<std::io::Result<(usize, String)>> {
+    match String::from_utf8(buf) {
+        Ok(s) if s.trim().is_empty() => None,
+        Ok(s) => Some(Ok((
+            start + s.len() - s.trim_start().len(),
+            s.trim().to_string(),
+        ))),
+        Err(e) => Some(Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e))),
+    }
+}
+
+/// Where the end of a buffer is, a `;` only ends a statement in `Code`.
+enum Scan {
+    Code,
+    Str { escaped: bool },
+    Char { escaped: bool },
+    Comment { depth: usize },
+}
+
+fn inside_literal(buf: &[u8]) -> bool {
+    let mut scan = Scan::Code;
+    let mut bytes = buf.iter().peekable();
+    while let Some(b) = bytes.next() {
+        scan = match (scan, b) {
+            (Scan::Code, b'"') => Scan::Str { escaped: false },
+            (Scan::Code, b'\'') => Scan::Char { escaped: false },
+            (Scan::Code, b'/') if bytes.peek() == Some(&&b'*') => {
+                bytes.next();
+                Scan::Comment { depth: 1 }
+            }
+            (Scan::Str { escaped: false }, b'"') | (Scan::Char { escaped: false }, b'\'') => {
+                Scan::Code
+            }
+            (Scan::Str { escaped }, b'\\') => Scan::Str { escaped: !escaped },
+            (Scan::Char { escaped }, b'\\') => Scan::Char { escaped: !escaped },
+            (Scan::Str { .. }, _) => Scan::Str { escaped: false },
+            (Scan::Char { .. }, _) => Scan::Char { escaped: false },
+            (Scan::Comment { depth }, b'/') if bytes.peek() == Some(&&b'*') => {
+                bytes.next();
+                Scan::Comment { depth: depth + 1 }
+            }
+            (Scan::Comment { depth }, b'*') if bytes.peek() == Some(&&b'/') => {
+                bytes.next();
+                match depth - 1 {
+                    0 => Scan::Code,
+                    depth => Scan::Comment { depth },
+                }
+            }
+            (scan, _) => scan,
+        };
+    }
+    !matches!(scan, Scan::Code)
+}
+
+/// Parses and executes the `;` separated statements of a WQL file one at a time.
+/// A failing statement is recorded in `ExecStats::errors` and does not stop the execution of the next ones.
+/// `USE my_entity` is not sent to the `Executor`, it sets the entity of the following `INSERT`s without `INTO`.
+/// `LET name = {...}` is not sent to the `Executor` either, it binds a map that following statements can spread.
+pub fn execute_file<E: Executor>(path: &Path, exec: &mut E) -> Result<ExecStats, String> {
+    let file =
+        File::open(path).map_err(|e| format!("Couldn't open file {:?}. Error {:?}", path, e))?;
+    let mut stats = ExecStats::default();
+    let mut options = ParseOptions::default();
+
+    for (index, statement) in Statements::new(BufReader::new(file)).enumerate() {
+        let (offset, statement) =
+            statement.map_err(|e| format!("Couldn't read file {:?}. Error {:?}", path, e))?;
+        let result = match parse_at(&statement, offset, &options) {
+            Ok(Wql::Use(entity_name)) => {
+                options.default_entity = Some(entity_name);
+                Ok((false, false))
+            }
+            Ok(Wql::Let(name, value)) => {
+                options.bindings.insert(name, value);
+                Ok((false, false))
+            }
+            Ok(wql) => {
+                let (created, inserted) = (
+                    matches!(wql, Wql::CreateEntity(_, _, _, _)),
+                    matches!(wql, Wql::Insert(_, _, _, _) | Wql::NestedInsert(_, _)),
+                );
+                exec.execute(wql).map(|_| (created, inserted))
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok((created, inserted)) => {
+                stats.executed += 1;
+                stats.created += created as usize;
+                stats.inserted += inserted as usize;
+            }
+            Err(e) => {
+                stats.failed += 1;
+                stats.errors.push((index, e));
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Parses every `;` separated statement of `program`, error positions are relative to the whole program.
+pub fn parse_program(program: &str) -> Vec<Result<Wql, String>> {
+    let mut options = ParseOptions::default();
+    Statements::new(program.as_bytes())
+        .map(|statement| {
+            let (offset, statement) = statement.map_err(|e| format!("{:?}", e))?;
+            let wql = parse_at(&statement, offset, &options)?;
+            match &wql {
+                Wql::Use(entity_name) => options.default_entity = Some(entity_name.to_owned()),
+                Wql::Let(name, value) => {
+                    options.bindings.insert(name.to_owned(), value.to_owned());
+                }
+                _ => (),
+            }
+            Ok(wql)
+        })
+        .collect()
+}
+
+/// Parses every statement of `program` before any of them is executed. Returns all statements only if
+/// every one of them is valid, otherwise returns the index and error of each invalid statement.
+pub fn validate_program(program: &str) -> Result<Vec<Wql>, Vec<(usize, String)>> {
+    let (wqls, errors): (Vec<_>, Vec<_>) = parse_program(program)
+        .into_iter()
+        .enumerate()
+        .partition(|(_, wql)| wql.is_ok());
+
+    if errors.is_empty() {
+        Ok(wqls.into_iter().filter_map(|(_, wql)| wql.ok()).collect())
+    } else {
+        Err(errors
+            .into_iter()
+            .filter_map(|(index, wql)| wql.err().map(|e| (index, e)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ToSelect, Types};
+    use std::io::Write;
+
+    #[derive(Default)]
+    struct VecExecutor(Vec<Wql>);
+
+    impl Executor for VecExecutor {
+        fn execute(&mut self, wql: Wql) -> Result<(), String> {
+            if let Wql::Evict(_, _) = wql {
+                return Err(String::from("EVICT not allowed"));
+            }
+            self.0.push(wql);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn statements_split_outside_strings() {
+        let input = "INSERT {a: \"x;y\",} INTO e;\n;\n  EVICT e  ".as_bytes();
+        let statements = Statements::new(input)
+            .map(Result::unwrap)
+            .collect::<Vec<(usize, String)>>();
+
+        assert_eq!(
+            statements,
+            vec![
+                (0, String::from("INSERT {a: \"x;y\",} INTO e")),
+                (31, String::from("EVICT e"))
+            ]
+        );
+    }
+
+    #[test]
+    fn statements_split_outside_chars_and_comments() {
+        let statements = |input: &str| {
+            Statements::new(input.as_bytes())
+                .map(|statement| statement.unwrap().1)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            statements("INSERT {a: ';',} INTO e; SELECT * FROM e"),
+            vec![
+                String::from("INSERT {a: ';',} INTO e"),
+                String::from("SELECT * FROM e")
+            ]
+        );
+        assert_eq!(
+            statements("INSERT {a: '\"', b: '\\'',} INTO e; SELECT * FROM e"),
+            vec![
+                String::from("INSERT {a: '\"', b: '\\'',} INTO e"),
+                String::from("SELECT * FROM e")
+            ]
+        );
+        assert_eq!(
+            statements("SELECT * /* a; /* b; */ c; */ FROM e; EVICT e"),
+            vec![
+                String::from("SELECT * /* a; /* b; */ c; */ FROM e"),
+                String::from("EVICT e")
+            ]
+        );
+        assert_eq!(
+            statements("INSERT {a: \"/*\",} INTO e; EVICT e"),
+            vec![
+                String::from("INSERT {a: \"/*\",} INTO e"),
+                String::from("EVICT e")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_program_with_chars_and_comments() {
+        let wqls = parse_program(
+            "INSERT {a: ';', b: '\"',} INTO e; /* keep; this */ SELECT * FROM e; DELETE FROM e WHERE id IN (2df2b8cf-49da-474d-8a00-c596c0bb6fd1)",
+        );
+
+        assert_eq!(wqls.len(), 3);
+        assert!(wqls.iter().all(Result::is_ok));
+        assert_eq!(
+            validate_program("INSERT {a: ';',} INTO e; /* ; */ EVICT e")
+                .unwrap()
+                .len(),
+            2
+        );
+        if let Ok(Wql::Insert(_, entity, _, _)) = &wqls[0] {
+            assert_eq!(entity["a"], Types::Char(';'));
+            assert_eq!(entity["b"], Types::Char('"'));
+        } else {
+            panic!("expected INSERT");
+        }
+    }
+
+    #[test]
+    fn unknown_symbol_offset_in_program() {
+        let program = "CREATE ENTITY my_entity;\n  INSRT {a: 1,} INTO my_entity;";
+        let wqls = parse_program(program);
+
+        assert_eq!(wqls.len(), 2);
+        assert!(wqls[0].is_ok());
+        assert_eq!(
+            wqls[1],
+            Err(String::from("Symbol `INSRT` not implemented at offset 27"))
+        );
+        assert_eq!(&program[27..32], "INSRT");
+    }
+
+    #[test]
+    fn execute_file_with_mixed_statements() {
+        let path = std::env::temp_dir().join(format!("wql_{}.wql", uuid::Uuid::new_v4()));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(
+            b"CREATE ENTITY my_entity;
+            INSERT {a: 1, b: \"hello;\",} INTO my_entity;
+            INSERT {a: 2,} my_entity;
+            EVICT my_entity;
+            INSERT {a: 3,} INTO my_entity",
+        )
+        .unwrap();
+
+        let mut exec = VecExecutor::default();
+        let stats = execute_file(&path, &mut exec).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stats.created, 1);
+        assert_eq!(stats.inserted, 2);
+        assert_eq!(stats.executed, 3);
+        assert_eq!(stats.failed, 2);
+        assert_eq!(
+            stats.errors,
+            vec![
+                (2, String::from("Keyword INTO is required for INSERT")),
+                (3, String::from("EVICT not allowed"))
+            ]
+        );
+        assert_eq!(exec.0.len(), 3);
+        if let Wql::Insert(_, entity, _, _) = &exec.0[1] {
+            assert_eq!(entity["b"], Types::String(String::from("hello;")));
+        } else {
+            panic!("expected INSERT");
+        }
+    }
+
+    #[test]
+    fn execute_file_with_use() {
+        let path = std::env::temp_dir().join(format!("wql_{}.wql", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "INSERT {a: 1,};
+            USE my_entity;
+            INSERT {a: 2,};
+            INSERT {a: 3,} INTO other_entity",
+        )
+        .unwrap();
+
+        let mut exec = VecExecutor::default();
+        let stats = execute_file(&path, &mut exec).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stats.inserted, 2);
+        assert_eq!(
+            stats.errors,
+            vec![(0, String::from("Keyword INTO is required for INSERT"))]
+        );
+        assert!(matches!(&exec.0[0], Wql::Insert(e, _, _, _) if e == "my_entity"));
+        assert!(matches!(&exec.0[1], Wql::Insert(e, _, _, _) if e == "other_entity"));
+    }
+
+    #[test]
+    fn execute_file_not_found() {
+        let mut exec = VecExecutor::default();
+        let stats = execute_file(Path::new("not/a/file.wql"), &mut exec);
+
+        assert!(stats.unwrap_err().starts_with("Couldn't open file"));
+    }
+
+    #[test]
+    fn validate_valid_program() {
+        let program =
+            "CREATE ENTITY my_entity; INSERT {a: 1,} INTO my_entity; SELECT * FROM my_entity";
+        let wqls = validate_program(program).unwrap();
+
+        assert_eq!(wqls.len(), 3);
+        assert_eq!(
+            wqls[2],
+            Wql::Select("my_entity".to_string(), ToSelect::All, None)
+        );
+    }
+
+    #[test]
+    fn validate_program_reports_invalid_statement() {
+        let program = "CREATE ENTITY my_entity;
+            INSERT {a: 1,} INTO my_entity;
+            INSERT {a: 2,} my_entity;
+            SELECT * FROM my_entity;";
+
+        assert_eq!(
+            validate_program(program),
+            Err(vec![(
+                2,
+                String::from("Keyword INTO is required for INSERT")
+            )])
+        );
+        assert_eq!(
+            parse_program(program)
+                .iter()
+                .map(Result::is_ok)
+                .collect::<Vec<bool>>(),
+            vec![true, true, false, true]
+        );
+    }
+
+    #[test]
+    fn validate_program_reports_every_invalid_statement() {
+        let program = "KREATE ENTITY my_entity; SELECT * FROM my_entity; INSERT {a: 1,} INTO";
+
+        assert_eq!(
+            validate_program(program),
+            Err(vec![
+                (
+                    0,
+                    String::from("Symbol `KREATE` not implemented at offset 0")
+                ),
+                (2, String::from("Entity name is required after INTO"))
+            ])
+        );
+    }
+}
@@ -0,0 +1,854 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use crate::{Clause, Entity, Expr, Operator, ToSelect, Types, Value, Wql};
+
+/// Variant of a `Types` value without its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypeTag {
+    Char,
+    Integer,
+    String,
+    Uuid,
+    Float,
+    Boolean,
+    Vector,
+    Map,
+    Pairs,
+    Hash,
+    Precise,
+    Ref,
+    Param,
+    SelfRef,
+    DateTime,
+    Duration,
+    Range,
+    Nil,
+}
+
+const TYPE_TAGS: [TypeTag; 18] = [
+    TypeTag::Char,
+    TypeTag::Integer,
+    TypeTag::String,
+    TypeTag::Uuid,
+    TypeTag::Float,
+    TypeTag::Boolean,
+    TypeTag::Vector,
+    TypeTag::Map,
+    TypeTag::Pairs,
+    TypeTag::Hash,
+    TypeTag::Precise,
+    TypeTag::Ref,
+    TypeTag::Param,
+    TypeTag::SelfRef,
+    TypeTag::DateTime,
+    TypeTag::Duration,
+    TypeTag::Range,
+    TypeTag::Nil,
+];
+
+impl TypeTag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TypeTag::Char => "Char",
+            TypeTag::Integer => "Integer",
+            TypeTag::String => "String",
+            TypeTag::Uuid => "Uuid",
+            TypeTag::Float => "Float",
+            TypeTag::Boolean => "Boolean",
+            TypeTag::Vector => "Vector",
+            TypeTag::Map => "Map",
+            TypeTag::Pairs => "Pairs",
+            TypeTag::Hash => "Hash",
+            TypeTag::Precise => "Precise",
+            TypeTag::Ref => "Ref",
+            TypeTag::Param => "Param",
+            TypeTag::SelfRef => "SelfRef",
+            TypeTag::DateTime => "DateTime",
+            TypeTag::Duration => "Duration",
+            TypeTag::Range => "Range",
+            TypeTag::Nil => "Nil",
+        }
+    }
+}
+
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TypeTag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TYPE_TAGS
+            .iter()
+            .find(|tag| tag.as_str() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown type `{}`", s))
+    }
+}
+
+impl Types {
+    pub fn type_tag(&self) -> TypeTag {
+        match self {
+            Types::Char(_) => TypeTag::Char,
+            Types::Integer(_) => TypeTag::Integer,
+            Types::String(_) => TypeTag::String,
+            Types::Uuid(_) => TypeTag::Uuid,
+            Types::Float(_) => TypeTag::Float,
+            Types::Boolean(_) => TypeTag::Boolean,
+            Types::Vector(_) => TypeTag::Vector,
+            Types::Map(_) => TypeTag::Map,
+            Types::Pairs(_) => TypeTag::Pairs,
+            Types::Hash(_) => TypeTag::Hash,
+            Types::Precise(_) => TypeTag::Precise,
+            Types::Ref(_, _) => TypeTag::Ref,
+            Types::Param(_) => TypeTag::Param,
+            Types::SelfRef(_) => TypeTag::SelfRef,
+            Types::DateTime(_) => TypeTag::DateTime,
+            Types::Duration(_) => TypeTag::Duration,
+            Types::Range(_, _, _) => TypeTag::Range,
+            Types::Nil => TypeTag::Nil,
+        }
+    }
+
+    /// Converts the value toward `tag`, `None` when the conversion would lose information.
+    /// `Nil` and values that already are of type `tag` are kept as they are.
+    pub fn coerce(&self, tag: TypeTag) -> Option<Types> {
+        if self.type_tag() == tag || self == &Types::Nil {
+            return Some(self.clone());
+        }
+        match (self, tag) {
+            (Types::Integer(i), TypeTag::Float) => Some(Types::Float(*i as f64)),
+            (Types::Integer(i), TypeTag::Precise) => Some(Types::Precise(i.to_string())),
+            (Types::Float(f), TypeTag::Precise) if f.is_finite() => {
+                Some(Types::Precise(f.to_string()))
+            }
+            (Types::Char(c), TypeTag::String) => Some(Types::String(c.to_string())),
+            (Types::String(s), TypeTag::Char) if s.chars().count() == 1 => {
+                s.chars().next().map(Types::Char)
+            }
+            (Types::String(s), TypeTag::Uuid) => uuid::Uuid::from_str(s).ok().map(Types::Uuid),
+            (Types::String(s), TypeTag::Integer) => s.parse().ok().map(Types::Integer),
+            (Types::String(s), TypeTag::Float) => s
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(Types::Float),
+            (Types::String(s), TypeTag::Boolean) => s.parse().ok().map(Types::Boolean),
+            (Types::String(s), TypeTag::DateTime) => crate::logic::read_datetime(s),
+            (Types::String(s), TypeTag::Duration) => crate::logic::read_duration(s),
+            _ => None,
+        }
+    }
+}
+
+/// Maps every field of `entity` to the `TypeTag` of its value. Nested maps are not
+/// recursed into, a field containing a map is recorded as `TypeTag::Map`.
+pub fn infer_schema(entity: &Entity) -> HashMap<String, TypeTag> {
+    entity
+        .iter()
+        .map(|(field, value)| (field.to_owned(), value.type_tag()))
+        .collect()
+}
+
+/// Differences between a declared schema and the entities actually stored, see `schema_drift`.
+/// Every list is sorted by field name.
+#[derive(Debug, Default, PartialEq)]
+pub struct SchemaDrift {
+    /// Fields of the samples that are not in the schema.
+    pub unknown_fields: Vec<String>,
+    /// Fields of the schema that none of the samples contain.
+    pub unseen_fields: Vec<String>,
+    /// Field, schema type and observed type of each value whose type is not the schema type.
+    pub mismatched_fields: Vec<(String, TypeTag, TypeTag)>,
+}
+
+/// Compares `schema` with the fields of `samples`. `Nil` values fit any schema type, and each
+/// observed type of a mismatched field is reported once.
+pub fn schema_drift(schema: &HashMap<String, TypeTag>, samples: &[Entity]) -> SchemaDrift {
+    let mut unknown = BTreeSet::new();
+    let mut seen = BTreeSet::new();
+    let mut mismatched = BTreeMap::new();
+    samples
+        .iter()
+        .flat_map(|entity| entity.iter())
+        .for_each(|(field, value)| {
+            let observed = value.type_tag();
+            match schema.get(field) {
+                None => {
+                    unknown.insert(field.to_owned());
+                }
+                Some(declared) => {
+                    seen.insert(field.as_str());
+                    if observed != *declared && observed != TypeTag::Nil {
+                        mismatched
+                            .insert((field.to_owned(), observed.as_str()), (*declared, observed));
+                    }
+                }
+            }
+        });
+
+    let mut unseen_fields = schema
+        .keys()
+        .filter(|field| !seen.contains(field.as_str()))
+        .map(ToOwned::to_owned)
+        .collect::<Vec<String>>();
+    unseen_fields.sort();
+    SchemaDrift {
+        unknown_fields: unknown.into_iter().collect(),
+        unseen_fields,
+        mismatched_fields: mismatched
+            .into_iter()
+            .map(|((field, _), (declared, observed))| (field, declared, observed))
+            .collect(),
+    }
+}
+
+/// Statements that migrate `entity` from the `from` schema to the `to` schema. A field removed from
+/// `from` and added to `to` with the same type, when it is the only one of its type, is renamed with
+/// `ALTER ENTITY ... RENAME FIELD` so the stored entities follow it, and the other removed fields are
+/// dropped from the stored entities with `ALTER ENTITY ... DROP FIELD`. A final `CREATE SCHEMA`
+/// registers `to` when it differs from the renamed `from`, it is omitted when `to` is empty.
+pub fn migration_script(
+    from: &HashMap<String, TypeTag>,
+    to: &HashMap<String, TypeTag>,
+    entity: &str,
+) -> Vec<Wql> {
+    let by_type = |a: &HashMap<String, TypeTag>, b: &HashMap<String, TypeTag>| {
+        a.iter().filter(|(field, _)| !b.contains_key(*field)).fold(
+            BTreeMap::new(),
+            |mut fields, (field, tag)| {
+                fields
+                    .entry(tag.as_str())
+                    .or_insert_with(Vec::new)
+                    .push(field.to_owned());
+                fields
+            },
+        )
+    };
+    let removed = by_type(from, to);
+    let added = by_type(to, from);
+
+    let mut renames = removed
+        .iter()
+        .filter_map(
+            |(tag, removed)| match (&removed[..], added.get(tag).map(|a| &a[..])) {
+                ([old], Some([new])) => Some((old.to_owned(), new.to_owned())),
+                _ => None,
+            },
+        )
+        .collect::<Vec<(String, String)>>();
+    renames.sort();
+
+    let renamed = renames
+        .iter()
+        .fold(from.to_owned(), |mut schema, (old, new)| {
+            if let Some(tag) = schema.remove(old) {
+                schema.insert(new.to_owned(), tag);
+            }
+            schema
+        });
+    let mut dropped = removed
+        .into_values()
+        .flatten()
+        .filter(|field| !renames.iter().any(|(old, _)| old == field))
+        .collect::<Vec<String>>();
+    dropped.sort();
+
+    let mut script = renames
+        .into_iter()
+        .map(|(old, new)| Wql::RenameField(entity.to_string(), old, new))
+        .chain(
+            dropped
+                .into_iter()
+                .map(|field| Wql::DropField(entity.to_string(), field)),
+        )
+        .collect::<Vec<Wql>>();
+    if &renamed != to && !to.is_empty() {
+        script.push(Wql::CreateSchema(
+            entity.to_string(),
+            to.to_owned(),
+            HashMap::new(),
+        ));
+    }
+    script
+}
+
+/// Sets the `OR DEFAULT` value of every field of `defaults` missing from `entity`, a field present
+/// in `entity`, even as `Nil`, is kept.
+pub fn apply_defaults(entity: &mut Entity, defaults: &Entity) {
+    defaults.iter().for_each(|(field, default)| {
+        if !entity.contains_key(field) {
+            entity.insert(field.to_owned(), default.to_owned());
+        }
+    });
+}
+
+/// Field names referenced by the projections, `WHERE` clauses and aggregates of `wql` that are not in
+/// `schema`, in the order they first appear, so typos are caught before the query is executed.
+/// Computed field aliases are not fields, the fields of their expressions are.
+pub fn check_fields(wql: &Wql, schema: &HashMap<String, TypeTag>) -> Result<(), Vec<String>> {
+    let mut fields = Vec::new();
+    match wql {
+        Wql::Select(_, to_select, _)
+        | Wql::SelectWhen(_, to_select, _, _)
+        | Wql::SelectIds(_, to_select, _) => selected_fields(to_select, &mut fields),
+        Wql::SelectWhere(_, to_select, clauses) => {
+            selected_fields(to_select, &mut fields);
+            clause_fields(clauses, &mut fields);
+        }
+        Wql::SelectCount(_, clauses) => clause_fields(clauses, &mut fields),
+        Wql::SelectSum(_, field, clauses) | Wql::SelectAvg(_, field, clauses) => {
+            fields.push(field.as_str());
+            clause_fields(clauses, &mut fields);
+        }
+        _ => (),
+    }
+
+    let mut missing: Vec<String> = Vec::new();
+    fields
+        .into_iter()
+        .filter(|field| !schema.contains_key(*field))
+        .for_each(|field| {
+            if !missing.iter().any(|m| m == field) {
+                missing.push(field.to_owned());
+            }
+        });
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(missing)
+    }
+}
+
+/// Output columns of a `SELECT` and their types under `schema`, for clients that need the result metadata
+/// before reading rows. `*` selects every field of the schema in name order, computed fields are typed
+/// by their expression, `COUNT` is an `Integer`, `SUM` keeps the type of its field and `AVG` is a `Float`.
+pub fn result_schema(
+    wql: &Wql,
+    schema: &HashMap<String, TypeTag>,
+) -> Result<Vec<(String, TypeTag)>, String> {
+    let field_type = |field: &str| {
+        schema
+            .get(field)
+            .copied()
+            .ok_or_else(|| format!("Field `{}` is not in the schema", field))
+    };
+    match wql {
+        Wql::Select(_, to_select, _)
+        | Wql::SelectWhen(_, to_select, _, _)
+        | Wql::SelectIds(_, to_select, _)
+        | Wql::SelectPage(_, to_select, _, _)
+        | Wql::SelectSample(_, to_select, _, _)
+        | Wql::SelectWhere(_, to_select, _) => selected_columns(to_select, schema),
+        Wql::SelectWhenRange(_, _, _, _) | Wql::SelectHistory(_, _) | Wql::SelectLatest(_, _) => {
+            selected_columns(&ToSelect::All, schema)
+        }
+        Wql::SelectCount(_, _) => Ok(vec![("count".to_string(), TypeTag::Integer)]),
+        Wql::SelectSum(_, field, _) => match field_type(field)? {
+            tag @ (TypeTag::Integer | TypeTag::Float) => Ok(vec![("sum".to_string(), tag)]),
+            tag => Err(format!(
+                "SUM of `{}` needs a numeric field, found {}",
+                field, tag
+            )),
+        },
+        Wql::SelectAvg(_, field, _) => match field_type(field)? {
+            TypeTag::Integer | TypeTag::Float => Ok(vec![("avg".to_string(), TypeTag::Float)]),
+            tag => Err(format!(
+                "AVG of `{}` needs a numeric field, found {}",
+                field, tag
+            )),
+        },
+        _ => Err(String::from("Only SELECT statements have a result schema")),
+    }
+}
+
+fn selected_columns(
+    to_select: &ToSelect,
+    schema: &HashMap<String, TypeTag>,
+) -> Result<Vec<(String, TypeTag)>, String> {
+    let column = |key: &String| {
+        schema
+            .get(key)
+            .map(|tag| (key.to_owned(), *tag))
+            .ok_or_else(|| format!("Field `{}` is not in the schema", key))
+    };
+    match to_select {
+        ToSelect::All => {
+            let mut columns = schema
+                .iter()
+                .map(|(key, tag)| (key.to_owned(), *tag))
+                .collect::<Vec<_>>();
+            columns.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Ok(columns)
+        }
+        ToSelect::Keys(keys) => keys.iter().map(column).collect(),
+        ToSelect::Computed(keys, computed) => {
+            keys.iter()
+                .map(column)
+                .chain(computed.iter().map(|(alias, expr)| {
+                    expr_type(expr, schema).map(|tag| (alias.to_owned(), tag))
+                }))
+                .collect()
+        }
+    }
+}
+
+/// Type of the values `Expr::eval` returns, operations it evaluates to `Nil` are typed `Nil`.
+fn expr_type(expr: &Expr, schema: &HashMap<String, TypeTag>) -> Result<TypeTag, String> {
+    match expr {
+        Expr::Field(field) => schema
+            .get(field)
+            .copied()
+            .ok_or_else(|| format!("Field `{}` is not in the schema", field)),
+        Expr::Value(value) => Ok(value.type_tag()),
+        Expr::Operation(op, lhs, rhs) => {
+            let numeric = |tag| matches!(tag, TypeTag::Integer | TypeTag::Float | TypeTag::Precise);
+            Ok(
+                match (op, expr_type(lhs, schema)?, expr_type(rhs, schema)?) {
+                    (Operator::Add | Operator::Sub, TypeTag::DateTime, TypeTag::Duration)
+                    | (Operator::Add, TypeTag::Duration, TypeTag::DateTime) => TypeTag::DateTime,
+                    (Operator::Sub, TypeTag::DateTime, TypeTag::DateTime)
+                    | (Operator::Add | Operator::Sub, TypeTag::Duration, TypeTag::Duration) => {
+                        TypeTag::Duration
+                    }
+                    (_, TypeTag::Integer, TypeTag::Integer) => TypeTag::Integer,
+                    (_, lhs, rhs) if numeric(lhs) && numeric(rhs) => TypeTag::Float,
+                    _ => TypeTag::Nil,
+                },
+            )
+        }
+        Expr::Case(branches, otherwise) => {
+            let tags = branches
+                .iter()
+                .map(|(_, then)| then)
+                .chain(otherwise.as_deref())
+                .map(|expr| expr_type(expr, schema))
+                .collect::<Result<Vec<_>, String>>()?;
+            match tags.iter().find(|tag| **tag != tags[0]) {
+                Some(other) => Err(format!(
+                    "CASE branches should have the same type, found {} and {}",
+                    tags[0], other
+                )),
+                None => Ok(tags.first().copied().unwrap_or(TypeTag::Nil)),
+            }
+        }
+    }
+}
+
+fn selected_fields<'a>(to_select: &'a ToSelect, fields: &mut Vec<&'a str>) {
+    match to_select {
+        ToSelect::All => (),
+        ToSelect::Keys(keys) => fields.extend(keys.iter().map(String::as_str)),
+        ToSelect::Computed(keys, computed) => {
+            fields.extend(keys.iter().map(String::as_str));
+            computed
+                .iter()
+                .for_each(|(_, expr)| expr_fields(expr, fields));
+        }
+    }
+}
+
+fn expr_fields<'a>(expr: &'a Expr, fields: &mut Vec<&'a str>) {
+    match expr {
+        Expr::Field(field) => fields.push(field),
+        Expr::Value(_) => (),
+        Expr::Operation(_, lhs, rhs) => {
+            expr_fields(lhs, fields);
+            expr_fields(rhs, fields);
+        }
+        Expr::Case(branches, default) => {
+            branches.iter().for_each(|(condition, then)| {
+                clause_fields(std::slice::from_ref(condition), fields);
+                expr_fields(then, fields);
+            });
+            if let Some(default) = default {
+                expr_fields(default, fields);
+            }
+        }
+    }
+}
+
+/// `?variables` are resolved to the field of their `ValueAttribution`, as `eval_filter` does.
+fn clause_fields<'a>(clauses: &'a [Clause], fields: &mut Vec<&'a str>) {
+    let args_to_key = clauses
+        .iter()
+        .filter_map(|clause| {
+            if let Clause::ValueAttribution(_, key, Value(arg)) = clause {
+                Some((arg.as_str(), key.as_str()))
+            } else {
+                None
+            }
+        })
+        .collect::<HashMap<&str, &str>>();
+    clauses_fields(clauses, &args_to_key, fields);
+}
+
+fn clauses_fields<'a>(
+    clauses: &'a [Clause],
+    args_to_key: &HashMap<&'a str, &'a str>,
+    fields: &mut Vec<&'a str>,
+) {
+    let key = |key: &'a str| *args_to_key.get(key).unwrap_or(&key);
+    clauses.iter().for_each(|clause| match clause {
+        Clause::ValueAttribution(_, field, _) | Clause::ContainsKeyValue(_, field, _) => {
+            fields.push(field)
+        }
+        Clause::SimpleComparisonFunction(_, field, _)
+        | Clause::ComplexComparisonFunctions(_, field, _)
+        | Clause::IsEmpty(field)
+        | Clause::IsNotEmpty(field)
+        | Clause::Missing(field) => fields.push(key(field)),
+        Clause::TupleEq(keys, _) => fields.extend(keys.iter().map(|field| key(field))),
+        Clause::Or(_, clauses) => clauses_fields(clauses, args_to_key, fields),
+        Clause::Error => (),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Wql;
+
+    fn inserted(query: &str) -> Entity {
+        match Wql::from_str(query) {
+            Ok(Wql::Insert(_, entity, _, _)) => entity,
+            wql => panic!("expected INSERT, got {:?}", wql),
+        }
+    }
+
+    #[test]
+    fn flat_entity() {
+        let entity =
+            inserted("INSERT {a: 1, b: 2.5, c: \"hello\", d: 'c', e: Nil,} INTO my_entity");
+
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), TypeTag::Integer);
+        expected.insert("b".to_string(), TypeTag::Float);
+        expected.insert("c".to_string(), TypeTag::String);
+        expected.insert("d".to_string(), TypeTag::Char);
+        expected.insert("e".to_string(), TypeTag::Nil);
+        assert_eq!(infer_schema(&entity), expected);
+    }
+
+    #[test]
+    fn nested_entity_records_map() {
+        let entity = inserted("INSERT {a: {b: 1, c: {d: true,},}, e: [1, 2,],} INTO my_entity");
+
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), TypeTag::Map);
+        expected.insert("e".to_string(), TypeTag::Vector);
+        assert_eq!(infer_schema(&entity), expected);
+    }
+
+    #[test]
+    fn check_fields_reports_unknown_fields() {
+        let schema = infer_schema(&inserted(
+            "INSERT {name: \"julia\", age: 30, score: 7.5,} INTO my_entity",
+        ));
+        let wql = Wql::from_str(
+            "SELECT #{name, nmae, (score * 2) AS double, (scroe + 1) AS typo,} FROM my_entity WHERE {
+                ?* my_entity:age ?age,
+                (>= ?age 18),
+                (or (== ?agee 1) (like name \"%uli%\")),
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            check_fields(&wql, &schema),
+            Err(vec![
+                "nmae".to_string(),
+                "scroe".to_string(),
+                "?agee".to_string()
+            ])
+        );
+        assert_eq!(
+            check_fields(
+                &Wql::from_str("SELECT AVG(amount) FROM my_entity").unwrap(),
+                &schema
+            ),
+            Err(vec!["amount".to_string()])
+        );
+    }
+
+    #[test]
+    fn check_fields_accepts_valid_query() {
+        let schema = infer_schema(&inserted(
+            "INSERT {name: \"julia\", age: 30, score: 7.5,} INTO my_entity",
+        ));
+        let wql = Wql::from_str(
+            "SELECT #{name, (score * 2) AS double,} FROM my_entity WHERE {
+                ?* my_entity:age ?age,
+                (>= ?age 18),
+                (or (== ?age 1) (like name \"%uli%\")),
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(check_fields(&wql, &schema), Ok(()));
+        assert_eq!(
+            check_fields(&Wql::from_str("SELECT * FROM my_entity").unwrap(), &schema),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn type_tag_from_str() {
+        assert_eq!("Precise".parse::<TypeTag>(), Ok(TypeTag::Precise));
+        assert_eq!(
+            "Int".parse::<TypeTag>(),
+            Err(String::from("Unknown type `Int`"))
+        );
+    }
+
+    #[test]
+    fn coerce_toward_tag() {
+        let id = "48c7640e-9287-468a-a07c-2fb00da5eaed";
+
+        assert_eq!(
+            Types::Integer(3).coerce(TypeTag::Float),
+            Some(Types::Float(3.0))
+        );
+        assert_eq!(
+            Types::Integer(3).coerce(TypeTag::Precise),
+            Some(Types::Precise("3".to_string()))
+        );
+        assert_eq!(
+            Types::String(id.to_string()).coerce(TypeTag::Uuid),
+            Some(Types::Uuid(uuid::Uuid::from_str(id).unwrap()))
+        );
+        assert_eq!(
+            Types::String("42".to_string()).coerce(TypeTag::Integer),
+            Some(Types::Integer(42))
+        );
+        assert_eq!(
+            Types::String("true".to_string()).coerce(TypeTag::Boolean),
+            Some(Types::Boolean(true))
+        );
+        assert_eq!(
+            Types::Char('c').coerce(TypeTag::String),
+            Some(Types::String("c".to_string()))
+        );
+        assert_eq!(Types::Nil.coerce(TypeTag::Uuid), Some(Types::Nil));
+        assert_eq!(
+            Types::Boolean(false).coerce(TypeTag::Boolean),
+            Some(Types::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn coerce_impossible() {
+        assert_eq!(Types::Float(3.5).coerce(TypeTag::Integer), None);
+        assert_eq!(
+            Types::String("not an uuid".to_string()).coerce(TypeTag::Uuid),
+            None
+        );
+        assert_eq!(Types::String("ab".to_string()).coerce(TypeTag::Char), None);
+        assert_eq!(Types::Vector(vec![]).coerce(TypeTag::Map), None);
+    }
+
+    #[test]
+    fn schema_drift_kinds() {
+        let mut schema = HashMap::new();
+        schema.insert("name".to_string(), TypeTag::String);
+        schema.insert("age".to_string(), TypeTag::Integer);
+        schema.insert("email".to_string(), TypeTag::String);
+        let samples = vec![
+            inserted("INSERT {name: \"julia\", age: 30,} INTO my_entity"),
+            inserted("INSERT {name: \"otavio\", age: 30.5, nickname: \"ota\",} INTO my_entity"),
+            inserted("INSERT {name: Nil, age: \"31\", score: 7,} INTO my_entity"),
+        ];
+
+        assert_eq!(
+            schema_drift(&schema, &samples),
+            SchemaDrift {
+                unknown_fields: vec!["nickname".to_string(), "score".to_string()],
+                unseen_fields: vec!["email".to_string()],
+                mismatched_fields: vec![
+                    ("age".to_string(), TypeTag::Integer, TypeTag::Float),
+                    ("age".to_string(), TypeTag::Integer, TypeTag::String),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn no_schema_drift() {
+        let entity = inserted("INSERT {name: \"julia\", age: 30,} INTO my_entity");
+
+        assert_eq!(
+            schema_drift(&infer_schema(&entity), &[entity]),
+            SchemaDrift::default()
+        );
+    }
+
+    fn schema(fields: &[(&str, TypeTag)]) -> HashMap<String, TypeTag> {
+        fields
+            .iter()
+            .map(|(field, tag)| (field.to_string(), *tag))
+            .collect()
+    }
+
+    #[test]
+    fn result_schema_of_select_all() {
+        let schema = schema(&[("name", TypeTag::String), ("age", TypeTag::Integer)]);
+
+        assert_eq!(
+            result_schema(&Wql::from_str("SELECT * FROM users").unwrap(), &schema),
+            Ok(vec![
+                ("age".to_string(), TypeTag::Integer),
+                ("name".to_string(), TypeTag::String)
+            ])
+        );
+    }
+
+    #[test]
+    fn result_schema_of_projection() {
+        let schema = schema(&[
+            ("name", TypeTag::String),
+            ("age", TypeTag::Integer),
+            ("score", TypeTag::Float),
+            ("created_at", TypeTag::DateTime),
+        ]);
+        let wql = Wql::from_str(
+            "SELECT #{name, (age * 2) AS double_age, (score + age) AS total, (created_at + PT1H) AS expires_at,} FROM users",
+        )
+        .unwrap();
+
+        assert_eq!(
+            result_schema(&wql, &schema),
+            Ok(vec![
+                ("name".to_string(), TypeTag::String),
+                ("double_age".to_string(), TypeTag::Integer),
+                ("total".to_string(), TypeTag::Float),
+                ("expires_at".to_string(), TypeTag::DateTime),
+            ])
+        );
+        assert_eq!(
+            result_schema(
+                &Wql::from_str("SELECT #{name, nmae,} FROM users").unwrap(),
+                &schema
+            ),
+            Err(String::from("Field `nmae` is not in the schema"))
+        );
+    }
+
+    #[test]
+    fn result_schema_of_aggregates() {
+        let schema = schema(&[("name", TypeTag::String), ("age", TypeTag::Integer)]);
+        let result = |query: &str| result_schema(&Wql::from_str(query).unwrap(), &schema);
+
+        assert_eq!(
+            result("SELECT COUNT FROM users WHERE { ?* users:age ?age, (> ?age 18), }"),
+            Ok(vec![("count".to_string(), TypeTag::Integer)])
+        );
+        assert_eq!(
+            result("SELECT SUM(age) FROM users"),
+            Ok(vec![("sum".to_string(), TypeTag::Integer)])
+        );
+        assert_eq!(
+            result("SELECT AVG(age) FROM users"),
+            Ok(vec![("avg".to_string(), TypeTag::Float)])
+        );
+        assert_eq!(
+            result("SELECT SUM(name) FROM users"),
+            Err(String::from(
+                "SUM of `name` needs a numeric field, found String"
+            ))
+        );
+        assert_eq!(
+            result("INSERT {age: 1,} INTO users"),
+            Err(String::from("Only SELECT statements have a result schema"))
+        );
+    }
+
+    #[test]
+    fn migration_adds_and_drops_fields() {
+        let from = schema(&[("name", TypeTag::String), ("age", TypeTag::Integer)]);
+        let added = schema(&[
+            ("name", TypeTag::String),
+            ("age", TypeTag::Integer),
+            ("score", TypeTag::Float),
+        ]);
+        let dropped = schema(&[("name", TypeTag::String)]);
+
+        assert_eq!(
+            migration_script(&from, &added, "users"),
+            vec![Wql::CreateSchema(
+                "users".to_string(),
+                added.clone(),
+                HashMap::new()
+            )]
+        );
+        assert_eq!(
+            migration_script(&from, &dropped, "users"),
+            vec![
+                Wql::DropField("users".to_string(), "age".to_string()),
+                Wql::CreateSchema("users".to_string(), dropped.clone(), HashMap::new())
+            ]
+        );
+        assert_eq!(
+            migration_script(&from, &HashMap::new(), "users"),
+            vec![
+                Wql::DropField("users".to_string(), "age".to_string()),
+                Wql::DropField("users".to_string(), "name".to_string())
+            ]
+        );
+        assert_eq!(migration_script(&from, &from, "users"), Vec::new());
+    }
+
+    #[test]
+    fn migration_renames_fields() {
+        let from = schema(&[("name", TypeTag::String), ("age", TypeTag::Integer)]);
+        let renamed = schema(&[("full_name", TypeTag::String), ("age", TypeTag::Integer)]);
+        let renamed_and_retyped =
+            schema(&[("full_name", TypeTag::String), ("age", TypeTag::Float)]);
+
+        assert_eq!(
+            migration_script(&from, &renamed, "users"),
+            vec![Wql::RenameField(
+                "users".to_string(),
+                "name".to_string(),
+                "full_name".to_string()
+            )]
+        );
+        assert_eq!(
+            migration_script(&from, &renamed_and_retyped, "users"),
+            vec![
+                Wql::RenameField(
+                    "users".to_string(),
+                    "name".to_string(),
+                    "full_name".to_string()
+                ),
+                Wql::CreateSchema(
+                    "users".to_string(),
+                    renamed_and_retyped.clone(),
+                    HashMap::new()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn migration_keeps_ambiguous_renames_as_add_and_drop() {
+        let from = schema(&[("first", TypeTag::String), ("last", TypeTag::String)]);
+        let to = schema(&[("given", TypeTag::String), ("family", TypeTag::String)]);
+
+        assert_eq!(
+            migration_script(&from, &to, "users"),
+            vec![
+                Wql::DropField("users".to_string(), "first".to_string()),
+                Wql::DropField("users".to_string(), "last".to_string()),
+                Wql::CreateSchema("users".to_string(), to.clone(), HashMap::new())
+            ]
+        );
+    }
+}
@@ -2,11 +2,11 @@ use std::str::FromStr;
 
 use uuid::Uuid;
 
-use crate::where_clause::where_selector;
+use crate::where_clause::{read_where_clauses, where_selector, Clause};
 
 use super::{
     logic::{read_select_args, read_uuids},
-    ToSelect, Wql,
+    Cursor, ToSelect, Wql,
 };
 
 pub(crate) fn select_all(chars: &mut std::str::Chars) -> Result<Wql, String> {
@@ -15,12 +15,159 @@ pub(crate) fn select_all(chars: &mut std::str::Chars) -> Result<Wql, String> {
 }
 
 pub(crate) fn select_args(chars: &mut std::str::Chars) -> Result<Wql, String> {
-    let args: Vec<String> = read_select_args(chars)?;
-    let arg = ToSelect::Keys(args);
+    let (args, computed) = read_select_args(chars)?;
+    let arg = if computed.is_empty() {
+        ToSelect::Keys(args)
+    } else {
+        ToSelect::Computed(args, computed)
+    };
 
     select_body(arg, chars)
 }
 
+pub(crate) fn select_function(c: char, chars: &mut std::str::Chars) -> Result<Wql, String> {
+    let symbol = format!(
+        "{}{}",
+        c,
+        chars.take_while(|c| !c.is_whitespace()).collect::<String>()
+    );
+
+    match &symbol.to_uppercase()[..] {
+        "COUNT" => select_count(chars),
+        "HISTORY" => select_history(chars),
+        "LATEST" => select_latest(chars),
+        sum if sum.starts_with("SUM(") && sum.ends_with(')') => {
+            select_sum(&symbol[4..symbol.len() - 1], chars)
+        }
+        avg if avg.starts_with("AVG(") && avg.ends_with(')') => {
+            select_avg(&symbol[4..symbol.len() - 1], chars)
+        }
+        _ => Err(format!("SELECT function `{}` not implemented", symbol)),
+    }
+}
+
+fn select_count(chars: &mut std::str::Chars) -> Result<Wql, String> {
+    let entity_name = function_entity_name("SELECT COUNT", chars)?;
+    let clauses = function_where_clauses("SELECT COUNT", &entity_name, chars)?;
+
+    Ok(Wql::SelectCount(entity_name, clauses))
+}
+
+fn select_sum(field: &str, chars: &mut std::str::Chars) -> Result<Wql, String> {
+    let field = field.trim();
+    if field.is_empty() || !field.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(String::from(
+            "SUM requires a single field name as `SUM(field)`",
+        ));
+    }
+    let entity_name = function_entity_name("SELECT SUM", chars)?;
+    let clauses = function_where_clauses("SELECT SUM", &entity_name, chars)?;
+
+    Ok(Wql::SelectSum(entity_name, field.to_string(), clauses))
+}
+
+fn select_avg(field: &str, chars: &mut std::str::Chars) -> Result<Wql, String> {
+    let field = field.trim();
+    if field.is_empty() || !field.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(String::from(
+            "AVG requires a single field name as `AVG(field)`",
+        ));
+    }
+    let entity_name = function_entity_name("SELECT AVG", chars)?;
+    let clauses = function_where_clauses("SELECT AVG", &entity_name, chars)?;
+
+    Ok(Wql::SelectAvg(entity_name, field.to_string(), clauses))
+}
+
+fn function_where_clauses(
+    function: &str,
+    entity_name: &str,
+    chars: &mut std::str::Chars,
+) -> Result<Vec<Clause>, String> {
+    let next_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    if next_symbol.to_uppercase() == "WHERE" {
+        read_where_clauses(entity_name, chars)
+    } else if next_symbol.is_empty() {
+        Ok(Vec::new())
+    } else {
+        Err(format!(
+            "Only WHERE is allowed after entity name for {}",
+            function
+        ))
+    }
+}
+
+fn select_history(chars: &mut std::str::Chars) -> Result<Wql, String> {
+    let (entity_name, uuid) = function_entity_id("SELECT HISTORY", chars)?;
+
+    Ok(Wql::SelectHistory(entity_name, uuid))
+}
+
+fn select_latest(chars: &mut std::str::Chars) -> Result<Wql, String> {
+    let (entity_name, uuid) = function_entity_id("SELECT LATEST", chars)?;
+
+    Ok(Wql::SelectLatest(entity_name, uuid))
+}
+
+fn function_entity_id(
+    function: &str,
+    chars: &mut std::str::Chars,
+) -> Result<(String, Uuid), String> {
+    let entity_name = function_entity_name(function, chars)?;
+
+    let where_id = (0..3)
+        .map(|_| {
+            chars
+                .skip_while(|c| c.is_whitespace())
+                .take_while(|c| !c.is_whitespace())
+                .collect::<String>()
+                .to_uppercase()
+        })
+        .collect::<Vec<String>>();
+
+    if where_id != ["WHERE", "ID", "="] {
+        return Err(format!(
+            "{} requires `WHERE id = <uuid>` after entity name",
+            function
+        ));
+    }
+
+    let id = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || c == &'-')
+        .collect::<String>();
+
+    match Uuid::from_str(&id) {
+        Ok(uuid) => Ok((entity_name, uuid)),
+        Err(_) => Err(String::from("Field ID must be a UUID v4")),
+    }
+}
+
+fn function_entity_name(function: &str, chars: &mut std::str::Chars) -> Result<String, String> {
+    let entity_symbol = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    if entity_symbol.to_uppercase() != "FROM" {
+        return Err(format!("Keyword FROM is required for {}", function));
+    }
+
+    let entity_name = chars
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || c == &'_')
+        .collect::<String>();
+
+    if entity_name.is_empty() {
+        return Err(format!("Entity name is required for {}", function));
+    }
+    Ok(entity_name)
+}
+
 fn select_body(arg: ToSelect, chars: &mut std::str::Chars) -> Result<Wql, String> {
     let entity_symbol = chars
         .skip_while(|c| c.is_whitespace())
@@ -93,6 +240,10 @@ fn select_body(arg: ToSelect, chars: &mut std::str::Chars) -> Result<Wql, String
         when_selector(entity_name, arg, None, chars)
     } else if next_symbol.to_uppercase() == "WHERE" {
         where_selector(entity_name, arg, chars)
+    } else if next_symbol == "AFTER" || next_symbol == "LIMIT" {
+        page_selector(entity_name, arg, &next_symbol, chars)
+    } else if next_symbol == "SAMPLE" {
+        sample_selector(entity_name, arg, chars)
     } else if !next_symbol.is_empty()
         && (next_symbol.to_uppercase() != "ID" || next_symbol.to_uppercase() != "IDS")
     {
@@ -104,6 +255,95 @@ fn select_body(arg: ToSelect, chars: &mut std::str::Chars) -> Result<Wql, String
     }
 }
 
+/// Reads `[AFTER <cursor>] [LIMIT <n>]` after the entity name, `symbol` is the keyword already read.
+fn page_selector(
+    entity_name: String,
+    arg: ToSelect,
+    symbol: &str,
+    chars: &mut std::str::Chars,
+) -> Result<Wql, String> {
+    let mut symbol = symbol.to_string();
+    let cursor = if symbol == "AFTER" {
+        let cursor = chars
+            .skip_while(|c| c.is_whitespace())
+            .take_while(|c| !c.is_whitespace())
+            .collect::<String>();
+        if cursor.is_empty() {
+            return Err(String::from("Cursor is required after AFTER"));
+        }
+        symbol = chars
+            .skip_while(|c| c.is_whitespace())
+            .take_while(|c| !c.is_whitespace())
+            .collect::<String>();
+        Some(Cursor::from_str(&cursor)?)
+    } else {
+        None
+    };
+
+    let limit = if symbol.to_uppercase() == "LIMIT" {
+        let limit = chars
+            .skip_while(|c| c.is_whitespace())
+            .take_while(|c| !c.is_whitespace())
+            .collect::<String>();
+        match limit.parse::<usize>() {
+            Ok(limit) if limit > 0 => Some(limit),
+            _ => return Err(String::from("LIMIT requires a positive integer")),
+        }
+    } else if symbol.is_empty() {
+        None
+    } else {
+        let trailing = format!("{} {}", symbol, chars.as_str());
+        return Err(format!("Unexpected trailing input: `{}`", trailing.trim()));
+    };
+
+    Ok(Wql::SelectPage(entity_name, arg, cursor, limit))
+}
+
+/// Largest `n` of `SAMPLE <n>`.
+const MAX_SAMPLE: usize = 1_000_000;
+
+/// Reads `<n> [SEED <seed>]` after `SAMPLE`.
+fn sample_selector(
+    entity_name: String,
+    arg: ToSelect,
+    chars: &mut std::str::Chars,
+) -> Result<Wql, String> {
+    let mut next_word = || {
+        chars
+            .skip_while(|c| c.is_whitespace())
+            .take_while(|c| !c.is_whitespace())
+            .collect::<String>()
+    };
+    let size = match next_word().parse::<usize>() {
+        Ok(size) if size > MAX_SAMPLE => {
+            return Err(format!("SAMPLE cannot be larger than {}", MAX_SAMPLE))
+        }
+        Ok(size) if size > 0 => size,
+        _ => return Err(String::from("SAMPLE requires a positive integer")),
+    };
+
+    let symbol = next_word();
+    let seed = if symbol.to_uppercase() == "SEED" {
+        match next_word().parse::<u64>() {
+            Ok(seed) => Some(seed),
+            _ => return Err(String::from("SEED requires an unsigned integer")),
+        }
+    } else if symbol.is_empty() {
+        None
+    } else {
+        let trailing = format!("{} {}", symbol, chars.as_str());
+        return Err(format!("Unexpected trailing input: `{}`", trailing.trim()));
+    };
+    if seed.is_some() {
+        let trailing = chars.as_str().trim();
+        if !trailing.is_empty() {
+            return Err(format!("Unexpected trailing input: `{}`", trailing));
+        }
+    }
+
+    Ok(Wql::SelectSample(entity_name, arg, size, seed))
+}
+
 fn when_selector(
     entity_name: String,
     arg: ToSelect,
@@ -176,8 +416,10 @@ fn when_time_range(
 mod test {
     use uuid::Uuid;
 
-    use crate::{ToSelect, Wql};
-    use std::str::FromStr;
+    use crate::{
+        project_computed, Clause, Cursor, Expr, Function, Operator, ToSelect, Types, Value, Wql,
+    };
+    use std::{collections::HashMap, str::FromStr};
 
     #[test]
     fn select_all() {
@@ -209,6 +451,94 @@ mod test {
         );
     }
 
+    #[test]
+    fn select_page() {
+        let id = Uuid::from_str("2df2b8cf-49da-474d-8a00-c596c0bb6fd1").unwrap();
+
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity LIMIT 2").unwrap(),
+            Wql::SelectPage("my_entity".to_string(), ToSelect::All, None, Some(2))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT #{a,} FROM my_entity after LfK4z0naR02KAMWWwLtv0Q limit 2")
+                .unwrap(),
+            Wql::SelectPage(
+                "my_entity".to_string(),
+                ToSelect::Keys(vec!["a".to_string()]),
+                Some(Cursor::new(id)),
+                Some(2)
+            )
+        );
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity AFTER LfK4z0naR02KAMWWwLtv0Q").unwrap(),
+            Wql::SelectPage(
+                "my_entity".to_string(),
+                ToSelect::All,
+                Some(Cursor::new(id)),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn select_sample() {
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity SAMPLE 100").unwrap(),
+            Wql::SelectSample("my_entity".to_string(), ToSelect::All, 100, None)
+        );
+        assert_eq!(
+            Wql::from_str("SELECT #{a,} FROM my_entity sample 10 seed 42").unwrap(),
+            Wql::SelectSample(
+                "my_entity".to_string(),
+                ToSelect::Keys(vec!["a".to_string()]),
+                10,
+                Some(42)
+            )
+        );
+    }
+
+    #[test]
+    fn select_sample_errors() {
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity SAMPLE 0").err(),
+            Some(String::from("SAMPLE requires a positive integer"))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity SAMPLE 18446744073709551615").err(),
+            Some(String::from("SAMPLE cannot be larger than 1000000"))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity SAMPLE 10 SEED -1").err(),
+            Some(String::from("SEED requires an unsigned integer"))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity SAMPLE 10 SEED 42 LIMIT 2").err(),
+            Some(String::from("Unexpected trailing input: `LIMIT 2`"))
+        );
+    }
+
+    #[test]
+    fn select_page_errors() {
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity AFTER not-a-cursor LIMIT 2").err(),
+            Some(String::from(
+                "Cursor `not-a-cursor` is not a valid pagination cursor"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity AFTER").err(),
+            Some(String::from("Cursor is required after AFTER"))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity LIMIT 0").err(),
+            Some(String::from("LIMIT requires a positive integer"))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT * FROM my_entity AFTER LfK4z0naR02KAMWWwLtv0Q WHERE {}").err(),
+            Some(String::from("Unexpected trailing input: `WHERE {}`"))
+        );
+    }
+
     #[test]
     fn select_arg() {
         let wql = Wql::from_str("SelEct #{hello,} FROM my_entity");
@@ -241,6 +571,119 @@ mod test {
         );
     }
 
+    #[test]
+    fn select_computed_field() {
+        let wql = Wql::from_str("SELECT #{a, (b + c) AS total,} FROM my_entity");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Select(
+                "my_entity".to_string(),
+                ToSelect::Computed(
+                    vec!["a".to_string()],
+                    vec![(
+                        "total".to_string(),
+                        Expr::Operation(
+                            Operator::Add,
+                            Box::new(Expr::Field("b".to_string())),
+                            Box::new(Expr::Field("c".to_string()))
+                        )
+                    )]
+                ),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn select_computed_field_evaluated_per_row() {
+        let wql = Wql::from_str("SELECT #{a, ((b + c) * 2) AS total,} FROM my_entity").unwrap();
+        let (keys, computed) = match wql {
+            Wql::Select(_, ToSelect::Computed(keys, computed), None) => (keys, computed),
+            _ => panic!("expected computed SELECT"),
+        };
+        let mut entity = HashMap::new();
+        entity.insert("a".to_string(), Types::Integer(1));
+        entity.insert("b".to_string(), Types::Integer(2));
+        entity.insert("c".to_string(), Types::Float(0.5));
+        entity.insert("d".to_string(), Types::Boolean(true));
+
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), Types::Integer(1));
+        expected.insert("total".to_string(), Types::Float(5.0));
+        assert_eq!(project_computed(&entity, &keys, &computed), expected);
+
+        entity.insert("c".to_string(), Types::String("0.5".to_string()));
+        expected.insert("total".to_string(), Types::Nil);
+        assert_eq!(project_computed(&entity, &keys, &computed), expected);
+    }
+
+    #[test]
+    fn select_case_field_evaluated_per_row() {
+        let wql = Wql::from_str(
+            "SELECT #{name, CASE WHEN age < 18 THEN \"minor\" ELSE \"adult\" END AS group,} FROM my_entity",
+        )
+        .unwrap();
+        let (keys, computed) = match wql {
+            Wql::Select(_, ToSelect::Computed(keys, computed), None) => (keys, computed),
+            _ => panic!("expected computed SELECT"),
+        };
+        assert_eq!(keys, vec!["name".to_string()]);
+        assert_eq!(
+            computed,
+            vec![(
+                "group".to_string(),
+                Expr::Case(
+                    vec![(
+                        Clause::SimpleComparisonFunction(
+                            Function::L,
+                            "age".to_string(),
+                            Types::Integer(18)
+                        ),
+                        Expr::Value(Types::String("minor".to_string()))
+                    )],
+                    Some(Box::new(Expr::Value(Types::String("adult".to_string()))))
+                )
+            )]
+        );
+
+        let mut entity = HashMap::new();
+        entity.insert("name".to_string(), Types::String("julia".to_string()));
+        entity.insert("age".to_string(), Types::Integer(12));
+        let group = |entity: &HashMap<String, Types>| {
+            project_computed(entity, &keys, &computed)["group"].to_owned()
+        };
+        assert_eq!(group(&entity), Types::String("minor".to_string()));
+
+        entity.insert("age".to_string(), Types::Integer(32));
+        assert_eq!(group(&entity), Types::String("adult".to_string()));
+    }
+
+    #[test]
+    fn select_case_field_requires_end() {
+        let wql =
+            Wql::from_str("SELECT #{CASE WHEN age < 18 THEN \"minor\" AS group,} FROM my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "CASE expression `CASE WHEN age < 18 THEN \"minor\" AS group` requires END"
+            ))
+        );
+    }
+
+    #[test]
+    fn select_computed_field_requires_alias() {
+        let wql = Wql::from_str("SELECT #{(b + c),} FROM my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "Computed field `(b + c)` requires `AS field_name`"
+            ))
+        );
+    }
+
     #[test]
     fn select_all_id() {
         let wql = Wql::from_str("SelEct * FROM my_entity ID 2df2b8cf-49da-474d-8a00-c596c0bb6fd1");
@@ -311,6 +754,215 @@ mod test {
         );
     }
 
+    #[test]
+    fn select_count() {
+        let wql = Wql::from_str("SelEct COUNT FROM my_entity");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectCount("my_entity".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn select_count_where() {
+        let wql = Wql::from_str(
+            "SELECT count FROM my_entity WHERE {
+            ?* my_entity:a ?a,
+            (>= ?a 3),
+        }",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectCount(
+                "my_entity".to_string(),
+                vec![
+                    Clause::ValueAttribution(
+                        "my_entity".to_string(),
+                        "a".to_string(),
+                        Value("?a".to_string())
+                    ),
+                    Clause::SimpleComparisonFunction(
+                        Function::GEq,
+                        "?a".to_string(),
+                        Types::Integer(3)
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn select_history() {
+        let wql = Wql::from_str(
+            "SELECT HISTORY FROM my_entity WHERE id = 2df2b8cf-49da-474d-8a00-c596c0bb6fd1",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectHistory(
+                "my_entity".to_string(),
+                Uuid::from_str("2df2b8cf-49da-474d-8a00-c596c0bb6fd1").unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn select_history_requires_uuid() {
+        let wql = Wql::from_str("SELECT HISTORY FROM my_entity WHERE id = ");
+
+        assert_eq!(wql.err(), Some(String::from("Field ID must be a UUID v4")));
+    }
+
+    #[test]
+    fn select_history_requires_where_id() {
+        let wql = Wql::from_str("SELECT HISTORY FROM my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "SELECT HISTORY requires `WHERE id = <uuid>` after entity name"
+            ))
+        );
+    }
+
+    #[test]
+    fn select_latest() {
+        let wql = Wql::from_str(
+            "SELECT LATEST FROM my_entity WHERE id = 2df2b8cf-49da-474d-8a00-c596c0bb6fd1",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectLatest(
+                "my_entity".to_string(),
+                Uuid::from_str("2df2b8cf-49da-474d-8a00-c596c0bb6fd1").unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn select_latest_requires_uuid() {
+        let wql = Wql::from_str("SELECT LATEST FROM my_entity WHERE id = my_id");
+
+        assert_eq!(wql.err(), Some(String::from("Field ID must be a UUID v4")));
+    }
+
+    #[test]
+    fn select_latest_requires_where_id() {
+        let wql = Wql::from_str("SELECT LATEST FROM my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "SELECT LATEST requires `WHERE id = <uuid>` after entity name"
+            ))
+        );
+    }
+
+    #[test]
+    fn select_sum() {
+        let wql = Wql::from_str("SELECT SUM(amount) FROM my_entity");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectSum("my_entity".to_string(), "amount".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn select_sum_where() {
+        let wql = Wql::from_str(
+            "SELECT sum(amount) FROM my_entity WHERE {
+            ?* my_entity:amount ?a,
+            (> ?a 3),
+        }",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectSum(
+                "my_entity".to_string(),
+                "amount".to_string(),
+                vec![
+                    Clause::ValueAttribution(
+                        "my_entity".to_string(),
+                        "amount".to_string(),
+                        Value("?a".to_string())
+                    ),
+                    Clause::SimpleComparisonFunction(
+                        Function::G,
+                        "?a".to_string(),
+                        Types::Integer(3)
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn select_sum_without_field() {
+        let wql = Wql::from_str("SELECT SUM() FROM my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "SUM requires a single field name as `SUM(field)`"
+            ))
+        );
+    }
+
+    #[test]
+    fn select_avg_where() {
+        let wql = Wql::from_str(
+            "SELECT AVG(amount) FROM my_entity WHERE {
+            ?* my_entity:amount ?a,
+            (> ?a 3),
+        }",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectAvg(
+                "my_entity".to_string(),
+                "amount".to_string(),
+                vec![
+                    Clause::ValueAttribution(
+                        "my_entity".to_string(),
+                        "amount".to_string(),
+                        Value("?a".to_string())
+                    ),
+                    Clause::SimpleComparisonFunction(
+                        Function::G,
+                        "?a".to_string(),
+                        Types::Integer(3)
+                    ),
+                ]
+            )
+        );
+        assert_eq!(
+            Wql::from_str("SELECT AVG(a b) FROM my_entity").err(),
+            Some(String::from("SELECT function `AVG(a` not implemented"))
+        );
+        assert_eq!(
+            Wql::from_str("SELECT AVG() FROM my_entity").err(),
+            Some(String::from(
+                "AVG requires a single field name as `AVG(field)`"
+            ))
+        );
+    }
+
+    #[test]
+    fn select_count_unknown_function() {
+        let wql = Wql::from_str("SELECT COUNTS FROM my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("SELECT function `COUNTS` not implemented"))
+        );
+    }
+
     #[test]
     fn when_at() {
         let wql = Wql::from_str("SelEct * FROM my_entity ID 2df2b8cf-49da-474d-8a00-c596c0bb6fd1 WHEN AT 2020-01-01T00:00:00Z");
@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Entity, Types, Wql};
+
+pub(crate) fn read_self_ref(field: &str) -> Result<Types, String> {
+    if !field.is_empty() && field.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Ok(Types::SelfRef(field.to_string()))
+    } else {
+        Err(format!(
+            "Self reference `$self.{}` must be formatted as `$self.field`",
+            field
+        ))
+    }
+}
+
+/// Replaces every `$self.field` of an entity map by the value of its sibling `field`, a field referencing
+/// fields with references is resolved after them. Fails with references to missing fields and cycles.
+pub(crate) fn resolve_self_refs(mut entity: Entity) -> Result<Entity, String> {
+    let references = entity
+        .iter()
+        .map(|(field, value)| (field.to_owned(), self_refs(value)))
+        .filter(|(_, refs)| !refs.is_empty())
+        .collect::<HashMap<String, Vec<String>>>();
+    if references.is_empty() {
+        return Ok(entity);
+    }
+
+    let mut resolved = HashSet::new();
+    let mut fields = references.keys().collect::<Vec<&String>>();
+    fields.sort();
+    for field in fields {
+        let mut path = Vec::new();
+        resolve(field, &mut entity, &references, &mut resolved, &mut path)?;
+    }
+    Ok(entity)
+}
+
+fn resolve(
+    field: &str,
+    entity: &mut Entity,
+    references: &HashMap<String, Vec<String>>,
+    resolved: &mut HashSet<String>,
+    path: &mut Vec<String>,
+) -> Result<(), String> {
+    if resolved.contains(field) {
+        return Ok(());
+    }
+    if let Some(start) = path.iter().position(|f| f == field) {
+        let cycle = path[start..]
+            .iter()
+            .chain(std::iter::once(&field.to_string()))
+            .map(|f| format!("`{}`", f))
+            .collect::<Vec<String>>();
+        return Err(format!(
+            "Self references form a cycle: {}",
+            cycle.join(" -> ")
+        ));
+    }
+
+    path.push(field.to_string());
+    for reference in references.get(field).into_iter().flatten() {
+        if !entity.contains_key(reference) {
+            return Err(format!(
+                "Self reference `$self.{}` has no field `{}` in the entity map",
+                reference, reference
+            ));
+        }
+        resolve(reference, entity, references, resolved, path)?;
+    }
+    path.pop();
+
+    let siblings = references
+        .get(field)
+        .into_iter()
+        .flatten()
+        .map(|reference| (reference.to_owned(), entity[reference].to_owned()))
+        .collect::<HashMap<String, Types>>();
+    if let Some(value) = entity.get_mut(field) {
+        replace_self_refs(value, &siblings);
+    }
+    resolved.insert(field.to_string());
+    Ok(())
+}
+
+/// Fields referenced by `value`, visited without recursion as values can be nested up to `max_depth`.
+fn self_refs(value: &Types) -> Vec<String> {
+    let mut refs = Vec::new();
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Types::SelfRef(field) => refs.push(field.to_owned()),
+            Types::Vector(vec) => stack.extend(vec),
+            Types::Map(map) => stack.extend(map.values()),
+            Types::Pairs(pairs) => stack.extend(pairs.iter().map(|(_, v)| v.as_ref())),
+            _ => (),
+        }
+    }
+    refs
+}
+
+fn replace_self_refs(value: &mut Types, siblings: &HashMap<String, Types>) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Types::SelfRef(field) => *value = siblings[field.as_str()].to_owned(),
+            Types::Vector(vec) => stack.extend(vec),
+            Types::Map(map) => stack.extend(map.values_mut()),
+            Types::Pairs(pairs) => stack.extend(pairs.iter_mut().map(|(_, v)| v.as_mut())),
+            _ => (),
+        }
+    }
+}
+
+/// A self reference left in `wql` was written outside of an entity map.
+pub(crate) fn unresolved(wql: &Wql) -> Result<(), String> {
+    match wql.values().into_iter().flat_map(self_refs).next() {
+        Some(field) => Err(format!(
+            "Self reference `$self.{}` is only allowed in an entity map",
+            field
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn inserted(query: &str) -> Result<Entity, String> {
+        match Wql::from_str(query)? {
+            Wql::Insert(_, entity, _, _) => Ok(entity),
+            wql => Err(format!("{:?} is not an INSERT", wql)),
+        }
+    }
+
+    #[test]
+    fn sibling_references_are_resolved() {
+        let entity =
+            inserted("INSERT {a: 5, c: [$self.b, {d: $self.a,},], b: $self.a,} INTO my_entity")
+                .unwrap();
+        let mut nested = HashMap::new();
+        nested.insert("d".to_string(), Types::Integer(5));
+
+        assert_eq!(entity["b"], Types::Integer(5));
+        assert_eq!(
+            entity["c"],
+            Types::Vector(vec![Types::Integer(5), Types::Map(nested)])
+        );
+    }
+
+    #[test]
+    fn cyclic_references_are_rejected() {
+        assert_eq!(
+            inserted("INSERT {a: $self.b, b: [$self.a,], c: 1,} INTO my_entity"),
+            Err(String::from(
+                "Self references form a cycle: `a` -> `b` -> `a`"
+            ))
+        );
+        assert_eq!(
+            inserted("INSERT {a: $self.a,} INTO my_entity"),
+            Err(String::from("Self references form a cycle: `a` -> `a`"))
+        );
+    }
+
+    #[test]
+    fn invalid_references_are_rejected() {
+        assert_eq!(
+            inserted("INSERT {a: 5, b: $self.c,} INTO my_entity"),
+            Err(String::from(
+                "Self reference `$self.c` has no field `c` in the entity map"
+            ))
+        );
+        assert_eq!(
+            inserted("INSERT {a: $self.,} INTO my_entity"),
+            Err(String::from(
+                "Self reference `$self.` must be formatted as `$self.field`"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("LET limit = $self.a"),
+            Err(String::from(
+                "Self reference `$self.a` is only allowed in an entity map"
+            ))
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use serde::{Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    fmt::{self, Write},
+    hash::{Hash, Hasher},
+};
+
+use crate::{Entity, Types, Wql};
+
+/// Serializes a map with its keys sorted, so the same entity always has the same textual form.
+pub(crate) fn sorted_map<S: Serializer>(
+    map: &HashMap<String, Types>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter()
+        .collect::<BTreeMap<&String, &Types>>()
+        .serialize(serializer)
+}
+
+/// Textual form of an entity, a RON map with sorted keys that `entity_from_str` reads back.
+pub fn entity_to_string(entity: &Entity) -> Result<String, String> {
+    ron::ser::to_string(&entity.iter().collect::<BTreeMap<&String, &Types>>())
+        .map_err(|e| format!("Couldn't serialize entity. Error: {:?}", e))
+}
+
+/// Reads an entity written by `entity_to_string`.
+pub fn entity_from_str(s: &str) -> Result<Entity, String> {
+    ron::de::from_str(s).map_err(|e| format!("Couldn't deserialize entity. Error: {:?}", e))
+}
+
+/// `Entity` compared by its `entity_to_string` form, it can be stored in a `HashSet` to dedup entities
+/// and two entities with the same content are equal whatever the order of their keys.
+#[derive(Debug, Clone)]
+pub struct CanonicalEntity {
+    entity: Entity,
+    canonical: String,
+}
+
+impl CanonicalEntity {
+    pub fn new(entity: Entity) -> Self {
+        let canonical = entity_to_string(&entity).unwrap_or_default();
+        Self { entity, canonical }
+    }
+
+    pub fn into_inner(self) -> Entity {
+        self.entity
+    }
+}
+
+impl From<Entity> for CanonicalEntity {
+    fn from(entity: Entity) -> Self {
+        Self::new(entity)
+    }
+}
+
+impl std::ops::Deref for CanonicalEntity {
+    type Target = Entity;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entity
+    }
+}
+
+impl PartialEq for CanonicalEntity {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical == other.canonical
+    }
+}
+
+impl Eq for CanonicalEntity {}
+
+impl Hash for CanonicalEntity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical.hash(state);
+    }
+}
+
+/// Bytes of the big-endian `u32` length before the statement of a wire command.
+const WIRE_PREFIX: usize = 4;
+
+impl Wql {
+    /// Length-prefixed binary command to send a parsed statement to a server, that reads it back with
+    /// `Wql::from_wire` instead of parsing the WQL text again. The statement is encoded as CBOR after a
+    /// big-endian `u32` with its length in bytes, statements longer than `u32::MAX` bytes fail.
+    pub fn to_wire(&self) -> Result<Vec<u8>, String> {
+        let statement = serde_cbor::to_vec(self)
+            .map_err(|e| format!("Couldn't write wire command. Error: {:?}", e))?;
+        let len = u32::try_from(statement.len()).map_err(|_| {
+            format!(
+                "Wire command is too long, {} bytes do not fit a u32 length prefix",
+                statement.len()
+            )
+        })?;
+        let mut wire = Vec::with_capacity(WIRE_PREFIX + statement.len());
+        wire.extend_from_slice(&len.to_be_bytes());
+        wire.extend(statement);
+        Ok(wire)
+    }
+
+    pub fn from_wire(b: &[u8]) -> Result<Wql, String> {
+        if b.len() < WIRE_PREFIX {
+            return Err(format!(
+                "Wire command is truncated, expected a {} bytes length prefix and found {} bytes",
+                WIRE_PREFIX,
+                b.len()
+            ));
+        }
+        let (prefix, statement) = b.split_at(WIRE_PREFIX);
+        let len = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        if statement.len() < len {
+            return Err(format!(
+                "Wire command is truncated, expected a {} bytes statement and found {} bytes",
+                len,
+                statement.len()
+            ));
+        }
+        if statement.len() > len {
+            return Err(format!(
+                "Wire command has {} trailing bytes after its {} bytes statement",
+                statement.len() - len,
+                len
+            ));
+        }
+
+        serde_cbor::from_slice(statement)
+            .map_err(|e| format!("Couldn't read wire command. Error: {:?}", e))
+    }
+
+    /// Bytes of the canonical WQL of the statement, its `Display` form, counted without allocating it,
+    /// so servers can enforce statement size limits after parsing.
+    pub fn canonical_len(&self) -> usize {
+        let mut counter = ByteCounter(0);
+        write!(counter, "{}", self).map_or(0, |_| counter.0)
+    }
+}
+
+/// `fmt::Write` that only counts the bytes written to it.
+struct ByteCounter(usize);
+
+impl fmt::Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn create_round_trip() {
+        let wql = Wql::from_str("CREATE ENTITY my_entity UNIQUES #{name, ssn,} ENCRYPT #{pswd,}")
+            .unwrap();
+
+        assert_eq!(Wql::from_wire(&wql.to_wire().unwrap()), Ok(wql));
+    }
+
+    #[test]
+    fn insert_round_trip() {
+        let wql = Wql::from_str(
+            "INSERT {a: 123, b: 12.3, c: 'd', d: true, e: \"hello\", f: Nil, g: [1, {h: 2,},], id: 2df2b8cf-49da-474d-8a00-c596c0bb6fd1,} INTO my_entity",
+        )
+        .unwrap();
+        let wire = wql.to_wire().unwrap();
+
+        assert!(wire.len() < ron::ser::to_string(&wql).unwrap().len());
+        assert_eq!(
+            u32::from_be_bytes([wire[0], wire[1], wire[2], wire[3]]) as usize,
+            wire.len() - WIRE_PREFIX
+        );
+        assert_eq!(Wql::from_wire(&wire), Ok(wql));
+    }
+
+    #[test]
+    fn canonical_len_matches_display_len() {
+        [
+            "CREATE ENTITY my_entity UNIQUES #{name, ssn,} ENCRYPT #{pswd,}",
+            "INSERT {a: 123, b: \"héllo\", c: [1, {d: 'ç',},],} INTO my_entity",
+            "SELECT #{a, b,} FROM my_entity WHERE { ?* my_entity:a ?a, (> ?a 3), }",
+            "DELETE 2df2b8cf-49da-474d-8a00-c596c0bb6fd1 FROM my_entity",
+        ]
+        .iter()
+        .map(|query| Wql::from_str(query).unwrap())
+        .for_each(|wql| {
+            assert_eq!(wql.canonical_len(), wql.to_string().len());
+        });
+    }
+
+    #[test]
+    fn truncated_wire() {
+        let wire = Wql::from_str("CREATE ENTITY my_entity")
+            .unwrap()
+            .to_wire()
+            .unwrap();
+
+        assert_eq!(
+            Wql::from_wire(&wire[..2]),
+            Err(String::from(
+                "Wire command is truncated, expected a 4 bytes length prefix and found 2 bytes"
+            ))
+        );
+        assert_eq!(
+            Wql::from_wire(&wire[..wire.len() - 1]),
+            Err(format!(
+                "Wire command is truncated, expected a {} bytes statement and found {} bytes",
+                wire.len() - WIRE_PREFIX,
+                wire.len() - WIRE_PREFIX - 1
+            ))
+        );
+        assert_eq!(
+            Wql::from_wire(&[&wire[..], &[0, 0]].concat()),
+            Err(format!(
+                "Wire command has 2 trailing bytes after its {} bytes statement",
+                wire.len() - WIRE_PREFIX
+            ))
+        );
+    }
+
+    #[test]
+    fn canonical_entities_dedup_in_hash_set() {
+        let entity = |query: &str| match Wql::from_str(query).unwrap() {
+            Wql::Insert(_, entity, _, _) => CanonicalEntity::new(entity),
+            _ => unreachable!(),
+        };
+        let entities = [
+            entity("INSERT {a: 1, b: {c: \"x\", d: [1, 2,],}, e: Nil,} INTO my_entity"),
+            entity("INSERT {e: Nil, b: {d: [1, 2,], c: \"x\",}, a: 1,} INTO my_entity"),
+            entity("INSERT {a: 1, b: {c: \"x\", d: [2, 1,],}, e: Nil,} INTO my_entity"),
+            entity("INSERT {a: 1.0, b: {c: \"x\", d: [1, 2,],}, e: Nil,} INTO my_entity"),
+        ];
+        let deduped = entities
+            .iter()
+            .cloned()
+            .collect::<std::collections::HashSet<CanonicalEntity>>();
+
+        assert_eq!(entities[0], entities[1]);
+        assert_eq!(deduped.len(), 3);
+        assert!(deduped.contains(&entities[1]));
+        assert_eq!(entities[0].get("a"), Some(&Types::Integer(1)));
+        assert_eq!(entities[1].clone().into_inner().len(), 3);
+    }
+}
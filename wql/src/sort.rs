@@ -0,0 +1,174 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::Types;
+
+/// Totally ordered key of a `Types`, sorting a column of mixed types by `Types::sort_key` orders it as
+/// `Nil < Boolean < numbers < DateTime < Duration < Range < Char < String < Hash < Uuid < Ref < Vector < Map < Pairs < Param < SelfRef`.
+/// `Integer`, `Float` and `Precise` are compared by numeric value, an equal `Float` sorts before the
+/// `Integer` and `NaN` sorts after every other number. `Vector`s, `Map`s, by sorted keys, and `Pairs`, in
+/// order, are compared element by element.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey(Key);
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Nil,
+    Boolean(bool),
+    Number(Number),
+    DateTime(DateTime<Utc>),
+    Duration(i64),
+    Range(Box<Key>, Box<Key>, bool),
+    Char(char),
+    String(String),
+    Hash(String),
+    Uuid(Uuid),
+    Ref(String, Uuid),
+    Vector(Vec<Key>),
+    Map(Vec<(String, Key)>),
+    Pairs(Vec<(String, Key)>),
+    Param(String),
+    SelfRef(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Number {
+    value: f64,
+    exact: Option<isize>,
+}
+
+impl Number {
+    fn new(value: f64, exact: Option<isize>) -> Self {
+        // `-0.0` and `0.0` are the same number
+        let value = if value == 0f64 { 0f64 } else { value };
+        Self { value, exact }
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.value, other.value);
+        match (a.is_nan(), b.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => a.total_cmp(&b).then_with(|| self.exact.cmp(&other.exact)),
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl Types {
+    /// Key to sort values of possibly different types consistently, see `SortKey` for the order.
+    pub fn sort_key(&self) -> SortKey {
+        SortKey(self.to_key())
+    }
+
+    fn to_key(&self) -> Key {
+        match self {
+            Types::Nil => Key::Nil,
+            Types::Boolean(b) => Key::Boolean(*b),
+            Types::Integer(i) => Key::Number(Number::new(*i as f64, Some(*i))),
+            Types::Float(f) => Key::Number(Number::new(*f, None)),
+            Types::Precise(p) => match p.parse::<f64>() {
+                Ok(f) => Key::Number(Number::new(f, None)),
+                Err(_) => Key::String(p.to_owned()),
+            },
+            Types::DateTime(date) => Key::DateTime(*date),
+            Types::Duration(ms) => Key::Duration(*ms),
+            Types::Range(start, end, inclusive) => {
+                Key::Range(Box::new(start.to_key()), Box::new(end.to_key()), *inclusive)
+            }
+            Types::Char(c) => Key::Char(*c),
+            Types::String(s) => Key::String(s.to_owned()),
+            Types::Hash(h) => Key::Hash(h.to_owned()),
+            Types::Uuid(id) => Key::Uuid(*id),
+            Types::Ref(entity, id) => Key::Ref(entity.to_owned(), *id),
+            Types::Vector(vec) => Key::Vector(vec.iter().map(Types::to_key).collect()),
+            Types::Map(map) => {
+                let mut entries = map
+                    .iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_key()))
+                    .collect::<Vec<(String, Key)>>();
+                entries.sort();
+                Key::Map(entries)
+            }
+            Types::Pairs(pairs) => Key::Pairs(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_key()))
+                    .collect(),
+            ),
+            Types::Param(param) => Key::Param(param.key()),
+            Types::SelfRef(field) => Key::SelfRef(field.to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn mixed_column_sorts_in_documented_order() {
+        let id = Uuid::nil();
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), Types::Integer(1));
+        let mut column = vec![
+            Types::Map(map),
+            Types::Vector(vec![Types::Integer(1)]),
+            Types::Ref("e".to_string(), id),
+            Types::Uuid(id),
+            Types::Hash("$2b$".to_string()),
+            Types::String("b".to_string()),
+            Types::String("a".to_string()),
+            Types::Char('z'),
+            Types::Float(f64::NAN),
+            Types::Integer(10),
+            Types::Precise("2.5".to_string()),
+            Types::Integer(2),
+            Types::Float(2.0),
+            Types::Float(-1.5),
+            Types::Boolean(true),
+            Types::Boolean(false),
+            Types::Nil,
+        ];
+        let expected = column.iter().rev().cloned().collect::<Vec<Types>>();
+
+        column.sort_by_key(Types::sort_key);
+        assert_eq!(format!("{:?}", column), format!("{:?}", expected));
+    }
+
+    #[test]
+    fn numbers_are_unified() {
+        assert!(Types::Integer(2).sort_key() < Types::Float(2.5).sort_key());
+        assert!(Types::Float(2.5).sort_key() < Types::Integer(3).sort_key());
+        assert!(Types::Float(-0.0).sort_key() == Types::Float(0.0).sort_key());
+        assert!(Types::Integer(isize::MAX - 1).sort_key() < Types::Integer(isize::MAX).sort_key());
+    }
+
+    #[test]
+    fn vectors_compare_element_by_element() {
+        let vec = |v: &[isize]| Types::Vector(v.iter().copied().map(Types::Integer).collect());
+
+        assert!(vec(&[1, 2]).sort_key() < vec(&[1, 3]).sort_key());
+        assert!(vec(&[1]).sort_key() < vec(&[1, 0]).sort_key());
+        assert!(vec(&[2]).sort_key() > vec(&[1, 9]).sort_key());
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+
+use crate::{node_id::statement_name, Clause, Function, MatchCondition, Types, Value, Wql};
+
+impl Wql {
+    /// Name of the entity tree the statement is executed on, `LET` is not executed on an entity tree.
+    pub fn entity_name(&self) -> Option<&str> {
+        Some(match self {
+            Wql::CreateEntity(entity, _, _, _)
+            | Wql::Insert(entity, _, _, _)
+            | Wql::InsertMany(entity, _)
+            | Wql::UpdateContent(entity, _, _)
+            | Wql::UpdateSet(entity, _, _)
+            | Wql::Delete(entity, _)
+            | Wql::DeleteMany(entity, _)
+            | Wql::MatchUpdate(entity, _, _, _)
+            | Wql::Evict(entity, _)
+            | Wql::Truncate(entity)
+            | Wql::Select(entity, _, _)
+            | Wql::SelectWhen(entity, _, _, _)
+            | Wql::SelectWhenRange(entity, _, _, _)
+            | Wql::SelectIds(entity, _, _)
+            | Wql::SelectPage(entity, _, _, _)
+            | Wql::SelectSample(entity, _, _, _)
+            | Wql::SelectWhere(entity, _, _)
+            | Wql::SelectCount(entity, _)
+            | Wql::SelectSum(entity, _, _)
+            | Wql::SelectAvg(entity, _, _)
+            | Wql::SelectHistory(entity, _)
+            | Wql::SelectLatest(entity, _)
+            | Wql::CheckValue(entity, _, _)
+            | Wql::Comment(entity, _, _)
+            | Wql::Use(entity)
+            | Wql::RenameField(entity, _, _)
+            | Wql::DropField(entity, _)
+            | Wql::CreateSchema(entity, _, _)
+            | Wql::CreateDocumentStore(entity) => entity,
+            Wql::NestedInsert(insert, _) => return insert.entity_name(),
+            Wql::Let(_, _) | Wql::Begin(_) => return None,
+        })
+    }
+
+    /// `InsertMany` split into one `Insert` per entity map, in order, for executors that only implement
+    /// single inserts. Any other statement is returned alone.
+    pub fn explode_insert_many(self) -> Vec<Wql> {
+        match self {
+            Wql::InsertMany(entity, entities) => entities
+                .into_iter()
+                .map(|content| Wql::Insert(entity.clone(), content, None, None))
+                .collect(),
+            wql => vec![wql],
+        }
+    }
+
+    /// Every value literal of the statement: entity map values, match conditions and WHERE clauses.
+    /// Nested values of `Vector`s and `Map`s can be visited with `Types::walk`.
+    pub(crate) fn values(&self) -> Vec<&Types> {
+        let mut values = Vec::new();
+        match self {
+            Wql::Insert(_, entity, _, _)
+            | Wql::UpdateContent(_, entity, _)
+            | Wql::UpdateSet(_, entity, _) => values.extend(entity.values()),
+            Wql::NestedInsert(insert, sub_inserts) => {
+                values.extend(insert.values());
+                sub_inserts
+                    .iter()
+                    .for_each(|(_, sub_insert)| values.extend(sub_insert.values()));
+            }
+            Wql::InsertMany(_, entities) => entities
+                .iter()
+                .for_each(|entity| values.extend(entity.values())),
+            Wql::MatchUpdate(_, entity, _, conditions) => {
+                values.extend(entity.values());
+                condition_values(conditions, &mut values);
+            }
+            Wql::SelectWhere(_, _, clauses)
+            | Wql::SelectCount(_, clauses)
+            | Wql::SelectSum(_, _, clauses)
+            | Wql::SelectAvg(_, _, clauses) => clause_values(clauses, &mut values),
+            Wql::Let(_, value) => values.push(value),
+            Wql::CreateSchema(_, _, defaults) => values.extend(defaults.values()),
+            Wql::CreateEntity(_, _, _, _)
+            | Wql::Delete(_, _)
+            | Wql::DeleteMany(_, _)
+            | Wql::Evict(_, _)
+            | Wql::Truncate(_)
+            | Wql::Select(_, _, _)
+            | Wql::SelectWhen(_, _, _, _)
+            | Wql::SelectWhenRange(_, _, _, _)
+            | Wql::SelectIds(_, _, _)
+            | Wql::SelectPage(_, _, _, _)
+            | Wql::SelectSample(_, _, _, _)
+            | Wql::SelectHistory(_, _)
+            | Wql::SelectLatest(_, _)
+            | Wql::CheckValue(_, _, _)
+            | Wql::Comment(_, _, _)
+            | Wql::Use(_)
+            | Wql::RenameField(_, _, _)
+            | Wql::DropField(_, _)
+            | Wql::CreateDocumentStore(_)
+            | Wql::Begin(_) => (),
+        }
+        values
+    }
+
+    /// Mutable version of `Wql::values`.
+    pub(crate) fn values_mut(&mut self) -> Vec<&mut Types> {
+        let mut values = Vec::new();
+        match self {
+            Wql::Insert(_, entity, _, _)
+            | Wql::UpdateContent(_, entity, _)
+            | Wql::UpdateSet(_, entity, _) => values.extend(entity.values_mut()),
+            Wql::NestedInsert(insert, sub_inserts) => {
+                values.extend(insert.values_mut());
+                sub_inserts
+                    .iter_mut()
+                    .for_each(|(_, sub_insert)| values.extend(sub_insert.values_mut()));
+            }
+            Wql::InsertMany(_, entities) => entities
+                .iter_mut()
+                .for_each(|entity| values.extend(entity.values_mut())),
+            Wql::MatchUpdate(_, entity, _, conditions) => {
+                values.extend(entity.values_mut());
+                condition_values_mut(conditions, &mut values);
+            }
+            Wql::SelectWhere(_, _, clauses)
+            | Wql::SelectCount(_, clauses)
+            | Wql::SelectSum(_, _, clauses)
+            | Wql::SelectAvg(_, _, clauses) => clause_values_mut(clauses, &mut values),
+            Wql::Let(_, value) => values.push(value),
+            Wql::CreateSchema(_, _, defaults) => values.extend(defaults.values_mut()),
+            _ => (),
+        }
+        values
+    }
+}
+
+fn condition_values<'a>(condition: &'a MatchCondition, values: &mut Vec<&'a Types>) {
+    match condition {
+        MatchCondition::All(conditions) | MatchCondition::Any(conditions) => conditions
+            .iter()
+            .for_each(|condition| condition_values(condition, values)),
+        MatchCondition::Eq(_, value)
+        | MatchCondition::NotEq(_, value)
+        | MatchCondition::GEq(_, value)
+        | MatchCondition::G(_, value)
+        | MatchCondition::LEq(_, value)
+        | MatchCondition::L(_, value) => values.push(value),
+    }
+}
+
+fn clause_values<'a>(clauses: &'a [Clause], values: &mut Vec<&'a Types>) {
+    clauses.iter().for_each(|clause| match clause {
+        Clause::ContainsKeyValue(_, _, value) | Clause::SimpleComparisonFunction(_, _, value) => {
+            values.push(value)
+        }
+        Clause::ComplexComparisonFunctions(_, _, vec) | Clause::TupleEq(_, vec) => {
+            values.extend(vec)
+        }
+        Clause::Or(_, clauses) => clause_values(clauses, values),
+        Clause::ValueAttribution(_, _, _)
+        | Clause::IsEmpty(_)
+        | Clause::IsNotEmpty(_)
+        | Clause::Missing(_)
+        | Clause::Error => (),
+    })
+}
+
+fn condition_values_mut<'a>(condition: &'a mut MatchCondition, values: &mut Vec<&'a mut Types>) {
+    match condition {
+        MatchCondition::All(conditions) | MatchCondition::Any(conditions) => conditions
+            .iter_mut()
+            .for_each(|condition| condition_values_mut(condition, values)),
+        MatchCondition::Eq(_, value)
+        | MatchCondition::NotEq(_, value)
+        | MatchCondition::GEq(_, value)
+        | MatchCondition::G(_, value)
+        | MatchCondition::LEq(_, value)
+        | MatchCondition::L(_, value) => values.push(value),
+    }
+}
+
+fn clause_values_mut<'a>(clauses: &'a mut [Clause], values: &mut Vec<&'a mut Types>) {
+    clauses.iter_mut().for_each(|clause| match clause {
+        Clause::ContainsKeyValue(_, _, value) | Clause::SimpleComparisonFunction(_, _, value) => {
+            values.push(value)
+        }
+        Clause::ComplexComparisonFunctions(_, _, vec) | Clause::TupleEq(_, vec) => {
+            values.extend(vec.iter_mut())
+        }
+        Clause::Or(_, clauses) => clause_values_mut(clauses, values),
+        Clause::ValueAttribution(_, _, _)
+        | Clause::IsEmpty(_)
+        | Clause::IsNotEmpty(_)
+        | Clause::Missing(_)
+        | Clause::Error => (),
+    })
+}
+
+/// Every entity name a statement touches, its own entity tree first and then the entities
+/// of `Types::Ref` values in the order they appear, without duplicates.
+pub fn referenced_entities(wql: &Wql) -> Vec<String> {
+    let mut entities = wql
+        .entity_name()
+        .map(|entity| vec![entity.to_string()])
+        .unwrap_or_default();
+    wql.values().into_iter().for_each(|value| {
+        value.walk(&mut |_, v| {
+            if let Types::Ref(entity, _) = v {
+                if !entities.contains(entity) {
+                    entities.push(entity.to_owned());
+                }
+            }
+        })
+    });
+    if let Wql::NestedInsert(_, sub_inserts) = wql {
+        sub_inserts.iter().for_each(|(_, sub_insert)| {
+            referenced_entities(sub_insert)
+                .into_iter()
+                .for_each(|entity| {
+                    if !entities.contains(&entity) {
+                        entities.push(entity);
+                    }
+                })
+        });
+    }
+    entities
+}
+
+/// Every `Types::String` literal of a statement, including the ones nested in `Vector`s and `Map`s,
+/// in the order they appear. Used to collect user-facing text, like for translations.
+pub fn collect_strings(wql: &Wql) -> Vec<String> {
+    let mut strings = Vec::new();
+    wql.values().into_iter().for_each(|value| {
+        value.walk(&mut |_, v| {
+            if let Types::String(s) = v {
+                strings.push(s.to_owned());
+            }
+        })
+    });
+    strings
+}
+
+/// Whether `a` and `b` have the same verb on the same entity tree, like two `INSERT`s into `e`,
+/// so a batching layer can merge them into a single request. `LET` has no entity and never batches.
+pub fn can_batch(a: &Wql, b: &Wql) -> bool {
+    statement_name(a) == statement_name(b)
+        && a.entity_name().is_some()
+        && a.entity_name() == b.entity_name()
+}
+
+/// Fields of the WHERE clauses of a query compared by equality, `IN`, a range or `BETWEEN`, in the order
+/// they appear and without duplicates, an index on them would narrow the entities to read.
+/// `?variables` are resolved to the field of their `?* e:field ?variable`.
+pub fn suggest_indexes(wql: &Wql) -> Vec<String> {
+    let clauses = match wql {
+        Wql::SelectWhere(_, _, clauses)
+        | Wql::SelectCount(_, clauses)
+        | Wql::SelectSum(_, _, clauses)
+        | Wql::SelectAvg(_, _, clauses) => clauses,
+        _ => return Vec::new(),
+    };
+    let args_to_key = clauses
+        .iter()
+        .filter_map(|clause| {
+            if let Clause::ValueAttribution(_, key, Value(arg)) = clause {
+                Some((arg.as_str(), key.as_str()))
+            } else {
+                None
+            }
+        })
+        .collect::<HashMap<&str, &str>>();
+
+    let mut fields = Vec::new();
+    indexable_fields(clauses, &args_to_key, &mut fields);
+    fields.into_iter().fold(Vec::new(), |mut unique, field| {
+        if !unique.iter().any(|f| f == field) {
+            unique.push(field.to_string());
+        }
+        unique
+    })
+}
+
+fn indexable_fields<'a>(
+    clauses: &'a [Clause],
+    args_to_key: &HashMap<&'a str, &'a str>,
+    fields: &mut Vec<&'a str>,
+) {
+    let key = |key: &'a str| *args_to_key.get(key).unwrap_or(&key);
+    clauses.iter().for_each(|clause| match clause {
+        Clause::ContainsKeyValue(_, field, _) => fields.push(field),
+        Clause::SimpleComparisonFunction(
+            Function::Eq | Function::G | Function::GEq | Function::L | Function::LEq,
+            field,
+            _,
+        )
+        | Clause::ComplexComparisonFunctions(Function::In | Function::Between, field, _) => {
+            fields.push(key(field))
+        }
+        Clause::TupleEq(keys, _) => fields.extend(keys.iter().map(|field| key(field))),
+        // Negated clauses cannot narrow an index, `push_not` moves the negation to the comparisons
+        Clause::Or(Function::Not, _) => (),
+        Clause::Or(_, clauses) => indexable_fields(clauses, args_to_key, fields),
+        _ => (),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn select_references_its_entity() {
+        let wql = Wql::from_str("SELECT * FROM my_entity").unwrap();
+
+        assert_eq!(referenced_entities(&wql), vec!["my_entity".to_string()]);
+    }
+
+    #[test]
+    fn insert_references_ref_entities() {
+        let wql = Wql::from_str(
+            "INSERT {
+                author: @authors/2df2b8cf-49da-474d-8a00-c596c0bb6fd1,
+                reviewers: [@authors/53315090-e14d-4738-a4d2-f1ec2a93664c, @editors/53315090-e14d-4738-a4d2-f1ec2a93664c,],
+            } INTO posts",
+        )
+        .unwrap();
+        let mut entities = referenced_entities(&wql);
+        entities[1..].sort();
+
+        assert_eq!(
+            entities,
+            vec![
+                "posts".to_string(),
+                "authors".to_string(),
+                "editors".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn where_references_nested_ref_entities() {
+        let wql = Wql::from_str(
+            "SELECT * FROM posts WHERE {
+                ?* posts:author ?author,
+                (or
+                    (== ?author @authors/2df2b8cf-49da-474d-8a00-c596c0bb6fd1)
+                    (== ?author @posts/2df2b8cf-49da-474d-8a00-c596c0bb6fd1)
+                ),
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            referenced_entities(&wql),
+            vec!["posts".to_string(), "authors".to_string()]
+        );
+    }
+
+    #[test]
+    fn collect_insert_strings() {
+        let wql = Wql::from_str(
+            "INSERT {title: \"Hello\", tags: [\"greeting\", 3,], meta: {label: \"Welcome\",}, views: 1,} INTO posts",
+        )
+        .unwrap();
+        let mut strings = collect_strings(&wql);
+        strings.sort();
+
+        assert_eq!(
+            strings,
+            vec![
+                "Hello".to_string(),
+                "Welcome".to_string(),
+                "greeting".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn collect_where_strings() {
+        let wql = Wql::from_str(
+            "SELECT * FROM posts WHERE {
+                ?* posts:title ?title,
+                (or (== ?title \"Hello\") (in ?title \"Hi\" \"Hey\")),
+                (> ?views 3),
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            collect_strings(&wql),
+            vec!["Hello".to_string(), "Hi".to_string(), "Hey".to_string()]
+        );
+    }
+
+    #[test]
+    fn explode_three_inserts() {
+        let entity = |a: isize| {
+            let mut entity = HashMap::new();
+            entity.insert("a".to_string(), Types::Integer(a));
+            entity
+        };
+        let wql = Wql::InsertMany("posts".to_string(), vec![entity(1), entity(2), entity(3)]);
+
+        assert_eq!(
+            wql.explode_insert_many(),
+            vec![
+                Wql::Insert("posts".to_string(), entity(1), None, None),
+                Wql::Insert("posts".to_string(), entity(2), None, None),
+                Wql::Insert("posts".to_string(), entity(3), None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn explode_passes_plain_insert() {
+        let insert = || Wql::from_str("INSERT {a: 1,} INTO posts").unwrap();
+
+        assert_eq!(insert().explode_insert_many(), vec![insert()]);
+    }
+
+    #[test]
+    fn batch_same_verb_and_entity() {
+        let wql = |query: &str| Wql::from_str(query).unwrap();
+
+        assert!(can_batch(
+            &wql("INSERT {a: 1,} INTO posts"),
+            &wql("INSERT {b: \"hello\",} INTO posts")
+        ));
+        assert!(can_batch(
+            &wql("DELETE 2df2b8cf-49da-474d-8a00-c596c0bb6fd1 FROM posts"),
+            &wql("DELETE FROM posts WHERE id IN (53315090-e14d-4738-a4d2-f1ec2a93664c)")
+        ));
+    }
+
+    #[test]
+    fn no_batch_across_verbs_or_entities() {
+        let wql = |query: &str| Wql::from_str(query).unwrap();
+
+        assert!(!can_batch(
+            &wql("INSERT {a: 1,} INTO posts"),
+            &wql("INSERT {a: 1,} INTO authors")
+        ));
+        assert!(!can_batch(
+            &wql("INSERT {a: 1,} INTO posts"),
+            &wql("SELECT * FROM posts")
+        ));
+        assert!(!can_batch(&wql("LET a = 1"), &wql("LET b = 2")));
+    }
+
+    #[test]
+    fn suggest_filtered_and_range_fields() {
+        let wql = Wql::from_str(
+            "SELECT * FROM users WHERE {
+                ?* users:email ?email,
+                ?* users:age ?age,
+                ?* users:name ?name,
+                ?* users:country \"br\",
+                (== ?email \"jane@example.com\"),
+                (>= ?age 18),
+                (like ?name \"j%\"),
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            suggest_indexes(&wql),
+            vec![
+                "country".to_string(),
+                "email".to_string(),
+                "age".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn no_suggestion_without_filters() {
+        let select = Wql::from_str("SELECT * FROM users").unwrap();
+        let count = Wql::from_str(
+            "SELECT COUNT FROM users WHERE { ?* users:age ?age, (!= ?age 18), (between ?age 1 5), }",
+        )
+        .unwrap();
+
+        assert!(suggest_indexes(&select).is_empty());
+        assert_eq!(suggest_indexes(&count), vec!["age".to_string()]);
+    }
+}
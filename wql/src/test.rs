@@ -31,7 +31,7 @@ mod test_create {
 
         assert_eq!(
             wql.err(),
-            Some(String::from("Symbol `KREATE` not implemented"))
+            Some(String::from("Symbol `KREATE` not implemented at offset 0"))
         );
     }
 
@@ -41,7 +41,42 @@ mod test_create {
 
         assert_eq!(
             wql.unwrap(),
-            Wql::CreateEntity(String::from("entity"), Vec::new(), Vec::new())
+            Wql::CreateEntity(String::from("entity"), Vec::new(), Vec::new(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn create_entity_trailing_input() {
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY foo bar baz").err(),
+            Some(String::from("Unexpected trailing input: `bar baz`"))
+        );
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY foo UNIQUES #{name,} baz").err(),
+            Some(String::from("Unexpected trailing input: `baz`"))
+        );
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY foo \n\t ").unwrap(),
+            Wql::CreateEntity(String::from("foo"), Vec::new(), Vec::new(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn create_entity_keeps_name_case() {
+        let wql = Wql::from_str("CREATE entity my_Entity uniques #{name,}");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::CreateEntity(
+                String::from("my_Entity"),
+                vec!["name".to_string()],
+                Vec::new(),
+                Vec::new()
+            )
+        );
+        assert_eq!(
+            Wql::from_str("insert {a: 1,} Into my_Entity").unwrap(),
+            Wql::from_str("INSERT {a: 1,} INTO my_Entity").unwrap()
         );
     }
 
@@ -58,6 +93,7 @@ mod test_create {
                     "ssn".to_string(),
                     "something".to_string()
                 ],
+                Vec::new(),
                 Vec::new()
             )
         );
@@ -77,6 +113,7 @@ mod test_create {
                     "ssn".to_string(),
                     "something".to_string()
                 ],
+                Vec::new(),
             )
         );
     }
@@ -93,10 +130,69 @@ mod test_create {
                 String::from("entity"),
                 vec!["name".to_string(), "ssn".to_string(),],
                 vec!["password".to_string(), "something".to_string()],
+                Vec::new(),
+            )
+        );
+    }
+
+    #[test]
+    fn create_entity_with_primary_key() {
+        let wql = Wql::from_str("CREATE ENTITY user PRIMARY KEY (email) UNIQUES #{name,}");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::CreateEntity(
+                String::from("user"),
+                vec!["name".to_string()],
+                Vec::new(),
+                vec!["email".to_string()],
+            )
+        );
+    }
+
+    #[test]
+    fn create_entity_with_composite_primary_key() {
+        let wql = Wql::from_str("CREATE ENTITY user primary key (tenant, email)");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::CreateEntity(
+                String::from("user"),
+                Vec::new(),
+                Vec::new(),
+                vec!["tenant".to_string(), "email".to_string()],
             )
         );
     }
 
+    #[test]
+    fn create_entity_with_invalid_primary_key() {
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY user PRIMARY (email)").err(),
+            Some(String::from("Keyword KEY is required for PRIMARY"))
+        );
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY user PRIMARY KEY ()").err(),
+            Some(String::from("Fields list cannot be empty"))
+        );
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY user PRIMARY KEY (email").err(),
+            Some(String::from(
+                "Fields list should start with `(` and end with `)`"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY user PRIMARY KEY (tenant, email, tenant)").err(),
+            Some(String::from("Duplicate field `tenant` in PRIMARY KEY"))
+        );
+        assert_eq!(
+            Wql::from_str("CREATE ENTITY user PRIMARY KEY (email) ENCRYPT #{email,}").err(),
+            Some(String::from(
+                "Encrypted arguments cannot be set to PRIMARY KEY"
+            ))
+        );
+    }
+
     #[test]
     fn create_uniques_in_encrypt() {
         let wql = Wql::from_str(
@@ -133,6 +229,7 @@ mod test_create {
                 String::from("entity"),
                 vec!["name".to_string(), "ssn".to_string(),],
                 vec!["password".to_string(), "something".to_string()],
+                Vec::new(),
             )
         );
     }
@@ -144,6 +241,14 @@ mod test_delete {
 
     use super::*;
 
+    #[test]
+    fn delete_trailing_input() {
+        assert_eq!(
+            Wql::from_str("DELETE d6ca73c0-41ff-4975-8a60-fc4a061ce536 FROM my_entity!").err(),
+            Some(String::from("Unexpected trailing input: `!`"))
+        );
+    }
+
     #[test]
     fn delete_id() {
         let wql = Wql::from_str("DELETE this-is-an-uuid FROM my_entity");
@@ -164,6 +269,62 @@ mod test_delete {
         );
     }
 
+    #[test]
+    fn delete_many_ids() {
+        let wql = Wql::from_str(
+            "DELETE FROM my_entity WHERE id IN (2df2b8cf-49da-474d-8a00-c596c0bb6fd1, 53315090-e14d-4738-a4d2-f1ec2a93664c)",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::DeleteMany(
+                "my_entity".to_string(),
+                vec![
+                    Uuid::from_str("2df2b8cf-49da-474d-8a00-c596c0bb6fd1").unwrap(),
+                    Uuid::from_str("53315090-e14d-4738-a4d2-f1ec2a93664c").unwrap(),
+                ]
+            )
+        )
+    }
+
+    #[test]
+    fn delete_many_empty_ids() {
+        let wql = Wql::from_str("DELETE FROM my_entity WHERE id IN ()");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "At least one Uuid is required in `WHERE id IN (...)`"
+            ))
+        );
+    }
+
+    #[test]
+    fn delete_many_unclosed_ids() {
+        let wql = Wql::from_str(
+            "DELETE FROM my_entity WHERE id IN (2df2b8cf-49da-474d-8a00-c596c0bb6fd1",
+        );
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "Uuids in `WHERE id IN` are required to be inside `(` and `)`"
+            ))
+        );
+    }
+
+    #[test]
+    fn delete_many_malformed_uuid() {
+        let wql = Wql::from_str(
+            "DELETE FROM my_entity WHERE id IN (2df2b8cf-49da-474d-8a00-c596c0bb6fd1, not-an-uuid)",
+        );
+
+        assert!(wql
+            .err()
+            .unwrap()
+            .starts_with("Couldn't creat an Uuid from \"not-an-uuid\""));
+    }
+
     #[test]
     fn delete_missing_keyword_from() {
         let wql = Wql::from_str("DELETE this-is-an-uuid my_entity");
@@ -207,646 +368,2434 @@ mod test_insert {
 
         assert_eq!(
             wql.unwrap(),
-            Wql::Insert("my_entity".to_string(), hashmap())
+            Wql::Insert("my_entity".to_string(), hashmap(), None, None)
         );
     }
 
     #[test]
-    fn insert_precise() {
-        let wql = Wql::from_str(
-            "INSERT {
-            a: 98347883122138743294728345738925783257325789353593473247832493483478935673.9347324783249348347893567393473247832493483478935673P,
-        } INTO my_entity",
-        );
+    fn insert_versioned() {
+        let wql = Wql::from_str("INSERT @v2 {a: 123,} INTO my_entity");
+        let compact = Wql::from_str("INSERT @v2{a: 123,} INTO my_entity");
 
         let mut hm = HashMap::new();
-        hm.insert("a".to_string(), Types::Precise("98347883122138743294728345738925783257325789353593473247832493483478935673.9347324783249348347893567393473247832493483478935673".to_string()));
-
-        assert_eq!(wql.unwrap(), Wql::Insert("my_entity".to_string(), hm));
+        hm.insert("a".to_string(), Types::Integer(123));
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm.clone(), Some(2), None)
+        );
+        assert_eq!(
+            compact.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, Some(2), None)
+        );
     }
 
     #[test]
-    fn insert_missing_into() {
-        let wql = Wql::from_str(
-            "INSERT {
-            a: 123,
-        } INTRO my_entity",
-        );
+    fn insert_unversioned() {
+        let wql = Wql::from_str("INSERT {a: 123,} INTO my_entity");
 
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
         assert_eq!(
-            wql.err(),
-            Some(String::from("Keyword INTO is required for INSERT"))
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
         );
     }
 
     #[test]
-    fn insert_missing_entity_name() {
-        let wql = Wql::from_str(
-            "INSERT {
-            a: 123,
-        } INTO ",
-        );
+    fn insert_malformed_version() {
+        let wql = Wql::from_str("INSERT @version2 {a: 123,} INTO my_entity");
+        let negative = Wql::from_str("INSERT @v-1 {a: 123,} INTO my_entity");
 
         assert_eq!(
             wql.err(),
-            Some(String::from("Entity name is required after INTO"))
+            Some(String::from(
+                "Version tag `@version2` must be formatted as `@v<number>`"
+            ))
+        );
+        assert_eq!(
+            negative.err(),
+            Some(String::from(
+                "Version tag `@v-1` must be formatted as `@v<number>`"
+            ))
         );
     }
 
-    fn hashmap() -> Entity {
-        let mut hm = HashMap::new();
-        hm.insert("a".to_string(), Types::Integer(123));
-        hm.insert("b".to_string(), Types::Float(12.3));
-        hm.insert("c".to_string(), Types::Char('d'));
-        hm.insert("d".to_string(), Types::Boolean(true));
-        hm.insert("e".to_string(), Types::Boolean(false));
-        hm.insert("f".to_string(), Types::String("hello".to_string()));
-        hm.insert("g".to_string(), Types::Nil);
-        hm
-    }
-}
-
-#[cfg(test)]
-mod test_update {
-    use std::str::FromStr;
-
-    use super::*;
-
     #[test]
-    fn update_set_entity() {
+    fn quoted_uuid_stays_string() {
         let wql = Wql::from_str(
-            "UPDATE this_entity 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+            "INSERT {
+            a: \"2df2b8cf-49da-474d-8a00-c596c0bb6fd1\",
+            b: 2df2b8cf-49da-474d-8a00-c596c0bb6fd1,
+            c: [\"2df2b8cf-49da-474d-8a00-c596c0bb6fd1\",],
+        } INTO my_entity",
         );
 
+        let id = "2df2b8cf-49da-474d-8a00-c596c0bb6fd1";
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::String(id.to_string()));
+        hm.insert("b".to_string(), Types::Uuid(Uuid::from_str(id).unwrap()));
+        hm.insert(
+            "c".to_string(),
+            Types::Vector(vec![Types::String(id.to_string())]),
+        );
         assert_eq!(
             wql.unwrap(),
-            Wql::UpdateSet(
-                "this_entity".to_string(),
-                hashmap(),
-                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap()
-            )
+            Wql::Insert("my_entity".to_string(), hm, None, None)
         );
     }
 
     #[test]
-    fn update_content_entity() {
+    fn insert_precise() {
         let wql = Wql::from_str(
-            "UPDATE this_entity 
-        Content {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+            "INSERT {
+            a: 98347883122138743294728345738925783257325789353593473247832493483478935673.9347324783249348347893567393473247832493483478935673P,
+        } INTO my_entity",
         );
 
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Precise("98347883122138743294728345738925783257325789353593473247832493483478935673.9347324783249348347893567393473247832493483478935673".to_string()));
+
         assert_eq!(
             wql.unwrap(),
-            Wql::UpdateContent(
-                "this_entity".to_string(),
-                hashmap(),
-                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap()
-            )
+            Wql::Insert("my_entity".to_string(), hm, None, None)
         );
     }
 
     #[test]
-    fn update_set_missing_entity() {
-        let wql = Wql::from_str(
-            "UPDATE 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
-        );
+    fn insert_multibyte_char() {
+        let wql = Wql::from_str("INSERT {a: 'é',} INTO my_entity");
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Char('é'));
 
         assert_eq!(
-            wql.err(),
-            Some(String::from("Entity name is required for UPDATE"))
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
         );
     }
 
-    fn hashmap() -> Entity {
-        let mut hm = HashMap::new();
-        hm.insert("a".to_string(), Types::Integer(123));
-        hm.insert("g".to_string(), Types::Nil);
-        hm
-    }
-
     #[test]
-    fn update_entity_mispelled_action() {
+    fn insert_escaped_chars() {
         let wql = Wql::from_str(
-            "UPDATE this_entity 
-        TO {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+            "INSERT {a: '\\n', b: '\\t', c: '\\r', d: '\\\\', e: '\\'',} INTO my_entity",
         );
 
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Char('\n'));
+        hm.insert("b".to_string(), Types::Char('\t'));
+        hm.insert("c".to_string(), Types::Char('\r'));
+        hm.insert("d".to_string(), Types::Char('\\'));
+        hm.insert("e".to_string(), Types::Char('\''));
+
         assert_eq!(
-            wql.err(),
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: '\\x',} INTO my_entity").err(),
+            Some(String::from("Invalid escape sequence \\x"))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: '\\nn',} INTO my_entity").err(),
             Some(String::from(
-                "UPDATE type is required after entity. Keywords are SET or CONTENT"
+                "Char literal `'\\nn'` must contain a single character"
             ))
         );
     }
 
     #[test]
-    fn update_entity_missing_into() {
-        let wql = Wql::from_str(
-            "UPDATE this_entity 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        to d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+    fn insert_delimiter_chars() {
+        let wql = Wql::from_str("INSERT {a: ' ', b: ',', c: [' ', ',',],} INTO my_entity");
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Char(' '));
+        hm.insert("b".to_string(), Types::Char(','));
+        hm.insert(
+            "c".to_string(),
+            Types::Vector(vec![Types::Char(' '), Types::Char(',')]),
         );
 
         assert_eq!(
-            wql.err(),
-            Some(String::from("Keyword INTO is required for UPDATE"))
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
         );
     }
 
     #[test]
-    fn update_entity_missing_uuid() {
-        let wql = Wql::from_str(
-            "UPDATE this_entity 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        into Some-crazy-id",
-        );
+    fn insert_unicode_escaped_chars() {
+        let wql = Wql::from_str("INSERT {a: '\\u{1F600}', b: '\\u{e9}',} INTO my_entity");
 
-        assert!(wql
-            .err()
-            .unwrap()
-            .starts_with("Couldn\'t create uuid from Some-crazy-id"));
-    }
-}
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Char('😀'));
+        hm.insert("b".to_string(), Types::Char('é'));
 
-#[cfg(test)]
-mod test_match {
-    use super::*;
-    use std::str::FromStr;
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
+        );
+    }
 
     #[test]
-    fn test_read_match_args() {
-        let mut args = "(a == 1, b != 2, c > 3, d >= 4, e < 5, f <= 6)".chars();
-        let actual = read_match_args(&mut args).unwrap();
-        let expected = vec![
-            MatchCondition::Eq("a".to_string(), Types::Integer(1)),
-            MatchCondition::NotEq("b".to_string(), Types::Integer(2)),
-            MatchCondition::G("c".to_string(), Types::Integer(3)),
-            MatchCondition::GEq("d".to_string(), Types::Integer(4)),
-            MatchCondition::L("e".to_string(), Types::Integer(5)),
-            MatchCondition::LEq("f".to_string(), Types::Integer(6)),
-        ];
-        assert_eq!(actual, expected);
+    fn insert_invalid_unicode_escaped_chars() {
+        assert_eq!(
+            Wql::from_str("INSERT {a: '\\u{D800}',} INTO my_entity").err(),
+            Some(String::from(
+                "Unicode escape \\u{D800} is not a Unicode scalar value, surrogates D800 to DFFF and values above 10FFFF are not chars"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: '\\u{110000}',} INTO my_entity").err(),
+            Some(String::from(
+                "Unicode escape \\u{110000} is not a Unicode scalar value, surrogates D800 to DFFF and values above 10FFFF are not chars"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: '\\u{12g}',} INTO my_entity").err(),
+            Some(String::from(
+                "Invalid unicode escape \\u{12g}, expected 1 to 6 hex digits"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: '\\u{}',} INTO my_entity").err(),
+            Some(String::from(
+                "Invalid unicode escape \\u{}, expected 1 to 6 hex digits"
+            ))
+        );
     }
 
     #[test]
-    fn match_update_set_entity() {
-        let wql = Wql::from_str(
-            " MATCH ALL(a == 1, b >= 3, c != \"hello\", d < 7)
-        UPDATE this_entity 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
-        );
+    fn insert_empty_char() {
+        let wql = Wql::from_str("INSERT {a: '',} INTO my_entity");
 
         assert_eq!(
-            wql.unwrap(),
-            Wql::MatchUpdate(
-                "this_entity".to_string(),
-                hashmap(),
-                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap(),
-                MatchCondition::All(vec![
-                    MatchCondition::Eq("a".to_string(), Types::Integer(1)),
-                    MatchCondition::GEq("b".to_string(), Types::Integer(3)),
-                    MatchCondition::NotEq("c".to_string(), Types::String("hello".to_string())),
-                    MatchCondition::L("d".to_string(), Types::Integer(7)),
-                ])
-            )
+            wql.err(),
+            Some(String::from("Char literal cannot be empty"))
         );
     }
 
     #[test]
-    fn match_update_missing_logical_arg() {
-        let wql = Wql::from_str(
-            " MATCH (a == 1, b >= 3, c != \"hello\", d < 7)
-        UPDATE this_entity 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
-        );
+    fn insert_multiple_chars() {
+        let wql = Wql::from_str("INSERT {a: 'ab',} INTO my_entity");
 
         assert_eq!(
-            wql.err().unwrap(),
-            String::from("MATCH requires ALL or ANY symbols")
+            wql.err(),
+            Some(String::from(
+                "Char literal `'ab'` must contain a single character"
+            ))
         );
     }
 
     #[test]
-    fn match_update_missing_update_key() {
-        let wql = Wql::from_str(
-            " MATCH Any(a == 1, b >= 3, c != \"hello\", d < 7)
-        this_entity 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
-        );
+    fn insert_vector_of_refs() {
+        let uuid1 = Uuid::new_v4();
+        let uuid2 = Uuid::new_v4();
+        let wql = Wql::from_str(&format!(
+            "INSERT {{tags: [@tags/{}, @tags/{},],}} INTO my_entity",
+            uuid1, uuid2
+        ));
 
+        let mut hm = HashMap::new();
+        hm.insert(
+            "tags".to_string(),
+            Types::Vector(vec![
+                Types::Ref("tags".to_string(), uuid1),
+                Types::Ref("tags".to_string(), uuid2),
+            ]),
+        );
         assert_eq!(
-            wql.err().unwrap(),
-            String::from("UPDATE keyword is required for MATCH UPDATE")
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
         );
     }
 
     #[test]
-    fn match_update_missing_entity_name() {
-        let wql = Wql::from_str(
-            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
-        UPDATE 
-        SET {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
-        );
+    fn insert_invalid_ref() {
+        let wql = Wql::from_str("INSERT {tag: @tags/not-a-uuid,} INTO my_entity");
 
         assert_eq!(
-            wql.err().unwrap(),
-            String::from("Entity name is required for MATCH UPDATE")
+            wql.err(),
+            Some(String::from(
+                "Ref `@tags/not-a-uuid` must have a valid Uuid"
+            ))
         );
     }
 
     #[test]
-    fn match_update_missing_set() {
+    fn insert_type_assertion() {
         let wql = Wql::from_str(
-            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
-        UPDATE this_entity 
-        {
-            a: 123,
-            g: NiL
-        } 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+            "INSERT {a: (Integer) 5, b: (Map) {c: (Float) 1.5,}, d: (Vector) [1,],} INTO my_entity",
         );
 
+        let mut inner = HashMap::new();
+        inner.insert("c".to_string(), Types::Float(1.5));
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(5));
+        hm.insert("b".to_string(), Types::Map(inner));
+        hm.insert("d".to_string(), Types::Vector(vec![Types::Integer(1)]));
         assert_eq!(
-            wql.err().unwrap(),
-            String::from("MATCH UPDATE type is required after entity. Keyword is SET")
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
         );
     }
 
     #[test]
-    fn match_update_missing_content() {
-        let wql = Wql::from_str(
-            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
-        UPDATE this_entity 
-        SET 
-        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+    fn insert_type_assertion_mismatch() {
+        let wql = Wql::from_str("INSERT {a: (Integer) 5.5,} INTO my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "Type assertion `(Integer)` failed, value is Float"
+            ))
         );
+    }
+
+    #[test]
+    fn insert_unknown_type_assertion() {
+        let wql = Wql::from_str("INSERT {a: (Number) 5,} INTO my_entity");
 
         assert_eq!(
-            wql.err().unwrap(),
-            String::from("Entity map should start with `{` and end with `}`")
+            wql.err(),
+            Some(String::from("Unknown type `Number` in type assertion"))
         );
     }
 
     #[test]
-    fn match_update_missing_into() {
+    fn insert_missing_into() {
         let wql = Wql::from_str(
-            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
-        UPDATE this_entity 
-        SET {
+            "INSERT {
             a: 123,
-            g: NiL
-        } 
-        d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        } INTRO my_entity",
         );
 
         assert_eq!(
-            wql.err().unwrap(),
-            String::from("Keyword INTO is required for MATCH UPDATE")
+            wql.err(),
+            Some(String::from("Keyword INTO is required for INSERT"))
         );
     }
 
     #[test]
-    fn match_update_missing_id() {
+    fn insert_missing_entity_name() {
         let wql = Wql::from_str(
-            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
-        UPDATE this_entity 
-        SET {
+            "INSERT {
             a: 123,
-            g: NiL
-        } 
-        INTO",
+        } INTO ",
         );
 
-        assert!(wql
-            .err()
-            .unwrap()
-            .starts_with("Couldn\'t create uuid from "));
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Entity name is required after INTO"))
+        );
     }
 
     fn hashmap() -> Entity {
         let mut hm = HashMap::new();
         hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert("b".to_string(), Types::Float(12.3));
+        hm.insert("c".to_string(), Types::Char('d'));
+        hm.insert("d".to_string(), Types::Boolean(true));
+        hm.insert("e".to_string(), Types::Boolean(false));
+        hm.insert("f".to_string(), Types::String("hello".to_string()));
         hm.insert("g".to_string(), Types::Nil);
         hm
     }
-}
-
-#[cfg(test)]
-mod evict {
-    use std::str::FromStr;
-
-    use super::*;
 
     #[test]
-    fn evict_entity() {
-        let wql = Wql::from_str("EVICT my_entity");
+    fn entity_round_trip() {
+        let entity = hashmap();
+        let text = crate::entity_to_string(&entity).unwrap();
 
-        assert_eq!(wql.unwrap(), Wql::Evict(String::from("my_entity"), None));
+        assert_eq!(crate::entity_from_str(&text).unwrap(), entity);
+        assert_eq!(crate::entity_to_string(&hashmap()).unwrap(), text);
     }
 
     #[test]
-    fn evict_entity_with_dash() {
-        let wql = Wql::from_str("EVICT my-entity");
+    fn nested_entity_round_trip() {
+        let id = Uuid::from_str("2df2b8cf-49da-474d-8a00-c596c0bb6fd1").unwrap();
+        let mut inner = HashMap::new();
+        inner.insert("z".to_string(), Types::Float(-0.5));
+        inner.insert(
+            "y".to_string(),
+            Types::Vector(vec![Types::Nil, Types::Uuid(id)]),
+        );
+        inner.insert("x".to_string(), Types::Ref("other".to_string(), id));
+        let mut entity = hashmap();
+        entity.insert("h".to_string(), Types::Map(inner));
+        entity.insert(
+            "i".to_string(),
+            Types::Vector(vec![Types::Integer(1), Types::Float(2.0)]),
+        );
+
+        let text = crate::entity_to_string(&entity).unwrap();
+
+        assert_eq!(crate::entity_from_str(&text).unwrap(), entity);
+        assert!(text
+            .contains("Map({\"x\":Ref(\"other\",\"2df2b8cf-49da-474d-8a00-c596c0bb6fd1\"),\"y\""));
+    }
 
+    #[test]
+    fn insert_trailing_input() {
         assert_eq!(
-            wql.err(),
-            Some(String::from("Entity name cannot contain `-`"))
+            Wql::from_str("INSERT {a: 1,} INTO my_entity junk").err(),
+            Some(String::from("Unexpected trailing input: `junk`"))
         );
+        assert_eq!(
+            Wql::from_str("INSERT {a: 1,} INTO my_entity-junk").err(),
+            Some(String::from("Unexpected trailing input: `-junk`"))
+        );
+        assert!(Wql::from_str("INSERT {a: 1,} INTO my_entity  \n").is_ok());
     }
 
     #[test]
-    fn evict_entity_from_id() {
-        let wql = Wql::from_str("EVICT d6ca73c0-41ff-4975-8a60-fc4a061ce536 FROM my_entity");
+    fn insert_on_conflict() {
+        let mut hm = HashMap::new();
+        hm.insert(
+            "email".to_string(),
+            Types::String("julia@wooridb.io".to_string()),
+        );
+        let insert =
+            |on_conflict| Wql::Insert("users".to_string(), hm.clone(), None, Some(on_conflict));
 
         assert_eq!(
-            wql.unwrap(),
-            Wql::Evict(
-                String::from("my_entity"),
-                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").ok()
+            Wql::from_str(
+                "INSERT {email: \"julia@wooridb.io\",} INTO users ON CONFLICT (email) DO UPDATE"
             )
+            .unwrap(),
+            insert(OnConflict::Update("email".to_string()))
         );
-    }
-
-    #[test]
-    fn evict_entity_without_from() {
-        let wql = Wql::from_str("EVICT d6ca73c0-41ff-4975-8a60-fc4a061ce536 my_entity");
-
         assert_eq!(
-            wql.err(),
-            Some(String::from("FROM keyword is required to EVICT an UUID"))
+            Wql::from_str(
+                "INSERT {email: \"julia@wooridb.io\",} INTO users on conflict ( email ) do nothing"
+            )
+            .unwrap(),
+            insert(OnConflict::Nothing("email".to_string()))
         );
     }
 
     #[test]
-    fn evict_entity_without_entity_name() {
-        let wql = Wql::from_str("EVICT d6ca73c0-41ff-4975-8a60-fc4a061ce536 FROM");
-
-        assert_eq!(wql.err(), Some(String::from("Entity name is required")));
+    fn insert_on_conflict_errors() {
+        assert_eq!(
+            Wql::from_str("INSERT {a: 1,} INTO my_entity ON CONFLICT (a) DO REPLACE").err(),
+            Some(String::from(
+                "Unknown ON CONFLICT action `REPLACE`, expected UPDATE or NOTHING"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: 1,} INTO my_entity ON CONFLICT (a, b) DO NOTHING").err(),
+            Some(String::from("ON CONFLICT requires a single unique field"))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: 1,} INTO my_entity ON (a) DO NOTHING").err(),
+            Some(String::from("Keyword CONFLICT is required for ON"))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: 1,} INTO my_entity ON CONFLICT (a) NOTHING").err(),
+            Some(String::from("Keyword DO is required for ON CONFLICT"))
+        );
     }
-}
 
-#[cfg(test)]
-mod test_data_sructures {
-    use super::*;
     #[test]
-    fn insert_vec() {
+    fn insert_sub_insert() {
         let wql = Wql::from_str(
-            "INSERT {
-            a: 123,
-            b: [12.3, 34, \"hello\",]
-        } INTO my_entity",
+            "INSERT { author: (INSERT {name: \"Ada (Lovelace)\"} INTO authors), title: \"x\" } INTO posts",
+        );
+        let mut author = HashMap::new();
+        author.insert(
+            "name".to_string(),
+            Types::String("Ada (Lovelace)".to_string()),
         );
+        let mut post = HashMap::new();
+        post.insert("title".to_string(), Types::String("x".to_string()));
 
         assert_eq!(
             wql.unwrap(),
-            Wql::Insert("my_entity".to_string(), hashmap())
+            Wql::NestedInsert(
+                Box::new(Wql::Insert("posts".to_string(), post, None, None)),
+                vec![(
+                    "author".to_string(),
+                    Wql::Insert("authors".to_string(), author, None, None)
+                )]
+            )
         );
     }
 
     #[test]
-    fn insert_vec_in_vec() {
-        let wql = Wql::from_str(
-            "INSERT {
-            a: 123,
-            b: [12.3, 34, [\"hello\"]]
-        } INTO my_entity",
-        );
+    fn insert_sub_insert_errors() {
+        assert_eq!(
+            Wql::from_str(
+                "INSERT {a: (INSERT {b: (INSERT {c: 1,} INTO cs),} INTO bs),} INTO my_entity"
+            )
+            .err(),
+            Some(String::from("Sub-inserts cannot contain sub-inserts"))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: (INSERT {b: 1,} INTO bs,} INTO my_entity").err(),
+            Some(String::from(
+                "Sub-insert should start with `(` and end with `)`"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: (INSERT {b: 1,} INTO bs), a: 1,} INTO my_entity").err(),
+            Some(String::from("Duplicate key `a` in entity map"))
+        );
+        assert_eq!(
+            Wql::from_str("UPDATE my_entity SET {a: (INSERT {b: 1,} INTO bs),} INTO 2df2b8cf-49da-474d-8a00-c596c0bb6fd1").err(),
+            Some(String::from(
+                "Sub-inserts are only allowed in the entity map of an INSERT"
+            ))
+        );
+    }
+
+    #[test]
+    fn insert_duplicated_key() {
+        let wql = Wql::from_str("INSERT {a: 1, b: 2, a: 3,} INTO my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Duplicate key `a` in entity map"))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: {b: 1,}, a: 2,} INTO my_entity").err(),
+            Some(String::from("Duplicate key `a` in entity map"))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: {b: 1, b: 2,},} INTO my_entity").err(),
+            Some(String::from("Duplicate key `b` in entity map"))
+        );
+        assert_eq!(
+            Wql::from_str("INSERT {a: [{c: 1,}, {d: {c: 1, c: 1,},},],} INTO my_entity").err(),
+            Some(String::from("Duplicate key `c` in entity map"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_update {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn update_trailing_input() {
+        assert_eq!(
+            Wql::from_str(
+                "UPDATE my_entity SET {a: 1,} INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536!"
+            )
+            .err(),
+            Some(String::from("Unexpected trailing input: `!`"))
+        );
+        assert_eq!(
+            Wql::from_str(
+                "UPDATE my_entity CONTENT {a: 1,} INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536)"
+            )
+            .err(),
+            Some(String::from("Unexpected trailing input: `)`"))
+        );
+    }
+
+    #[test]
+    fn update_set_entity() {
+        let wql = Wql::from_str(
+            "UPDATE this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::UpdateSet(
+                "this_entity".to_string(),
+                hashmap(),
+                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn update_content_entity() {
+        let wql = Wql::from_str(
+            "UPDATE this_entity 
+        Content {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::UpdateContent(
+                "this_entity".to_string(),
+                hashmap(),
+                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn update_set_missing_entity() {
+        let wql = Wql::from_str(
+            "UPDATE 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Entity name is required for UPDATE"))
+        );
+    }
+
+    fn hashmap() -> Entity {
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert("g".to_string(), Types::Nil);
+        hm
+    }
+
+    #[test]
+    fn update_entity_mispelled_action() {
+        let wql = Wql::from_str(
+            "UPDATE this_entity 
+        TO {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "UPDATE type is required after entity. Keywords are SET or CONTENT"
+            ))
+        );
+    }
+
+    #[test]
+    fn update_entity_missing_into() {
+        let wql = Wql::from_str(
+            "UPDATE this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        to d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Keyword INTO is required for UPDATE"))
+        );
+    }
+
+    #[test]
+    fn update_entity_missing_uuid() {
+        let wql = Wql::from_str(
+            "UPDATE this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        into Some-crazy-id",
+        );
+
+        assert!(wql
+            .err()
+            .unwrap()
+            .starts_with("Couldn\'t create uuid from Some-crazy-id"));
+    }
+
+    #[test]
+    fn update_entity_without_uuid() {
+        let wql = Wql::from_str(
+            "UPDATE this_entity 
+        SET {
+            a: 123,
+        } 
+        INTO ",
+        );
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Entity UUID is required for UPDATE"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_match {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn match_update_trailing_input() {
+        assert_eq!(
+            Wql::from_str(
+                "MATCH ALL(a > 100, b <= 20) UPDATE my_entity SET {a: 1,} INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536!"
+            )
+            .err(),
+            Some(String::from("Unexpected trailing input: `!`"))
+        );
+    }
+
+    #[test]
+    fn test_read_match_args() {
+        let mut args = "(a == 1, b != 2, c > 3, d >= 4, e < 5, f <= 6)".chars();
+        let actual = read_match_args(&mut args).unwrap();
+        let expected = vec![
+            MatchCondition::Eq("a".to_string(), Types::Integer(1)),
+            MatchCondition::NotEq("b".to_string(), Types::Integer(2)),
+            MatchCondition::G("c".to_string(), Types::Integer(3)),
+            MatchCondition::GEq("d".to_string(), Types::Integer(4)),
+            MatchCondition::L("e".to_string(), Types::Integer(5)),
+            MatchCondition::LEq("f".to_string(), Types::Integer(6)),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn match_update_set_entity() {
+        let wql = Wql::from_str(
+            " MATCH ALL(a == 1, b >= 3, c != \"hello\", d < 7)
+        UPDATE this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::MatchUpdate(
+                "this_entity".to_string(),
+                hashmap(),
+                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap(),
+                MatchCondition::All(vec![
+                    MatchCondition::Eq("a".to_string(), Types::Integer(1)),
+                    MatchCondition::GEq("b".to_string(), Types::Integer(3)),
+                    MatchCondition::NotEq("c".to_string(), Types::String("hello".to_string())),
+                    MatchCondition::L("d".to_string(), Types::Integer(7)),
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn match_update_missing_logical_arg() {
+        let wql = Wql::from_str(
+            " MATCH (a == 1, b >= 3, c != \"hello\", d < 7)
+        UPDATE this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err().unwrap(),
+            String::from("MATCH requires ALL or ANY symbols")
+        );
+    }
+
+    #[test]
+    fn match_update_missing_update_key() {
+        let wql = Wql::from_str(
+            " MATCH Any(a == 1, b >= 3, c != \"hello\", d < 7)
+        this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err().unwrap(),
+            String::from("UPDATE keyword is required for MATCH UPDATE")
+        );
+    }
+
+    #[test]
+    fn match_update_missing_entity_name() {
+        let wql = Wql::from_str(
+            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
+        UPDATE 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err().unwrap(),
+            String::from("Entity name is required for MATCH UPDATE")
+        );
+    }
+
+    #[test]
+    fn match_update_missing_set() {
+        let wql = Wql::from_str(
+            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
+        UPDATE this_entity 
+        {
+            a: 123,
+            g: NiL
+        } 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err().unwrap(),
+            String::from("MATCH UPDATE type is required after entity. Keyword is SET")
+        );
+    }
+
+    #[test]
+    fn match_update_missing_content() {
+        let wql = Wql::from_str(
+            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
+        UPDATE this_entity 
+        SET 
+        INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err().unwrap(),
+            String::from("Entity map should start with `{` and end with `}`")
+        );
+    }
+
+    #[test]
+    fn match_update_missing_into() {
+        let wql = Wql::from_str(
+            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
+        UPDATE this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err().unwrap(),
+            String::from("Keyword INTO is required for MATCH UPDATE")
+        );
+    }
+
+    #[test]
+    fn match_update_missing_id() {
+        let wql = Wql::from_str(
+            " MATCH All(a == 1, b >= 3, c != \"hello\", d < 7)
+        UPDATE this_entity 
+        SET {
+            a: 123,
+            g: NiL
+        } 
+        INTO",
+        );
+
+        assert!(wql
+            .err()
+            .unwrap()
+            .starts_with("Couldn\'t create uuid from "));
+    }
+
+    fn hashmap() -> Entity {
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert("g".to_string(), Types::Nil);
+        hm
+    }
+}
+
+#[cfg(test)]
+mod evict {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn evict_trailing_input() {
+        assert_eq!(
+            Wql::from_str("EVICT my_entity!").err(),
+            Some(String::from("Unexpected trailing input: `!`"))
+        );
+        assert_eq!(
+            Wql::from_str("EVICT d6ca73c0-41ff-4975-8a60-fc4a061ce536 FROM my_entity!").err(),
+            Some(String::from("Unexpected trailing input: `!`"))
+        );
+    }
+
+    #[test]
+    fn evict_entity() {
+        let wql = Wql::from_str("EVICT my_entity");
+
+        assert_eq!(wql.unwrap(), Wql::Evict(String::from("my_entity"), None));
+    }
+
+    #[test]
+    fn evict_entity_with_dash() {
+        let wql = Wql::from_str("EVICT my-entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Entity name cannot contain `-`"))
+        );
+    }
+
+    #[test]
+    fn evict_entity_from_id() {
+        let wql = Wql::from_str("EVICT d6ca73c0-41ff-4975-8a60-fc4a061ce536 FROM my_entity");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Evict(
+                String::from("my_entity"),
+                Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").ok()
+            )
+        );
+    }
+
+    #[test]
+    fn evict_entity_without_from() {
+        let wql = Wql::from_str("EVICT d6ca73c0-41ff-4975-8a60-fc4a061ce536 my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("FROM keyword is required to EVICT an UUID"))
+        );
+    }
+
+    #[test]
+    fn evict_entity_without_entity_name() {
+        let wql = Wql::from_str("EVICT d6ca73c0-41ff-4975-8a60-fc4a061ce536 FROM");
+
+        assert_eq!(wql.err(), Some(String::from("Entity name is required")));
+    }
+
+    #[test]
+    fn truncate_entity() {
+        let wql = Wql::from_str("TRUNCATE my_entity");
+
+        assert_eq!(wql.unwrap(), Wql::Truncate(String::from("my_entity")));
+    }
+
+    #[test]
+    fn truncate_errors() {
+        assert_eq!(
+            Wql::from_str("TRUNCATE").err(),
+            Some(String::from("Entity name is required for TRUNCATE"))
+        );
+        assert_eq!(
+            Wql::from_str("TRUNCATE my-entity").err(),
+            Some(String::from("Unexpected trailing input: `-entity`"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_data_sructures {
+    use super::*;
+    #[test]
+    fn insert_vec() {
+        let wql = Wql::from_str(
+            "INSERT {
+            a: 123,
+            b: [12.3, 34, \"hello\",]
+        } INTO my_entity",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hashmap(), None, None)
+        );
+    }
+
+    #[test]
+    fn insert_vec_in_vec() {
+        let wql = Wql::from_str(
+            "INSERT {
+            a: 123,
+            b: [12.3, 34, [\"hello\"]]
+        } INTO my_entity",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hashmap2(), None, None)
+        );
+    }
+
+    #[test]
+    fn insert_vec_err() {
+        let wql = Wql::from_str(
+            "INSERT {
+            a: 123,
+            b: [12.3, 34, \"hello\", nkjsld,]
+        } INTO my_entity",
+        );
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Value Type could not be created from nkjsld"))
+        );
+    }
+
+    #[test]
+    fn insert_vec_with_map() {
+        let wql = Wql::from_str(
+            "INSERT {
+            a: 123,
+            b: { a: 12.3, b: 34, }
+        } INTO my_entity",
+        );
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hashmap3(), None, None)
+        );
+    }
+
+    fn hashmap() -> HashMap<String, Types> {
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert(
+            "b".to_string(),
+            Types::Vector(vec![
+                Types::Float(12.3),
+                Types::Integer(34),
+                Types::String("hello".to_string()),
+            ]),
+        );
+        hm
+    }
+
+    fn hashmap2() -> HashMap<String, Types> {
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert(
+            "b".to_string(),
+            Types::Vector(vec![
+                Types::Float(12.3),
+                Types::Integer(34),
+                Types::Vector(vec![Types::String("hello".to_string())]),
+            ]),
+        );
+        hm
+    }
+
+    fn hashmap3() -> HashMap<String, Types> {
+        let mut inner_map = HashMap::new();
+        inner_map.insert("a".to_string(), Types::Float(12.3));
+        inner_map.insert("b".to_string(), Types::Integer(34));
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert("b".to_string(), Types::Map(inner_map));
+        hm
+    }
+}
+
+#[cfg(test)]
+mod check {
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn check_trailing_input() {
+        assert_eq!(
+            Wql::from_str(
+                "CHECK {a: \"b\",} FROM my_entity ID d6ca73c0-41ff-4975-8a60-fc4a061ce536)"
+            )
+            .err(),
+            Some(String::from("Unexpected trailing input: `)`"))
+        );
+    }
+
+    #[test]
+    fn check_encrypt_values() {
+        let wql = Wql::from_str(
+            "CHECK {
+            ssn: 123,
+            pswd: \"my-password\"
+        } FROM my_entity ID d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        let uuid = Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap();
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::CheckValue("my_entity".to_string(), uuid, hashmap())
+        );
+    }
+
+    fn hashmap() -> HashMap<String, String> {
+        let mut hm = HashMap::new();
+        hm.insert("ssn".to_string(), "123".to_string());
+        hm.insert("pswd".to_string(), "my-password".to_string());
+        hm
+    }
+}
+
+#[cfg(test)]
+mod test_where {
+    use super::*;
+
+    #[test]
+    fn where_ok() {
+        let query = "Select * FROM my_entity WherE {
+            (in ?id 32434 45345 345346436),
+            (between ?age 30 35),
+        }";
+        let wql = Wql::from_str(query);
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectWhere(
+                "my_entity".to_string(),
+                ToSelect::All,
+                vec![
+                    Clause::ComplexComparisonFunctions(
+                        where_clause::Function::In,
+                        "?id".to_string(),
+                        vec![
+                            Types::Integer(32434),
+                            Types::Integer(45345),
+                            Types::Integer(345346436),
+                        ]
+                    ),
+                    Clause::ComplexComparisonFunctions(
+                        where_clause::Function::Between,
+                        "?age".to_string(),
+                        vec![Types::Integer(30), Types::Integer(35),]
+                    )
+                ]
+            )
+        )
+    }
+
+    #[test]
+    fn or_clause() {
+        let query = "Select * FROM my_entity WherE {
+            ?* my_entity:a ?a,
+            ?* my_entity:c ?c,
+            (== ?a 123),
+            (or
+                (>= c 4300.0)
+                (< c 6.9)
+            ),
+        }";
+        let wql = Wql::from_str(query);
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectWhere(
+                "my_entity".to_string(),
+                ToSelect::All,
+                vec![
+                    Clause::ValueAttribution(
+                        "my_entity".to_string(),
+                        "a".to_string(),
+                        Value("?a".to_string())
+                    ),
+                    Clause::ValueAttribution(
+                        "my_entity".to_string(),
+                        "c".to_string(),
+                        Value("?c".to_string())
+                    ),
+                    Clause::SimpleComparisonFunction(
+                        Function::Eq,
+                        "?a".to_string(),
+                        Types::Integer(123)
+                    ),
+                    Clause::Or(
+                        Function::Or,
+                        vec![
+                            Clause::SimpleComparisonFunction(
+                                Function::GEq,
+                                "c".to_string(),
+                                Types::Float(4300.0)
+                            ),
+                            Clause::SimpleComparisonFunction(
+                                Function::L,
+                                "c".to_string(),
+                                Types::Float(6.9)
+                            )
+                        ]
+                    )
+                ]
+            )
+        )
+    }
+}
+
+#[cfg(test)]
+mod comment {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn field_comment() {
+        let wql = Wql::from_str("COMMENT ON my_entity.email IS \"user contact address\"");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Comment(
+                "my_entity".to_string(),
+                Some("email".to_string()),
+                "user contact address".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn entity_comment() {
+        let wql = Wql::from_str("COMMENT ON my_entity IS \"all users\"");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Comment("my_entity".to_string(), None, "all users".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_is() {
+        let wql = Wql::from_str("COMMENT ON my_entity.email \"user contact address\"");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Keyword IS is required for COMMENT"))
+        );
+    }
+
+    #[test]
+    fn non_string_comment() {
+        let wql = Wql::from_str("COMMENT ON my_entity.email IS 123");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("COMMENT text must be a String"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod use_entity {
+    use super::*;
+    use crate::{parse_with_options, ParseOptions};
+    use std::str::FromStr;
+
+    #[test]
+    fn use_statement() {
+        let wql = Wql::from_str("USE my_entity");
+
+        assert_eq!(wql.unwrap(), Wql::Use("my_entity".to_string()));
+    }
+
+    #[test]
+    fn use_without_entity() {
+        let wql = Wql::from_str("USE ");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Entity name is required for USE"))
+        );
+    }
+
+    #[test]
+    fn insert_into_default_entity() {
+        let options = ParseOptions {
+            default_entity: Some("my_entity".to_string()),
+            ..ParseOptions::default()
+        };
+        let wql = parse_with_options("INSERT {a: 123,}", &options);
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
+        );
+    }
+
+    #[test]
+    fn insert_without_into_or_default_entity() {
+        let wql = Wql::from_str("INSERT {a: 123,}");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Keyword INTO is required for INSERT"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod project {
+    use super::*;
+
+    fn entity() -> Entity {
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        hm.insert("b".to_string(), Types::String("hello".to_string()));
+        hm.insert("c".to_string(), Types::Boolean(true));
+        hm
+    }
+
+    #[test]
+    fn project_subset() {
+        let projected = crate::project(&entity(), &["a".to_string(), "c".to_string()]);
+
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), Types::Integer(123));
+        expected.insert("c".to_string(), Types::Boolean(true));
+        assert_eq!(projected, expected);
+    }
+
+    #[test]
+    fn project_all_fields() {
+        let fields = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let projected = crate::project(&entity(), &fields);
+
+        assert_eq!(projected, entity());
+    }
+
+    #[test]
+    fn project_skips_absent_fields() {
+        let projected = crate::project(&entity(), &["b".to_string(), "d".to_string()]);
+
+        let mut expected = HashMap::new();
+        expected.insert("b".to_string(), Types::String("hello".to_string()));
+        assert_eq!(projected, expected);
+    }
+}
+
+#[cfg(test)]
+mod structurally_eq {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn entity(name: &str, updated_at: &str) -> Entity {
+        let mut hm = HashMap::new();
+        hm.insert("name".to_string(), Types::String(name.to_string()));
+        hm.insert(
+            "updated_at".to_string(),
+            Types::String(updated_at.to_string()),
+        );
+        hm
+    }
+
+    fn ignore() -> HashSet<String> {
+        let mut ignore = HashSet::new();
+        ignore.insert("updated_at".to_string());
+        ignore
+    }
+
+    #[test]
+    fn differ_only_in_ignored_field() {
+        let a = entity("julia", "2014-11-28T09:00:09Z");
+        let b = entity("julia", "2014-11-28T21:00:09Z");
+
+        assert!(crate::structurally_eq(&a, &b, &ignore()));
+        assert!(!crate::structurally_eq(&a, &b, &HashSet::new()));
+    }
+
+    #[test]
+    fn differ_in_compared_field() {
+        let a = entity("julia", "2014-11-28T09:00:09Z");
+        let b = entity("naomi", "2014-11-28T09:00:09Z");
+
+        assert!(!crate::structurally_eq(&a, &b, &ignore()));
+    }
+
+    #[test]
+    fn extra_compared_field() {
+        let a = entity("julia", "2014-11-28T09:00:09Z");
+        let mut b = a.clone();
+        b.insert("age".to_string(), Types::Integer(30));
+
+        assert!(!crate::structurally_eq(&a, &b, &ignore()));
+        assert!(!crate::structurally_eq(&b, &a, &ignore()));
+    }
+}
+
+#[cfg(test)]
+mod approx_eq {
+    use super::*;
+
+    #[test]
+    fn floats_within_epsilon() {
+        assert!(Types::Float(0.1 + 0.2).approx_eq(&Types::Float(0.3), 1e-9));
+        assert!(Types::Float(2.00001).approx_eq(&Types::Integer(2), 1e-3));
+        assert!(Types::Precise("2.5001".to_string()).approx_eq(&Types::Float(2.5), 1e-3));
+    }
+
+    #[test]
+    fn floats_outside_epsilon() {
+        assert!(!Types::Float(0.1).approx_eq(&Types::Float(0.2), 1e-3));
+        assert!(!Types::Integer(2).approx_eq(&Types::Float(2.1), 1e-3));
+        assert!(!Types::Float(f64::NAN).approx_eq(&Types::Float(f64::NAN), 1e-3));
+    }
+
+    #[test]
+    fn nested_values() {
+        let a = Types::Vector(vec![Types::Float(0.1 + 0.2), Types::Boolean(true)]);
+        let b = Types::Vector(vec![Types::Float(0.3), Types::Boolean(true)]);
+        assert!(a.approx_eq(&b, 1e-9));
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), a);
+        let mut other = HashMap::new();
+        other.insert("a".to_string(), b);
+        other.insert("b".to_string(), Types::Nil);
+        assert!(!Types::Map(hm).approx_eq(&Types::Map(other), 1e-9));
+    }
+
+    #[test]
+    fn non_floats_are_exact() {
+        assert!(Types::String("a".to_string()).approx_eq(&Types::String("a".to_string()), 1.0));
+        assert!(!Types::Char('a').approx_eq(&Types::Char('b'), 1.0));
+        assert!(!Types::String("1".to_string()).approx_eq(&Types::Integer(1), 1.0));
+        assert!(Types::Nil.approx_eq(&Types::Nil, 0.0));
+    }
+}
+
+#[cfg(test)]
+mod is_empty {
+    use super::*;
+
+    #[test]
+    fn empty_collections() {
+        assert_eq!(Types::Vector(Vec::new()).is_empty(), Some(true));
+        assert_eq!(Types::Map(HashMap::new()).is_empty(), Some(true));
+        assert_eq!(Types::String(String::new()).is_empty(), Some(true));
+    }
+
+    #[test]
+    fn non_empty_collections() {
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Nil);
+
+        assert_eq!(Types::Vector(vec![Types::Nil]).is_empty(), Some(false));
+        assert_eq!(Types::Map(hm).is_empty(), Some(false));
+        assert_eq!(Types::String(" ".to_string()).is_empty(), Some(false));
+    }
+
+    #[test]
+    fn scalars_are_not_collections() {
+        assert_eq!(Types::Integer(0).is_empty(), None);
+        assert_eq!(Types::Char(' ').is_empty(), None);
+        assert_eq!(Types::Nil.is_empty(), None);
+    }
+}
+
+#[cfg(test)]
+mod crlf {
+    use super::*;
+    use crate::parse_program;
+
+    #[test]
+    fn crlf_program() {
+        let program = "CREATE ENTITY my_entity\r\nUNIQUES #{name,}\r\nENCRYPT #{pswd,};\r\n\
+            INSERT {\r\n    name: \"julia\",\r\n    ids: [1,\r\n2,\r\n],\r\n}\r\nINTO\r\nmy_entity;\r\n\
+            UPDATE\r\nmy_entity\r\nSET {name: \"otavio\",\r\n}\r\nINTO\r\nd6ca73c0-41ff-4975-8a60-fc4a061ce536;\r\n\
+            SELECT\r\n*\r\nFROM my_entity\r\nIDS IN #{\r\nd6ca73c0-41ff-4975-8a60-fc4a061ce536,\r\n};\r\n\
+            SELECT * FROM my_entity WHERE {\r\n?* my_entity:name ?name,\r\n(==\r\n?name \"julia\"),\r\n};\r\n\
+            EVICT\r\nmy_entity\r\n";
+        let uuid = Uuid::parse_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap();
+        let wqls = parse_program(program)
+            .into_iter()
+            .collect::<Result<Vec<Wql>, String>>()
+            .unwrap();
+
+        let mut insert = HashMap::new();
+        insert.insert("name".to_string(), Types::String("julia".to_string()));
+        insert.insert(
+            "ids".to_string(),
+            Types::Vector(vec![Types::Integer(1), Types::Integer(2)]),
+        );
+        let mut update = HashMap::new();
+        update.insert("name".to_string(), Types::String("otavio".to_string()));
+        assert_eq!(
+            wqls,
+            vec![
+                Wql::CreateEntity(
+                    "my_entity".to_string(),
+                    vec!["name".to_string()],
+                    vec!["pswd".to_string()],
+                    Vec::new()
+                ),
+                Wql::Insert("my_entity".to_string(), insert, None, None),
+                Wql::UpdateSet("my_entity".to_string(), update, uuid),
+                Wql::SelectIds("my_entity".to_string(), ToSelect::All, vec![uuid]),
+                Wql::SelectWhere(
+                    "my_entity".to_string(),
+                    ToSelect::All,
+                    vec![
+                        Clause::ValueAttribution(
+                            "my_entity".to_string(),
+                            "name".to_string(),
+                            Value("?name".to_string())
+                        ),
+                        Clause::SimpleComparisonFunction(
+                            Function::Eq,
+                            "?name".to_string(),
+                            Types::String("julia".to_string())
+                        ),
+                    ]
+                ),
+                Wql::Evict("my_entity".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn crlf_match_condition() {
+        let wql = Wql::from_str(
+            "MATCH ALL(a\r\n== 1,\r\nb >= 3\r\n) UPDATE my_entity SET {a: 123,} INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert!(matches!(
+            wql,
+            Ok(Wql::MatchUpdate(_, _, _, MatchCondition::All(conditions))) if conditions.len() == 2
+        ));
+    }
+
+    #[test]
+    fn incomplete_match_condition_errors() {
+        let wql = Wql::from_str(
+            "MATCH ALL(a ==) UPDATE my_entity SET {a: 123,} INTO d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+        );
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Not able to parse match argument"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod spread {
+    use super::*;
+    use crate::parse_program;
+    use std::str::FromStr;
+
+    #[test]
+    fn let_map() {
+        let wql = Wql::from_str("LET base = {a: 1, b: \"hello\",}");
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(1));
+        hm.insert("b".to_string(), Types::String("hello".to_string()));
+        assert_eq!(wql.unwrap(), Wql::Let("base".to_string(), Types::Map(hm)));
+    }
+
+    #[test]
+    fn let_requires_eq() {
+        let wql = Wql::from_str("LET base {a: 1,}");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Keyword `=` is required for LET"))
+        );
+    }
+
+    #[test]
+    fn spread_with_override() {
+        let wqls = parse_program(
+            "LET base = {a: 1, b: \"hello\",};
+            INSERT {...base, b: \"world\", c: true,} INTO my_entity;
+            INSERT {c: true, ...base} INTO my_entity",
+        );
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(1));
+        hm.insert("b".to_string(), Types::String("world".to_string()));
+        hm.insert("c".to_string(), Types::Boolean(true));
+        assert_eq!(
+            wqls[1],
+            Ok(Wql::Insert("my_entity".to_string(), hm.clone(), None, None))
+        );
+
+        hm.insert("b".to_string(), Types::String("hello".to_string()));
+        assert_eq!(
+            wqls[2],
+            Ok(Wql::Insert("my_entity".to_string(), hm, None, None))
+        );
+    }
+
+    #[test]
+    fn let_scalar_and_vector() {
+        let wqls = parse_program("LET x = 3.5; LET s = \"hello\"; LET v = [1, true,]");
+
+        assert_eq!(wqls[0], Ok(Wql::Let("x".to_string(), Types::Float(3.5))));
+        assert_eq!(
+            wqls[1],
+            Ok(Wql::Let(
+                "s".to_string(),
+                Types::String("hello".to_string())
+            ))
+        );
+        assert_eq!(
+            wqls[2],
+            Ok(Wql::Let(
+                "v".to_string(),
+                Types::Vector(vec![Types::Integer(1), Types::Boolean(true)])
+            ))
+        );
+    }
+
+    #[test]
+    fn binding_as_value_in_later_statement() {
+        let wqls = parse_program(
+            "LET base = {a: 1,};
+            LET limit = 10;
+            INSERT {config: base, limit: limit,} INTO my_entity",
+        );
+
+        let mut base = HashMap::new();
+        base.insert("a".to_string(), Types::Integer(1));
+        let mut hm = HashMap::new();
+        hm.insert("config".to_string(), Types::Map(base));
+        hm.insert("limit".to_string(), Types::Integer(10));
+        assert_eq!(
+            wqls[2],
+            Ok(Wql::Insert("my_entity".to_string(), hm, None, None))
+        );
+    }
+
+    #[test]
+    fn binding_as_nested_value() {
+        let wqls = parse_program("LET x = 1; INSERT {a: x, b: [x,], c: {d: x,},} INTO e");
+
+        let mut c = HashMap::new();
+        c.insert("d".to_string(), Types::Integer(1));
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(1));
+        hm.insert("b".to_string(), Types::Vector(vec![Types::Integer(1)]));
+        hm.insert("c".to_string(), Types::Map(c));
+        assert_eq!(wqls[1], Ok(Wql::Insert("e".to_string(), hm, None, None)));
+    }
+
+    #[test]
+    fn rebinding_shadows_previous_value() {
+        let wqls = parse_program(
+            "LET limit = 10;
+            LET limit = 20;
+            INSERT {limit: limit,} INTO my_entity",
+        );
+
+        let mut hm = HashMap::new();
+        hm.insert("limit".to_string(), Types::Integer(20));
+        assert_eq!(
+            wqls[2],
+            Ok(Wql::Insert("my_entity".to_string(), hm, None, None))
+        );
+    }
+
+    #[test]
+    fn let_requires_value() {
+        let wql = Wql::from_str("LET x =   ");
+
+        assert_eq!(wql.err(), Some(String::from("Value is required for LET")));
+    }
+
+    #[test]
+    fn spread_unknown_binding() {
+        let wql = Wql::from_str("INSERT {...base, a: 1,} INTO my_entity");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Binding `base` used in spread is not defined"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod rename_field {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn alter_entity_rename_field() {
+        let wql = Wql::from_str("ALTER ENTITY my_entity RENAME FIELD old_name TO new_name");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::RenameField(
+                "my_entity".to_string(),
+                "old_name".to_string(),
+                "new_name".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rename_field_is_case_insensitive() {
+        let wql = Wql::from_str("alter entity my_entity rename field a to b");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::RenameField("my_entity".to_string(), "a".to_string(), "b".to_string())
+        );
+    }
+
+    #[test]
+    fn rename_field_requires_to() {
+        let wql = Wql::from_str("ALTER ENTITY my_entity RENAME FIELD a b");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Keyword TO is required for RENAME FIELD"))
+        );
+    }
+
+    #[test]
+    fn rename_field_requires_new_name() {
+        let wql = Wql::from_str("ALTER ENTITY my_entity RENAME FIELD a TO");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("New field name is required after TO"))
+        );
+    }
+
+    #[test]
+    fn rename_field_onto_itself() {
+        let wql = Wql::from_str("ALTER ENTITY my_entity RENAME FIELD a TO a");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Field `a` cannot be renamed to itself"))
+        );
+    }
+
+    #[test]
+    fn alter_requires_entity() {
+        let wql = Wql::from_str("ALTER my_entity RENAME FIELD a TO b");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Keyword ENTITY is required for ALTER"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod drop_field {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn alter_entity_drop_field() {
+        let wql = Wql::from_str("ALTER ENTITY my_entity DROP FIELD middle_name");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::DropField("my_entity".to_string(), "middle_name".to_string())
+        );
+        assert_eq!(
+            Wql::from_str("alter entity my_entity drop field a").unwrap(),
+            Wql::DropField("my_entity".to_string(), "a".to_string())
+        );
+    }
+
+    #[test]
+    fn drop_field_errors() {
+        assert_eq!(
+            Wql::from_str("ALTER ENTITY my_entity DROP middle_name").err(),
+            Some(String::from("Keyword FIELD is required for DROP"))
+        );
+        assert_eq!(
+            Wql::from_str("ALTER ENTITY my_entity DROP FIELD").err(),
+            Some(String::from("Field name is required for DROP FIELD"))
+        );
+        assert_eq!(
+            Wql::from_str("ALTER ENTITY my_entity DROP FIELD a b").err(),
+            Some(String::from("Unexpected trailing input: `b`"))
+        );
+        assert_eq!(
+            Wql::from_str("ALTER ENTITY my_entity REMOVE FIELD a").err(),
+            Some(String::from(
+                "Keyword RENAME FIELD or DROP FIELD is required for ALTER ENTITY"
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod create_schema {
+    use super::*;
+    use crate::{apply_defaults, TypeTag};
+    use std::str::FromStr;
+
+    #[test]
+    fn create_schema() {
+        let wql = Wql::from_str("CREATE SCHEMA my_entity {a: Float, id: Uuid,}");
+
+        let mut schema = HashMap::new();
+        schema.insert("a".to_string(), TypeTag::Float);
+        schema.insert("id".to_string(), TypeTag::Uuid);
+        assert_eq!(
+            wql.unwrap(),
+            Wql::CreateSchema("my_entity".to_string(), schema, HashMap::new())
+        );
+    }
+
+    #[test]
+    fn create_schema_with_collection_default() {
+        let wql = Wql::from_str(
+            "CREATE SCHEMA my_entity {name: String, tags: Vector OR DEFAULT [], meta: Map or default {},}",
+        );
+
+        let mut schema = HashMap::new();
+        schema.insert("name".to_string(), TypeTag::String);
+        schema.insert("tags".to_string(), TypeTag::Vector);
+        schema.insert("meta".to_string(), TypeTag::Map);
+        let mut defaults = HashMap::new();
+        defaults.insert("tags".to_string(), Types::Vector(Vec::new()));
+        defaults.insert("meta".to_string(), Types::Map(HashMap::new()));
+        assert_eq!(
+            wql.unwrap(),
+            Wql::CreateSchema("my_entity".to_string(), schema, defaults)
+        );
+    }
+
+    #[test]
+    fn default_applied_to_insert_missing_field() {
+        let defaults = match Wql::from_str(
+            "CREATE SCHEMA my_entity {name: String, tags: Vector OR DEFAULT [],}",
+        ) {
+            Ok(Wql::CreateSchema(_, _, defaults)) => defaults,
+            wql => panic!("expected CREATE SCHEMA, got {:?}", wql),
+        };
+        let mut content = match Wql::from_str("INSERT {name: \"julia\",} INTO my_entity") {
+            Ok(Wql::Insert(_, content, _, _)) => content,
+            wql => panic!("expected INSERT, got {:?}", wql),
+        };
+
+        apply_defaults(&mut content, &defaults);
+
+        assert_eq!(content["tags"], Types::Vector(Vec::new()));
+        assert_eq!(content["name"], Types::String("julia".to_string()));
+    }
+
+    #[test]
+    fn create_schema_default_type_mismatch() {
+        assert_eq!(
+            Wql::from_str("CREATE SCHEMA my_entity {tags: Vector OR DEFAULT {},}").err(),
+            Some(String::from(
+                "DEFAULT {} of `tags` should be a Vector, found Map"
+            ))
+        );
+        assert_eq!(
+            Wql::from_str("CREATE SCHEMA my_entity {tags: Vector OR [],}").err(),
+            Some(String::from(
+                "Keyword DEFAULT is required for OR in CREATE SCHEMA"
+            ))
+        );
+    }
+
+    #[test]
+    fn create_schema_unknown_type() {
+        let wql = Wql::from_str("CREATE SCHEMA my_entity {a: Double,}");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Unknown type `Double` in CREATE SCHEMA"))
+        );
+    }
+
+    #[test]
+    fn create_schema_requires_fields() {
+        let wql = Wql::from_str("CREATE SCHEMA my_entity {}");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "CREATE SCHEMA requires at least one field type"
+            ))
+        );
+    }
+
+    #[test]
+    fn create_schema_requires_entity() {
+        let wql = Wql::from_str("CREATE SCHEMA {a: Float,}");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from("Entity name is required for CREATE SCHEMA"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod create_document_store {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn create_document_store() {
+        let wql = Wql::from_str("CREATE DOCUMENT STORE my_docs");
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::CreateDocumentStore("my_docs".to_string())
+        );
+    }
+
+    #[test]
+    fn create_document_requires_store() {
+        let wql = Wql::from_str("CREATE DOCUMENT my_docs");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "Keyword STORE is required for CREATE DOCUMENT"
+            ))
+        );
+    }
+
+    #[test]
+    fn create_document_store_requires_name() {
+        let wql = Wql::from_str("CREATE DOCUMENT STORE ");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "Entity name is required for CREATE DOCUMENT STORE"
+            ))
+        );
+    }
+
+    #[test]
+    fn create_document_store_rejects_uniques() {
+        let wql = Wql::from_str("CREATE DOCUMENT STORE my_docs UNIQUES #{id,}");
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "Unexpected `UNIQUES #{id,}` after CREATE DOCUMENT STORE my_docs, document stores have no UNIQUES nor ENCRYPT"
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod single_value {
+    use super::*;
+    use crate::parse_single_value;
+    use std::str::FromStr;
+
+    #[test]
+    fn scalars() {
+        let id = "48c7640e-9287-468a-a07c-2fb00da5eaed";
+
+        assert_eq!(parse_single_value("42"), Ok(Types::Integer(42)));
+        assert_eq!(parse_single_value(" -4.5 "), Ok(Types::Float(-4.5)));
+        assert_eq!(
+            parse_single_value("98347883122138743294728345738925783257325789353593473247832493483478935673.9347324783249348347893567393473247832493483478935673P"),
+            Ok(Types::Precise("98347883122138743294728345738925783257325789353593473247832493483478935673.9347324783249348347893567393473247832493483478935673".to_string()))
+        );
+        assert_eq!(
+            parse_single_value("\"hello, world\""),
+            Ok(Types::String("hello, world".to_string()))
+        );
+        assert_eq!(parse_single_value("'c'"), Ok(Types::Char('c')));
+        assert_eq!(parse_single_value("' '"), Ok(Types::Char(' ')));
+        assert_eq!(parse_single_value(" ',' "), Ok(Types::Char(',')));
+        assert_eq!(
+            parse_single_value("' ' 1"),
+            Err(String::from("Unexpected input `1` after value"))
+        );
+        assert_eq!(parse_single_value("true"), Ok(Types::Boolean(true)));
+        assert_eq!(parse_single_value("nil"), Ok(Types::Nil));
+        assert_eq!(
+            parse_single_value(id),
+            Ok(Types::Uuid(Uuid::from_str(id).unwrap()))
+        );
+    }
+
+    #[test]
+    fn nested_structure() {
+        let value = parse_single_value("{a: [1, {b: \"c\",},], d: {e: 2.5,},}");
+
+        let mut inner = HashMap::new();
+        inner.insert("b".to_string(), Types::String("c".to_string()));
+        let mut d = HashMap::new();
+        d.insert("e".to_string(), Types::Float(2.5));
+        let mut expected = HashMap::new();
+        expected.insert(
+            "a".to_string(),
+            Types::Vector(vec![Types::Integer(1), Types::Map(inner)]),
+        );
+        expected.insert("d".to_string(), Types::Map(d));
+        assert_eq!(value, Ok(Types::Map(expected)));
+    }
+
+    #[test]
+    fn trailing_input() {
+        assert_eq!(
+            parse_single_value("1 2"),
+            Err(String::from("Unexpected input `2` after value"))
+        );
+        assert_eq!(
+            parse_single_value("[1,] INTO"),
+            Err(String::from("Unexpected input `INTO` after value"))
+        );
+        assert_eq!(
+            parse_single_value("\"a\","),
+            Err(String::from("Unexpected input `,` after value"))
+        );
+        assert_eq!(
+            parse_single_value("  "),
+            Err(String::from("A value is required"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod make_update {
+    use super::*;
+    use crate::make_update;
+    use std::str::FromStr;
+
+    fn id() -> Uuid {
+        Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap()
+    }
+
+    fn entity(fields: &[(&str, Types)]) -> Entity {
+        fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn added_and_changed_fields() {
+        let old = entity(&[("a", Types::Integer(1)), ("b", Types::Integer(2))]);
+        let new = entity(&[
+            ("a", Types::Integer(1)),
+            ("b", Types::Integer(3)),
+            ("c", Types::Boolean(true)),
+        ]);
+
+        assert_eq!(
+            make_update(&old, &new, "my_entity", id()),
+            Some(Wql::UpdateSet(
+                "my_entity".to_string(),
+                entity(&[("b", Types::Integer(3)), ("c", Types::Boolean(true))]),
+                id()
+            ))
+        );
+    }
+
+    #[test]
+    fn removed_fields_are_nil() {
+        let old = entity(&[
+            ("a", Types::Integer(1)),
+            ("b", Types::Integer(2)),
+            ("c", Types::Nil),
+        ]);
+        let new = entity(&[("a", Types::Integer(1))]);
+
+        assert_eq!(
+            make_update(&old, &new, "my_entity", id()),
+            Some(Wql::UpdateSet(
+                "my_entity".to_string(),
+                entity(&[("b", Types::Nil)]),
+                id()
+            ))
+        );
+    }
+
+    #[test]
+    fn no_change() {
+        let old = entity(&[("a", Types::Integer(1))]);
+
+        assert_eq!(make_update(&old, &old.clone(), "my_entity", id()), None);
+    }
+}
+
+#[cfg(test)]
+mod uuid_values {
+    use super::*;
+    use crate::parse_single_value;
+    use std::str::FromStr;
+
+    #[test]
+    fn valid_uuid() {
+        let id = "d6ca73c0-41ff-4975-8a60-fc4a061ce536";
 
         assert_eq!(
-            wql.unwrap(),
-            Wql::Insert("my_entity".to_string(), hashmap2())
+            parse_single_value(id),
+            Ok(Types::Uuid(Uuid::from_str(id).unwrap()))
         );
     }
 
     #[test]
-    fn insert_vec_err() {
-        let wql = Wql::from_str(
-            "INSERT {
-            a: 123,
-            b: [12.3, 34, \"hello\", nkjsld,]
-        } INTO my_entity",
-        );
+    fn near_miss_uuid() {
+        let wql = Wql::from_str("INSERT {id: d6ca73c041ff-4975-8a60-fc4a061ce536,} INTO my_entity");
 
         assert_eq!(
             wql.err(),
-            Some(String::from("Value Type could not be created from nkjsld"))
+            Some(String::from(
+                "Malformed UUID: d6ca73c041ff-4975-8a60-fc4a061ce536, hyphens should split its 32 hex digits as 8-4-4-4-12"
+            ))
+        );
+        assert_eq!(
+            parse_single_value("d6ca73c0-41ff-4975-8a60-fc4a061ce53"),
+            Err(String::from(
+                "Value Type could not be created from d6ca73c0-41ff-4975-8a60-fc4a061ce53"
+            ))
         );
     }
+}
+
+#[cfg(test)]
+mod datetime_values {
+    use super::*;
+    use crate::parse_single_value;
+    use chrono::{DateTime, Utc};
+    use std::str::FromStr;
+
+    fn utc(s: &str) -> Types {
+        Types::DateTime(DateTime::<Utc>::from_str(s).unwrap())
+    }
 
     #[test]
-    fn insert_vec_with_map() {
+    fn valid_timestamp() {
         let wql = Wql::from_str(
-            "INSERT {
-            a: 123,
-            b: { a: 12.3, b: 34, }
-        } INTO my_entity",
+            "INSERT {created_at: 2020-01-01T00:00:00Z, seen_at: 2020-01-01T09:00:00+09:00,} INTO my_entity",
         );
 
+        let mut hm = HashMap::new();
+        hm.insert("created_at".to_string(), utc("2020-01-01T00:00:00Z"));
+        hm.insert("seen_at".to_string(), utc("2020-01-01T00:00:00Z"));
         assert_eq!(
             wql.unwrap(),
-            Wql::Insert("my_entity".to_string(), hashmap3())
+            Wql::Insert("my_entity".to_string(), hm, None, None)
+        );
+        assert_eq!(
+            utc("2020-01-01T00:00:00.5Z").to_string(),
+            "2020-01-01T00:00:00.500Z"
         );
     }
 
-    fn hashmap() -> HashMap<String, Types> {
-        let mut hm = HashMap::new();
-        hm.insert("a".to_string(), Types::Integer(123));
-        hm.insert(
-            "b".to_string(),
-            Types::Vector(vec![
-                Types::Float(12.3),
-                Types::Integer(34),
-                Types::String("hello".to_string()),
-            ]),
+    #[test]
+    fn bare_year_is_integer() {
+        assert_eq!(parse_single_value("2020"), Ok(Types::Integer(2020)));
+    }
+
+    #[test]
+    fn malformed_date() {
+        assert_eq!(
+            parse_single_value("2020-13-01T00:00:00Z"),
+            Err(String::from(
+                "Malformed DateTime: 2020-13-01T00:00:00Z, expected an RFC 3339 timestamp like 2020-01-01T00:00:00Z"
+            ))
         );
-        hm
+        assert!(parse_single_value("2020-01-01").is_err());
     }
 
-    fn hashmap2() -> HashMap<String, Types> {
+    #[test]
+    fn duration_literals() {
+        let day = 24 * 60 * 60 * 1000;
+        assert_eq!(parse_single_value("P1D"), Ok(Types::Duration(day)));
+        assert_eq!(
+            parse_single_value("P1DT2H30M"),
+            Ok(Types::Duration(day + 9_000_000))
+        );
+        assert_eq!(parse_single_value("-PT0.5S"), Ok(Types::Duration(-500)));
+        assert_eq!(parse_single_value("P2W"), Ok(Types::Duration(14 * day)));
+
+        for literal in &["P1D", "P1DT2H30M", "PT5.25S", "PT0S", "-P1DT1M"] {
+            assert_eq!(
+                parse_single_value(literal).unwrap().to_string(),
+                literal.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_duration() {
+        assert_eq!(
+            parse_single_value("P1DT"),
+            Err(String::from(
+                "Malformed Duration: P1DT, expected an ISO 8601 duration like P1DT2H30M"
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod range_values {
+    use super::*;
+    use crate::parse_single_value;
+
+    fn range(start: Types, end: Types, inclusive: bool) -> Types {
+        Types::Range(Box::new(start), Box::new(end), inclusive)
+    }
+
+    #[test]
+    fn exclusive_range() {
+        let wql = Wql::from_str("INSERT {age: 18..65,} INTO my_entity");
+
         let mut hm = HashMap::new();
-        hm.insert("a".to_string(), Types::Integer(123));
         hm.insert(
-            "b".to_string(),
-            Types::Vector(vec![
-                Types::Float(12.3),
-                Types::Integer(34),
-                Types::Vector(vec![Types::String("hello".to_string())]),
-            ]),
+            "age".to_string(),
+            range(Types::Integer(18), Types::Integer(65), false),
+        );
+        assert_eq!(
+            wql.unwrap(),
+            Wql::Insert("my_entity".to_string(), hm, None, None)
+        );
+        assert_eq!(
+            parse_single_value("-1.5..2.5"),
+            Ok(range(Types::Float(-1.5), Types::Float(2.5), false))
         );
-        hm
     }
 
-    fn hashmap3() -> HashMap<String, Types> {
-        let mut inner_map = HashMap::new();
-        inner_map.insert("a".to_string(), Types::Float(12.3));
-        inner_map.insert("b".to_string(), Types::Integer(34));
-        let mut hm = HashMap::new();
-        hm.insert("a".to_string(), Types::Integer(123));
-        hm.insert("b".to_string(), Types::Map(inner_map));
-        hm
+    #[test]
+    fn inclusive_range() {
+        let value = parse_single_value("18..=65").unwrap();
+
+        assert_eq!(value, range(Types::Integer(18), Types::Integer(65), true));
+        assert_eq!(value.to_string(), "18..=65");
+        assert_eq!(
+            parse_single_value("1P..=3.5P"),
+            Ok(range(
+                Types::Precise("1".to_string()),
+                Types::Precise("3.5".to_string()),
+                true
+            ))
+        );
+    }
+
+    #[test]
+    fn mismatched_bounds() {
+        assert_eq!(
+            Wql::from_str("INSERT {age: 18..6.5,} INTO my_entity").err(),
+            Some(String::from(
+                "Range `18..6.5` bounds should have the same numeric type, found Integer and Float"
+            ))
+        );
+        assert_eq!(
+            parse_single_value("1..=2020-01-01T00:00:00Z"),
+            Err(String::from(
+                "Range `1..=2020-01-01T00:00:00Z` bounds should be Integer, Float or Precise"
+            ))
+        );
     }
 }
 
 #[cfg(test)]
-mod check {
-    use std::collections::HashMap;
-    use std::str::FromStr;
-
+mod pairs_values {
     use super::*;
+    use crate::parse_single_value;
+
+    fn pairs(entries: Vec<(&str, Types)>) -> Types {
+        Types::Pairs(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), Box::new(v)))
+                .collect(),
+        )
+    }
 
     #[test]
-    fn check_encrypt_values() {
+    fn duplicates_and_order_are_kept() {
         let wql = Wql::from_str(
-            "CHECK {
-            ssn: 123,
-            pswd: \"my-password\"
-        } FROM my_entity ID d6ca73c0-41ff-4975-8a60-fc4a061ce536",
+            "INSERT {headers: &{accept: \"text/html\", cookie: \"a=1\", accept: \"*/*\",},} INTO requests",
         );
 
-        let uuid = Uuid::from_str("d6ca73c0-41ff-4975-8a60-fc4a061ce536").unwrap();
-
+        let mut hm = HashMap::new();
+        hm.insert(
+            "headers".to_string(),
+            pairs(vec![
+                ("accept", Types::String("text/html".to_string())),
+                ("cookie", Types::String("a=1".to_string())),
+                ("accept", Types::String("*/*".to_string())),
+            ]),
+        );
         assert_eq!(
             wql.unwrap(),
-            Wql::CheckValue("my_entity".to_string(), uuid, hashmap())
+            Wql::Insert("requests".to_string(), hm, None, None)
         );
     }
 
-    fn hashmap() -> HashMap<String, String> {
-        let mut hm = HashMap::new();
-        hm.insert("ssn".to_string(), "123".to_string());
-        hm.insert("pswd".to_string(), "my-password".to_string());
-        hm
+    #[test]
+    fn nested_pairs() {
+        assert_eq!(
+            parse_single_value("[&{a: 1, a: &{b: [2,],},}, {c: 3,},]"),
+            Ok(Types::Vector(vec![
+                pairs(vec![
+                    ("a", Types::Integer(1)),
+                    (
+                        "a",
+                        pairs(vec![("b", Types::Vector(vec![Types::Integer(2)]))])
+                    ),
+                ]),
+                parse_single_value("{c: 3,}").unwrap(),
+            ]))
+        );
+        assert_eq!(parse_single_value("&{}"), Ok(pairs(Vec::new())));
+    }
+
+    #[test]
+    fn pairs_round_trip() {
+        let value = parse_single_value("&{b: 2, a: 1, b: 3,}").unwrap();
+
+        assert_eq!(value.to_string(), "&{b: 2, a: 1, b: 3, }");
+        assert_eq!(parse_single_value(&value.to_string()), Ok(value));
+    }
+
+    #[test]
+    fn malformed_pairs() {
+        assert_eq!(
+            Wql::from_str("INSERT {a: &b,} INTO my_entity").err(),
+            Some(String::from(
+                "Pairs should start with `&{` and end with `}`"
+            ))
+        );
     }
 }
 
 #[cfg(test)]
-mod test_where {
+mod nested_values {
     use super::*;
+    use crate::{parse_single_value, parse_with_options, ParseOptions};
+
+    fn nested_insert(depth: usize) -> String {
+        format!(
+            "INSERT {{a: {}1,{} b: {{c: 2,}},}} INTO my_entity",
+            "[".repeat(depth),
+            "],".repeat(depth)
+        )
+    }
+
+    fn vector_depth(mut value: &Types) -> usize {
+        let mut depth = 0;
+        while let Types::Vector(vec) = value {
+            depth += 1;
+            value = &vec[0];
+        }
+        depth
+    }
 
     #[test]
-    fn where_ok() {
-        let query = "Select * FROM my_entity WherE {
-            (in ?id 32434 45345 345346436),
-            (between ?age 30 35),
-        }";
-        let wql = Wql::from_str(query);
+    fn deep_nesting_in_small_stack() {
+        let options = ParseOptions {
+            max_depth: 20_000,
+            ..ParseOptions::default()
+        };
+        let wql = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || parse_with_options(&nested_insert(10_000), &options))
+            .unwrap()
+            .join()
+            .unwrap();
+
+        match wql {
+            Ok(Wql::Insert(_, entity, _, _)) => {
+                assert_eq!(vector_depth(&entity["a"]), 10_000);
+                assert_eq!(entity["b"], parse_single_value("{c: 2,}").unwrap());
+            }
+            wql => panic!("unexpected {:?}", wql),
+        }
+    }
 
+    #[test]
+    fn default_max_depth() {
+        assert!(Wql::from_str(&nested_insert(128)).is_ok());
         assert_eq!(
-            wql.unwrap(),
-            Wql::SelectWhere(
-                "my_entity".to_string(),
-                ToSelect::All,
-                vec![
-                    Clause::ComplexComparisonFunctions(
-                        where_clause::Function::In,
-                        "?id".to_string(),
-                        vec![
-                            Types::Integer(32434),
-                            Types::Integer(45345),
-                            Types::Integer(345346436),
-                        ]
-                    ),
-                    Clause::ComplexComparisonFunctions(
-                        where_clause::Function::Between,
-                        "?age".to_string(),
-                        vec![Types::Integer(30), Types::Integer(35),]
-                    )
-                ]
+            Wql::from_str(&nested_insert(129)).err(),
+            Some(String::from(
+                "Maps and vectors cannot be nested deeper than 128 levels"
+            ))
+        );
+        assert_eq!(
+            parse_single_value(&format!("{}1,{}", "[".repeat(129), "],".repeat(129))).err(),
+            Some(String::from(
+                "Maps and vectors cannot be nested deeper than 128 levels"
+            ))
+        );
+    }
+
+    #[test]
+    fn configured_max_depth() {
+        let options = ParseOptions {
+            max_depth: 2,
+            ..ParseOptions::default()
+        };
+
+        assert!(parse_with_options("INSERT {a: [{b: 1,},],} INTO my_entity", &options).is_ok());
+        assert_eq!(
+            parse_with_options(
+                "INSERT {a: [{b: (Vector) [1,],},],} INTO my_entity",
+                &options
             )
-        )
+            .err(),
+            Some(String::from(
+                "Maps and vectors cannot be nested deeper than 2 levels"
+            ))
+        );
     }
+}
+
+#[cfg(test)]
+mod begin {
+    use super::*;
+    use std::str::FromStr;
 
     #[test]
-    fn or_clause() {
-        let query = "Select * FROM my_entity WherE {
-            ?* my_entity:a ?a,
-            ?* my_entity:c ?c,
-            (== ?a 123),
-            (or
-                (>= c 4300.0)
-                (< c 6.9)
+    fn isolation_levels() {
+        let levels = vec![
+            ("BEGIN", IsolationLevel::Serializable),
+            ("BEGIN ISOLATION SERIALIZABLE", IsolationLevel::Serializable),
+            (
+                "begin isolation read committed",
+                IsolationLevel::ReadCommitted,
             ),
-        }";
-        let wql = Wql::from_str(query);
+            (
+                "BEGIN ISOLATION READ  UNCOMMITTED",
+                IsolationLevel::ReadUncommitted,
+            ),
+            (
+                "BEGIN ISOLATION REPEATABLE READ\n",
+                IsolationLevel::RepeatableRead,
+            ),
+        ];
+
+        for (query, level) in levels {
+            assert_eq!(Wql::from_str(query), Ok(Wql::Begin(level)), "{}", query);
+        }
+    }
 
+    #[test]
+    fn unknown_isolation_level() {
         assert_eq!(
-            wql.unwrap(),
-            Wql::SelectWhere(
-                "my_entity".to_string(),
-                ToSelect::All,
-                vec![
-                    Clause::ValueAttribution(
-                        "my_entity".to_string(),
-                        "a".to_string(),
-                        Value("?a".to_string())
-                    ),
-                    Clause::ValueAttribution(
-                        "my_entity".to_string(),
-                        "c".to_string(),
-                        Value("?c".to_string())
-                    ),
-                    Clause::SimpleComparisonFunction(
-                        Function::Eq,
-                        "?a".to_string(),
-                        Types::Integer(123)
-                    ),
-                    Clause::Or(
-                        Function::Or,
-                        vec![
-                            Clause::SimpleComparisonFunction(
-                                Function::GEq,
-                                "c".to_string(),
-                                Types::Float(4300.0)
-                            ),
-                            Clause::SimpleComparisonFunction(
-                                Function::L,
-                                "c".to_string(),
-                                Types::Float(6.9)
-                            )
-                        ]
-                    )
-                ]
-            )
-        )
+            Wql::from_str("BEGIN ISOLATION SNAPSHOT").err(),
+            Some(String::from("Unknown isolation level `SNAPSHOT`, expected READ UNCOMMITTED, READ COMMITTED, REPEATABLE READ or SERIALIZABLE"))
+        );
+        assert_eq!(
+            Wql::from_str("BEGIN ISOLATION").err(),
+            Some(String::from("Isolation level is required after ISOLATION"))
+        );
+        assert_eq!(
+            Wql::from_str("BEGIN SERIALIZABLE").err(),
+            Some(String::from("Keyword ISOLATION is required for BEGIN"))
+        );
     }
 }
@@ -0,0 +1,70 @@
+//! Assertions against the canonical parser, enabled by the `testkit` feature for crates that generate WQL.
+use std::str::FromStr;
+
+use crate::Wql;
+
+/// Panics unless `input` parses into `expected`.
+pub fn assert_parses_to(input: &str, expected: Wql) {
+    assert_eq!(
+        Wql::from_str(input),
+        Ok(expected),
+        "WQL `{}` did not parse into the expected statement",
+        input
+    );
+}
+
+/// Panics unless parsing `input` fails with exactly `expected_msg`.
+pub fn assert_parse_error(input: &str, expected_msg: &str) {
+    assert_eq!(
+        Wql::from_str(input).err().as_deref(),
+        Some(expected_msg),
+        "WQL `{}` did not fail with the expected error",
+        input
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ToSelect, Types};
+    use std::collections::HashMap;
+
+    #[test]
+    fn parses_to() {
+        assert_parses_to(
+            "CREATE ENTITY my_entity",
+            Wql::CreateEntity("my_entity".to_string(), Vec::new(), Vec::new(), Vec::new()),
+        );
+
+        let mut hm = HashMap::new();
+        hm.insert("a".to_string(), Types::Integer(123));
+        assert_parses_to(
+            "INSERT {a: 123,} INTO my_entity",
+            Wql::Insert("my_entity".to_string(), hm, None, None),
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_parse_error("", "Empty WQL");
+        assert_parse_error(
+            "CREATE SHIT oh_yeah",
+            "Keyword ENTITY is required for CREATE",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "did not parse into the expected statement")]
+    fn parses_to_panics_on_mismatch() {
+        assert_parses_to(
+            "SELECT * FROM my_entity",
+            Wql::Select("other_entity".to_string(), ToSelect::All, None),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "did not fail with the expected error")]
+    fn parse_error_panics_on_success() {
+        assert_parse_error("SELECT * FROM my_entity", "Empty WQL");
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::HashSet;
+
+use crate::{node_id::statement_name, Entity, ToSelect, Wql};
+
+impl Wql {
+    /// Checks the invariants the parser guarantees, for statements built without it: entity names are
+    /// alphanumeric, entity maps have no empty keys, field lists have no duplicates and lists of ids,
+    /// fields and field types are not empty. A parsed `Wql` is always valid.
+    pub fn validate(&self) -> Result<(), String> {
+        let statement = statement_name(self);
+        if let Some(entity) = self.entity_name() {
+            entity_name(entity, statement)?;
+        }
+
+        match self {
+            Wql::CreateEntity(_, uniques, encrypts, primary_key) => {
+                distinct_fields(uniques, statement)?;
+                distinct_fields(encrypts, statement)?;
+                distinct_fields(primary_key, statement)?;
+                if uniques.iter().any(|field| encrypts.contains(field)) {
+                    return Err(String::from("Encrypted arguments cannot be set to UNIQUE"));
+                }
+                if primary_key.iter().any(|field| encrypts.contains(field)) {
+                    return Err(String::from(
+                        "Encrypted arguments cannot be set to PRIMARY KEY",
+                    ));
+                }
+                Ok(())
+            }
+            Wql::Insert(_, entity, _, _)
+            | Wql::UpdateContent(_, entity, _)
+            | Wql::UpdateSet(_, entity, _)
+            | Wql::MatchUpdate(_, entity, _, _) => entity_keys(entity, statement),
+            Wql::NestedInsert(insert, sub_inserts) => {
+                let entity = match insert.as_ref() {
+                    Wql::Insert(_, entity, _, _) => entity,
+                    _ => return Err(String::from("Sub-inserts can only be nested in an INSERT")),
+                };
+                insert.validate()?;
+                let mut fields = HashSet::new();
+                sub_inserts.iter().try_for_each(|(field, sub_insert)| {
+                    if entity.contains_key(field) || !fields.insert(field) {
+                        return Err(format!("Duplicate key `{}` in entity map", field));
+                    }
+                    match sub_insert {
+                        Wql::Insert(_, _, _, _) => sub_insert.validate(),
+                        Wql::NestedInsert(_, _) => {
+                            Err(String::from("Sub-inserts cannot contain sub-inserts"))
+                        }
+                        _ => Err(String::from("Sub-insert should be an INSERT")),
+                    }
+                })
+            }
+            Wql::InsertMany(_, entities) if entities.is_empty() => Err(String::from(
+                "At least one entity map is required to INSERT many entities",
+            )),
+            Wql::InsertMany(_, entities) => entities
+                .iter()
+                .try_for_each(|entity| entity_keys(entity, statement)),
+            Wql::DeleteMany(_, ids) if ids.is_empty() => Err(String::from(
+                "At least one Uuid is required in `WHERE id IN (...)`",
+            )),
+            Wql::SelectIds(_, _, ids) if ids.is_empty() => Err(String::from(
+                "At least one Uuid is required for SELECT IDS IN",
+            )),
+            Wql::SelectPage(_, _, _, Some(0)) => {
+                Err(String::from("LIMIT requires a positive integer"))
+            }
+            Wql::SelectSample(_, _, 0, _) => {
+                Err(String::from("SAMPLE requires a positive integer"))
+            }
+            Wql::Select(_, to_select, _)
+            | Wql::SelectWhen(_, to_select, _, _)
+            | Wql::SelectIds(_, to_select, _)
+            | Wql::SelectPage(_, to_select, _, _)
+            | Wql::SelectSample(_, to_select, _, _)
+            | Wql::SelectWhere(_, to_select, _) => selected_fields(to_select, statement),
+            Wql::CheckValue(_, _, fields) if fields.is_empty() => {
+                Err(String::from("CHECK requires at least one field"))
+            }
+            Wql::CreateSchema(_, fields, _) if fields.is_empty() => Err(String::from(
+                "CREATE SCHEMA requires at least one field type",
+            )),
+            Wql::RenameField(_, field, _) if field.is_empty() => {
+                Err(String::from("Field name is required for RENAME FIELD"))
+            }
+            Wql::RenameField(_, _, new_field) if new_field.is_empty() => {
+                Err(String::from("New field name is required after TO"))
+            }
+            Wql::DropField(_, field) if field.is_empty() => {
+                Err(String::from("Field name is required for DROP FIELD"))
+            }
+            Wql::Let(name, _) if name.is_empty() => {
+                Err(String::from("Binding name is required for LET"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn entity_name(entity: &str, statement: &str) -> Result<(), String> {
+    if entity.is_empty() {
+        Err(format!("Entity name is required for {}", statement))
+    } else if !entity.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Err(format!(
+            "Entity name `{}` should only contain alphanumeric characters and `_`",
+            entity
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn entity_keys(entity: &Entity, statement: &str) -> Result<(), String> {
+    if entity.keys().any(String::is_empty) {
+        Err(format!("Entity map keys cannot be empty in {}", statement))
+    } else {
+        Ok(())
+    }
+}
+
+fn distinct_fields<'a>(
+    fields: impl IntoIterator<Item = &'a String>,
+    statement: &str,
+) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    fields.into_iter().try_for_each(|field| {
+        if field.is_empty() {
+            Err(format!("Field names cannot be empty in {}", statement))
+        } else if !seen.insert(field) {
+            Err(format!("Duplicate field `{}` in {}", field, statement))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+fn selected_fields(to_select: &ToSelect, statement: &str) -> Result<(), String> {
+    match to_select {
+        ToSelect::All => Ok(()),
+        ToSelect::Keys(keys) if keys.is_empty() => Err(String::from("Fields list cannot be empty")),
+        ToSelect::Keys(keys) => distinct_fields(keys, statement),
+        ToSelect::Computed(keys, computed) => distinct_fields(
+            keys.iter().chain(computed.iter().map(|(alias, _)| alias)),
+            statement,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Types;
+    use std::{collections::HashMap, str::FromStr};
+    use uuid::Uuid;
+
+    fn insert(entity: &str, keys: &[&str]) -> Wql {
+        let entity_map = keys
+            .iter()
+            .map(|key| (key.to_string(), Types::Integer(1)))
+            .collect();
+        Wql::Insert(entity.to_string(), entity_map, None, None)
+    }
+
+    #[test]
+    fn parsed_statements_are_valid() {
+        for query in &[
+            "CREATE ENTITY my_entity PRIMARY KEY (id) UNIQUES #{name,} ENCRYPT #{pswd,}",
+            "INSERT {a: 1, b: (INSERT {c: 2,} INTO other),} INTO my_entity",
+            "SELECT #{a, (b + c) AS total,} FROM my_entity",
+            "SELECT * FROM my_entity LIMIT 3",
+            "LET limit = 3",
+        ] {
+            assert_eq!(Wql::from_str(query).unwrap().validate(), Ok(()));
+        }
+        assert_eq!(insert("my_entity", &["a", "b"]).validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_entity_names() {
+        assert_eq!(
+            insert("", &["a"]).validate(),
+            Err(String::from("Entity name is required for INSERT"))
+        );
+        assert_eq!(
+            Wql::Select("my entity".to_string(), ToSelect::All, None).validate(),
+            Err(String::from(
+                "Entity name `my entity` should only contain alphanumeric characters and `_`"
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert_eq!(
+            insert("my_entity", &["a", ""]).validate(),
+            Err(String::from("Entity map keys cannot be empty in INSERT"))
+        );
+        assert_eq!(
+            Wql::CreateEntity(
+                "my_entity".to_string(),
+                vec!["name".to_string(), "name".to_string()],
+                Vec::new(),
+                Vec::new()
+            )
+            .validate(),
+            Err(String::from("Duplicate field `name` in CREATE"))
+        );
+        assert_eq!(
+            Wql::CreateEntity(
+                "my_entity".to_string(),
+                vec!["name".to_string()],
+                vec!["name".to_string()],
+                Vec::new()
+            )
+            .validate(),
+            Err(String::from("Encrypted arguments cannot be set to UNIQUE"))
+        );
+        assert_eq!(
+            Wql::Select(
+                "my_entity".to_string(),
+                ToSelect::Keys(vec!["a".to_string(), "a".to_string()]),
+                None
+            )
+            .validate(),
+            Err(String::from("Duplicate field `a` in SELECT"))
+        );
+        assert_eq!(
+            Wql::SelectIds(
+                "my_entity".to_string(),
+                ToSelect::Keys(Vec::new()),
+                vec![Uuid::nil()]
+            )
+            .validate(),
+            Err(String::from("Fields list cannot be empty"))
+        );
+        assert_eq!(
+            Wql::CreateSchema("my_entity".to_string(), HashMap::new(), HashMap::new()).validate(),
+            Err(String::from(
+                "CREATE SCHEMA requires at least one field type"
+            ))
+        );
+    }
+
+    #[test]
+    fn empty_lists() {
+        assert_eq!(
+            Wql::InsertMany("my_entity".to_string(), Vec::new()).validate(),
+            Err(String::from(
+                "At least one entity map is required to INSERT many entities"
+            ))
+        );
+        assert_eq!(
+            Wql::DeleteMany("my_entity".to_string(), Vec::new()).validate(),
+            Err(String::from(
+                "At least one Uuid is required in `WHERE id IN (...)`"
+            ))
+        );
+        assert_eq!(
+            Wql::SelectIds("my_entity".to_string(), ToSelect::All, Vec::new()).validate(),
+            Err(String::from(
+                "At least one Uuid is required for SELECT IDS IN"
+            ))
+        );
+        assert_eq!(
+            Wql::SelectPage("my_entity".to_string(), ToSelect::All, None, Some(0)).validate(),
+            Err(String::from("LIMIT requires a positive integer"))
+        );
+    }
+
+    #[test]
+    fn invalid_sub_inserts() {
+        let nested = |outer: Wql, sub_inserts: Vec<(&str, Wql)>| {
+            Wql::NestedInsert(
+                Box::new(outer),
+                sub_inserts
+                    .into_iter()
+                    .map(|(field, wql)| (field.to_string(), wql))
+                    .collect(),
+            )
+            .validate()
+        };
+
+        assert_eq!(
+            nested(
+                insert("posts", &["title"]),
+                vec![("author", insert("authors", &["name"]))]
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            nested(
+                insert("posts", &["author"]),
+                vec![("author", insert("authors", &["name"]))]
+            ),
+            Err(String::from("Duplicate key `author` in entity map"))
+        );
+        assert_eq!(
+            nested(
+                insert("posts", &["title"]),
+                vec![("author", insert("", &["name"]))]
+            ),
+            Err(String::from("Entity name is required for INSERT"))
+        );
+        assert_eq!(
+            nested(
+                insert("posts", &["title"]),
+                vec![("author", Wql::Use("authors".to_string()))]
+            ),
+            Err(String::from("Sub-insert should be an INSERT"))
+        );
+        assert_eq!(
+            nested(
+                Wql::Use("posts".to_string()),
+                vec![("author", insert("authors", &["name"]))]
+            ),
+            Err(String::from("Sub-inserts can only be nested in an INSERT"))
+        );
+    }
+}
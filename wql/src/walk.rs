@@ -0,0 +1,163 @@
+use crate::Types;
+
+/// A step in the path from the root `Types` to a visited node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSeg {
+    Key(String),
+    Index(usize),
+}
+
+impl Types {
+    /// Visits `self` and every nested value of `Vector`s, `Map`s and `Pairs`, parents before children.
+    pub fn walk<F: FnMut(&[PathSeg], &Types)>(&self, f: &mut F) {
+        let mut path = Vec::new();
+        self.walk_path(&mut path, f);
+    }
+
+    /// Same as `walk` but allows `f` to modify the visited value. Children are visited after `f`
+    /// is applied to their parent, so replacing a `Map` skips its old content.
+    pub fn walk_mut<F: FnMut(&[PathSeg], &mut Types)>(&mut self, f: &mut F) {
+        let mut path = Vec::new();
+        self.walk_path_mut(&mut path, f);
+    }
+
+    /// Navigates a dotted path like `a.b.0.c`, where numeric segments index `Vector`s and the segment of
+    /// a duplicated key of `Pairs` navigates its first value.
+    /// Returns `None` for missing keys, out of bounds indexes or segments of the wrong type.
+    pub fn get_path(&self, path: &str) -> Option<&Types> {
+        path.split('.').try_fold(self, |value, seg| match value {
+            Types::Map(map) => map.get(seg),
+            Types::Pairs(pairs) => pairs
+                .iter()
+                .find(|(k, _)| k == seg)
+                .map(|(_, v)| v.as_ref()),
+            Types::Vector(vec) => seg.parse::<usize>().ok().and_then(|i| vec.get(i)),
+            _ => None,
+        })
+    }
+
+    fn walk_path<F: FnMut(&[PathSeg], &Types)>(&self, path: &mut Vec<PathSeg>, f: &mut F) {
+        f(path, self);
+        match self {
+            Types::Vector(vec) => vec.iter().enumerate().for_each(|(i, v)| {
+                path.push(PathSeg::Index(i));
+                v.walk_path(path, f);
+                path.pop();
+            }),
+            Types::Map(map) => map.iter().for_each(|(k, v)| {
+                path.push(PathSeg::Key(k.to_owned()));
+                v.walk_path(path, f);
+                path.pop();
+            }),
+            Types::Pairs(pairs) => pairs.iter().for_each(|(k, v)| {
+                path.push(PathSeg::Key(k.to_owned()));
+                v.walk_path(path, f);
+                path.pop();
+            }),
+            _ => (),
+        }
+    }
+
+    fn walk_path_mut<F: FnMut(&[PathSeg], &mut Types)>(
+        &mut self,
+        path: &mut Vec<PathSeg>,
+        f: &mut F,
+    ) {
+        f(path, self);
+        match self {
+            Types::Vector(vec) => vec.iter_mut().enumerate().for_each(|(i, v)| {
+                path.push(PathSeg::Index(i));
+                v.walk_path_mut(path, f);
+                path.pop();
+            }),
+            Types::Map(map) => map.iter_mut().for_each(|(k, v)| {
+                path.push(PathSeg::Key(k.to_owned()));
+                v.walk_path_mut(path, f);
+                path.pop();
+            }),
+            Types::Pairs(pairs) => pairs.iter_mut().for_each(|(k, v)| {
+                path.push(PathSeg::Key(k.to_owned()));
+                v.walk_path_mut(path, f);
+                path.pop();
+            }),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn person() -> Types {
+        let mut address = HashMap::new();
+        address.insert("street".to_string(), Types::String("Rua 1".to_string()));
+        address.insert(
+            "numbers".to_string(),
+            Types::Vector(vec![Types::Integer(1), Types::Integer(2)]),
+        );
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), Types::String("julia".to_string()));
+        map.insert("address".to_string(), Types::Map(address));
+        Types::Map(map)
+    }
+
+    #[test]
+    fn get_deep_path() {
+        assert_eq!(
+            person().get_path("address.numbers.1"),
+            Some(&Types::Integer(2))
+        );
+        assert_eq!(
+            person().get_path("address.street"),
+            Some(&Types::String("Rua 1".to_string()))
+        );
+    }
+
+    #[test]
+    fn get_path_out_of_bounds() {
+        assert_eq!(person().get_path("address.numbers.2"), None);
+    }
+
+    #[test]
+    fn get_path_wrong_type() {
+        assert_eq!(person().get_path("name.first"), None);
+        assert_eq!(person().get_path("address.numbers.first"), None);
+    }
+
+    #[test]
+    fn walk_visits_every_node() {
+        let mut paths = Vec::new();
+        person().walk(&mut |path, _| paths.push(path.to_vec()));
+
+        assert_eq!(paths.len(), 7);
+        assert!(paths.contains(&vec![
+            PathSeg::Key("address".to_string()),
+            PathSeg::Key("numbers".to_string()),
+            PathSeg::Index(1)
+        ]));
+    }
+
+    #[test]
+    fn walk_mut_nulls_values_under_key() {
+        let mut value = person();
+        value.walk_mut(&mut |path, v| {
+            let under_address = path.first() == Some(&PathSeg::Key("address".to_string()));
+            if under_address && path.len() > 1 && !matches!(v, Types::Map(_) | Types::Vector(_)) {
+                *v = Types::Nil;
+            }
+        });
+
+        let mut address = HashMap::new();
+        address.insert("street".to_string(), Types::Nil);
+        address.insert(
+            "numbers".to_string(),
+            Types::Vector(vec![Types::Nil, Types::Nil]),
+        );
+        let mut expected = HashMap::new();
+        expected.insert("name".to_string(), Types::String("julia".to_string()));
+        expected.insert("address".to_string(), Types::Map(address));
+        assert_eq!(value, Types::Map(expected));
+    }
+}
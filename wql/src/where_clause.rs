@@ -1,6 +1,6 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::{logic::parse_value, ToSelect, Types, Wql};
+use crate::{logic::parse_value, Entity, ToSelect, Types, Wql};
 use serde::{Deserialize, Serialize};
 
 pub fn where_selector(
@@ -8,6 +8,15 @@ pub fn where_selector(
     arg: ToSelect,
     chars: &mut std::str::Chars,
 ) -> Result<Wql, String> {
+    let clauses = read_where_clauses(&entity_name, chars)?;
+
+    Ok(Wql::SelectWhere(entity_name, arg, clauses))
+}
+
+pub(crate) fn read_where_clauses(
+    entity_name: &str,
+    chars: &mut std::str::Chars,
+) -> Result<Vec<Clause>, String> {
     let mut open = chars.skip_while(|c| c.is_whitespace()).take(1);
 
     if open.next() != Some('{') {
@@ -18,58 +27,146 @@ pub fn where_selector(
 
     let mut clauses = Vec::new();
     let mut clause = String::new();
+    let mut depth = 0usize;
     loop {
         match chars.next() {
-            Some(',') => {
+            Some(',') if depth == 0 => {
                 clauses.push(clause);
                 clause = String::new();
             }
             Some('}') | None => break,
-            Some(c) => clause.push(c),
+            Some(c) => {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth = depth.saturating_sub(1),
+                    _ => (),
+                }
+                clause.push(c)
+            }
         }
     }
 
     let clauses = clauses
         .into_iter()
         .filter(|c| !c.is_empty())
-        .map(|c| {
-            let mut chs = c.trim().chars();
-            set_clause(&entity_name, &mut chs)
+        .map(|c| match tuple_eq(c.trim()) {
+            Some(tuple) => tuple,
+            None => {
+                let mut chs = c.trim().chars();
+                Ok(set_clause(entity_name, &mut chs))
+            }
         })
-        .collect::<Vec<Clause>>();
+        .collect::<Result<Vec<Clause>, String>>()?;
     if clauses.is_empty() {
         return Err(String::from("WHERE clause cannot be empty"));
     }
 
-    Ok(Wql::SelectWhere(entity_name, arg, clauses))
+    Ok(clauses)
+}
+
+/// Reads a composite key equality `(tenant, id) == ("acme", 48c7640e-...)`.
+fn tuple_eq(clause: &str) -> Option<Result<Clause, String>> {
+    let (keys, values) = clause.split_once("==")?;
+    let keys = keys.trim().strip_prefix('(')?.strip_suffix(')')?;
+    let values = values.trim().strip_prefix('(')?.strip_suffix(')')?;
+    if keys.contains(['(', ')']) {
+        return None;
+    }
+
+    let keys = keys
+        .split(',')
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .map(String::from)
+        .collect::<Vec<String>>();
+    let values = match split_values(values) {
+        Ok(values) => values,
+        Err(e) => return Some(Err(e)),
+    };
+
+    Some(if keys.is_empty() {
+        Err(String::from("Tuple equality requires at least one field"))
+    } else if keys.len() != values.len() {
+        Err(format!(
+            "Tuple equality requires the same arity on both sides, found {} fields and {} values",
+            keys.len(),
+            values.len()
+        ))
+    } else {
+        Ok(Clause::TupleEq(keys, values))
+    })
+}
+
+/// Reads `key IS EMPTY` and `key IS NOT EMPTY`, where the key is a `?variable` or a field name.
+fn is_empty_clause(clause: &str) -> Option<Clause> {
+    let words = clause
+        .split_whitespace()
+        .map(str::to_uppercase)
+        .collect::<Vec<String>>();
+    let key = clause.split_whitespace().next()?.to_string();
+    match words
+        .iter()
+        .skip(1)
+        .map(String::as_str)
+        .collect::<Vec<&str>>()[..]
+    {
+        ["IS", "EMPTY"] => Some(Clause::IsEmpty(key)),
+        ["IS", "NOT", "EMPTY"] => Some(Clause::IsNotEmpty(key)),
+        _ => None,
+    }
+}
+
+/// Splits comma separated values, commas inside `String`s are kept.
+fn split_values(values: &str) -> Result<Vec<Types>, String> {
+    let mut splits = Vec::new();
+    let mut value = String::new();
+    let mut in_str = false;
+    let mut escaped = false;
+    for c in values.chars() {
+        match c {
+            ',' if !in_str => splits.push(std::mem::take(&mut value)),
+            '"' if !escaped => {
+                in_str = !in_str;
+                value.push(c)
+            }
+            _ => value.push(c),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    splits.push(value);
+
+    splits
+        .iter()
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            let mut chs = v.chars();
+            parse_value(chs.next().unwrap(), &mut chs)
+        })
+        .collect()
 }
 
 fn set_clause(entity_name: &str, chs: &mut std::str::Chars) -> Clause {
-    let c_str: String = chs
-        .skip_while(|c| c.is_whitespace())
-        .take_while(|c| c != &',')
-        .collect();
+    let c_str: String = chs.skip_while(|c| c.is_whitespace()).collect();
 
     if c_str.starts_with("?*") {
         clause_entity_definition(entity_name, &c_str)
     } else if c_str.starts_with('(') && c_str.ends_with(')') {
         clause_function(entity_name, &c_str[1..c_str.len() - 1])
+    } else if let Some(clause) = is_empty_clause(&c_str) {
+        clause
     } else {
         Clause::Error
     }
 }
 fn clause_function(entity_name: &str, clause: &str) -> Clause {
-    let args: Vec<&str> = clause
-        .split(' ')
-        .filter(|c| !c.is_empty())
-        .map(str::trim)
-        .collect();
+    let args: Vec<&str> = clause.split_whitespace().collect();
     if args.len() < 3 {
         return Clause::Error;
     }
 
     match &args[0].to_lowercase()[..] {
-        ">=" | ">" | "==" | "<=" | "<" | "like" => {
+        ">=" | ">" | "==" | "!=" | "<>" | "<=" | "<" | "like" | "ilike" | "contains" => {
             let mut chs = args[2].chars();
             let function = Function::from_str(args[0]).unwrap();
             if Function::Error == function {
@@ -99,30 +196,51 @@ fn clause_function(entity_name: &str, clause: &str) -> Clause {
                 Clause::ComplexComparisonFunctions(function, key, values)
             }
         }
-        "or" => {
-            let clauses = or_clauses(entity_name, clause);
-            Clause::Or(Function::Or, clauses)
+        "or" | "xor" | "and" | "not" => {
+            let function = match &args[0].to_lowercase()[..] {
+                "xor" => Function::Xor,
+                "and" => Function::And,
+                "not" => Function::Not,
+                _ => Function::Or,
+            };
+            let clauses = or_clauses(entity_name, &clause.trim_start()[args[0].len()..]);
+            Clause::Or(function, clauses)
         }
         _ => Clause::Error,
     }
 }
 
+/// Reads the clauses of an `(or ...)`, `(xor ...)`, `(and ...)` or `(not ...)` after its function name.
 fn or_clauses(entity_name: &str, clause: &str) -> Vec<Clause> {
-    let mut chars = clause[2..].chars();
+    let mut chars = clause.chars();
     let mut clauses = Vec::new();
     let mut clause = String::new();
+    // Nested `(or ...)` and the other compound clauses are kept whole as a single clause.
+    let mut depth = 0usize;
     loop {
         match chars.next() {
-            Some(',') => {
+            Some(',') if depth == 0 => {
                 clauses.push(clause);
                 clause = String::new();
             }
-            Some(')') => {
+            Some(')') if depth <= 1 => {
+                depth = 0;
                 clause.push(')');
                 clauses.push(clause);
                 clause = String::new();
             }
-            Some('(') => clause = String::from('('),
+            Some(')') => {
+                depth -= 1;
+                clause.push(')');
+            }
+            Some('(') if depth == 0 => {
+                depth = 1;
+                clause = String::from('(');
+            }
+            Some('(') => {
+                depth += 1;
+                clause.push('(');
+            }
             Some(c) => clause.push(c),
             None => break,
         }
@@ -138,11 +256,7 @@ fn or_clauses(entity_name: &str, clause: &str) -> Vec<Clause> {
 }
 
 fn clause_entity_definition(entity_name: &str, clause: &str) -> Clause {
-    let elements = clause
-        .split(' ')
-        .filter(|c| !c.is_empty())
-        .map(str::trim)
-        .collect::<Vec<&str>>();
+    let elements = clause.split_whitespace().collect::<Vec<&str>>();
     if elements.len() != 3 {
         return Clause::Error;
     }
@@ -172,6 +286,177 @@ fn clause_entity_definition(entity_name: &str, clause: &str) -> Clause {
     }
 }
 
+/// Evaluates WHERE `clauses` against an `Entity`, without the need of a database.
+/// All clauses must match, a clause over a missing field does not match and `Clause::Error` never matches.
+/// Inside `(or ...)` and functions, keys can be a `?variable` bound by `?* entity:key ?variable` or the field name itself.
+pub fn eval_filter(clauses: &[Clause], entity: &Entity) -> bool {
+    let args_to_key = clauses
+        .iter()
+        .filter_map(|clause| {
+            if let Clause::ValueAttribution(_, key, Value(arg)) = clause {
+                Some((arg.as_str(), key.as_str()))
+            } else {
+                None
+            }
+        })
+        .collect::<HashMap<&str, &str>>();
+
+    clauses
+        .iter()
+        .all(|clause| clause_matches(clause, entity, &args_to_key))
+}
+
+fn clause_matches(clause: &Clause, entity: &Entity, args_to_key: &HashMap<&str, &str>) -> bool {
+    let get = |key: &str| entity.get(*args_to_key.get(key).unwrap_or(&key));
+    match clause {
+        Clause::ValueAttribution(_, _, _) => true,
+        Clause::Or(Function::Xor, clauses) => {
+            clauses
+                .iter()
+                .filter(|clause| clause_matches(clause, entity, args_to_key))
+                .count()
+                % 2
+                == 1
+        }
+        Clause::Or(Function::And, clauses) => clauses
+            .iter()
+            .all(|clause| clause_matches(clause, entity, args_to_key)),
+        Clause::Or(Function::Not, clauses) => !clauses
+            .iter()
+            .all(|clause| clause_matches(clause, entity, args_to_key)),
+        Clause::Or(_, clauses) => clauses
+            .iter()
+            .any(|clause| clause_matches(clause, entity, args_to_key)),
+        Clause::ContainsKeyValue(_, key, value) => entity.get(key).is_some_and(|v| v == value),
+        Clause::TupleEq(keys, values) => keys
+            .iter()
+            .zip(values)
+            .all(|(key, value)| get(key).is_some_and(|v| v == value)),
+        Clause::SimpleComparisonFunction(f, key, value) => get(key).is_some_and(|v| match f {
+            Function::Eq => v == value,
+            Function::NotEq => v != value,
+            Function::GEq => v >= value,
+            Function::G => v > value,
+            Function::LEq => v <= value,
+            Function::L => v < value,
+            Function::Like => like(v, value, false),
+            Function::ILike => like(v, value, true),
+            Function::Contains => matches!(v, Types::Vector(vec) if vec.contains(value)),
+            _ => false,
+        }),
+        Clause::ComplexComparisonFunctions(Function::In, key, set) => {
+            get(key).is_some_and(|v| set.contains(v))
+        }
+        Clause::IsEmpty(key) => get(key).is_some_and(|v| v.is_empty() == Some(true)),
+        Clause::IsNotEmpty(key) => get(key).is_some_and(|v| v.is_empty() != Some(true)),
+        Clause::Missing(key) => get(key).is_none(),
+        Clause::ComplexComparisonFunctions(Function::Between, key, start_end) => get(key)
+            .is_some_and(|v| start_end.len() == 2 && v >= &start_end[0] && v <= &start_end[1]),
+        _ => false,
+    }
+}
+
+fn like(value: &Types, pattern: &Types, ignore_case: bool) -> bool {
+    if let (Types::String(content), Types::String(pattern)) = (value, pattern) {
+        if ignore_case {
+            like_str(&content.to_lowercase(), &pattern.to_lowercase())
+        } else {
+            like_str(content, pattern)
+        }
+    } else {
+        false
+    }
+}
+
+fn like_str(content: &str, pattern: &str) -> bool {
+    if pattern.len() > 1 && pattern.starts_with('%') && pattern.ends_with('%') {
+        content.contains(&pattern[1..pattern.len() - 1])
+    } else if let Some(suffix) = pattern.strip_prefix('%') {
+        content.ends_with(suffix)
+    } else if let Some(prefix) = pattern.strip_suffix('%') {
+        content.starts_with(prefix)
+    } else {
+        content.contains(pattern)
+    }
+}
+
+/// Rewrites WHERE `clauses` with De Morgan's laws so `(not ...)` only wraps leaf clauses without a negated
+/// form, and negated comparisons can use an index. Comparisons never match a missing field while their
+/// negation does, so `(not (== ?a 1) (== ?b 2))` becomes `(or (!= ?a 1) (missing ?a) (!= ?b 2) (missing ?b))`
+/// and matches the same entities.
+pub fn push_not(clauses: Vec<Clause>) -> Vec<Clause> {
+    clauses
+        .into_iter()
+        .flat_map(|clause| match positive(clause) {
+            Clause::Or(Function::And, clauses) => clauses,
+            clause => vec![clause],
+        })
+        .collect()
+}
+
+fn positive(clause: Clause) -> Clause {
+    match clause {
+        Clause::Or(Function::Not, clauses) => negate_all(clauses),
+        Clause::Or(f, clauses) => Clause::Or(f, clauses.into_iter().map(positive).collect()),
+        clause => clause,
+    }
+}
+
+/// Negation of the conjunction of `clauses`, the disjunction of their negations.
+fn negate_all(mut clauses: Vec<Clause>) -> Clause {
+    if clauses.len() == 1 {
+        negate(clauses.remove(0))
+    } else {
+        let negations = clauses
+            .into_iter()
+            .map(negate)
+            .flat_map(|clause| match clause {
+                Clause::Or(Function::Or, clauses) => clauses,
+                clause => vec![clause],
+            })
+            .collect();
+        Clause::Or(Function::Or, negations)
+    }
+}
+
+fn negate(clause: Clause) -> Clause {
+    let negated = |f: &Function| match f {
+        Function::Eq => Some(Function::NotEq),
+        Function::NotEq => Some(Function::Eq),
+        Function::G => Some(Function::LEq),
+        Function::GEq => Some(Function::L),
+        Function::L => Some(Function::GEq),
+        Function::LEq => Some(Function::G),
+        _ => None,
+    };
+    match clause {
+        Clause::Or(Function::Not, mut clauses) if clauses.len() == 1 => positive(clauses.remove(0)),
+        Clause::Or(Function::Not, clauses) => {
+            Clause::Or(Function::And, clauses.into_iter().map(positive).collect())
+        }
+        Clause::Or(Function::Or, clauses) => {
+            Clause::Or(Function::And, clauses.into_iter().map(negate).collect())
+        }
+        Clause::Or(Function::And, clauses) => negate_all(clauses),
+        Clause::SimpleComparisonFunction(f, key, value) => match negated(&f) {
+            Some(f) => or_missing(Clause::SimpleComparisonFunction(f, key.clone(), value), key),
+            None => not(Clause::SimpleComparisonFunction(f, key, value)),
+        },
+        Clause::IsEmpty(key) => or_missing(Clause::IsNotEmpty(key.clone()), key),
+        Clause::IsNotEmpty(key) => or_missing(Clause::IsEmpty(key.clone()), key),
+        clause => not(positive(clause)),
+    }
+}
+
+/// `clause` or `key` is missing, a negated leaf also matches entities without the field.
+fn or_missing(clause: Clause, key: String) -> Clause {
+    Clause::Or(Function::Or, vec![clause, Clause::Missing(key)])
+}
+
+fn not(clause: Clause) -> Clause {
+    Clause::Or(Function::Not, vec![clause])
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Clause {
     ContainsKeyValue(String, String, Types),
@@ -179,6 +464,14 @@ pub enum Clause {
     SimpleComparisonFunction(Function, String, Types),
     ComplexComparisonFunctions(Function, String, Vec<Types>),
     Or(Function, Vec<Clause>),
+    /// Composite key equality, every field equals the value at the same position.
+    TupleEq(Vec<String>, Vec<Types>),
+    /// `?tags IS EMPTY`, the field is an empty `Vector`, `Map` or `String`.
+    IsEmpty(String),
+    /// `?tags IS NOT EMPTY`, the field is not an empty `Vector`, `Map` or `String`, scalars are never empty.
+    IsNotEmpty(String),
+    /// `(missing ?a)`, the entity has no field `a`, built by `push_not` for negated leaves.
+    Missing(String),
     Error,
 }
 
@@ -191,9 +484,19 @@ pub enum Function {
     L,
     NotEq,
     Like,
+    /// Case-insensitive `Like`, `(ilike ?name "ada%")` matches `"Ada Lovelace"`.
+    ILike,
     Between,
     Or,
+    /// Matches when an odd number of its clauses match, only one of two for `(xor a b)`.
+    Xor,
+    /// Matches when all of its clauses match, `(and a b)` nests a conjunction inside an `(or ...)`.
+    And,
+    /// Matches when not all of its clauses match, `(not a b)` is `NOT (a AND b)`.
+    Not,
     In,
+    /// Matches when the field is a `Types::Vector` with the value, `(contains ?tags "rust")`.
+    Contains,
     Error,
 }
 
@@ -209,8 +512,10 @@ impl FromStr for Function {
             "<" => Function::L,
             "!=" | "<>" => Function::NotEq,
             "like" => Function::Like,
+            "ilike" => Function::ILike,
             "between" => Function::Between,
             "in" => Function::In,
+            "contains" => Function::Contains,
             _ => Function::Error,
         })
     }
@@ -219,6 +524,128 @@ impl FromStr for Function {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Value(pub String);
 
+/// Human-readable description of WHERE `clauses`, e.g. `age is greater than 18 and status equals "active"`.
+/// Variables are described by the field they are bound to and `?* e:key ?var` bindings are omitted.
+pub fn describe_clauses(clauses: &[Clause]) -> String {
+    let args_to_key = clauses
+        .iter()
+        .filter_map(|clause| {
+            if let Clause::ValueAttribution(_, key, Value(arg)) = clause {
+                Some((arg.as_str(), key.as_str()))
+            } else {
+                None
+            }
+        })
+        .collect::<HashMap<&str, &str>>();
+    let descriptions = clauses
+        .iter()
+        .filter(|clause| !matches!(clause, Clause::ValueAttribution(..)))
+        .map(|clause| clause.describe_nested(&args_to_key))
+        .collect::<Vec<String>>();
+
+    if descriptions.is_empty() {
+        String::from("every entity")
+    } else {
+        descriptions.join(" and ")
+    }
+}
+
+impl Clause {
+    /// Human-readable description of a single clause, see `describe_clauses` for a full WHERE.
+    pub fn describe(&self) -> String {
+        self.describe_with(&HashMap::new())
+    }
+
+    fn describe_nested(&self, args_to_key: &HashMap<&str, &str>) -> String {
+        match self {
+            Clause::Or(_, clauses) if clauses.len() > 1 => {
+                format!("({})", self.describe_with(args_to_key))
+            }
+            _ => self.describe_with(args_to_key),
+        }
+    }
+
+    fn describe_with(&self, args_to_key: &HashMap<&str, &str>) -> String {
+        let key = |key: &str| -> String {
+            match args_to_key.get(key) {
+                Some(field) => (*field).to_string(),
+                None => key.trim_start_matches('?').to_string(),
+            }
+        };
+        let values = |values: &[Types], separator: &str| {
+            values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(separator)
+        };
+
+        match self {
+            Clause::ContainsKeyValue(_, k, value) => format!("{} equals {}", key(k), value),
+            Clause::ValueAttribution(_, k, Value(arg)) => {
+                format!("{} is bound to {}", k, arg)
+            }
+            Clause::SimpleComparisonFunction(f, k, value) => {
+                format!("{} {} {}", key(k), f.describe(), value)
+            }
+            Clause::ComplexComparisonFunctions(Function::Between, k, bounds) => {
+                format!("{} is between {}", key(k), values(bounds, " and "))
+            }
+            Clause::ComplexComparisonFunctions(f, k, options) => {
+                format!("{} {} {}", key(k), f.describe(), values(options, ", "))
+            }
+            Clause::Or(Function::Not, clauses) => format!(
+                "not ({})",
+                clauses
+                    .iter()
+                    .map(|clause| clause.describe_nested(args_to_key))
+                    .collect::<Vec<String>>()
+                    .join(" and ")
+            ),
+            Clause::Or(f, clauses) => clauses
+                .iter()
+                .map(|clause| clause.describe_nested(args_to_key))
+                .collect::<Vec<String>>()
+                .join(&format!(" {} ", f.describe())),
+            Clause::TupleEq(keys, tuple) => format!(
+                "({}) equals ({})",
+                keys.iter()
+                    .map(|k| key(k))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                values(tuple, ", ")
+            ),
+            Clause::IsEmpty(k) => format!("{} is empty", key(k)),
+            Clause::IsNotEmpty(k) => format!("{} is not empty", key(k)),
+            Clause::Missing(k) => format!("{} is missing", key(k)),
+            Clause::Error => String::from("an invalid clause"),
+        }
+    }
+}
+
+impl Function {
+    fn describe(&self) -> &'static str {
+        match self {
+            Function::Eq => "equals",
+            Function::GEq => "is greater than or equal to",
+            Function::G => "is greater than",
+            Function::LEq => "is less than or equal to",
+            Function::L => "is less than",
+            Function::NotEq => "does not equal",
+            Function::Like => "is like",
+            Function::ILike => "is case-insensitively like",
+            Function::Between => "is between",
+            Function::Or => "or",
+            Function::Xor => "xor",
+            Function::And => "and",
+            Function::Not => "not",
+            Function::In => "is one of",
+            Function::Contains => "contains",
+            Function::Error => "has an invalid comparison with",
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -427,4 +854,376 @@ mod test {
             )
         )
     }
+
+    #[test]
+    fn xor_nested_in_or() {
+        let mut chars = " {
+            ?* my_entity:a ?a,
+            (or (xor (== ?a 1) (== b 2)) (== c 3)),
+        }"
+        .chars();
+        let wql = where_selector("my_entity".to_string(), ToSelect::All, &mut chars);
+        let eq = |key: &str, value| {
+            Clause::SimpleComparisonFunction(Function::Eq, key.to_string(), Types::Integer(value))
+        };
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectWhere(
+                "my_entity".to_string(),
+                ToSelect::All,
+                vec![
+                    Clause::ValueAttribution(
+                        "my_entity".to_string(),
+                        "a".to_string(),
+                        Value("?a".to_string())
+                    ),
+                    Clause::Or(
+                        Function::Or,
+                        vec![
+                            Clause::Or(Function::Xor, vec![eq("?a", 1), eq("b", 2)]),
+                            eq("c", 3),
+                        ]
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn contains_func() {
+        let mut chars = " { ?* my_entity:tags ?tags, (contains ?tags \"rust\"), }".chars();
+        let wql = where_selector("my_entity".to_string(), ToSelect::All, &mut chars);
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectWhere(
+                "my_entity".to_string(),
+                ToSelect::All,
+                vec![
+                    Clause::ValueAttribution(
+                        "my_entity".to_string(),
+                        "tags".to_string(),
+                        Value("?tags".to_string())
+                    ),
+                    Clause::SimpleComparisonFunction(
+                        Function::Contains,
+                        "?tags".to_string(),
+                        Types::String("rust".to_string())
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn ilike_ignores_case() {
+        let mut chars = " { ?* my_entity:name ?name, (ILIKE ?name \"ada%\"), }".chars();
+        let clauses = read_where_clauses("my_entity", &mut chars).unwrap();
+
+        assert_eq!(
+            clauses[1],
+            Clause::SimpleComparisonFunction(
+                Function::ILike,
+                "?name".to_string(),
+                Types::String("ada%".to_string())
+            )
+        );
+
+        let mut entity = Entity::new();
+        entity.insert(
+            "name".to_string(),
+            Types::String("Ada Lovelace".to_string()),
+        );
+        let like = |function: &str, pattern: &str| {
+            let query = format!(" {{ ({} name \"{}\"), }}", function, pattern);
+            eval_filter(
+                &read_where_clauses("my_entity", &mut query.chars()).unwrap(),
+                &entity,
+            )
+        };
+
+        assert!(like("ilike", "ada%"));
+        assert!(like("ilike", "%LOVELACE"));
+        assert!(like("ilike", "%LoVe%"));
+        assert!(!like("ilike", "%grace%"));
+        assert!(!like("like", "ada%"));
+        assert!(like("like", "Ada%"));
+    }
+
+    #[test]
+    fn is_empty_clauses() {
+        let mut chars =
+            " { ?* my_entity:tags ?tags, ?tags IS EMPTY, labels is not empty, }".chars();
+        let clauses = read_where_clauses("my_entity", &mut chars).unwrap();
+
+        assert_eq!(
+            clauses[1..],
+            [
+                Clause::IsEmpty("?tags".to_string()),
+                Clause::IsNotEmpty("labels".to_string())
+            ]
+        );
+        assert_eq!(
+            describe_clauses(&clauses),
+            "tags is empty and labels is not empty"
+        );
+    }
+
+    #[test]
+    fn tuple_equality() {
+        let mut chars = " {
+            (tenant, id) == (\"acme, inc\", 48c7640e-9287-468a-a07c-2fb00da5eaed),
+        }"
+        .chars();
+        let wql = where_selector("my_entity".to_string(), ToSelect::All, &mut chars);
+
+        assert_eq!(
+            wql.unwrap(),
+            Wql::SelectWhere(
+                "my_entity".to_string(),
+                ToSelect::All,
+                vec![Clause::TupleEq(
+                    vec!["tenant".to_string(), "id".to_string()],
+                    vec![
+                        Types::String("acme, inc".to_string()),
+                        Types::Uuid(
+                            uuid::Uuid::from_str("48c7640e-9287-468a-a07c-2fb00da5eaed").unwrap()
+                        ),
+                    ]
+                )]
+            )
+        )
+    }
+
+    #[test]
+    fn tuple_equality_arity_mismatch() {
+        let mut chars = " { (tenant, id) == (\"acme\"), }".chars();
+        let wql = where_selector("my_entity".to_string(), ToSelect::All, &mut chars);
+
+        assert_eq!(
+            wql.err(),
+            Some(String::from(
+                "Tuple equality requires the same arity on both sides, found 2 fields and 1 values"
+            ))
+        )
+    }
+
+    #[test]
+    fn eval_filter_table() {
+        let mut entity = Entity::new();
+        entity.insert("name".to_string(), Types::String("julia".to_string()));
+        entity.insert("age".to_string(), Types::Integer(30));
+        entity.insert("score".to_string(), Types::Float(7.5));
+        entity.insert("nothing".to_string(), Types::Nil);
+        entity.insert(
+            "tags".to_string(),
+            Types::Vector(vec![
+                Types::String("rust".to_string()),
+                Types::String("wql".to_string()),
+            ]),
+        );
+
+        entity.insert("labels".to_string(), Types::Vector(Vec::new()));
+
+        let table = vec![
+            ("?* e:name \"julia\"", true),
+            ("?* e:name \"otavio\"", false),
+            ("?* e:missing 30", false),
+            ("(== ?age 30)", true),
+            ("(== ?age 31)", false),
+            ("(>= ?age 30)", true),
+            ("(> ?age 30)", false),
+            ("(<= ?age 30)", true),
+            ("(< ?age 31)", true),
+            ("(< ?missing 31)", false),
+            ("(contains tags \"rust\")", true),
+            ("(contains tags \"go\")", false),
+            ("(contains ?name \"julia\")", false),
+            ("(like ?name \"%uli%\")", true),
+            ("(like ?name \"jul%\")", true),
+            ("(like ?name \"%lia\")", true),
+            ("(like ?name \"%xyz\")", false),
+            ("(like ?age \"%3%\")", false),
+            ("(in ?age 29 30 31)", true),
+            ("(in ?age 1 2)", false),
+            ("(between ?age 25 30)", true),
+            ("(between ?age 31 35)", false),
+            ("(or (== ?age 1) (like name \"%uli%\"))", true),
+            ("(or (== ?age 1) (>= score 8.0))", false),
+            ("(or (== ?age 1), (like name \"%uli%\"),)", true),
+            ("(xor (== ?age 30) (== ?age 1))", true),
+            ("(xor (== ?age 30) (>= ?age 1))", false),
+            ("(xor (== ?age 30) (>= ?age 1) (like name \"%uli%\"))", true),
+            ("(or (xor (== ?age 30) (>= ?age 1)) (== ?age 1))", false),
+            ("(xor (or (== ?age 30) (>= ?age 1)) (== ?age 1))", true),
+            ("?* e:nothing nil", true),
+            ("(name, age) == (\"julia\", 30)", true),
+            ("(?name, ?age) == (\"julia\", 31)", false),
+            ("labels IS EMPTY", true),
+            ("labels IS NOT EMPTY", false),
+            ("tags is empty", false),
+            ("tags is not empty", true),
+            ("?age IS EMPTY", false),
+            ("?age IS NOT EMPTY", true),
+            ("?missing IS NOT EMPTY", false),
+        ];
+
+        for (clause, expected) in table {
+            let query = format!(
+                " {{ ?* e:name ?name, ?* e:age ?age, ?* e:missing ?missing, {}, }}",
+                clause
+            );
+            let clauses = read_where_clauses("e", &mut query.chars()).unwrap();
+
+            assert_eq!(eval_filter(&clauses, &entity), expected, "{}", clause);
+        }
+    }
+
+    #[test]
+    fn eval_filter_error_clause() {
+        let entity = Entity::new();
+
+        assert!(!eval_filter(&[Clause::Error], &entity));
+        assert!(eval_filter(&[], &entity));
+    }
+
+    #[test]
+    fn describe_compound_clauses() {
+        let mut chars = " {
+            ?* my_entity:age ?age,
+            ?* my_entity:status ?status,
+            (> ?age 18),
+            (== ?status \"active\"),
+            (or
+                (< ?age 65)
+                (in ?status \"pending\" \"invited\")
+            ),
+        }"
+        .chars();
+        let clauses = read_where_clauses("my_entity", &mut chars).unwrap();
+
+        assert_eq!(
+            describe_clauses(&clauses),
+            "age is greater than 18 and status equals \"active\" and \
+             (age is less than 65 or status is one of \"pending\", \"invited\")"
+        );
+    }
+
+    #[test]
+    fn describe_single_clause() {
+        let between = Clause::ComplexComparisonFunctions(
+            Function::Between,
+            "?age".to_string(),
+            vec![Types::Integer(18), Types::Integer(65)],
+        );
+        let tuple = Clause::TupleEq(
+            vec!["tenant".to_string(), "id".to_string()],
+            vec![Types::String("acme".to_string()), Types::Integer(3)],
+        );
+
+        assert_eq!(between.describe(), "age is between 18 and 65");
+        assert_eq!(tuple.describe(), "(tenant, id) equals (\"acme\", 3)");
+        assert_eq!(describe_clauses(&[]), "every entity");
+    }
+
+    fn cmp(f: Function, key: &str, value: isize) -> Clause {
+        Clause::SimpleComparisonFunction(f, key.to_string(), Types::Integer(value))
+    }
+
+    #[test]
+    fn push_not_applies_de_morgan() {
+        let mut chars = " { ?* my_entity:a ?a, (not (== ?a 1) (== b 2)), }".chars();
+        let clauses = read_where_clauses("my_entity", &mut chars).unwrap();
+
+        assert_eq!(
+            clauses[1],
+            Clause::Or(
+                Function::Not,
+                vec![cmp(Function::Eq, "?a", 1), cmp(Function::Eq, "b", 2)]
+            )
+        );
+        assert_eq!(
+            push_not(clauses)[1],
+            Clause::Or(
+                Function::Or,
+                vec![
+                    cmp(Function::NotEq, "?a", 1),
+                    Clause::Missing("?a".to_string()),
+                    cmp(Function::NotEq, "b", 2),
+                    Clause::Missing("b".to_string())
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn push_not_through_nested_clauses() {
+        let mut chars =
+            " { (not (or (> a 1) (like b \"x%\"))), (or (not (not (<= c 3))) (== d 4)), }".chars();
+        let clauses = read_where_clauses("my_entity", &mut chars).unwrap();
+
+        assert_eq!(
+            push_not(clauses),
+            vec![
+                Clause::Or(
+                    Function::Or,
+                    vec![cmp(Function::LEq, "a", 1), Clause::Missing("a".to_string())]
+                ),
+                Clause::Or(
+                    Function::Not,
+                    vec![Clause::SimpleComparisonFunction(
+                        Function::Like,
+                        "b".to_string(),
+                        Types::String("x%".to_string())
+                    )]
+                ),
+                Clause::Or(
+                    Function::Or,
+                    vec![cmp(Function::LEq, "c", 3), cmp(Function::Eq, "d", 4)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn push_not_keeps_matches() {
+        let mut chars = " { (not (>= a 1) (!= b 2) (and (< c 3))), }".chars();
+        let clauses = read_where_clauses("my_entity", &mut chars).unwrap();
+        let pushed = push_not(clauses.clone());
+
+        for (a, b, c) in &[(0, 2, 0), (1, 2, 0), (1, 3, 0), (1, 2, 5), (0, 3, 5)] {
+            let mut entity = Entity::new();
+            entity.insert("a".to_string(), Types::Integer(*a));
+            entity.insert("b".to_string(), Types::Integer(*b));
+            entity.insert("c".to_string(), Types::Integer(*c));
+
+            assert_eq!(
+                eval_filter(&pushed, &entity),
+                eval_filter(&clauses, &entity)
+            );
+        }
+    }
+
+    #[test]
+    fn push_not_keeps_matches_without_the_field() {
+        let mut chars = " { ?* my_entity:a ?a, (not (== ?a 1)), (not (> b 1) (< c 3)), }".chars();
+        let mut clauses = read_where_clauses("my_entity", &mut chars).unwrap();
+        clauses.push(not(Clause::IsEmpty("tags".to_string())));
+        let pushed = push_not(clauses.clone());
+        let mut entity = Entity::new();
+        entity.insert("c".to_string(), Types::Integer(0));
+
+        assert!(eval_filter(&clauses, &entity));
+        assert_eq!(
+            eval_filter(&pushed, &entity),
+            eval_filter(&clauses, &entity)
+        );
+        entity.insert("tags".to_string(), Types::Vector(Vec::new()));
+        assert!(!eval_filter(&clauses, &entity));
+        assert_eq!(
+            eval_filter(&pushed, &entity),
+            eval_filter(&clauses, &entity)
+        );
+    }
 }